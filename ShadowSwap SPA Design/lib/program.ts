@@ -5,7 +5,7 @@
  */
 
 import { PublicKey, Connection } from '@solana/web3.js';
-import { Program, AnchorProvider, BN, Idl } from '@coral-xyz/anchor';
+import { Program, AnchorProvider, Idl } from '@coral-xyz/anchor';
 import * as anchor from '@coral-xyz/anchor';
 import { loadShadowSwapIdl } from './shadowSwapIdlLoader';
 
@@ -15,21 +15,39 @@ import { loadShadowSwapIdl } from './shadowSwapIdlLoader';
 
 /**
  * Derive Order PDA
- * Seeds: ["order", order_book, order_count]
+ * Seeds: ["order", order_book, owner, client_order_id]
  */
 export function deriveOrderPda(
   orderBookAddress: PublicKey,
-  orderCount: BN,
+  ownerAddress: PublicKey,
+  clientOrderId: Buffer,
   programId: PublicKey
 ): [PublicKey, number] {
-  // Use BN's toArrayLike method for better browser compatibility
-  const orderCountBuffer = orderCount.toArrayLike(Buffer, 'le', 8);
-  
   return PublicKey.findProgramAddressSync(
     [
       Buffer.from('order'),
       orderBookAddress.toBuffer(),
-      orderCountBuffer,
+      ownerAddress.toBuffer(),
+      clientOrderId,
+    ],
+    programId
+  );
+}
+
+/**
+ * Derive Order Shard PDA
+ * Seeds: ["order_shard", order_book, shard (u8)]
+ */
+export function deriveOrderShardPda(
+  orderBookAddress: PublicKey,
+  shard: number,
+  programId: PublicKey
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [
+      Buffer.from('order_shard'),
+      orderBookAddress.toBuffer(),
+      Buffer.from([shard]),
     ],
     programId
   );
@@ -102,40 +120,16 @@ export function deriveCallbackAuthPda(
 }
 
 /**
- * Fetch order count from OrderBook account
- * Note: This requires the program instance to properly deserialize the account
+ * Pick the order shard an owner's orders take IDs from
+ * Owners are spread over the book's shards by the first byte of their key
  */
-export async function fetchOrderCount(
-  connection: Connection,
+export async function fetchOrderShard(
   orderBookAddress: PublicKey,
-  program?: any
-): Promise<BN> {
-  try {
-    if (program) {
-      // Use Anchor program to fetch and deserialize the order book account
-      const orderBookAccount = await program.account.orderBook.fetch(orderBookAddress);
-      return new BN(orderBookAccount.orderCount.toString());
-    }
-    
-    // Fallback: manual parsing (less reliable)
-    const accountInfo = await connection.getAccountInfo(orderBookAddress);
-    
-    if (!accountInfo) {
-      throw new Error('Order book account not found');
-    }
-    
-    // OrderBook struct layout:
-    // discriminator (8) + authority (32) + base_mint (32) + quote_mint (32) = 104 bytes
-    // Then order_count (u64 - 8 bytes) starts at offset 104
-    const offset = 104;
-    const orderCountBuffer = accountInfo.data.slice(offset, offset + 8);
-    const orderCount = new BN(orderCountBuffer, 'le');
-    
-    return orderCount;
-  } catch (error) {
-    console.error('Error fetching order count:', error);
-    return new BN(0);
-  }
+  ownerAddress: PublicKey,
+  program: any
+): Promise<number> {
+  const orderBookAccount = await program.account.orderBook.fetch(orderBookAddress);
+  return ownerAddress.toBuffer()[0] % Math.max(orderBookAccount.orderShards, 1);
 }
 
 /**
@@ -14,9 +14,10 @@ import {
 } from '@solana/spl-token';
 import {
   deriveOrderPda,
+  deriveOrderShardPda,
   deriveEscrowPda,
   deriveEscrowTokenAccountPda,
-  fetchOrderCount,
+  fetchOrderShard,
   getProgramAsync,
   ORDER_STATUS,
 } from './program';
@@ -300,13 +301,16 @@ export class ShadowSwapClient {
       // Encrypt order
       const encryptedOrder = await encryptOrderWithArcium(plainOrder, this.orderBook);
 
-      // Get order count - pass program instance for proper deserialization
-      const orderCount = await fetchOrderCount(this.connection, this.orderBook, this.program);
+      // Orders are keyed by a random client order ID, so a resent transaction
+      // can't place the order twice; the order ID comes from a shard
+      const clientOrderId = Buffer.from(crypto.getRandomValues(new Uint8Array(16)));
+      const shard = await fetchOrderShard(this.orderBook, this.provider.publicKey, this.program);
+      const [orderShardPda] = deriveOrderShardPda(this.orderBook, shard, this.programId);
 
-      console.log('Current order count:', orderCount.toString());
+      console.log('Client order ID:', clientOrderId.toString('hex'), 'shard:', shard);
 
       // Derive PDAs
-      const [orderPda] = deriveOrderPda(this.orderBook, orderCount, this.programId);
+      const [orderPda] = deriveOrderPda(this.orderBook, this.provider.publicKey, clientOrderId, this.programId);
       console.log('Derived order PDA:', orderPda.toString());
       const [escrowPda] = deriveEscrowPda(orderPda, this.programId);
       const [escrowTokenPda] = deriveEscrowTokenAccountPda(orderPda, this.programId);
@@ -373,9 +377,11 @@ export class ShadowSwapClient {
 
       // Build submit order instruction
       const submitOrderIx = await this.program.methods
-        .submitEncryptedOrder(paddedCipherPayload, encryptedAmountBuffer, new BN(postedAmount))
+        .submitEncryptedOrder(Array.from(clientOrderId), paddedCipherPayload, encryptedAmountBuffer, new BN(postedAmount), 0)
         .accounts({
           orderBook: this.orderBook,
+          orderShard: orderShardPda,
+          orderOwner: this.provider.publicKey,
           order: orderPda,
           escrow: escrowPda,
           escrowTokenAccount: escrowTokenPda,
@@ -385,6 +391,7 @@ export class ShadowSwapClient {
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
           rent: SYSVAR_RENT_PUBKEY,
+          tradingDelegate: null,
         })
         .instruction();
 
@@ -7,9 +7,30 @@ mod circuits {
     /// Input structure for an encrypted order
     pub struct OrderInput {
         side: u8,        // 0 = Buy, 1 = Sell
-        price: u64,      // Price in quote tokens per base token
+        price: u64,      // Price in quote tokens per base token (limit for pegged orders, 0 = none)
         amount: u64,     // Amount in base token units
         timestamp: u64,  // Order timestamp for time priority
+        flags: u8,       // Bit 0 = midpoint peg
+    }
+
+    /// `OrderInput.flags` bit: order trades at the reference midpoint
+    const FLAG_MIDPOINT_PEG: u8 = 1;
+
+    /// Whether the order is pegged to the reference midpoint
+    fn is_pegged(order: &OrderInput) -> bool {
+        (order.flags & FLAG_MIDPOINT_PEG) != 0
+    }
+
+    /// Whether the order's limit allows trading at `price`
+    ///
+    /// Pegged orders with `price == 0` have no limit.
+    fn accepts_price(order: &OrderInput, price: u64) -> bool {
+        let unlimited = is_pegged(order) && order.price == 0;
+        if order.side == 0 {
+            unlimited || price <= order.price
+        } else {
+            unlimited || price >= order.price
+        }
     }
 
     /// Output structure for a match result
@@ -18,21 +39,385 @@ mod circuits {
         execution_price: u64,
     }
 
+    /// A single fill produced by batch matching
+    ///
+    /// `buy_index` and `sell_index` are positions in the batch input, so each
+    /// party can tell which of their orders the fill belongs to.
+    pub struct BatchFill {
+        buy_index: u64,
+        sell_index: u64,
+        matched_amount: u64,
+        execution_price: u64,
+    }
+
+    /// Unfilled quantity of an order after batch matching
+    pub struct RemainingOutput {
+        remaining: u64,
+    }
+
+    /// Encrypted order amount as posted alongside `place_order`
+    pub struct AmountInput {
+        amount: u64,
+    }
+
+    /// Check that an order's encrypted amount equals the amount it escrowed
+    ///
+    /// `posted_amount` is the plaintext quantity transferred into escrow by
+    /// `place_order`. Only the equality bit is revealed, never the amount.
+    #[instruction]
+    pub fn verify_posted_amount(
+        encrypted_amount: Enc<Shared, AmountInput>,
+        posted_amount: u64,
+    ) -> bool {
+        let input = encrypted_amount.to_arcis();
+        (input.amount == posted_amount).reveal()
+    }
+
+    /// Check that an order is fully covered by its escrow
+    ///
+    /// - Sell orders must escrow base tokens: `escrowed >= amount`
+    /// - Buy orders must escrow quote tokens: `escrowed >= amount * price / price_scale`
+    ///   (evaluated as `escrowed * price_scale >= amount * price` to avoid rounding)
+    /// - Pegged buy orders are checked against their limit, so a buy peg
+    ///   without a limit is never covered
+    ///
+    /// `escrow_is_quote` says which mint the escrow holds, so an order cannot
+    /// claim coverage with the wrong token. Only the result bit is revealed.
+    #[instruction]
+    pub fn verify_escrow_coverage(
+        order: Enc<Shared, OrderInput>,
+        escrowed_amount: u64,
+        escrow_is_quote: bool,
+        price_scale: u64,
+    ) -> bool {
+        let input = order.to_arcis();
+
+        let covered = if input.side == 0 {
+            let required = (input.amount as u128) * (input.price as u128);
+            escrow_is_quote
+                && input.price > 0
+                && (escrowed_amount as u128) * (price_scale as u128) >= required
+        } else {
+            !escrow_is_quote && escrowed_amount >= input.amount
+        };
+
+        covered.reveal()
+    }
+
+    /// Encrypted trigger of a conditional (stop-loss / take-profit) order
+    pub struct TriggerInput {
+        direction: u8,       // 0 = fire when price <= trigger, 1 = fire when price >= trigger
+        trigger_price: u64,  // Same units as the public oracle price
+    }
+
+    /// Decide whether a conditional order's trigger has fired
+    ///
+    /// `oracle_price` is the public reference price supplied by the crank.
+    /// Only the fired bit is revealed; direction and trigger price stay
+    /// encrypted.
+    #[instruction]
+    pub fn check_trigger(
+        trigger: Enc<Shared, TriggerInput>,
+        oracle_price: u64,
+    ) -> bool {
+        let input = trigger.to_arcis();
+
+        let fired = if input.direction == 0 {
+            oracle_price <= input.trigger_price
+        } else {
+            oracle_price >= input.trigger_price
+        };
+
+        fired.reveal()
+    }
+
+    /// Running trade volume of an order book since the last reveal
+    pub struct VolumeStats {
+        base: u64,
+        quote: u64,
+    }
+
+    /// Add a fill to an order book's encrypted running volume
+    ///
+    /// `fill` is the MXE-encrypted copy of a `match_two_orders` result. When
+    /// `has_volume` is false the book has no stored ciphertexts yet and
+    /// `current` is ignored (treated as zero). `version` is passed through
+    /// so the callback can reject results computed from stale state.
+    #[instruction]
+    pub fn update_volume(
+        current: Enc<Mxe, VolumeStats>,
+        has_volume: bool,
+        fill: Enc<Mxe, MatchOutput>,
+        price_scale: u64,
+        version: u64,
+    ) -> (Enc<Mxe, VolumeStats>, u64) {
+        let stats = current.to_arcis();
+        let fill_data = fill.to_arcis();
+
+        let base = if has_volume { stats.base } else { 0 };
+        let quote = if has_volume { stats.quote } else { 0 };
+
+        let fill_quote = ((fill_data.matched_amount as u128) * (fill_data.execution_price as u128)
+            / (price_scale as u128)) as u64;
+
+        let updated = VolumeStats {
+            base: base + fill_data.matched_amount,
+            quote: quote + fill_quote,
+        };
+
+        (current.owner.from_arcis(updated), version)
+    }
+
+    /// Reveal an order book's aggregate volume for the current period
+    ///
+    /// Only the period totals are revealed; individual fills never leave MPC.
+    #[instruction]
+    pub fn reveal_period_volume(
+        current: Enc<Mxe, VolumeStats>,
+        version: u64,
+    ) -> (u64, u64, u64) {
+        let stats = current.to_arcis();
+        (stats.base.reveal(), stats.quote.reveal(), version)
+    }
+
+    /// Encrypted token amount held in a shielded vault
+    ///
+    /// Used both for a user's free balance and for the allocation reserved by
+    /// one of their orders.
+    pub struct ShieldedAmount {
+        amount: u64,
+    }
+
+    /// A user's free balance after crediting deposits not yet folded in
+    ///
+    /// `has_balance == false` means the balance account has no ciphertext yet
+    /// and `balance` is ignored (treated as zero). `credit` is the public sum
+    /// of deposits made since the last computation on this balance.
+    fn credited_balance(balance: &ShieldedAmount, has_balance: bool, credit: u64) -> u64 {
+        let current = if has_balance { balance.amount } else { 0 };
+        current + credit
+    }
+
+    /// Reserve an order's escrow out of its owner's shielded balance
+    ///
+    /// The allocation covers the whole order: base `amount` for sells, quote
+    /// `amount * price / price_scale` (rounded up) for buys. If the balance
+    /// cannot cover it nothing is reserved. Only the covered bit is revealed.
+    ///
+    /// The order's base `amount` is also returned as its unfilled amount,
+    /// which `shielded_settle` counts down to decide when it is filled.
+    ///
+    /// Returns (new balance, allocation, unfilled amount, covered).
+    #[instruction]
+    pub fn shielded_allocate(
+        order: Enc<Shared, OrderInput>,
+        balance: Enc<Mxe, ShieldedAmount>,
+        has_balance: bool,
+        credit: u64,
+        allocation_owner: Mxe,
+        unfilled_owner: Mxe,
+        escrow_is_quote: bool,
+        price_scale: u64,
+    ) -> (
+        Enc<Mxe, ShieldedAmount>,
+        Enc<Mxe, ShieldedAmount>,
+        Enc<Mxe, ShieldedAmount>,
+        bool,
+    ) {
+        let input = order.to_arcis();
+        let available = credited_balance(&balance.to_arcis(), has_balance, credit);
+
+        let (required, right_mint) = if input.side == 0 {
+            let quote = ((input.amount as u128) * (input.price as u128) + (price_scale as u128) - 1)
+                / (price_scale as u128);
+            (quote, escrow_is_quote && input.price > 0)
+        } else {
+            (input.amount as u128, !escrow_is_quote)
+        };
+
+        let covered = right_mint && (available as u128) >= required;
+        let reserved = if covered { required as u64 } else { 0 };
+
+        (
+            balance.owner.from_arcis(ShieldedAmount {
+                amount: available - reserved,
+            }),
+            allocation_owner.from_arcis(ShieldedAmount { amount: reserved }),
+            unfilled_owner.from_arcis(ShieldedAmount {
+                amount: if covered { input.amount } else { 0 },
+            }),
+            covered.reveal(),
+        )
+    }
+
+    /// Return an order's unused allocation to its owner's shielded balance
+    #[instruction]
+    pub fn shielded_release(
+        allocation: Enc<Mxe, ShieldedAmount>,
+        balance: Enc<Mxe, ShieldedAmount>,
+        has_balance: bool,
+        credit: u64,
+    ) -> Enc<Mxe, ShieldedAmount> {
+        let unused = allocation.to_arcis().amount;
+        let available = credited_balance(&balance.to_arcis(), has_balance, credit);
+
+        balance.owner.from_arcis(ShieldedAmount {
+            amount: available + unused,
+        })
+    }
+
+    /// Settle a fill between two shielded orders
+    ///
+    /// `fill` is the MXE-encrypted copy of a `match_two_orders` result. Quote
+    /// moves from the buyer's allocation to the seller's quote balance and
+    /// base from the seller's allocation to the buyer's base balance, all
+    /// inside the vaults, so no token transfer reveals the fill size. If
+    /// either allocation or either order's unfilled amount cannot cover the
+    /// fill nothing changes.
+    ///
+    /// Each order is filled once its unfilled base amount reaches zero; only
+    /// those two bits and whether the fill settled are revealed.
+    ///
+    /// Returns (buyer allocation, seller allocation, buyer unfilled, seller
+    /// unfilled, buyer base balance, seller quote balance, settled,
+    /// buyer_filled, seller_filled).
+    #[instruction]
+    pub fn shielded_settle(
+        fill: Enc<Mxe, MatchOutput>,
+        buyer_allocation: Enc<Mxe, ShieldedAmount>,
+        seller_allocation: Enc<Mxe, ShieldedAmount>,
+        buyer_unfilled: Enc<Mxe, ShieldedAmount>,
+        seller_unfilled: Enc<Mxe, ShieldedAmount>,
+        buyer_base: Enc<Mxe, ShieldedAmount>,
+        buyer_has_base: bool,
+        buyer_base_credit: u64,
+        seller_quote: Enc<Mxe, ShieldedAmount>,
+        seller_has_quote: bool,
+        seller_quote_credit: u64,
+        price_scale: u64,
+    ) -> (
+        Enc<Mxe, ShieldedAmount>,
+        Enc<Mxe, ShieldedAmount>,
+        Enc<Mxe, ShieldedAmount>,
+        Enc<Mxe, ShieldedAmount>,
+        Enc<Mxe, ShieldedAmount>,
+        Enc<Mxe, ShieldedAmount>,
+        bool,
+        bool,
+        bool,
+    ) {
+        let fill_data = fill.to_arcis();
+        let buyer_alloc = buyer_allocation.to_arcis().amount;
+        let seller_alloc = seller_allocation.to_arcis().amount;
+        let buyer_left = buyer_unfilled.to_arcis().amount;
+        let seller_left = seller_unfilled.to_arcis().amount;
+        let buyer_base_balance = credited_balance(&buyer_base.to_arcis(), buyer_has_base, buyer_base_credit);
+        let seller_quote_balance =
+            credited_balance(&seller_quote.to_arcis(), seller_has_quote, seller_quote_credit);
+
+        let quote = ((fill_data.matched_amount as u128) * (fill_data.execution_price as u128)
+            / (price_scale as u128)) as u64;
+        let base = fill_data.matched_amount;
+
+        let settled = base > 0
+            && buyer_alloc >= quote
+            && seller_alloc >= base
+            && buyer_left >= base
+            && seller_left >= base;
+        let quote_moved = if settled { quote } else { 0 };
+        let base_moved = if settled { base } else { 0 };
+        let buyer_filled = settled && buyer_left == base;
+        let seller_filled = settled && seller_left == base;
+
+        (
+            buyer_allocation.owner.from_arcis(ShieldedAmount {
+                amount: buyer_alloc - quote_moved,
+            }),
+            seller_allocation.owner.from_arcis(ShieldedAmount {
+                amount: seller_alloc - base_moved,
+            }),
+            buyer_unfilled.owner.from_arcis(ShieldedAmount {
+                amount: buyer_left - base_moved,
+            }),
+            seller_unfilled.owner.from_arcis(ShieldedAmount {
+                amount: seller_left - base_moved,
+            }),
+            buyer_base.owner.from_arcis(ShieldedAmount {
+                amount: buyer_base_balance + base_moved,
+            }),
+            seller_quote.owner.from_arcis(ShieldedAmount {
+                amount: seller_quote_balance + quote_moved,
+            }),
+            settled.reveal(),
+            buyer_filled.reveal(),
+            seller_filled.reveal(),
+        )
+    }
+
+    /// Debit a withdrawal from a shielded balance
+    ///
+    /// `amount` is public (it leaves the vault as a token transfer). Only
+    /// whether the balance covered it is revealed; on failure the balance is
+    /// unchanged (apart from folding in `credit`).
+    #[instruction]
+    pub fn shielded_withdraw(
+        balance: Enc<Mxe, ShieldedAmount>,
+        has_balance: bool,
+        credit: u64,
+        amount: u64,
+    ) -> (Enc<Mxe, ShieldedAmount>, bool) {
+        let available = credited_balance(&balance.to_arcis(), has_balance, credit);
+
+        let approved = amount > 0 && available >= amount;
+        let debit = if approved { amount } else { 0 };
+
+        (
+            balance.owner.from_arcis(ShieldedAmount {
+                amount: available - debit,
+            }),
+            approved.reveal(),
+        )
+    }
+
     /// Match two orders using price-time priority
     ///
     /// This function runs in MPC and operates on encrypted data.
     /// It checks if buyer's price >= seller's price and returns match details.
+    ///
+    /// If either order is a midpoint peg, the match executes at
+    /// `reference_price` (the public midpoint supplied at match time) provided
+    /// both orders' limits accept it. Pegged orders never match without a
+    /// reference (`reference_price == 0`).
+    ///
+    /// The result is returned three times:
+    /// - encrypted for the buyer (buy_order's shared key)
+    /// - encrypted for the seller (sell_order's shared key)
+    /// - encrypted for the MXE cluster key, so the keeper can settle via MPC
     #[instruction]
     pub fn match_two_orders(
         buy_order: Enc<Shared, OrderInput>,
         sell_order: Enc<Shared, OrderInput>,
-    ) -> Enc<Shared, MatchOutput> {
+        keeper: Mxe,
+        reference_price: u64,
+    ) -> (
+        Enc<Shared, MatchOutput>,
+        Enc<Shared, MatchOutput>,
+        Enc<Mxe, MatchOutput>,
+    ) {
         // Decrypt within MPC (data never leaves encrypted state to outside)
         let buy = buy_order.to_arcis();
         let sell = sell_order.to_arcis();
 
-        // Check if match is possible (buy price >= sell price)
-        let can_match = buy.price >= sell.price;
+        // Check if match is possible (buy price >= sell price, or both
+        // limits accept the midpoint when pegged)
+        let pegged = is_pegged(&buy) || is_pegged(&sell);
+        let can_match = if pegged {
+            reference_price > 0
+                && accepts_price(&buy, reference_price)
+                && accepts_price(&sell, reference_price)
+        } else {
+            buy.price >= sell.price
+        };
 
         // Calculate matched amount (minimum of both orders)
         let matched_amount = if can_match {
@@ -45,9 +430,11 @@ mod circuits {
             0 // No match
         };
 
-        // Determine execution price using time priority
-        // Earlier order's price is used (maker's price)
-        let execution_price = if buy.timestamp <= sell.timestamp {
+        // Determine execution price: the midpoint for pegged matches,
+        // otherwise time priority (earlier order's price is the maker's price)
+        let execution_price = if pegged {
+            reference_price
+        } else if buy.timestamp <= sell.timestamp {
             buy.price
         } else {
             sell.price
@@ -59,18 +446,36 @@ mod circuits {
             execution_price,
         };
 
-        // Re-encrypt result for the same owner (shared secret)
-        buy_order.owner.from_arcis(result)
+        // Re-encrypt result for each counterparty and for the cluster
+        (
+            buy_order.owner.from_arcis(result.clone()),
+            sell_order.owner.from_arcis(result.clone()),
+            keeper.from_arcis(result),
+        )
     }
 
     /// Batch match orders from a list
     /// 
     /// This function demonstrates matching multiple orders in one MPC computation.
     /// It sorts buy/sell orders by price-time priority and finds matches.
+    /// 
+    /// Partial fills carry over: after each match the remaining amounts are
+    /// decremented and only the exhausted side advances, so the leftover of the
+    /// larger order is matched against the next counterparty.
+    /// 
+    /// Pegged orders are ranked at `reference_price` and left out of the
+    /// batch if their limit rejects it (or no reference is supplied). Any
+    /// match involving a pegged order executes at `reference_price`.
+    /// 
+    /// Returns:
+    /// - One `BatchFill` per match, encrypted for the buyer and then the seller
+    ///   (indices refer to positions in the `orders` input)
+    /// - One `RemainingOutput` per input order, encrypted for its owner
     #[instruction]
     pub fn batch_match_orders(
         orders: Vec<Enc<Shared, OrderInput>>,
-    ) -> Vec<Enc<Shared, MatchOutput>> {
+        reference_price: u64,
+    ) -> (Vec<Enc<Shared, BatchFill>>, Vec<Enc<Shared, RemainingOutput>>) {
         let mut decrypted_orders: Vec<OrderInput> = Vec::new();
         let mut owners: Vec<Shared> = Vec::new();
 
@@ -80,79 +485,248 @@ mod circuits {
             owners.push(order.owner.clone());
         }
 
-        // Separate buy and sell orders
-        let mut buy_orders: Vec<(usize, &OrderInput)> = Vec::new();
-        let mut sell_orders: Vec<(usize, &OrderInput)> = Vec::new();
+        // Track unfilled quantity per order (indexed like `orders`)
+        let mut remaining: Vec<u64> = Vec::new();
+        for order in decrypted_orders.iter() {
+            remaining.push(order.amount);
+        }
+
+        // Separate buy and sell orders, with the price each one ranks at
+        // (pegged orders rank at the reference midpoint)
+        let mut buy_orders: Vec<(usize, u64, &OrderInput)> = Vec::new();
+        let mut sell_orders: Vec<(usize, u64, &OrderInput)> = Vec::new();
 
         for (idx, order) in decrypted_orders.iter().enumerate() {
+            let effective_price = if is_pegged(order) {
+                if reference_price == 0 || !accepts_price(order, reference_price) {
+                    continue; // Peg cannot trade this round
+                }
+                reference_price
+            } else {
+                order.price
+            };
+
             if order.side == 0 {
-                buy_orders.push((idx, order));
+                buy_orders.push((idx, effective_price, order));
             } else {
-                sell_orders.push((idx, order));
+                sell_orders.push((idx, effective_price, order));
             }
         }
 
         // Sort buy orders: highest price first, then oldest
         buy_orders.sort_by(|a, b| {
-            if a.1.price != b.1.price {
-                b.1.price.cmp(&a.1.price) // Descending price
+            if a.1 != b.1 {
+                b.1.cmp(&a.1) // Descending price
             } else {
-                a.1.timestamp.cmp(&b.1.timestamp) // Ascending timestamp
+                a.2.timestamp.cmp(&b.2.timestamp) // Ascending timestamp
             }
         });
 
         // Sort sell orders: lowest price first, then oldest
         sell_orders.sort_by(|a, b| {
-            if a.1.price != b.1.price {
-                a.1.price.cmp(&b.1.price) // Ascending price
+            if a.1 != b.1 {
+                a.1.cmp(&b.1) // Ascending price
             } else {
-                a.1.timestamp.cmp(&b.1.timestamp) // Ascending timestamp
+                a.2.timestamp.cmp(&b.2.timestamp) // Ascending timestamp
             }
         });
 
         // Match orders
-        let mut results: Vec<Enc<Shared, MatchOutput>> = Vec::new();
+        let mut fills: Vec<Enc<Shared, BatchFill>> = Vec::new();
         let mut buy_idx = 0;
         let mut sell_idx = 0;
 
         while buy_idx < buy_orders.len() && sell_idx < sell_orders.len() {
-            let (buy_pos, buy_order) = buy_orders[buy_idx];
-            let (sell_pos, sell_order) = sell_orders[sell_idx];
+            let (buy_pos, buy_price, buy_order) = buy_orders[buy_idx];
+            let (sell_pos, sell_price, sell_order) = sell_orders[sell_idx];
 
             // Check if prices cross
-            if buy_order.price >= sell_order.price {
-                // Match found
-                let matched_amount = if buy_order.amount < sell_order.amount {
-                    buy_order.amount
-                } else {
-                    sell_order.amount
-                };
+            if buy_price < sell_price {
+                // No more matches possible
+                break;
+            }
 
-                let execution_price = if buy_order.timestamp <= sell_order.timestamp {
+            // Match against what is left of each order, not the original size
+            let matched_amount = if remaining[buy_pos] < remaining[sell_pos] {
+                remaining[buy_pos]
+            } else {
+                remaining[sell_pos]
+            };
+
+            if matched_amount > 0 {
+                let execution_price = if is_pegged(buy_order) || is_pegged(sell_order) {
+                    reference_price
+                } else if buy_order.timestamp <= sell_order.timestamp {
                     buy_order.price
                 } else {
                     sell_order.price
                 };
 
-                let result = MatchOutput {
+                remaining[buy_pos] -= matched_amount;
+                remaining[sell_pos] -= matched_amount;
+
+                let fill = BatchFill {
+                    buy_index: buy_pos as u64,
+                    sell_index: sell_pos as u64,
                     matched_amount,
                     execution_price,
                 };
 
-                // Encrypt result for buyer's owner
-                results.push(owners[buy_pos].from_arcis(result.clone()));
-                // Encrypt result for seller's owner  
-                results.push(owners[sell_pos].from_arcis(result));
+                // Encrypt fill for buyer's owner
+                fills.push(owners[buy_pos].from_arcis(fill.clone()));
+                // Encrypt fill for seller's owner
+                fills.push(owners[sell_pos].from_arcis(fill));
+            }
 
+            // Advance only the side(s) that are now exhausted
+            if remaining[buy_pos] == 0 {
                 buy_idx += 1;
+            }
+            if remaining[sell_pos] == 0 {
                 sell_idx += 1;
+            }
+        }
+
+        // Encrypt each order's remaining amount for its owner
+        let mut remaining_outputs: Vec<Enc<Shared, RemainingOutput>> = Vec::new();
+        for (idx, owner) in owners.iter().enumerate() {
+            remaining_outputs.push(owner.from_arcis(RemainingOutput {
+                remaining: remaining[idx],
+            }));
+        }
+
+        (fills, remaining_outputs)
+    }
+
+    /// Orders per batch auction computation
+    const AUCTION_BATCH_SIZE: usize = 8;
+
+    /// Clear one batch auction epoch at a single uniform price
+    ///
+    /// `orders[..order_count]` are the epoch's orders (later slots are
+    /// padding) and `filled[i]` is what order `i` already filled in earlier
+    /// epochs. Midpoint-pegged orders sit auctions out.
+    ///
+    /// The clearing price is the limit price of one of the orders that
+    /// maximises matched volume, then minimises the demand / supply
+    /// imbalance, then is lowest. Buys at or above it and sells at or below
+    /// it are eligible; the larger side is filled pro rata to open size, with
+    /// rounding dust handed out in input order so both sides fill exactly the
+    /// same volume.
+    ///
+    /// Returns the clearing price (0 when nothing crosses), each order's fill
+    /// and whether each order is now completely filled, all revealed: fills
+    /// become public token transfers at settlement anyway.
+    #[instruction]
+    pub fn clear_batch_auction(
+        orders: [Enc<Shared, OrderInput>; AUCTION_BATCH_SIZE],
+        filled: [u64; AUCTION_BATCH_SIZE],
+        order_count: u64,
+    ) -> (u64, [u64; AUCTION_BATCH_SIZE], [bool; AUCTION_BATCH_SIZE]) {
+        let mut is_buy = [false; AUCTION_BATCH_SIZE];
+        let mut prices = [0u64; AUCTION_BATCH_SIZE];
+        let mut open = [0u64; AUCTION_BATCH_SIZE];
+
+        for i in 0..AUCTION_BATCH_SIZE {
+            let order = orders[i].to_arcis();
+            let listed = (i as u64) < order_count && !is_pegged(&order);
+            is_buy[i] = order.side == 0;
+            prices[i] = order.price;
+            open[i] = if listed && order.amount > filled[i] {
+                order.amount - filled[i]
             } else {
-                // No more matches possible
-                break;
+                0
+            };
+        }
+
+        // Try every limit price in the batch as the clearing price
+        let mut clearing_price = 0u64;
+        let mut best_volume = 0u64;
+        let mut best_imbalance = 0u64;
+        let mut best_demand = 0u64;
+        let mut best_supply = 0u64;
+
+        for c in 0..AUCTION_BATCH_SIZE {
+            let candidate = prices[c];
+            let mut demand = 0u64;
+            let mut supply = 0u64;
+            for i in 0..AUCTION_BATCH_SIZE {
+                if is_buy[i] && prices[i] >= candidate {
+                    demand += open[i];
+                }
+                if !is_buy[i] && prices[i] <= candidate {
+                    supply += open[i];
+                }
+            }
+
+            let volume = if demand < supply { demand } else { supply };
+            let imbalance = if demand > supply {
+                demand - supply
+            } else {
+                supply - demand
+            };
+            let better = volume > best_volume
+                || (volume == best_volume
+                    && volume > 0
+                    && (imbalance < best_imbalance
+                        || (imbalance == best_imbalance && candidate < clearing_price)));
+
+            if open[c] > 0 && better {
+                clearing_price = candidate;
+                best_volume = volume;
+                best_imbalance = imbalance;
+                best_demand = demand;
+                best_supply = supply;
+            }
+        }
+
+        // Pro-rata fills on each side of the clearing price
+        let mut eligible = [false; AUCTION_BATCH_SIZE];
+        let mut fills = [0u64; AUCTION_BATCH_SIZE];
+        let mut buy_dust = best_volume;
+        let mut sell_dust = best_volume;
+
+        for i in 0..AUCTION_BATCH_SIZE {
+            eligible[i] = best_volume > 0
+                && open[i] > 0
+                && ((is_buy[i] && prices[i] >= clearing_price)
+                    || (!is_buy[i] && prices[i] <= clearing_price));
+            let side_total = if is_buy[i] { best_demand } else { best_supply };
+            if eligible[i] {
+                fills[i] = ((open[i] as u128) * (best_volume as u128) / (side_total as u128)) as u64;
+                if is_buy[i] {
+                    buy_dust -= fills[i];
+                } else {
+                    sell_dust -= fills[i];
+                }
             }
         }
 
-        results
+        for i in 0..AUCTION_BATCH_SIZE {
+            let room = open[i] - fills[i];
+            let dust = if is_buy[i] { buy_dust } else { sell_dust };
+            let extra = if !eligible[i] {
+                0
+            } else if room < dust {
+                room
+            } else {
+                dust
+            };
+            fills[i] += extra;
+            if is_buy[i] {
+                buy_dust -= extra;
+            } else {
+                sell_dust -= extra;
+            }
+        }
+
+        let mut revealed_fills = [0u64; AUCTION_BATCH_SIZE];
+        let mut revealed_done = [false; AUCTION_BATCH_SIZE];
+        for i in 0..AUCTION_BATCH_SIZE {
+            revealed_fills[i] = fills[i].reveal();
+            revealed_done[i] = (eligible[i] && fills[i] == open[i]).reveal();
+        }
+
+        (clearing_price.reveal(), revealed_fills, revealed_done)
     }
 }
-
@@ -2,9 +2,35 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 use arcium_anchor::prelude::*;
 
+pub mod oracle;
+
+use oracle::{OracleReader, PythPriceAccount};
+
 // Computation definition offset for match_two_orders encrypted instruction
 const COMP_DEF_OFFSET_MATCH: u32 = comp_def_offset("match_two_orders");
 
+// Computation definition offset for verify_posted_amount encrypted instruction
+const COMP_DEF_OFFSET_VERIFY_AMOUNT: u32 = comp_def_offset("verify_posted_amount");
+
+// Computation definition offset for verify_escrow_coverage encrypted instruction
+const COMP_DEF_OFFSET_VERIFY_ESCROW: u32 = comp_def_offset("verify_escrow_coverage");
+
+// Computation definition offsets for encrypted volume statistics
+const COMP_DEF_OFFSET_UPDATE_VOLUME: u32 = comp_def_offset("update_volume");
+const COMP_DEF_OFFSET_REVEAL_VOLUME: u32 = comp_def_offset("reveal_period_volume");
+
+// Computation definition offset for check_trigger encrypted instruction
+const COMP_DEF_OFFSET_CHECK_TRIGGER: u32 = comp_def_offset("check_trigger");
+
+// Computation definition offsets for the shielded escrow vaults
+const COMP_DEF_OFFSET_SHIELDED_ALLOCATE: u32 = comp_def_offset("shielded_allocate");
+const COMP_DEF_OFFSET_SHIELDED_RELEASE: u32 = comp_def_offset("shielded_release");
+const COMP_DEF_OFFSET_SHIELDED_SETTLE: u32 = comp_def_offset("shielded_settle");
+const COMP_DEF_OFFSET_SHIELDED_WITHDRAW: u32 = comp_def_offset("shielded_withdraw");
+
+// Computation definition offset for clear_batch_auction encrypted instruction
+const COMP_DEF_OFFSET_CLEAR_AUCTION: u32 = comp_def_offset("clear_batch_auction");
+
 declare_id!("Dk9p88PPmrApGwhpTZAYQkuZApVHEnquxxeng1sCndci");
 
 #[arcium_program]
@@ -31,12 +57,23 @@ pub mod shadow_swap {
         order_book.active_orders = 0;
         order_book.encrypted_volume_base = vec![];
         order_book.encrypted_volume_quote = vec![];
+        order_book.encrypted_volume_nonce = 0;
+        order_book.volume_version = 0;
+        order_book.volume_period_start = clock.unix_timestamp;
         order_book.created_at = clock.unix_timestamp;
         order_book.last_trade_at = 0;
         order_book.fee_bps = fee_bps;
         order_book.fee_collector = ctx.accounts.fee_collector.key();
         order_book.min_base_order_size = min_base_order_size;
         order_book.is_active = true;
+        order_book.oracle = Pubkey::default();
+        order_book.oracle_max_staleness = 0;
+        order_book.oracle_max_confidence_bps = 0;
+        order_book.oracle_price_expo = 0;
+        order_book.auction_epoch_duration = 0;
+        order_book.current_epoch = 0;
+        order_book.epoch_started_at = clock.unix_timestamp;
+        order_book.auction_floor = 0;
         order_book.bump = ctx.bumps.order_book;
 
         msg!("Order book initialized: {} / {}", base_mint, quote_mint);
@@ -44,15 +81,49 @@ pub mod shadow_swap {
     }
 
     /// Place a new encrypted order
+    /// 
+    /// `posted_amount` is the plaintext quantity moved into escrow: base tokens
+    /// for sell orders, quote tokens for buy orders. It is checked against the
+    /// book minimum here; whether it covers the encrypted order is checked in
+    /// MPC by `invoke_verify_escrow_coverage`, and whether it equals
+    /// `encrypted_amount` by `invoke_verify_posted_amount`.
+    /// 
+    /// New orders start in `ORDER_STATUS_PENDING_VERIFICATION` and only become
+    /// matchable once `verify_escrow_coverage_callback` confirms the escrow
+    /// covers the encrypted order.
+    /// 
+    /// `encryption_pubkey` / `encryption_nonce` are the owner's x25519 key and
+    /// the nonce the `OrderInput` ciphertexts in `cipher_payload` are encrypted
+    /// under. They are stored with the order and every MPC computation on it
+    /// uses them.
     pub fn place_order(
         ctx: Context<PlaceOrder>,
         cipher_payload: Vec<u8>,
         encrypted_amount: Vec<u8>,
+        posted_amount: u64,
+        encryption_pubkey: [u8; 32],
+        encryption_nonce: u128,
     ) -> Result<()> {
         require!(
             ctx.accounts.order_book.is_active,
             ShadowSwapError::OrderBookNotActive
         );
+        require!(posted_amount > 0, ShadowSwapError::OrderTooSmall);
+
+        // Escrow must hold one side of this book; sell orders also respect the
+        // minimum base order size
+        let token_mint = ctx.accounts.token_mint.key();
+        if token_mint == ctx.accounts.order_book.base_mint {
+            require!(
+                posted_amount >= ctx.accounts.order_book.min_base_order_size,
+                ShadowSwapError::OrderTooSmall
+            );
+        } else {
+            require!(
+                token_mint == ctx.accounts.order_book.quote_mint,
+                ShadowSwapError::InvalidTokenMint
+            );
+        }
         require!(
             cipher_payload.len() <= MAX_CIPHER_PAYLOAD_SIZE,
             ShadowSwapError::InvalidCipherPayload
@@ -82,12 +153,19 @@ pub mod shadow_swap {
         order.owner = ctx.accounts.owner.key();
         order.order_book = order_book.key();
         order.cipher_payload = cipher_payload;
-        order.status = ORDER_STATUS_ACTIVE;
+        order.encryption_pubkey = encryption_pubkey;
+        order.encryption_nonce = encryption_nonce;
+        order.status = ORDER_STATUS_PENDING_VERIFICATION;
         order.encrypted_remaining = encrypted_amount.clone();
         order.escrow = escrow.key();
         order.created_at = clock.unix_timestamp;
         order.updated_at = clock.unix_timestamp;
         order.order_id = order_id;
+        order.epoch = order_book.current_epoch;
+        order.auction_filled = 0;
+        order.continuous_filled = false;
+        order.pending_matches = 0;
+        order.fill = RecordedFill::default();
         order.bump = ctx.bumps.order;
 
         // Initialize escrow
@@ -98,11 +176,15 @@ pub mod shadow_swap {
         escrow.token_mint = ctx.accounts.token_mint.key();
         escrow.encrypted_amount = encrypted_amount.clone();
         escrow.encrypted_remaining = encrypted_amount;
+        escrow.encrypted_remaining_nonce = 0;
+        escrow.encrypted_unfilled = [0u8; 32];
+        escrow.encrypted_unfilled_nonce = 0;
+        escrow.vault = Pubkey::default();
         escrow.created_at = clock.unix_timestamp;
         escrow.bump = ctx.bumps.escrow;
 
-        // Transfer tokens to escrow (amount is encrypted, so we trust the client sent correct amount)
-        // In production, you'd verify the encrypted amount matches the actual transfer
+        // Transfer the posted amount to escrow. Coverage of the encrypted order
+        // is checked in MPC by verify_escrow_coverage before it can be matched.
         token::transfer(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
@@ -112,7 +194,7 @@ pub mod shadow_swap {
                     authority: ctx.accounts.owner.to_account_info(),
                 },
             ),
-            ctx.accounts.user_token_account.amount, // TODO: Parse from encrypted_amount in production
+            posted_amount,
         )?;
 
         msg!("Order placed: ID {}", order_id);
@@ -133,7 +215,11 @@ pub mod shadow_swap {
 
         // Verify order can be cancelled
         require!(
-            order.status == ORDER_STATUS_ACTIVE || order.status == ORDER_STATUS_PARTIAL,
+            order.status == ORDER_STATUS_ACTIVE
+                || order.status == ORDER_STATUS_PARTIAL
+                || order.status == ORDER_STATUS_PENDING_VERIFICATION
+                || order.status == ORDER_STATUS_AMOUNT_MISMATCH
+                || order.status == ORDER_STATUS_CONDITIONAL,
             ShadowSwapError::InvalidOrderStatus
         );
 
@@ -222,13 +308,25 @@ pub mod shadow_swap {
     /// 
     /// This function is called by the Arcium MPC network with the results
     /// of the privacy-preserving matching algorithm. It processes each match
-    /// and updates order statuses accordingly.
+    /// and moves both orders to `ORDER_STATUS_MATCHED_PENDING` so they can
+    /// only be settled (not cancelled or re-matched) afterwards.
+    /// 
+    /// Order and escrow accounts are passed via remaining_accounts, four per
+    /// result and in the same order as `results`:
+    /// [buyer_order_0, seller_order_0, buyer_escrow_0, seller_escrow_0, ...]
+    /// The escrows are read-only; they show both orders are escrow-backed,
+    /// since shielded orders only settle inside the vaults.
+    /// 
+    /// An order partially filled by an earlier result may appear again in a
+    /// later one (batch matching carries partial fills over). Each appearance
+    /// adds one to its `pending_matches`, and the last result's remaining
+    /// amount is the one kept.
     /// 
     /// NOTE: The #[arcium_callback] macro is a placeholder for the actual
     /// Arcium integration. In production, this would be:
     /// #[arcium_callback(mpc_program = "YOUR_MPC_PROGRAM_ID")]
-    pub fn match_callback(
-        ctx: Context<MatchCallback>,
+    pub fn match_callback<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MatchCallback<'info>>,
         results: Vec<MatchResult>,
     ) -> Result<()> {
         let callback_auth = &ctx.accounts.callback_auth;
@@ -244,8 +342,17 @@ pub mod shadow_swap {
             ShadowSwapError::CallbackAuthExpired
         );
         
+        // Two order and two escrow accounts per match result
+        require!(
+            ctx.remaining_accounts.len() == results.len() * 4,
+            ShadowSwapError::OrderNotFound
+        );
+        
         msg!("Processing {} match results", results.len());
         
+        let order_book_key = ctx.accounts.order_book.key();
+        let mut queued_orders: Vec<Pubkey> = Vec::with_capacity(results.len() * 2);
+        
         // Process each match result
         for (idx, match_result) in results.iter().enumerate() {
             msg!(
@@ -257,71 +364,104 @@ pub mod shadow_swap {
                 match_result.seller_order_id
             );
             
-            // Load buyer's order
-            let order_book_key = ctx.accounts.order_book.key();
-            let buyer_order_id_bytes = match_result.buyer_order_id.to_le_bytes();
-            let buyer_order_seeds = [
-                b"order".as_ref(),
-                order_book_key.as_ref(),
-                &buyer_order_id_bytes,
-            ];
+            require!(
+                match_result.buyer_encrypted_remaining.len() <= MAX_ENCRYPTED_AMOUNT_SIZE
+                    && match_result.seller_encrypted_remaining.len() <= MAX_ENCRYPTED_AMOUNT_SIZE,
+                ShadowSwapError::InvalidCipherPayload
+            );
+            
+            let buyer_info = &ctx.remaining_accounts[idx * 4];
+            let seller_info = &ctx.remaining_accounts[idx * 4 + 1];
+            require!(
+                buyer_info.key() != seller_info.key(),
+                ShadowSwapError::SelfMatch
+            );
+            
+            // Verify accounts are the expected order PDAs
             let (buyer_order_pda, _) = Pubkey::find_program_address(
-                &buyer_order_seeds,
+                &[
+                    ORDER_SEED,
+                    order_book_key.as_ref(),
+                    &match_result.buyer_order_id.to_le_bytes(),
+                ],
                 ctx.program_id
             );
-            
-            // Load seller's order
-            let seller_order_id_bytes = match_result.seller_order_id.to_le_bytes();
-            let seller_order_seeds = [
-                b"order".as_ref(),
-                order_book_key.as_ref(),
-                &seller_order_id_bytes,
-            ];
             let (seller_order_pda, _) = Pubkey::find_program_address(
-                &seller_order_seeds,
+                &[
+                    ORDER_SEED,
+                    order_book_key.as_ref(),
+                    &match_result.seller_order_id.to_le_bytes(),
+                ],
                 ctx.program_id
             );
-            
-            // Verify buyer order account matches expected PDA
             require!(
-                buyer_order_pda == match_result.buyer_pubkey,
-                ShadowSwapError::InvalidOrderBook
+                buyer_order_pda == match_result.buyer_pubkey && buyer_info.key() == buyer_order_pda,
+                ShadowSwapError::OrderNotFound
+            );
+            require!(
+                seller_order_pda == match_result.seller_pubkey && seller_info.key() == seller_order_pda,
+                ShadowSwapError::OrderNotFound
             );
             
-            // Verify seller order account matches expected PDA
+            // Load orders (Account::try_from checks owner and discriminator)
+            let mut buyer_order: Account<'info, EncryptedOrder> = Account::try_from(buyer_info)?;
+            let mut seller_order: Account<'info, EncryptedOrder> = Account::try_from(seller_info)?;
+            
+            // Verify both orders belong to this book and are still matchable
             require!(
-                seller_order_pda == match_result.seller_pubkey,
+                buyer_order.order_book == order_book_key && seller_order.order_book == order_book_key,
                 ShadowSwapError::InvalidOrderBook
             );
+            for (order, escrow_info) in [
+                (&buyer_order, &ctx.remaining_accounts[idx * 4 + 2]),
+                (&seller_order, &ctx.remaining_accounts[idx * 4 + 3]),
+            ] {
+                require!(escrow_info.key() == order.escrow, ShadowSwapError::InvalidEscrow);
+                let escrow: Account<'info, Escrow> = Account::try_from(escrow_info)?;
+                require!(
+                    escrow.vault == Pubkey::default(),
+                    ShadowSwapError::ShieldedEscrow
+                );
+            }
+            require!(
+                is_matchable(&buyer_order, &queued_orders),
+                ShadowSwapError::OrderNotActive
+            );
+            require!(
+                is_matchable(&seller_order, &queued_orders),
+                ShadowSwapError::OrderNotActive
+            );
             
-            // NOTE: In production, you would load these accounts using remaining_accounts
-            // and verify their status. For the MVP, we're documenting the intended logic:
-            //
-            // let buyer_order = &mut ctx.remaining_accounts[buyer_idx];
-            // let seller_order = &mut ctx.remaining_accounts[seller_idx];
-            //
-            // Verify both orders are active
-            // require!(
-            //     buyer_order.status == ORDER_STATUS_ACTIVE,
-            //     ShadowSwapError::OrderNotActive
-            // );
-            // require!(
-            //     seller_order.status == ORDER_STATUS_ACTIVE,
-            //     ShadowSwapError::OrderNotActive
-            // );
-            //
             // Update order statuses to "Matched_Pending_Exec" (status = 5)
-            // buyer_order.status = ORDER_STATUS_MATCHED_PENDING;
-            // buyer_order.encrypted_remaining = match_result.encrypted_remaining.clone();
-            // buyer_order.updated_at = clock.unix_timestamp;
-            //
-            // seller_order.status = ORDER_STATUS_MATCHED_PENDING;
-            // seller_order.encrypted_remaining = match_result.encrypted_remaining.clone();
-            // seller_order.updated_at = clock.unix_timestamp;
+            buyer_order.status = ORDER_STATUS_MATCHED_PENDING;
+            buyer_order.encrypted_remaining = match_result.buyer_encrypted_remaining.clone();
+            buyer_order.pending_matches = buyer_order
+                .pending_matches
+                .checked_add(1)
+                .ok_or(ShadowSwapError::NumericalOverflow)?;
+            buyer_order.updated_at = clock.unix_timestamp;
+            
+            seller_order.status = ORDER_STATUS_MATCHED_PENDING;
+            seller_order.encrypted_remaining = match_result.seller_encrypted_remaining.clone();
+            seller_order.pending_matches = seller_order
+                .pending_matches
+                .checked_add(1)
+                .ok_or(ShadowSwapError::NumericalOverflow)?;
+            seller_order.updated_at = clock.unix_timestamp;
+            
+            for key in [buyer_info.key(), seller_info.key()] {
+                if !queued_orders.contains(&key) {
+                    queued_orders.push(key);
+                }
+            }
+            
+            // Persist changes back to the account data
+            buyer_order.exit(ctx.program_id)?;
+            seller_order.exit(ctx.program_id)?;
             
             // Emit match queued event
             emit!(MatchQueued {
-                order_book: ctx.accounts.order_book.key(),
+                order_book: order_book_key,
                 buyer: match_result.buyer_pubkey,
                 seller: match_result.seller_pubkey,
                 buyer_order_id: match_result.buyer_order_id,
@@ -336,6 +476,34 @@ pub mod shadow_swap {
         Ok(())
     }
 
+    /// Configure the reference price oracle for midpoint-pegged orders
+    /// 
+    /// `oracle` is a Pyth price account; `Pubkey::default()` disables pegged
+    /// matching. `price_expo` is the exponent of the book's price units (e.g.
+    /// -6 for micro-USDC per base token) that oracle prices are rescaled to.
+    pub fn configure_oracle(
+        ctx: Context<ConfigureOracle>,
+        oracle: Pubkey,
+        max_staleness: i64,
+        max_confidence_bps: u16,
+        price_expo: i32,
+    ) -> Result<()> {
+        require!(max_staleness >= 0, ShadowSwapError::InvalidOracleConfiguration);
+        require!(
+            max_confidence_bps <= 10000,
+            ShadowSwapError::InvalidOracleConfiguration
+        );
+
+        let order_book = &mut ctx.accounts.order_book;
+        order_book.oracle = oracle;
+        order_book.oracle_max_staleness = max_staleness;
+        order_book.oracle_max_confidence_bps = max_confidence_bps;
+        order_book.oracle_price_expo = price_expo;
+
+        msg!("Order book oracle set to {}", oracle);
+        Ok(())
+    }
+
     /// Create callback authorization for keeper
     pub fn create_callback_auth(
         ctx: Context<CreateCallbackAuth>,
@@ -381,24 +549,50 @@ pub mod shadow_swap {
     /// The actual matching happens inside the Arcium MPC network where:
     /// 1. Orders are decrypted in a distributed manner (no single party sees plaintext)
     /// 2. Price-time priority matching is performed on encrypted data
-    /// 3. Results are re-encrypted and returned via callback
+    /// 3. Results are re-encrypted for the buyer, the seller and the MXE
+    ///    cluster, and returned via callback
+    /// 
+    /// Each order is passed as the ciphertexts of its `OrderInput` fields
+    /// (side, price, amount, timestamp, flags), which must be the prefix of the
+    /// order's `cipher_payload`; they are read under the key and nonce stored
+    /// on the order.
+    /// 
+    /// `reference_price` is the public midpoint used for pegged orders. A
+    /// non-zero value must fall within the confidence interval of the book's
+    /// oracle, which must be fresh and tight enough; zero means pegged orders
+    /// cannot match in this computation.
+    /// 
+    /// Both orders are passed to the callback, which records the fill on them.
     pub fn invoke_matching(
         ctx: Context<InvokeMatching>,
         computation_offset: u64,
-        buy_order_ciphertext: [u8; 32],
-        sell_order_ciphertext: [u8; 32],
-        client_pubkey: [u8; 32],
-        nonce: u128,
+        buy_order_ciphertexts: [[u8; 32]; ORDER_INPUT_FIELDS],
+        sell_order_ciphertexts: [[u8; 32]; ORDER_INPUT_FIELDS],
+        mxe_nonce: u128,
+        reference_price: u64,
     ) -> Result<()> {
         msg!("Queueing encrypted matching computation");
 
+        if reference_price > 0 {
+            let oracle = ctx
+                .accounts
+                .oracle
+                .as_ref()
+                .ok_or(ShadowSwapError::OracleNotConfigured)?;
+            let (price, conf) = validated_oracle_price(&ctx.accounts.order_book, oracle)?;
+            require!(
+                reference_price >= price.saturating_sub(conf)
+                    && reference_price <= price.saturating_add(conf),
+                ShadowSwapError::ReferencePriceOutOfRange
+            );
+        }
+
         // Prepare arguments for the MPC computation
-        let args = vec![
-            Argument::ArcisPubkey(client_pubkey),
-            Argument::PlaintextU128(nonce),
-            Argument::EncryptedU8(buy_order_ciphertext),
-            Argument::EncryptedU8(sell_order_ciphertext),
-        ];
+        let mut args = order_input_arguments(&ctx.accounts.buy_order, &buy_order_ciphertexts)?;
+        args.extend(order_input_arguments(&ctx.accounts.sell_order, &sell_order_ciphertexts)?);
+        // Nonce for the MXE-encrypted (keeper) copy of the result
+        args.push(Argument::PlaintextU128(mxe_nonce));
+        args.push(Argument::PlaintextU64(reference_price));
 
         // Set PDA bump
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
@@ -409,7 +603,16 @@ pub mod shadow_swap {
             computation_offset,
             args,
             None,
-            vec![ArciumMatchCallback::callback_ix(&[])],
+            vec![ArciumMatchCallback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: ctx.accounts.buy_order.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.sell_order.key(),
+                    is_writable: true,
+                },
+            ])],
         )?;
 
         msg!("Matching computation queued with offset: {}", computation_offset);
@@ -421,29 +624,91 @@ pub mod shadow_swap {
     /// This function is automatically called by the Arcium MPC network
     /// after the encrypted matching computation completes. It receives
     /// the encrypted match results and updates order statuses accordingly.
+    /// 
+    /// The circuit returns the same `MatchOutput` three times: encrypted for
+    /// the buyer, for the seller and for the MXE cluster (keeper). All three
+    /// are emitted so each trader can decrypt and verify their own fill.
+    /// 
+    /// The MXE copy is also recorded on both orders (`order.fill`), binding
+    /// the fill to the matched pair. It is only recorded while both orders
+    /// are still on the book and their last fill is not waiting for
+    /// `invoke_update_volume`, so a fill is never replaced before it has been
+    /// settled and counted.
     #[arcium_callback(encrypted_ix = "match_two_orders")]
     pub fn arcium_match_callback(
         ctx: Context<ArciumMatchCallback>,
         output: ComputationOutputs<MatchTwoOrdersOutput>,
     ) -> Result<()> {
-        let result = match output {
-            ComputationOutputs::Success(MatchTwoOrdersOutput { field_0 }) => field_0,
+        let (buyer_result, seller_result, keeper_result) = match output {
+            ComputationOutputs::Success(MatchTwoOrdersOutput {
+                field_0:
+                    MatchTwoOrdersOutputStruct0 {
+                        field_0,
+                        field_1,
+                        field_2,
+                    },
+            }) => (field_0, field_1, field_2),
             _ => return Err(ErrorCode::AbortedComputation.into()),
         };
 
         let clock = Clock::get()?;
+        let buy_order = &mut ctx.accounts.buy_order;
+        let sell_order = &mut ctx.accounts.sell_order;
 
         msg!(
             "Arcium callback received - matched_amount ciphertext: {:?}",
-            &result.ciphertexts[0][..8]
+            &keeper_result.ciphertexts[0][..8]
         );
 
-        // Emit event with encrypted results
+        let on_book = |order: &EncryptedOrder| {
+            (order.status == ORDER_STATUS_ACTIVE || order.status == ORDER_STATUS_PARTIAL)
+                && !order.fill.volume_pending
+        };
+        let recorded = on_book(buy_order) && on_book(sell_order);
+        if recorded {
+            let (buy_key, sell_key) = (buy_order.key(), sell_order.key());
+            for (order, counterparty, is_buyer) in [
+                (&mut **buy_order, sell_key, true),
+                (&mut **sell_order, buy_key, false),
+            ] {
+                order.fill = RecordedFill {
+                    counterparty,
+                    is_buyer,
+                    nonce: keeper_result.nonce,
+                    ciphertexts: keeper_result.ciphertexts,
+                    unsettled: true,
+                    volume_pending: false,
+                };
+                order.updated_at = clock.unix_timestamp;
+            }
+        }
+
+        // Emit event with encrypted results for every party
         emit!(ArciumMatchCompleted {
-            order_book: ctx.accounts.order_book.key(),
-            encrypted_matched_amount: result.ciphertexts[0].to_vec(),
-            encrypted_execution_price: result.ciphertexts[1].to_vec(),
-            nonce: result.nonce.to_le_bytes().to_vec(),
+            order_book: buy_order.order_book,
+            buy_order: buy_order.key(),
+            sell_order: sell_order.key(),
+            buyer_order_id: buy_order.order_id,
+            seller_order_id: sell_order.order_id,
+            recorded,
+            buyer_output: EncryptedMatchOutput {
+                encryption_key: buyer_result.encryption_key,
+                nonce: buyer_result.nonce.to_le_bytes().to_vec(),
+                encrypted_matched_amount: buyer_result.ciphertexts[0].to_vec(),
+                encrypted_execution_price: buyer_result.ciphertexts[1].to_vec(),
+            },
+            seller_output: EncryptedMatchOutput {
+                encryption_key: seller_result.encryption_key,
+                nonce: seller_result.nonce.to_le_bytes().to_vec(),
+                encrypted_matched_amount: seller_result.ciphertexts[0].to_vec(),
+                encrypted_execution_price: seller_result.ciphertexts[1].to_vec(),
+            },
+            keeper_output: EncryptedMatchOutput {
+                encryption_key: [0u8; 32],
+                nonce: keeper_result.nonce.to_le_bytes().to_vec(),
+                encrypted_matched_amount: keeper_result.ciphertexts[0].to_vec(),
+                encrypted_execution_price: keeper_result.ciphertexts[1].to_vec(),
+            },
             timestamp: clock.unix_timestamp,
         });
 
@@ -451,679 +716,4191 @@ pub mod shadow_swap {
         Ok(())
     }
 
-    /// Submit match results and execute settlement
+    /// Initialize the computation definition for posted amount verification
     /// 
-    /// This instruction is called by the authorized keeper bot after matching orders
-    /// off-chain. It performs the actual token transfers to settle the trade.
+    /// This must be called once after deployment to register the
+    /// verify_posted_amount encrypted instruction with Arcium.
+    pub fn init_verify_amount_comp_def(ctx: Context<InitVerifyAmountCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        msg!("Verify amount computation definition initialized");
+        Ok(())
+    }
+
+    /// Verify in MPC that an order's encrypted amount equals its escrow
     /// 
-    /// Flow:
-    /// 1. Verify keeper authorization via callback_auth
-    /// 2. Calculate transfer amounts based on matched_amount and execution_price
-    /// 3. Transfer quote tokens (USDC) from buyer's escrow to seller
-    /// 4. Transfer base tokens (WSOL) from seller's escrow to buyer
-    /// 5. Update order statuses to "Executed"
-    /// 6. Emit settlement event
-    pub fn submit_match_results(
-        ctx: Context<SubmitMatchResults>,
-        match_input: MatchResultInput,
+    /// The first 32 bytes of `escrow.encrypted_amount` must be the ciphertext
+    /// of the order amount, encrypted under the order's stored key with
+    /// `nonce`. The circuit compares it with the escrowed token balance and
+    /// only reveals whether they are equal. Orders that fail the check are
+    /// flagged and can no longer be matched, only cancelled.
+    /// 
+    /// Only the order owner or a keeper with a `callback_auth` for the book
+    /// may run the check, since a failed check flags the order for good.
+    pub fn invoke_verify_posted_amount(
+        ctx: Context<InvokeVerifyPostedAmount>,
+        computation_offset: u64,
+        nonce: u128,
+        amount_ciphertext: [u8; 32],
     ) -> Result<()> {
-        let callback_auth = &ctx.accounts.callback_auth;
-        let clock = Clock::get()?;
-
-        // Verify callback authorization
-        require!(
-            callback_auth.is_active,
-            ShadowSwapError::UnauthorizedCallback
-        );
-        require!(
-            callback_auth.expires_at > clock.unix_timestamp,
-            ShadowSwapError::CallbackAuthExpired
-        );
-        require!(
-            callback_auth.authority == ctx.accounts.keeper.key(),
-            ShadowSwapError::UnauthorizedCallback
-        );
-
-        let buyer_order = &mut ctx.accounts.buyer_order;
-        let seller_order = &mut ctx.accounts.seller_order;
-
-        // Verify orders are active or partially filled
+        let status = ctx.accounts.order.status;
         require!(
-            buyer_order.status == ORDER_STATUS_ACTIVE || buyer_order.status == ORDER_STATUS_PARTIAL,
+            status == ORDER_STATUS_ACTIVE || status == ORDER_STATUS_PENDING_VERIFICATION,
             ShadowSwapError::InvalidOrderStatus
         );
+        check_order_invoker(
+            &ctx.accounts.payer.key(),
+            &ctx.accounts.order,
+            &ctx.accounts.callback_auth,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        // Bind the MPC input to the ciphertext stored at placement time
         require!(
-            seller_order.status == ORDER_STATUS_ACTIVE || seller_order.status == ORDER_STATUS_PARTIAL,
-            ShadowSwapError::InvalidOrderStatus
+            ctx.accounts.escrow.encrypted_amount.get(..32) == Some(&amount_ciphertext[..]),
+            ShadowSwapError::InvalidCipherPayload
         );
 
-        // Calculate transfer amounts
-        // matched_amount is in base token (WSOL) units
-        // execution_price is quote tokens (USDC) per base token
-        // For simplicity in MVP, we assume execution_price includes decimals handling
-        let quote_amount = match_input.matched_amount
-            .checked_mul(match_input.execution_price)
-            .ok_or(ShadowSwapError::NumericalOverflow)?;
-
-        msg!(
-            "Settling match: buyer={}, seller={}, amount={}, price={}, quote_total={}",
-            match_input.buyer_pubkey,
-            match_input.seller_pubkey,
-            match_input.matched_amount,
-            match_input.execution_price,
-            quote_amount
-        );
+        let posted_amount = ctx.accounts.escrow_token_account.amount;
 
-        // Transfer quote tokens (USDC) from buyer's escrow to seller
-        // Buyer's escrow authority is the buyer_escrow PDA
-        let buyer_order_key = buyer_order.key();
-        let buyer_escrow_seeds = &[
-            ESCROW_SEED,
-            buyer_order_key.as_ref(),
-            &[ctx.accounts.buyer_escrow.bump],
+        let args = vec![
+            Argument::ArcisPubkey(ctx.accounts.order.encryption_pubkey),
+            Argument::PlaintextU128(nonce),
+            Argument::EncryptedU64(amount_ciphertext),
+            Argument::PlaintextU64(posted_amount),
         ];
-        let buyer_escrow_signer = &[&buyer_escrow_seeds[..]];
 
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.buyer_escrow_token_account.to_account_info(),
-                    to: ctx.accounts.seller_token_account.to_account_info(),
-                    authority: ctx.accounts.buyer_escrow.to_account_info(),
-                },
-                buyer_escrow_signer,
-            ),
-            quote_amount,
-        )?;
-
-        // Transfer base tokens (WSOL) from seller's escrow to buyer
-        // Seller's escrow authority is the seller_escrow PDA
-        let seller_order_key = seller_order.key();
-        let seller_escrow_seeds = &[
-            ESCROW_SEED,
-            seller_order_key.as_ref(),
-            &[ctx.accounts.seller_escrow.bump],
-        ];
-        let seller_escrow_signer = &[&seller_escrow_seeds[..]];
+        // Set PDA bump
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
 
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.seller_escrow_token_account.to_account_info(),
-                    to: ctx.accounts.buyer_token_account.to_account_info(),
-                    authority: ctx.accounts.seller_escrow.to_account_info(),
-                },
-                seller_escrow_signer,
-            ),
-            match_input.matched_amount,
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![VerifyPostedAmountCallback::callback_ix(&[CallbackAccount {
+                pubkey: ctx.accounts.order.key(),
+                is_writable: true,
+            }])],
         )?;
 
-        // Update order statuses to Executed
-        buyer_order.status = ORDER_STATUS_FILLED;
-        buyer_order.updated_at = clock.unix_timestamp;
+        msg!(
+            "Posted amount verification queued for order {}",
+            ctx.accounts.order.order_id
+        );
+        Ok(())
+    }
 
-        seller_order.status = ORDER_STATUS_FILLED;
-        seller_order.updated_at = clock.unix_timestamp;
+    /// Callback from Arcium MPC with the posted amount check result
+    #[arcium_callback(encrypted_ix = "verify_posted_amount")]
+    pub fn verify_posted_amount_callback(
+        ctx: Context<VerifyPostedAmountCallback>,
+        output: ComputationOutputs<VerifyPostedAmountOutput>,
+    ) -> Result<()> {
+        let is_consistent = match output {
+            ComputationOutputs::Success(VerifyPostedAmountOutput { field_0 }) => field_0,
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
 
-        // Update order book
-        let order_book = &mut ctx.accounts.order_book;
-        order_book.active_orders = order_book
-            .active_orders
-            .checked_sub(2) // Both orders are now filled
-            .ok_or(ShadowSwapError::NumericalOverflow)?;
-        order_book.last_trade_at = clock.unix_timestamp;
+        let order = &mut ctx.accounts.order;
+        let clock = Clock::get()?;
 
-        // Emit settlement event
-        emit!(TradeSettled {
-            order_book: order_book.key(),
-            buyer: match_input.buyer_pubkey,
-            seller: match_input.seller_pubkey,
-            buyer_order_id: buyer_order.order_id,
-            seller_order_id: seller_order.order_id,
-            base_amount: match_input.matched_amount,
-            quote_amount,
-            execution_price: match_input.execution_price,
+        // Only flag orders that have not moved on since the check was queued
+        if !is_consistent
+            && (order.status == ORDER_STATUS_ACTIVE
+                || order.status == ORDER_STATUS_PENDING_VERIFICATION)
+        {
+            order.status = ORDER_STATUS_AMOUNT_MISMATCH;
+            order.updated_at = clock.unix_timestamp;
+        }
+
+        emit!(PostedAmountVerified {
+            order_book: order.order_book,
+            order: order.key(),
+            order_id: order.order_id,
+            is_consistent,
             timestamp: clock.unix_timestamp,
         });
 
         msg!(
-            "Trade settled: buyer_order={}, seller_order={}, base={}, quote={}",
-            buyer_order.order_id,
-            seller_order.order_id,
-            match_input.matched_amount,
-            quote_amount
+            "Posted amount verification for order {}: {}",
+            order.order_id,
+            is_consistent
         );
+        Ok(())
+    }
 
+    /// Initialize the computation definition for volume updates
+    /// 
+    /// This must be called once after deployment to register the
+    /// update_volume encrypted instruction with Arcium.
+    pub fn init_update_volume_comp_def(ctx: Context<InitUpdateVolumeCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        msg!("Update volume computation definition initialized");
         Ok(())
     }
-}
 
-// ============================================================================
-// Account Structures
-// ============================================================================
+    /// Initialize the computation definition for period volume reveals
+    /// 
+    /// This must be called once after deployment to register the
+    /// reveal_period_volume encrypted instruction with Arcium.
+    pub fn init_reveal_volume_comp_def(ctx: Context<InitRevealVolumeCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        msg!("Reveal volume computation definition initialized");
+        Ok(())
+    }
 
-/// EncryptedOrder - Stores individual encrypted order data
-/// 
-/// This account stores fully encrypted order information. The on-chain program
-/// never sees plaintext order details (price, amount, side). All order data
-/// is encrypted client-side and stored as a cipher payload.
-#[account]
-pub struct EncryptedOrder {
-    /// Order owner's public key
-    pub owner: Pubkey,
-    
-    /// Order book this order belongs to
-    pub order_book: Pubkey,
-    
-    /// Encrypted order payload containing:
-    /// - Order side (buy/sell) - encrypted
-    /// - Price - encrypted
-    /// - Amount - encrypted
-    /// - Other order parameters - encrypted
-    /// Maximum size: 512 bytes for encrypted data
-    pub cipher_payload: Vec<u8>,
-    
-    /// Order status (1 = active, 2 = partially filled, 3 = filled, 4 = cancelled)
-    /// This can be public as it doesn't reveal order details
-    pub status: u8,
-    
-    /// Encrypted remaining amount (updates as order fills)
-    pub encrypted_remaining: Vec<u8>,
+    /// Add a settled fill to the order book's encrypted running volume
+    /// 
+    /// Called by the keeper for a buyer and seller order whose recorded MPC
+    /// fill has settled. The fill and the current volume ciphertexts are both
+    /// read from the accounts, never supplied here. The callback consumes the
+    /// fill, and `volume_version` rejects any second update of it queued in
+    /// the meantime.
+    pub fn invoke_update_volume(
+        ctx: Context<InvokeUpdateVolume>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            ctx.accounts.callback_auth.expires_at > clock.unix_timestamp,
+            ShadowSwapError::CallbackAuthExpired
+        );
+
+        let fill = paired_fill(&ctx.accounts.buyer_order, &ctx.accounts.seller_order)?;
+        require!(
+            ctx.accounts.buyer_order.fill.volume_pending && ctx.accounts.seller_order.fill.volume_pending,
+            ShadowSwapError::FillVolumeNotPending
+        );
+
+        let order_book = &ctx.accounts.order_book;
+        let (has_volume, volume_ciphertexts) = stored_volume_ciphertexts(order_book)?;
+
+        let args = vec![
+            // Current volume (Enc<Mxe, VolumeStats>)
+            Argument::PlaintextU128(order_book.encrypted_volume_nonce),
+            Argument::EncryptedU64(volume_ciphertexts[0]),
+            Argument::EncryptedU64(volume_ciphertexts[1]),
+            Argument::PlaintextBool(has_volume),
+            // Fill (Enc<Mxe, MatchOutput>)
+            Argument::PlaintextU128(fill.nonce),
+            Argument::EncryptedU64(fill.ciphertexts[0]),
+            Argument::EncryptedU64(fill.ciphertexts[1]),
+            Argument::PlaintextU64(QUOTE_PRICE_SCALE),
+            Argument::PlaintextU64(order_book.volume_version),
+        ];
+
+        // Set PDA bump
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![UpdateVolumeCallback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: ctx.accounts.order_book.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.buyer_order.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.seller_order.key(),
+                    is_writable: true,
+                },
+            ])],
+        )?;
+
+        msg!("Volume update queued with offset: {}", computation_offset);
+        Ok(())
+    }
+
+    /// Callback from Arcium MPC with the updated encrypted volume
+    /// 
+    /// Results computed from an older `volume_version` are rejected so a
+    /// concurrent update or reveal is never overwritten, and the same fill is
+    /// never counted twice. An accepted result consumes the orders' fill.
+    #[arcium_callback(encrypted_ix = "update_volume")]
+    pub fn update_volume_callback(
+        ctx: Context<UpdateVolumeCallback>,
+        output: ComputationOutputs<UpdateVolumeOutput>,
+    ) -> Result<()> {
+        let (volume, version) = match output {
+            ComputationOutputs::Success(UpdateVolumeOutput {
+                field_0: UpdateVolumeOutputStruct0 { field_0, field_1 },
+            }) => (field_0, field_1),
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        let order_book = &mut ctx.accounts.order_book;
+        let clock = Clock::get()?;
+
+        require!(
+            version == order_book.volume_version,
+            ShadowSwapError::StaleVolumeState
+        );
+
+        order_book.encrypted_volume_base = volume.ciphertexts[0].to_vec();
+        order_book.encrypted_volume_quote = volume.ciphertexts[1].to_vec();
+        order_book.encrypted_volume_nonce = volume.nonce;
+        order_book.volume_version = order_book
+            .volume_version
+            .checked_add(1)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+
+        ctx.accounts.buyer_order.fill.volume_pending = false;
+        ctx.accounts.seller_order.fill.volume_pending = false;
+
+        emit!(VolumeUpdated {
+            order_book: order_book.key(),
+            volume_version: order_book.volume_version,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Encrypted volume updated (version {})", order_book.volume_version);
+        Ok(())
+    }
+
+    /// Request a reveal of the order book's aggregate volume
+    /// 
+    /// Only the order book authority can call this. The callback reveals the
+    /// base/quote volume since `volume_period_start` and starts a new period.
+    pub fn request_volume_reveal(
+        ctx: Context<RequestVolumeReveal>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let order_book = &ctx.accounts.order_book;
+        let (has_volume, volume_ciphertexts) = stored_volume_ciphertexts(order_book)?;
+        require!(has_volume, ShadowSwapError::NoVolumeToReveal);
+
+        let args = vec![
+            Argument::PlaintextU128(order_book.encrypted_volume_nonce),
+            Argument::EncryptedU64(volume_ciphertexts[0]),
+            Argument::EncryptedU64(volume_ciphertexts[1]),
+            Argument::PlaintextU64(order_book.volume_version),
+        ];
+
+        // Set PDA bump
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![RevealPeriodVolumeCallback::callback_ix(&[CallbackAccount {
+                pubkey: ctx.accounts.order_book.key(),
+                is_writable: true,
+            }])],
+        )?;
+
+        msg!("Volume reveal queued with offset: {}", computation_offset);
+        Ok(())
+    }
+
+    /// Callback from Arcium MPC with the revealed period volume
+    #[arcium_callback(encrypted_ix = "reveal_period_volume")]
+    pub fn reveal_period_volume_callback(
+        ctx: Context<RevealPeriodVolumeCallback>,
+        output: ComputationOutputs<RevealPeriodVolumeOutput>,
+    ) -> Result<()> {
+        let (base_volume, quote_volume, version) = match output {
+            ComputationOutputs::Success(RevealPeriodVolumeOutput {
+                field_0:
+                    RevealPeriodVolumeOutputStruct0 {
+                        field_0,
+                        field_1,
+                        field_2,
+                    },
+            }) => (field_0, field_1, field_2),
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        let order_book = &mut ctx.accounts.order_book;
+        let clock = Clock::get()?;
+
+        require!(
+            version == order_book.volume_version,
+            ShadowSwapError::StaleVolumeState
+        );
+
+        emit!(VolumeRevealed {
+            order_book: order_book.key(),
+            period_start: order_book.volume_period_start,
+            period_end: clock.unix_timestamp,
+            base_volume,
+            quote_volume,
+        });
+
+        // Start a new period from zero
+        order_book.encrypted_volume_base = vec![];
+        order_book.encrypted_volume_quote = vec![];
+        order_book.encrypted_volume_nonce = 0;
+        order_book.volume_period_start = clock.unix_timestamp;
+        order_book.volume_version = order_book
+            .volume_version
+            .checked_add(1)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+
+        msg!("Period volume revealed: base={}, quote={}", base_volume, quote_volume);
+        Ok(())
+    }
+
+    /// Initialize the computation definition for escrow coverage checks
+    /// 
+    /// This must be called once after deployment to register the
+    /// verify_escrow_coverage encrypted instruction with Arcium.
+    pub fn init_verify_escrow_comp_def(ctx: Context<InitVerifyEscrowCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        msg!("Verify escrow computation definition initialized");
+        Ok(())
+    }
+
+    /// Verify in MPC that a pending order is covered by its escrow
+    /// 
+    /// The first `ORDER_INPUT_FIELDS * 32` bytes of `order.cipher_payload`
+    /// must be the ciphertexts of the order's `OrderInput` fields (side,
+    /// price, amount, timestamp, flags), encrypted under the key and nonce
+    /// stored at placement. The callback activates the order if it is covered.
+    /// 
+    /// Only the order owner or a keeper with a `callback_auth` for the book
+    /// may run the check, since a failed check flags the order for good.
+    pub fn invoke_verify_escrow_coverage(
+        ctx: Context<InvokeVerifyEscrowCoverage>,
+        computation_offset: u64,
+        order_ciphertexts: [[u8; 32]; ORDER_INPUT_FIELDS],
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.order.status == ORDER_STATUS_PENDING_VERIFICATION,
+            ShadowSwapError::InvalidOrderStatus
+        );
+        check_order_invoker(
+            &ctx.accounts.payer.key(),
+            &ctx.accounts.order,
+            &ctx.accounts.callback_auth,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        let escrowed_amount = ctx.accounts.escrow_token_account.amount;
+        let escrow_is_quote = ctx.accounts.escrow.token_mint == ctx.accounts.order_book.quote_mint;
+
+        let mut args = order_input_arguments(&ctx.accounts.order, &order_ciphertexts)?;
+        args.extend([
+            Argument::PlaintextU64(escrowed_amount),
+            Argument::PlaintextBool(escrow_is_quote),
+            Argument::PlaintextU64(QUOTE_PRICE_SCALE),
+        ]);
+
+        // Set PDA bump
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![VerifyEscrowCoverageCallback::callback_ix(&[CallbackAccount {
+                pubkey: ctx.accounts.order.key(),
+                is_writable: true,
+            }])],
+        )?;
+
+        msg!(
+            "Escrow coverage verification queued for order {}",
+            ctx.accounts.order.order_id
+        );
+        Ok(())
+    }
+
+    /// Callback from Arcium MPC with the escrow coverage result
+    /// 
+    /// Covered orders become `ORDER_STATUS_ACTIVE`; uncovered orders are
+    /// flagged `ORDER_STATUS_AMOUNT_MISMATCH` and can only be cancelled.
+    #[arcium_callback(encrypted_ix = "verify_escrow_coverage")]
+    pub fn verify_escrow_coverage_callback(
+        ctx: Context<VerifyEscrowCoverageCallback>,
+        output: ComputationOutputs<VerifyEscrowCoverageOutput>,
+    ) -> Result<()> {
+        let is_covered = match output {
+            ComputationOutputs::Success(VerifyEscrowCoverageOutput { field_0 }) => field_0,
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        let order = &mut ctx.accounts.order;
+        let clock = Clock::get()?;
+
+        // The order may have been cancelled while the computation was queued
+        require!(
+            order.status == ORDER_STATUS_PENDING_VERIFICATION,
+            ShadowSwapError::InvalidOrderStatus
+        );
+
+        order.status = if is_covered {
+            ORDER_STATUS_ACTIVE
+        } else {
+            ORDER_STATUS_AMOUNT_MISMATCH
+        };
+        order.updated_at = clock.unix_timestamp;
+
+        emit!(EscrowCoverageVerified {
+            order_book: order.order_book,
+            order: order.key(),
+            order_id: order.order_id,
+            is_covered,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!(
+            "Escrow coverage for order {}: {}",
+            order.order_id,
+            is_covered
+        );
+        Ok(())
+    }
+
+    /// Initialize the computation definition for conditional order triggers
+    /// 
+    /// This must be called once after deployment to register the
+    /// check_trigger encrypted instruction with Arcium.
+    pub fn init_check_trigger_comp_def(ctx: Context<InitCheckTriggerCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        msg!("Check trigger computation definition initialized");
+        Ok(())
+    }
+
+    /// Turn a freshly placed order into a hidden stop-loss / take-profit order
+    /// 
+    /// Sent in the same transaction as `place_order`. The order is parked in
+    /// `ORDER_STATUS_CONDITIONAL` with its escrow until the encrypted trigger
+    /// (`TriggerInput` ciphertexts: direction, trigger price, under
+    /// `trigger_pubkey` / `trigger_nonce`) fires.
+    pub fn attach_trigger(
+        ctx: Context<AttachTrigger>,
+        trigger_pubkey: [u8; 32],
+        trigger_nonce: u128,
+        trigger_ciphertexts: [[u8; 32]; TRIGGER_INPUT_FIELDS],
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        let order = &mut ctx.accounts.order;
+        order.status = ORDER_STATUS_CONDITIONAL;
+        order.updated_at = clock.unix_timestamp;
+
+        let conditional = &mut ctx.accounts.conditional_order;
+        conditional.owner = order.owner;
+        conditional.order_book = order.order_book;
+        conditional.order = order.key();
+        conditional.trigger_pubkey = trigger_pubkey;
+        conditional.trigger_nonce = trigger_nonce;
+        conditional.encrypted_trigger = trigger_ciphertexts;
+        conditional.last_checked_price = 0;
+        conditional.last_checked_at = 0;
+        conditional.triggered_at = 0;
+        conditional.created_at = clock.unix_timestamp;
+        conditional.bump = ctx.bumps.conditional_order;
+
+        msg!("Trigger attached to order {}", order.order_id);
+        Ok(())
+    }
+
+    /// Check a conditional order's trigger against the current oracle price
+    /// 
+    /// Permissionless crank. The book's oracle price (validated for staleness
+    /// and confidence, in book price units) is fed to the `check_trigger`
+    /// circuit; the callback activates the order if the trigger fired.
+    pub fn crank_conditional_order(
+        ctx: Context<CrankConditionalOrder>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let (oracle_price, _) =
+            validated_oracle_price(&ctx.accounts.order_book, &ctx.accounts.oracle)?;
+
+        let conditional = &mut ctx.accounts.conditional_order;
+        conditional.last_checked_price = oracle_price;
+        conditional.last_checked_at = Clock::get()?.unix_timestamp;
+
+        let args = vec![
+            Argument::ArcisPubkey(conditional.trigger_pubkey),
+            Argument::PlaintextU128(conditional.trigger_nonce),
+            Argument::EncryptedU8(conditional.encrypted_trigger[0]), // direction
+            Argument::EncryptedU64(conditional.encrypted_trigger[1]), // trigger_price
+            Argument::PlaintextU64(oracle_price),
+        ];
+
+        // Set PDA bump
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![CheckTriggerCallback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: ctx.accounts.conditional_order.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.order.key(),
+                    is_writable: true,
+                },
+            ])],
+        )?;
+
+        msg!(
+            "Trigger check queued for order {} at price {}",
+            ctx.accounts.order.order_id,
+            oracle_price
+        );
+        Ok(())
+    }
+
+    /// Callback from Arcium MPC with the trigger result
+    /// 
+    /// A fired trigger moves the order to `ORDER_STATUS_PENDING_VERIFICATION`
+    /// with a fresh `created_at`, so it enters the book like a newly placed
+    /// order (escrow coverage check, then time priority from trigger time).
+    #[arcium_callback(encrypted_ix = "check_trigger")]
+    pub fn check_trigger_callback(
+        ctx: Context<CheckTriggerCallback>,
+        output: ComputationOutputs<CheckTriggerOutput>,
+    ) -> Result<()> {
+        let fired = match output {
+            ComputationOutputs::Success(CheckTriggerOutput { field_0 }) => field_0,
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        let order = &mut ctx.accounts.order;
+        // The order may have been cancelled or triggered by an earlier crank
+        if !fired || order.status != ORDER_STATUS_CONDITIONAL {
+            return Ok(());
+        }
+
+        let clock = Clock::get()?;
+        order.status = ORDER_STATUS_PENDING_VERIFICATION;
+        order.created_at = clock.unix_timestamp;
+        order.updated_at = clock.unix_timestamp;
+
+        let conditional = &mut ctx.accounts.conditional_order;
+        conditional.triggered_at = clock.unix_timestamp;
+
+        emit!(ConditionalOrderTriggered {
+            order_book: order.order_book,
+            order: order.key(),
+            order_id: order.order_id,
+            oracle_price: conditional.last_checked_price,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Conditional order {} triggered", order.order_id);
+        Ok(())
+    }
+
+    /// Initialize the computation definitions for the shielded escrow vaults
+    /// 
+    /// These must be called once after deployment to register the
+    /// shielded_* encrypted instructions with Arcium.
+    pub fn init_shielded_allocate_comp_def(ctx: Context<InitShieldedAllocateCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        msg!("Shielded allocate computation definition initialized");
+        Ok(())
+    }
+
+    pub fn init_shielded_release_comp_def(ctx: Context<InitShieldedReleaseCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        msg!("Shielded release computation definition initialized");
+        Ok(())
+    }
+
+    pub fn init_shielded_settle_comp_def(ctx: Context<InitShieldedSettleCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        msg!("Shielded settle computation definition initialized");
+        Ok(())
+    }
+
+    pub fn init_shielded_withdraw_comp_def(ctx: Context<InitShieldedWithdrawCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        msg!("Shielded withdraw computation definition initialized");
+        Ok(())
+    }
+
+    /// Create the pooled vault for one mint of an order book
+    /// 
+    /// All shielded deposits of that mint sit in a single vault-owned token
+    /// account, so its balance says nothing about individual orders.
+    pub fn initialize_shielded_vault(ctx: Context<InitializeShieldedVault>) -> Result<()> {
+        let order_book = &ctx.accounts.order_book;
+        let token_mint = ctx.accounts.token_mint.key();
+        require!(
+            token_mint == order_book.base_mint || token_mint == order_book.quote_mint,
+            ShadowSwapError::InvalidTokenMint
+        );
+
+        let vault = &mut ctx.accounts.vault;
+        vault.order_book = order_book.key();
+        vault.token_mint = token_mint;
+        vault.token_account = ctx.accounts.vault_token_account.key();
+        vault.bump = ctx.bumps.vault;
+
+        msg!("Shielded vault initialized for mint {}", token_mint);
+        Ok(())
+    }
+
+    /// Open a user's encrypted balance in a shielded vault
+    pub fn open_shielded_balance(ctx: Context<OpenShieldedBalance>) -> Result<()> {
+        let balance = &mut ctx.accounts.balance;
+        balance.owner = ctx.accounts.owner.key();
+        balance.vault = ctx.accounts.vault.key();
+        balance.encrypted_balance = [0u8; 32];
+        balance.balance_nonce = 0;
+        balance.has_balance = false;
+        balance.pending_deposit = 0;
+        balance.in_flight_credit = 0;
+        balance.in_flight_withdrawal = 0;
+        balance.busy = false;
+        balance.created_at = Clock::get()?.unix_timestamp;
+        balance.bump = ctx.bumps.balance;
+
+        msg!("Shielded balance opened for {}", balance.owner);
+        Ok(())
+    }
+
+    /// Deposit tokens into a shielded vault
+    /// 
+    /// The deposit itself is a public transfer. It is recorded in
+    /// `pending_deposit` and folded into the encrypted balance by the next
+    /// MPC computation on that balance, so no separate computation is needed.
+    pub fn shielded_deposit(ctx: Context<ShieldedDeposit>, amount: u64) -> Result<()> {
+        require!(amount > 0, ShadowSwapError::OrderTooSmall);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user_token_account.to_account_info(),
+                    to: ctx.accounts.vault_token_account.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let balance = &mut ctx.accounts.balance;
+        balance.pending_deposit = balance
+            .pending_deposit
+            .checked_add(amount)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+
+        emit!(ShieldedDepositMade {
+            vault: balance.vault,
+            owner: balance.owner,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Shielded deposit: {}", amount);
+        Ok(())
+    }
+
+    /// Place an order backed by a shielded vault balance
+    /// 
+    /// No tokens move and no per-order token account is created. The order
+    /// starts in `ORDER_STATUS_PENDING_VERIFICATION`; `invoke_shielded_allocation`
+    /// then reserves its escrow out of the owner's encrypted balance.
+    pub fn place_shielded_order(
+        ctx: Context<PlaceShieldedOrder>,
+        cipher_payload: Vec<u8>,
+        encrypted_amount: Vec<u8>,
+        encryption_pubkey: [u8; 32],
+        encryption_nonce: u128,
+    ) -> Result<()> {
+        require!(
+            cipher_payload.len() <= MAX_CIPHER_PAYLOAD_SIZE,
+            ShadowSwapError::InvalidCipherPayload
+        );
+        require!(
+            encrypted_amount.len() <= MAX_ENCRYPTED_AMOUNT_SIZE,
+            ShadowSwapError::InvalidCipherPayload
+        );
+
+        let order_book = &mut ctx.accounts.order_book;
+        let order = &mut ctx.accounts.order;
+        let escrow = &mut ctx.accounts.escrow;
+        let vault = &ctx.accounts.vault;
+        let clock = Clock::get()?;
+
+        let order_id = order_book.order_count;
+        order_book.order_count = order_book
+            .order_count
+            .checked_add(1)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+        order_book.active_orders = order_book
+            .active_orders
+            .checked_add(1)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+
+        order.owner = ctx.accounts.owner.key();
+        order.order_book = order_book.key();
+        order.cipher_payload = cipher_payload;
+        order.encryption_pubkey = encryption_pubkey;
+        order.encryption_nonce = encryption_nonce;
+        order.status = ORDER_STATUS_PENDING_VERIFICATION;
+        order.encrypted_remaining = encrypted_amount;
+        order.escrow = escrow.key();
+        order.created_at = clock.unix_timestamp;
+        order.updated_at = clock.unix_timestamp;
+        order.order_id = order_id;
+        order.epoch = order_book.current_epoch;
+        order.auction_filled = 0;
+        order.continuous_filled = false;
+        order.pending_matches = 0;
+        order.fill = RecordedFill::default();
+        order.bump = ctx.bumps.order;
+
+        // The allocation ciphertext is written by the allocation callback
+        escrow.order = order.key();
+        escrow.owner = ctx.accounts.owner.key();
+        escrow.order_book = order_book.key();
+        escrow.token_account = vault.token_account;
+        escrow.token_mint = vault.token_mint;
+        escrow.encrypted_amount = vec![];
+        escrow.encrypted_remaining = vec![];
+        escrow.encrypted_remaining_nonce = 0;
+        escrow.encrypted_unfilled = [0u8; 32];
+        escrow.encrypted_unfilled_nonce = 0;
+        escrow.vault = vault.key();
+        escrow.created_at = clock.unix_timestamp;
+        escrow.bump = ctx.bumps.escrow;
+
+        msg!("Shielded order placed: ID {}", order_id);
+        Ok(())
+    }
+
+    /// Reserve a pending shielded order's escrow in MPC
+    /// 
+    /// Takes the same `OrderInput` ciphertexts and has the same invoker rule
+    /// as `invoke_verify_escrow_coverage`. The owner's balance is locked until
+    /// the callback; `allocation_nonce` encrypts the new allocation for the MXE
+    /// and `unfilled_nonce` the order's unfilled amount.
+    pub fn invoke_shielded_allocation(
+        ctx: Context<InvokeShieldedAllocation>,
+        computation_offset: u64,
+        order_ciphertexts: [[u8; 32]; ORDER_INPUT_FIELDS],
+        allocation_nonce: u128,
+        unfilled_nonce: u128,
+    ) -> Result<()> {
+        check_order_invoker(
+            &ctx.accounts.payer.key(),
+            &ctx.accounts.order,
+            &ctx.accounts.callback_auth,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        let escrow_is_quote = ctx.accounts.vault.token_mint == ctx.accounts.order_book.quote_mint;
+        let mut args = order_input_arguments(&ctx.accounts.order, &order_ciphertexts)?;
+        let balance = &mut ctx.accounts.balance;
+        lock_shielded_balance(balance)?;
+
+        args.extend([
+            // Current balance (Enc<Mxe, ShieldedAmount>)
+            Argument::PlaintextU128(balance.balance_nonce),
+            Argument::EncryptedU64(balance.encrypted_balance),
+            Argument::PlaintextBool(balance.has_balance),
+            Argument::PlaintextU64(balance.in_flight_credit),
+            Argument::PlaintextU128(allocation_nonce),
+            Argument::PlaintextU128(unfilled_nonce),
+            Argument::PlaintextBool(escrow_is_quote),
+            Argument::PlaintextU64(QUOTE_PRICE_SCALE),
+        ]);
+
+        // Set PDA bump
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![ShieldedAllocateCallback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: ctx.accounts.order.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.escrow.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.balance.key(),
+                    is_writable: true,
+                },
+            ])],
+        )?;
+
+        msg!(
+            "Shielded allocation queued for order {}",
+            ctx.accounts.order.order_id
+        );
+        Ok(())
+    }
+
+    /// Callback from Arcium MPC with the shielded allocation
+    /// 
+    /// Covered orders become `ORDER_STATUS_ACTIVE` with the allocation stored
+    /// in `escrow.encrypted_remaining` and the order amount in
+    /// `escrow.encrypted_unfilled`; uncovered orders are flagged
+    /// `ORDER_STATUS_AMOUNT_MISMATCH`. If the order left the pending state
+    /// while queued (e.g. it was cancelled) the result is discarded.
+    #[arcium_callback(encrypted_ix = "shielded_allocate")]
+    pub fn shielded_allocate_callback(
+        ctx: Context<ShieldedAllocateCallback>,
+        output: ComputationOutputs<ShieldedAllocateOutput>,
+    ) -> Result<()> {
+        let balance = &mut ctx.accounts.balance;
+        let order = &mut ctx.accounts.order;
+
+        let (new_balance, allocation, unfilled, is_covered) = match output {
+            ComputationOutputs::Success(ShieldedAllocateOutput {
+                field_0:
+                    ShieldedAllocateOutputStruct0 {
+                        field_0,
+                        field_1,
+                        field_2,
+                        field_3,
+                    },
+            }) if order.status == ORDER_STATUS_PENDING_VERIFICATION => (field_0, field_1, field_2, field_3),
+            _ => {
+                unlock_shielded_balance(balance, None)?;
+                msg!("Shielded allocation for order {} discarded", order.order_id);
+                return Ok(());
+            }
+        };
+
+        let clock = Clock::get()?;
+        unlock_shielded_balance(balance, Some((new_balance.ciphertexts[0], new_balance.nonce)))?;
+
+        if is_covered {
+            let escrow = &mut ctx.accounts.escrow;
+            escrow.encrypted_amount = allocation.ciphertexts[0].to_vec();
+            escrow.encrypted_remaining = allocation.ciphertexts[0].to_vec();
+            escrow.encrypted_remaining_nonce = allocation.nonce;
+            escrow.encrypted_unfilled = unfilled.ciphertexts[0];
+            escrow.encrypted_unfilled_nonce = unfilled.nonce;
+            order.status = ORDER_STATUS_ACTIVE;
+        } else {
+            order.status = ORDER_STATUS_AMOUNT_MISMATCH;
+        }
+        order.updated_at = clock.unix_timestamp;
+
+        emit!(EscrowCoverageVerified {
+            order_book: order.order_book,
+            order: order.key(),
+            order_id: order.order_id,
+            is_covered,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Shielded allocation for order {}: {}", order.order_id, is_covered);
+        Ok(())
+    }
+
+    /// Cancel a shielded order and return its allocation to the owner's balance
+    /// 
+    /// Orders without an allocation (never verified or not covered) are
+    /// cancelled immediately. Otherwise the order is cancelled now and the
+    /// allocation is returned by MPC; if that computation fails the owner can
+    /// call this again on the cancelled order.
+    /// 
+    /// Filled orders keep their status and only release what is left of the
+    /// allocation: a buy reserves `amount * limit` but is charged at the
+    /// execution price, so a fill below the limit leaves quote behind.
+    pub fn cancel_shielded_order(
+        ctx: Context<CancelShieldedOrder>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let order = &mut ctx.accounts.order;
+        let clock = Clock::get()?;
+
+        if order.status != ORDER_STATUS_CANCELLED && order.status != ORDER_STATUS_FILLED {
+            order.status = ORDER_STATUS_CANCELLED;
+            order.updated_at = clock.unix_timestamp;
+
+            let order_book = &mut ctx.accounts.order_book;
+            order_book.active_orders = order_book
+                .active_orders
+                .checked_sub(1)
+                .ok_or(ShadowSwapError::NumericalOverflow)?;
+        }
+
+        let escrow = &ctx.accounts.escrow;
+        if escrow.encrypted_remaining.is_empty() {
+            msg!("Shielded order cancelled: ID {}", order.order_id);
+            return Ok(());
+        }
+        let allocation = escrow_allocation_ciphertext(escrow)?;
+
+        let balance = &mut ctx.accounts.balance;
+        lock_shielded_balance(balance)?;
+
+        let args = vec![
+            // Allocation (Enc<Mxe, ShieldedAmount>)
+            Argument::PlaintextU128(escrow.encrypted_remaining_nonce),
+            Argument::EncryptedU64(allocation),
+            // Current balance (Enc<Mxe, ShieldedAmount>)
+            Argument::PlaintextU128(balance.balance_nonce),
+            Argument::EncryptedU64(balance.encrypted_balance),
+            Argument::PlaintextBool(balance.has_balance),
+            Argument::PlaintextU64(balance.in_flight_credit),
+        ];
+
+        // Set PDA bump
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![ShieldedReleaseCallback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: ctx.accounts.escrow.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.balance.key(),
+                    is_writable: true,
+                },
+            ])],
+        )?;
+
+        msg!(
+            "Shielded order {}: ID {} (allocation release queued)",
+            if ctx.accounts.order.status == ORDER_STATUS_FILLED { "released" } else { "cancelled" },
+            ctx.accounts.order.order_id
+        );
+        Ok(())
+    }
+
+    /// Callback from Arcium MPC returning a cancelled order's allocation
+    #[arcium_callback(encrypted_ix = "shielded_release")]
+    pub fn shielded_release_callback(
+        ctx: Context<ShieldedReleaseCallback>,
+        output: ComputationOutputs<ShieldedReleaseOutput>,
+    ) -> Result<()> {
+        let balance = &mut ctx.accounts.balance;
+        let new_balance = match output {
+            ComputationOutputs::Success(ShieldedReleaseOutput { field_0 }) => field_0,
+            _ => {
+                unlock_shielded_balance(balance, None)?;
+                msg!("Shielded allocation release failed; cancel can be retried");
+                return Ok(());
+            }
+        };
+
+        unlock_shielded_balance(balance, Some((new_balance.ciphertexts[0], new_balance.nonce)))?;
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.encrypted_remaining = vec![];
+        escrow.encrypted_remaining_nonce = 0;
+
+        msg!("Shielded allocation of order {} released", escrow.order);
+        Ok(())
+    }
+
+    /// Settle a matched pair of shielded orders inside the vaults
+    /// 
+    /// Settles the MPC fill `arcium_match_callback` recorded on both orders,
+    /// which is consumed here. Both orders move to
+    /// `ORDER_STATUS_MATCHED_PENDING` and the receiving balances are locked
+    /// until the callback. Whether each order is filled is decided in MPC
+    /// from its unfilled amount.
+    pub fn invoke_shielded_settlement(
+        ctx: Context<InvokeShieldedSettlement>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            ctx.accounts.callback_auth.expires_at > clock.unix_timestamp,
+            ShadowSwapError::CallbackAuthExpired
+        );
+
+        let fill = paired_fill(&ctx.accounts.buyer_order, &ctx.accounts.seller_order)?;
+        require!(fill.unsettled, ShadowSwapError::FillAlreadySettled);
+
+        let buyer_allocation = escrow_allocation_ciphertext(&ctx.accounts.buyer_escrow)?;
+        let seller_allocation = escrow_allocation_ciphertext(&ctx.accounts.seller_escrow)?;
+
+        lock_shielded_balance(&mut ctx.accounts.buyer_base_balance)?;
+        lock_shielded_balance(&mut ctx.accounts.seller_quote_balance)?;
+
+        for order in [&mut ctx.accounts.buyer_order, &mut ctx.accounts.seller_order] {
+            order.status = ORDER_STATUS_MATCHED_PENDING;
+            order.fill.unsettled = false;
+            order.updated_at = clock.unix_timestamp;
+        }
+
+        let buyer_escrow = &ctx.accounts.buyer_escrow;
+        let seller_escrow = &ctx.accounts.seller_escrow;
+        let buyer_base = &ctx.accounts.buyer_base_balance;
+        let seller_quote = &ctx.accounts.seller_quote_balance;
+
+        let args = vec![
+            // Fill (Enc<Mxe, MatchOutput>)
+            Argument::PlaintextU128(fill.nonce),
+            Argument::EncryptedU64(fill.ciphertexts[0]),
+            Argument::EncryptedU64(fill.ciphertexts[1]),
+            // Allocations (Enc<Mxe, ShieldedAmount>)
+            Argument::PlaintextU128(buyer_escrow.encrypted_remaining_nonce),
+            Argument::EncryptedU64(buyer_allocation),
+            Argument::PlaintextU128(seller_escrow.encrypted_remaining_nonce),
+            Argument::EncryptedU64(seller_allocation),
+            // Unfilled amounts (Enc<Mxe, ShieldedAmount>)
+            Argument::PlaintextU128(buyer_escrow.encrypted_unfilled_nonce),
+            Argument::EncryptedU64(buyer_escrow.encrypted_unfilled),
+            Argument::PlaintextU128(seller_escrow.encrypted_unfilled_nonce),
+            Argument::EncryptedU64(seller_escrow.encrypted_unfilled),
+            // Receiving balances (Enc<Mxe, ShieldedAmount>)
+            Argument::PlaintextU128(buyer_base.balance_nonce),
+            Argument::EncryptedU64(buyer_base.encrypted_balance),
+            Argument::PlaintextBool(buyer_base.has_balance),
+            Argument::PlaintextU64(buyer_base.in_flight_credit),
+            Argument::PlaintextU128(seller_quote.balance_nonce),
+            Argument::EncryptedU64(seller_quote.encrypted_balance),
+            Argument::PlaintextBool(seller_quote.has_balance),
+            Argument::PlaintextU64(seller_quote.in_flight_credit),
+            Argument::PlaintextU64(QUOTE_PRICE_SCALE),
+        ];
+
+        // Set PDA bump
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        let callback_accounts = [
+            ctx.accounts.order_book.key(),
+            ctx.accounts.buyer_order.key(),
+            ctx.accounts.seller_order.key(),
+            ctx.accounts.buyer_escrow.key(),
+            ctx.accounts.seller_escrow.key(),
+            ctx.accounts.buyer_base_balance.key(),
+            ctx.accounts.seller_quote_balance.key(),
+        ]
+        .map(|pubkey| CallbackAccount {
+            pubkey,
+            is_writable: true,
+        });
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![ShieldedSettleCallback::callback_ix(&callback_accounts)],
+        )?;
+
+        msg!(
+            "Shielded settlement queued: buyer_order={}, seller_order={}",
+            ctx.accounts.buyer_order.order_id,
+            ctx.accounts.seller_order.order_id
+        );
+        Ok(())
+    }
+
+    /// Callback from Arcium MPC with the shielded settlement
+    /// 
+    /// On success the allocations, unfilled amounts and receiving balances are
+    /// replaced and the orders move to FILLED or PARTIAL. If the allocations
+    /// or unfilled amounts did not cover the fill the orders go back on the
+    /// book; if the computation failed the fill can also be settled again.
+    /// 
+    /// A filled order's leftover allocation stays in its escrow until the
+    /// owner releases it with `cancel_shielded_order`.
+    #[arcium_callback(encrypted_ix = "shielded_settle")]
+    pub fn shielded_settle_callback(
+        ctx: Context<ShieldedSettleCallback>,
+        output: ComputationOutputs<ShieldedSettleOutput>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let accounts = &mut ctx.accounts;
+
+        let (
+            buyer_allocation,
+            seller_allocation,
+            buyer_unfilled,
+            seller_unfilled,
+            buyer_base,
+            seller_quote,
+            settled,
+            buyer_filled,
+            seller_filled,
+        ) = match output {
+            ComputationOutputs::Success(ShieldedSettleOutput {
+                field_0:
+                    ShieldedSettleOutputStruct0 {
+                        field_0,
+                        field_1,
+                        field_2,
+                        field_3,
+                        field_4,
+                        field_5,
+                        field_6,
+                        field_7,
+                        field_8,
+                    },
+            }) => (field_0, field_1, field_2, field_3, field_4, field_5, field_6, field_7, field_8),
+            _ => {
+                unlock_shielded_balance(&mut accounts.buyer_base_balance, None)?;
+                unlock_shielded_balance(&mut accounts.seller_quote_balance, None)?;
+                restore_unsettled_order(&mut accounts.buyer_order, &accounts.buyer_escrow, clock.unix_timestamp);
+                restore_unsettled_order(&mut accounts.seller_order, &accounts.seller_escrow, clock.unix_timestamp);
+                accounts.buyer_order.fill.unsettled = true;
+                accounts.seller_order.fill.unsettled = true;
+                msg!("Shielded settlement failed; orders returned to the book");
+                return Ok(());
+            }
+        };
+
+        // Balances always take the new ciphertexts: pending deposits were
+        // folded in even when the fill itself was rejected
+        unlock_shielded_balance(
+            &mut accounts.buyer_base_balance,
+            Some((buyer_base.ciphertexts[0], buyer_base.nonce)),
+        )?;
+        unlock_shielded_balance(
+            &mut accounts.seller_quote_balance,
+            Some((seller_quote.ciphertexts[0], seller_quote.nonce)),
+        )?;
+
+        if !settled {
+            restore_unsettled_order(&mut accounts.buyer_order, &accounts.buyer_escrow, clock.unix_timestamp);
+            restore_unsettled_order(&mut accounts.seller_order, &accounts.seller_escrow, clock.unix_timestamp);
+            msg!("Shielded settlement rejected: the orders do not cover the fill");
+            return Ok(());
+        }
+
+        accounts.buyer_escrow.encrypted_remaining = buyer_allocation.ciphertexts[0].to_vec();
+        accounts.buyer_escrow.encrypted_remaining_nonce = buyer_allocation.nonce;
+        accounts.seller_escrow.encrypted_remaining = seller_allocation.ciphertexts[0].to_vec();
+        accounts.seller_escrow.encrypted_remaining_nonce = seller_allocation.nonce;
+        accounts.buyer_escrow.encrypted_unfilled = buyer_unfilled.ciphertexts[0];
+        accounts.buyer_escrow.encrypted_unfilled_nonce = buyer_unfilled.nonce;
+        accounts.seller_escrow.encrypted_unfilled = seller_unfilled.ciphertexts[0];
+        accounts.seller_escrow.encrypted_unfilled_nonce = seller_unfilled.nonce;
+
+        let mut filled_orders = 0u64;
+        for (order, filled) in [
+            (&mut accounts.buyer_order, buyer_filled),
+            (&mut accounts.seller_order, seller_filled),
+        ] {
+            order.fill.volume_pending = true;
+            order.status = if filled {
+                filled_orders += 1;
+                ORDER_STATUS_FILLED
+            } else {
+                ORDER_STATUS_PARTIAL
+            };
+            order.updated_at = clock.unix_timestamp;
+        }
+
+        let order_book = &mut accounts.order_book;
+        order_book.active_orders = order_book
+            .active_orders
+            .checked_sub(filled_orders)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+        order_book.last_trade_at = clock.unix_timestamp;
+
+        emit!(ShieldedTradeSettled {
+            order_book: order_book.key(),
+            buyer_order_id: accounts.buyer_order.order_id,
+            seller_order_id: accounts.seller_order.order_id,
+            buyer_filled,
+            seller_filled,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!(
+            "Shielded trade settled: buyer_order={}, seller_order={}",
+            accounts.buyer_order.order_id,
+            accounts.seller_order.order_id
+        );
+        Ok(())
+    }
+
+    /// Request a withdrawal from a shielded balance
+    /// 
+    /// `amount` is public since it leaves the vault as a token transfer. MPC
+    /// checks the encrypted balance covers it; the callback pays out to
+    /// `destination`.
+    pub fn request_shielded_withdrawal(
+        ctx: Context<RequestShieldedWithdrawal>,
+        computation_offset: u64,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, ShadowSwapError::OrderTooSmall);
+
+        let balance = &mut ctx.accounts.balance;
+        lock_shielded_balance(balance)?;
+        balance.in_flight_withdrawal = amount;
+
+        let args = vec![
+            // Current balance (Enc<Mxe, ShieldedAmount>)
+            Argument::PlaintextU128(balance.balance_nonce),
+            Argument::EncryptedU64(balance.encrypted_balance),
+            Argument::PlaintextBool(balance.has_balance),
+            Argument::PlaintextU64(balance.in_flight_credit),
+            Argument::PlaintextU64(amount),
+        ];
+
+        // Set PDA bump
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        let callback_accounts = [
+            (ctx.accounts.vault.key(), false),
+            (ctx.accounts.vault_token_account.key(), true),
+            (ctx.accounts.balance.key(), true),
+            (ctx.accounts.destination.key(), true),
+            (ctx.accounts.token_program.key(), false),
+        ]
+        .map(|(pubkey, is_writable)| CallbackAccount {
+            pubkey,
+            is_writable,
+        });
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![ShieldedWithdrawCallback::callback_ix(&callback_accounts)],
+        )?;
+
+        msg!("Shielded withdrawal of {} queued", amount);
+        Ok(())
+    }
+
+    /// Callback from Arcium MPC with the withdrawal decision
+    #[arcium_callback(encrypted_ix = "shielded_withdraw")]
+    pub fn shielded_withdraw_callback(
+        ctx: Context<ShieldedWithdrawCallback>,
+        output: ComputationOutputs<ShieldedWithdrawOutput>,
+    ) -> Result<()> {
+        let amount = ctx.accounts.balance.in_flight_withdrawal;
+        ctx.accounts.balance.in_flight_withdrawal = 0;
+
+        let (new_balance, approved) = match output {
+            ComputationOutputs::Success(ShieldedWithdrawOutput {
+                field_0: ShieldedWithdrawOutputStruct0 { field_0, field_1 },
+            }) => (field_0, field_1),
+            _ => {
+                unlock_shielded_balance(&mut ctx.accounts.balance, None)?;
+                msg!("Shielded withdrawal failed");
+                return Ok(());
+            }
+        };
+
+        unlock_shielded_balance(
+            &mut ctx.accounts.balance,
+            Some((new_balance.ciphertexts[0], new_balance.nonce)),
+        )?;
+
+        if approved {
+            let vault = &ctx.accounts.vault;
+            let seeds = &[
+                SHIELDED_VAULT_SEED,
+                vault.order_book.as_ref(),
+                vault.token_mint.as_ref(),
+                &[vault.bump],
+            ];
+            let signer = &[&seeds[..]];
+
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.vault_token_account.to_account_info(),
+                        to: ctx.accounts.destination.to_account_info(),
+                        authority: ctx.accounts.vault.to_account_info(),
+                    },
+                    signer,
+                ),
+                amount,
+            )?;
+        }
+
+        emit!(ShieldedWithdrawal {
+            vault: ctx.accounts.vault.key(),
+            owner: ctx.accounts.balance.owner,
+            amount,
+            approved,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Shielded withdrawal of {}: {}", amount, approved);
+        Ok(())
+    }
+
+    /// Switch an order book between continuous matching and batch auctions
+    /// 
+    /// With `epoch_duration > 0` orders accumulate into numbered epochs of
+    /// that many seconds and only trade through `clear_batch_auction`;
+    /// pairwise matching is disabled. `0` switches back to continuous
+    /// matching.
+    pub fn configure_auction(ctx: Context<ConfigureAuction>, epoch_duration: i64) -> Result<()> {
+        require!(epoch_duration >= 0, ShadowSwapError::InvalidAuctionConfiguration);
+
+        let order_book = &mut ctx.accounts.order_book;
+        if order_book.auction_epoch_duration == 0 && epoch_duration > 0 {
+            order_book.epoch_started_at = Clock::get()?.unix_timestamp;
+        }
+        order_book.auction_epoch_duration = epoch_duration;
+
+        msg!(
+            "Auction epochs for order book {}: {}s (epoch {})",
+            order_book.key(),
+            epoch_duration,
+            order_book.current_epoch
+        );
+        Ok(())
+    }
+
+    /// Close the current auction epoch once its duration has elapsed
+    /// 
+    /// Permissionless crank. Creates the epoch's `AuctionEpoch` record and
+    /// starts the next epoch; orders placed from now on belong to it.
+    pub fn close_auction_epoch(ctx: Context<CloseAuctionEpoch>) -> Result<()> {
+        let clock = Clock::get()?;
+        let order_book = &mut ctx.accounts.order_book;
+        require!(
+            clock.unix_timestamp
+                >= order_book
+                    .epoch_started_at
+                    .saturating_add(order_book.auction_epoch_duration),
+            ShadowSwapError::AuctionEpochNotOver
+        );
+
+        let auction_epoch = &mut ctx.accounts.auction_epoch;
+        auction_epoch.order_book = order_book.key();
+        auction_epoch.epoch = order_book.current_epoch;
+        auction_epoch.status = AUCTION_STATUS_CLOSED;
+        auction_epoch.closed_at = clock.unix_timestamp;
+        auction_epoch.order_count = 0;
+        auction_epoch.orders = [Pubkey::default(); AUCTION_BATCH_SIZE];
+        auction_epoch.clearing_price = 0;
+        auction_epoch.fills = [0; AUCTION_BATCH_SIZE];
+        auction_epoch.done = [false; AUCTION_BATCH_SIZE];
+        auction_epoch.clearing_started_at = 0;
+        auction_epoch.settled_at = 0;
+        auction_epoch.bump = ctx.bumps.auction_epoch;
+
+        order_book.current_epoch = order_book
+            .current_epoch
+            .checked_add(1)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+        order_book.epoch_started_at = clock.unix_timestamp;
+
+        emit!(AuctionEpochClosed {
+            order_book: order_book.key(),
+            epoch: auction_epoch.epoch,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Auction epoch {} closed", auction_epoch.epoch);
+        Ok(())
+    }
+
+    /// Initialize the computation definition for batch auction clearing
+    /// 
+    /// This must be called once after deployment to register the
+    /// clear_batch_auction encrypted instruction with Arcium.
+    pub fn init_clear_auction_comp_def(ctx: Context<InitClearAuctionCompDef>) -> Result<()> {
+        init_comp_def(ctx.accounts, true, 0, None, None)?;
+        msg!("Clear auction computation definition initialized");
+        Ok(())
+    }
+
+    /// Queue the uniform-price clearing of a closed auction epoch
+    /// 
+    /// Orders are offered oldest first and the keeper cannot pick them:
+    /// remaining_accounts are `[order, escrow]` pairs for consecutive order
+    /// IDs starting at `order_book.auction_floor`. The first
+    /// `AUCTION_BATCH_SIZE` active orders from this or an earlier epoch are
+    /// included, with `inputs` holding their ciphertexts in the same order;
+    /// any other order is skipped. The scan must end when the batch is full,
+    /// at the first order from a later epoch, or at the end of the book.
+    /// Orders that do not fit roll into the next epoch. The included orders
+    /// are locked in `ORDER_STATUS_MATCHED_PENDING` until `settle_auction`.
+    /// 
+    /// Orders are sized by `auction_filled` only, so orders with continuous
+    /// fills (from before the book switched to auctions) never join, and
+    /// neither do shielded orders. Such orders, like filled, cancelled or
+    /// flagged ones, move `auction_floor` past them when they start the scan.
+    pub fn invoke_auction_clearing<'info>(
+        ctx: Context<'_, '_, 'info, 'info, InvokeAuctionClearing<'info>>,
+        computation_offset: u64,
+        inputs: Vec<AuctionOrderInput>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            ctx.accounts.callback_auth.expires_at > clock.unix_timestamp,
+            ShadowSwapError::CallbackAuthExpired
+        );
+        require!(
+            !inputs.is_empty()
+                && inputs.len() <= AUCTION_BATCH_SIZE
+                && ctx.remaining_accounts.len() % 2 == 0,
+            ShadowSwapError::InvalidAuctionBatch
+        );
+
+        let order_book_key = ctx.accounts.order_book.key();
+        let scan_start = ctx.accounts.order_book.auction_floor;
+        let mut floor = scan_start;
+        let epoch = ctx.accounts.auction_epoch.epoch;
+        let mut orders = [Pubkey::default(); AUCTION_BATCH_SIZE];
+        let mut filled = [0u64; AUCTION_BATCH_SIZE];
+        let mut args = vec![];
+        let mut included = 0;
+        let mut reached_later_epoch = false;
+
+        for (idx, accounts) in ctx.remaining_accounts.chunks(2).enumerate() {
+            // Nothing may follow the end of the scan
+            require!(
+                included < AUCTION_BATCH_SIZE && !reached_later_epoch,
+                ShadowSwapError::InvalidAuctionBatch
+            );
+
+            let order_id = scan_start + idx as u64;
+            let (order_pda, _) = Pubkey::find_program_address(
+                &[ORDER_SEED, order_book_key.as_ref(), &order_id.to_le_bytes()],
+                ctx.program_id,
+            );
+            require!(accounts[0].key() == order_pda, ShadowSwapError::OrderNotFound);
+
+            // Account::try_from checks owner and discriminator
+            let mut order: Account<'info, EncryptedOrder> = Account::try_from(&accounts[0])?;
+            if order.epoch > epoch {
+                // Order IDs grow with epochs, so every later order is newer too
+                reached_later_epoch = true;
+                continue;
+            }
+
+            require!(accounts[1].key() == order.escrow, ShadowSwapError::InvalidEscrow);
+            let escrow: Account<'info, Escrow> = Account::try_from(&accounts[1])?;
+
+            let barred = order.status == ORDER_STATUS_FILLED
+                || order.status == ORDER_STATUS_CANCELLED
+                || order.status == ORDER_STATUS_AMOUNT_MISMATCH
+                || order.continuous_filled
+                || escrow.vault != Pubkey::default();
+            if barred && floor == order_id {
+                floor += 1;
+            }
+            // Pending, conditional and already locked orders wait their turn
+            if barred
+                || !(order.status == ORDER_STATUS_ACTIVE || order.status == ORDER_STATUS_PARTIAL)
+            {
+                continue;
+            }
+
+            let input = inputs
+                .get(included)
+                .ok_or(ShadowSwapError::InvalidAuctionBatch)?;
+            // Bind the MPC input to the payload and key stored at placement
+            args.extend(order_input_arguments(&order, &input.order_ciphertexts)?);
+
+            orders[included] = order.key();
+            filled[included] = order.auction_filled;
+            included += 1;
+
+            order.status = ORDER_STATUS_MATCHED_PENDING;
+            order.updated_at = clock.unix_timestamp;
+            order.exit(ctx.program_id)?;
+        }
+
+        let scan_end = scan_start + (ctx.remaining_accounts.len() / 2) as u64;
+        require!(
+            included == inputs.len()
+                && (included == AUCTION_BATCH_SIZE
+                    || reached_later_epoch
+                    || scan_end == ctx.accounts.order_book.order_count),
+            ShadowSwapError::InvalidAuctionBatch
+        );
+        ctx.accounts.order_book.auction_floor = floor;
+
+        let auction_epoch = &mut ctx.accounts.auction_epoch;
+        auction_epoch.status = AUCTION_STATUS_CLEARING;
+        auction_epoch.clearing_started_at = clock.unix_timestamp;
+        auction_epoch.order_count = included as u8;
+        auction_epoch.orders = orders;
+
+        // Unused slots are padded with zero ciphertexts; the circuit ignores
+        // everything from `order_count` on
+        for _ in included..AUCTION_BATCH_SIZE {
+            args.push(Argument::ArcisPubkey([0u8; 32]));
+            args.push(Argument::PlaintextU128(0));
+            args.push(Argument::EncryptedU8([0u8; 32])); // side
+            for _ in 0..3 {
+                args.push(Argument::EncryptedU64([0u8; 32])); // price, amount, timestamp
+            }
+            args.push(Argument::EncryptedU8([0u8; 32])); // flags
+        }
+        for amount in filled {
+            args.push(Argument::PlaintextU64(amount));
+        }
+        args.push(Argument::PlaintextU64(included as u64));
+
+        // Set PDA bump
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![ClearBatchAuctionCallback::callback_ix(&[CallbackAccount {
+                pubkey: ctx.accounts.auction_epoch.key(),
+                is_writable: true,
+            }])],
+        )?;
+
+        msg!("Auction clearing queued for epoch {} ({} orders)", epoch, included);
+        Ok(())
+    }
+
+    /// Callback from Arcium MPC with the epoch's clearing price and fills
+    /// 
+    /// A failed computation is recorded as an auction with no fills, so
+    /// `settle_auction` returns the orders to the book.
+    #[arcium_callback(encrypted_ix = "clear_batch_auction")]
+    pub fn clear_batch_auction_callback(
+        ctx: Context<ClearBatchAuctionCallback>,
+        output: ComputationOutputs<ClearBatchAuctionOutput>,
+    ) -> Result<()> {
+        let auction_epoch = &mut ctx.accounts.auction_epoch;
+        require!(
+            auction_epoch.status == AUCTION_STATUS_CLEARING,
+            ShadowSwapError::InvalidAuctionStatus
+        );
+
+        match output {
+            ComputationOutputs::Success(ClearBatchAuctionOutput {
+                field_0:
+                    ClearBatchAuctionOutputStruct0 {
+                        field_0,
+                        field_1,
+                        field_2,
+                    },
+            }) => {
+                auction_epoch.clearing_price = field_0;
+                auction_epoch.fills = field_1;
+                auction_epoch.done = field_2;
+            }
+            _ => msg!("Auction clearing failed; orders will return to the book"),
+        }
+        auction_epoch.status = AUCTION_STATUS_CLEARED;
+
+        emit!(AuctionCleared {
+            order_book: auction_epoch.order_book,
+            epoch: auction_epoch.epoch,
+            clearing_price: auction_epoch.clearing_price,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Auction epoch {} cleared at {}",
+            auction_epoch.epoch,
+            auction_epoch.clearing_price
+        );
+        Ok(())
+    }
+
+    /// Give up on an auction clearing whose MPC callback never arrived
+    /// 
+    /// Permissionless once `AUCTION_CLEARING_TIMEOUT` seconds have passed
+    /// since `invoke_auction_clearing`. The epoch is recorded as cleared with
+    /// no fills, like a failed computation, so `settle_auction` returns its
+    /// orders to the book. A late callback is rejected.
+    pub fn expire_auction_clearing(ctx: Context<ExpireAuctionClearing>) -> Result<()> {
+        let clock = Clock::get()?;
+        let auction_epoch = &mut ctx.accounts.auction_epoch;
+        require!(
+            clock.unix_timestamp
+                >= auction_epoch
+                    .clearing_started_at
+                    .saturating_add(AUCTION_CLEARING_TIMEOUT),
+            ShadowSwapError::AuctionClearingNotExpired
+        );
+
+        auction_epoch.clearing_price = 0;
+        auction_epoch.fills = [0; AUCTION_BATCH_SIZE];
+        auction_epoch.done = [false; AUCTION_BATCH_SIZE];
+        auction_epoch.status = AUCTION_STATUS_CLEARED;
+
+        emit!(AuctionCleared {
+            order_book: auction_epoch.order_book,
+            epoch: auction_epoch.epoch,
+            clearing_price: 0,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Auction clearing for epoch {} expired", auction_epoch.epoch);
+        Ok(())
+    }
+
+    /// Apply every fill of a cleared auction epoch at its clearing price
+    /// 
+    /// Permissionless. Remaining accounts are five per order of the epoch, in
+    /// the order they were cleared:
+    /// `[order, escrow, escrow_token_account, owner_token_account,
+    /// owner_refund_account]`, where the owner's token account receives base
+    /// for buys and quote for sells, and the refund account is the owner's
+    /// account for the escrowed mint.
+    /// Buys and sells are paired in input order; every pair trades at the
+    /// clearing price. Completely filled orders become FILLED and get back
+    /// whatever their escrow still holds (a buy escrows at its limit, so
+    /// clearing below it leaves quote behind); the rest go back on the book
+    /// for later epochs.
+    pub fn settle_auction<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleAuction<'info>>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let auction_epoch = &ctx.accounts.auction_epoch;
+        let order_count = auction_epoch.order_count as usize;
+        require!(
+            ctx.remaining_accounts.len() == order_count * 5,
+            ShadowSwapError::InvalidAuctionBatch
+        );
+
+        let quote_mint = ctx.accounts.order_book.quote_mint;
+        let base_mint = ctx.accounts.order_book.base_mint;
+        let price = auction_epoch.clearing_price;
+
+        let mut orders: Vec<Account<'info, EncryptedOrder>> = Vec::with_capacity(order_count);
+        let mut escrows: Vec<Account<'info, Escrow>> = Vec::with_capacity(order_count);
+        let mut is_buy = Vec::with_capacity(order_count);
+
+        for idx in 0..order_count {
+            let accounts = &ctx.remaining_accounts[idx * 5..idx * 5 + 5];
+            let order: Account<'info, EncryptedOrder> = Account::try_from(&accounts[0])?;
+            let escrow: Account<'info, Escrow> = Account::try_from(&accounts[1])?;
+            let owner_token_account: Account<'info, TokenAccount> = Account::try_from(&accounts[3])?;
+            let owner_refund_account: Account<'info, TokenAccount> = Account::try_from(&accounts[4])?;
+
+            require!(
+                order.key() == auction_epoch.orders[idx],
+                ShadowSwapError::OrderNotFound
+            );
+            require!(
+                escrow.key() == order.escrow && accounts[2].key() == escrow.token_account,
+                ShadowSwapError::InvalidEscrow
+            );
+
+            let buys = escrow.token_mint == quote_mint;
+            require!(
+                owner_token_account.owner == order.owner,
+                ShadowSwapError::UnauthorizedCallback
+            );
+            require!(
+                owner_token_account.mint == if buys { base_mint } else { quote_mint },
+                ShadowSwapError::InvalidTokenMint
+            );
+            require!(
+                owner_refund_account.owner == order.owner,
+                ShadowSwapError::UnauthorizedCallback
+            );
+            require!(
+                owner_refund_account.mint == escrow.token_mint,
+                ShadowSwapError::InvalidTokenMint
+            );
+
+            orders.push(order);
+            escrows.push(escrow);
+            is_buy.push(buys);
+        }
+
+        // Pair buys with sells in input order at the clearing price
+        let mut left = auction_epoch.fills;
+        let mut volume = 0u64;
+        let mut buy_idx = 0;
+        let mut sell_idx = 0;
+        loop {
+            while buy_idx < order_count && (!is_buy[buy_idx] || left[buy_idx] == 0) {
+                buy_idx += 1;
+            }
+            while sell_idx < order_count && (is_buy[sell_idx] || left[sell_idx] == 0) {
+                sell_idx += 1;
+            }
+            if buy_idx == order_count || sell_idx == order_count {
+                break;
+            }
+
+            let base_amount = left[buy_idx].min(left[sell_idx]);
+            let quote_amount = u64::try_from(
+                (base_amount as u128) * (price as u128) / (QUOTE_PRICE_SCALE as u128),
+            )
+            .map_err(|_| error!(ShadowSwapError::NumericalOverflow))?;
+
+            escrow_transfer(
+                &ctx.accounts.token_program,
+                &escrows[sell_idx],
+                &ctx.remaining_accounts[sell_idx * 5 + 2],
+                &ctx.remaining_accounts[buy_idx * 5 + 3],
+                base_amount,
+            )?;
+            escrow_transfer(
+                &ctx.accounts.token_program,
+                &escrows[buy_idx],
+                &ctx.remaining_accounts[buy_idx * 5 + 2],
+                &ctx.remaining_accounts[sell_idx * 5 + 3],
+                quote_amount,
+            )?;
+
+            left[buy_idx] -= base_amount;
+            left[sell_idx] -= base_amount;
+            volume += base_amount;
+        }
+
+        // Done orders get back what their escrow still holds
+        for idx in 0..order_count {
+            if !auction_epoch.done[idx] {
+                continue;
+            }
+            let escrow_token_info = &ctx.remaining_accounts[idx * 5 + 2];
+            let leftover = Account::<TokenAccount>::try_from(escrow_token_info)?.amount;
+            if leftover > 0 {
+                escrow_transfer(
+                    &ctx.accounts.token_program,
+                    &escrows[idx],
+                    escrow_token_info,
+                    &ctx.remaining_accounts[idx * 5 + 4],
+                    leftover,
+                )?;
+            }
+        }
+
+        let mut filled_orders = 0u64;
+        for (idx, order) in orders.iter_mut().enumerate() {
+            order.auction_filled = order
+                .auction_filled
+                .checked_add(auction_epoch.fills[idx])
+                .ok_or(ShadowSwapError::NumericalOverflow)?;
+            order.status = if auction_epoch.done[idx] {
+                filled_orders += 1;
+                ORDER_STATUS_FILLED
+            } else if order.auction_filled > 0 {
+                ORDER_STATUS_PARTIAL
+            } else {
+                ORDER_STATUS_ACTIVE
+            };
+            order.updated_at = clock.unix_timestamp;
+            order.exit(ctx.program_id)?;
+        }
+
+        let epoch = auction_epoch.epoch;
+        let auction_epoch = &mut ctx.accounts.auction_epoch;
+        auction_epoch.status = AUCTION_STATUS_SETTLED;
+        auction_epoch.settled_at = clock.unix_timestamp;
+
+        let order_book = &mut ctx.accounts.order_book;
+        order_book.active_orders = order_book
+            .active_orders
+            .checked_sub(filled_orders)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+        if volume > 0 {
+            order_book.last_trade_at = clock.unix_timestamp;
+        }
+
+        emit!(AuctionSettled {
+            order_book: order_book.key(),
+            epoch,
+            clearing_price: price,
+            base_volume: volume,
+            filled_orders,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!(
+            "Auction epoch {} settled: {} base at {}",
+            epoch,
+            volume,
+            price
+        );
+        Ok(())
+    }
+
+    /// Submit match results and execute settlement
+    /// 
+    /// This instruction is called by the authorized keeper bot after matching orders
+    /// off-chain. It performs the actual token transfers to settle the trade.
+    /// 
+    /// Flow:
+    /// 1. Verify keeper authorization via callback_auth
+    /// 2. Calculate transfer amounts based on matched_amount and execution_price
+    /// 3. Transfer quote tokens (USDC) from buyer's escrow to seller
+    /// 4. Transfer base tokens (WSOL) from seller's escrow to buyer
+    /// 5. Update order statuses from the keeper's remaining amounts
+    /// 6. Emit settlement event
+    /// 
+    /// Only orders that went through `match_callback` (status
+    /// `ORDER_STATUS_MATCHED_PENDING`) can be settled. An order with nothing
+    /// remaining is FILLED; otherwise it stays MATCHED_PENDING while it has
+    /// other queued matches and goes back on the book as PARTIAL after the
+    /// last one.
+    /// 
+    /// If the pair carries an MPC fill recorded by `arcium_match_callback`
+    /// that is still unsettled, it is marked settled for `invoke_update_volume`.
+    pub fn submit_match_results(
+        ctx: Context<SubmitMatchResults>,
+        match_input: MatchResultInput,
+    ) -> Result<()> {
+        let callback_auth = &ctx.accounts.callback_auth;
+        let clock = Clock::get()?;
+
+        // Verify callback authorization
+        require!(
+            callback_auth.is_active,
+            ShadowSwapError::UnauthorizedCallback
+        );
+        require!(
+            callback_auth.expires_at > clock.unix_timestamp,
+            ShadowSwapError::CallbackAuthExpired
+        );
+        require!(
+            callback_auth.authority == ctx.accounts.keeper.key(),
+            ShadowSwapError::UnauthorizedCallback
+        );
+
+        let buyer_order = &mut ctx.accounts.buyer_order;
+        let seller_order = &mut ctx.accounts.seller_order;
+
+        // Verify orders were matched by the MPC callback
+        require!(
+            buyer_order.status == ORDER_STATUS_MATCHED_PENDING && buyer_order.pending_matches > 0,
+            ShadowSwapError::InvalidOrderStatus
+        );
+        require!(
+            seller_order.status == ORDER_STATUS_MATCHED_PENDING && seller_order.pending_matches > 0,
+            ShadowSwapError::InvalidOrderStatus
+        );
+
+        // Calculate transfer amounts
+        // matched_amount is in base token (WSOL) units
+        // execution_price is quote tokens (USDC) per base token
+        // For simplicity in MVP, we assume execution_price includes decimals handling
+        let quote_amount = match_input.matched_amount
+            .checked_mul(match_input.execution_price)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+
+        msg!(
+            "Settling match: buyer={}, seller={}, amount={}, price={}, quote_total={}",
+            match_input.buyer_pubkey,
+            match_input.seller_pubkey,
+            match_input.matched_amount,
+            match_input.execution_price,
+            quote_amount
+        );
+
+        // Transfer quote tokens (USDC) from buyer's escrow to seller
+        // Buyer's escrow authority is the buyer_escrow PDA
+        let buyer_order_key = buyer_order.key();
+        let buyer_escrow_seeds = &[
+            ESCROW_SEED,
+            buyer_order_key.as_ref(),
+            &[ctx.accounts.buyer_escrow.bump],
+        ];
+        let buyer_escrow_signer = &[&buyer_escrow_seeds[..]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.buyer_escrow_token_account.to_account_info(),
+                    to: ctx.accounts.seller_token_account.to_account_info(),
+                    authority: ctx.accounts.buyer_escrow.to_account_info(),
+                },
+                buyer_escrow_signer,
+            ),
+            quote_amount,
+        )?;
+
+        // Transfer base tokens (WSOL) from seller's escrow to buyer
+        // Seller's escrow authority is the seller_escrow PDA
+        let seller_order_key = seller_order.key();
+        let seller_escrow_seeds = &[
+            ESCROW_SEED,
+            seller_order_key.as_ref(),
+            &[ctx.accounts.seller_escrow.bump],
+        ];
+        let seller_escrow_signer = &[&seller_escrow_seeds[..]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.seller_escrow_token_account.to_account_info(),
+                    to: ctx.accounts.buyer_token_account.to_account_info(),
+                    authority: ctx.accounts.seller_escrow.to_account_info(),
+                },
+                seller_escrow_signer,
+            ),
+            match_input.matched_amount,
+        )?;
+
+        // A recorded MPC fill between these orders now counts toward volume
+        if paired_fill(buyer_order, seller_order).is_ok_and(|fill| fill.unsettled) {
+            for order in [&mut **buyer_order, &mut **seller_order] {
+                order.fill.unsettled = false;
+                order.fill.volume_pending = true;
+            }
+        }
+
+        // Update order statuses from the keeper's remaining amounts
+        let filled_orders =
+            settle_matched_order(buyer_order, match_input.buyer_remaining, clock.unix_timestamp)
+                + settle_matched_order(seller_order, match_input.seller_remaining, clock.unix_timestamp);
+
+        // Update order book
+        let order_book = &mut ctx.accounts.order_book;
+        order_book.active_orders = order_book
+            .active_orders
+            .checked_sub(filled_orders)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+        order_book.last_trade_at = clock.unix_timestamp;
+
+        // Emit settlement event
+        emit!(TradeSettled {
+            order_book: order_book.key(),
+            buyer: match_input.buyer_pubkey,
+            seller: match_input.seller_pubkey,
+            buyer_order_id: buyer_order.order_id,
+            seller_order_id: seller_order.order_id,
+            base_amount: match_input.matched_amount,
+            quote_amount,
+            execution_price: match_input.execution_price,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!(
+            "Trade settled: buyer_order={}, seller_order={}, base={}, quote={}",
+            buyer_order.order_id,
+            seller_order.order_id,
+            match_input.matched_amount,
+            quote_amount
+        );
+
+        Ok(())
+    }
+}
+
+/// Transfer `amount` out of an order's escrow token account
+fn escrow_transfer<'info>(
+    token_program: &Program<'info, Token>,
+    escrow: &Account<'info, Escrow>,
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let seeds = &[ESCROW_SEED, escrow.order.as_ref(), &[escrow.bump]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: from.clone(),
+                to: to.clone(),
+                authority: escrow.to_account_info(),
+            },
+            &[&seeds[..]],
+        ),
+        amount,
+    )
+}
+
+/// Whether an order can be matched by the current `match_callback`
+/// 
+/// Orders already queued by an earlier result of the same callback are
+/// MATCHED_PENDING but may match again with what they have left.
+fn is_matchable(order: &Account<EncryptedOrder>, queued_orders: &[Pubkey]) -> bool {
+    order.status == ORDER_STATUS_ACTIVE
+        || order.status == ORDER_STATUS_PARTIAL
+        || (order.status == ORDER_STATUS_MATCHED_PENDING && queued_orders.contains(&order.key()))
+}
+
+/// Settle one queued match of an order, returning 1 if it is now filled
+/// 
+/// The order stays MATCHED_PENDING while other matches of it are queued.
+fn settle_matched_order(order: &mut EncryptedOrder, remaining: u64, now: i64) -> u64 {
+    order.pending_matches -= 1;
+    order.continuous_filled = true;
+    order.updated_at = now;
+    if remaining == 0 {
+        order.pending_matches = 0;
+        order.status = ORDER_STATUS_FILLED;
+        return 1;
+    }
+    if order.pending_matches == 0 {
+        order.status = ORDER_STATUS_PARTIAL;
+    }
+    0
+}
+
+/// Read the order book's oracle, checking staleness and confidence
+/// 
+/// Returns the price and confidence rescaled to the book's price units.
+fn validated_oracle_price(order_book: &OrderBook, oracle: &AccountInfo) -> Result<(u64, u64)> {
+    require!(
+        order_book.oracle != Pubkey::default(),
+        ShadowSwapError::OracleNotConfigured
+    );
+
+    let oracle_price = PythPriceAccount::read_price(oracle)?;
+    oracle_price.validate(
+        Clock::get()?.unix_timestamp,
+        order_book.oracle_max_staleness,
+        order_book.oracle_max_confidence_bps,
+    )?;
+    oracle_price.scaled_to(order_book.oracle_price_expo)
+}
+
+/// Check that MPC order ciphertexts are the prefix of the stored payload
+fn check_payload_ciphertexts(
+    payload: &[u8],
+    order_ciphertexts: &[[u8; 32]; ORDER_INPUT_FIELDS],
+) -> Result<()> {
+    require!(
+        payload.len() >= ORDER_INPUT_FIELDS * 32,
+        ShadowSwapError::InvalidCipherPayload
+    );
+    for (idx, ciphertext) in order_ciphertexts.iter().enumerate() {
+        require!(
+            payload[idx * 32..(idx + 1) * 32] == ciphertext[..],
+            ShadowSwapError::InvalidCipherPayload
+        );
+    }
+    Ok(())
+}
+
+/// MPC arguments for an order's `OrderInput`
+/// 
+/// The ciphertexts must be the prefix of the stored payload and are read
+/// under the key and nonce the owner registered at placement, never ones
+/// supplied by the caller.
+fn order_input_arguments(
+    order: &EncryptedOrder,
+    order_ciphertexts: &[[u8; 32]; ORDER_INPUT_FIELDS],
+) -> Result<Vec<Argument>> {
+    check_payload_ciphertexts(&order.cipher_payload, order_ciphertexts)?;
+    Ok(vec![
+        Argument::ArcisPubkey(order.encryption_pubkey),
+        Argument::PlaintextU128(order.encryption_nonce),
+        Argument::EncryptedU8(order_ciphertexts[0]), // side
+        Argument::EncryptedU64(order_ciphertexts[1]), // price
+        Argument::EncryptedU64(order_ciphertexts[2]), // amount
+        Argument::EncryptedU64(order_ciphertexts[3]), // timestamp
+        Argument::EncryptedU8(order_ciphertexts[4]), // flags
+    ])
+}
+
+/// Check the invoker of an order's verification is its owner or a keeper
+/// 
+/// A keeper proves itself with its `callback_auth` (seeds and activity are
+/// checked by the account constraints).
+fn check_order_invoker(
+    invoker: &Pubkey,
+    order: &EncryptedOrder,
+    callback_auth: &Option<Account<CallbackAuth>>,
+    now: i64,
+) -> Result<()> {
+    match callback_auth {
+        Some(callback_auth) => require!(
+            callback_auth.expires_at > now,
+            ShadowSwapError::CallbackAuthExpired
+        ),
+        None => require!(*invoker == order.owner, ShadowSwapError::UnauthorizedCallback),
+    }
+    Ok(())
+}
+
+/// Lock a shielded balance for one MPC computation
+/// 
+/// Deposits made so far are handed to the computation as `in_flight_credit`;
+/// deposits arriving while it runs stay pending for the next one.
+fn lock_shielded_balance(balance: &mut ShieldedBalance) -> Result<()> {
+    require!(!balance.busy, ShadowSwapError::ShieldedBalanceBusy);
+    balance.busy = true;
+    balance.in_flight_credit = balance.pending_deposit;
+    Ok(())
+}
+
+/// Unlock a shielded balance, storing the computation's result if it has one
+/// 
+/// Without a result the ciphertext and pending deposits are left untouched.
+fn unlock_shielded_balance(
+    balance: &mut ShieldedBalance,
+    updated: Option<([u8; 32], u128)>,
+) -> Result<()> {
+    if let Some((ciphertext, nonce)) = updated {
+        balance.encrypted_balance = ciphertext;
+        balance.balance_nonce = nonce;
+        balance.has_balance = true;
+        balance.pending_deposit = balance
+            .pending_deposit
+            .checked_sub(balance.in_flight_credit)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+    }
+    balance.in_flight_credit = 0;
+    balance.busy = false;
+    Ok(())
+}
+
+/// Allocation ciphertext of a shielded order's escrow
+fn escrow_allocation_ciphertext(escrow: &Escrow) -> Result<[u8; 32]> {
+    escrow
+        .encrypted_remaining
+        .as_slice()
+        .try_into()
+        .map_err(|_| error!(ShadowSwapError::NoShieldedAllocation))
+}
+
+/// The fill `arcium_match_callback` recorded on a buyer and seller order
+/// 
+/// Each order must name the other as counterparty, on its own side, with the
+/// same ciphertexts, so the fill belongs to exactly this pair.
+fn paired_fill(
+    buyer_order: &Account<EncryptedOrder>,
+    seller_order: &Account<EncryptedOrder>,
+) -> Result<RecordedFill> {
+    let (buyer_fill, seller_fill) = (buyer_order.fill, seller_order.fill);
+    require!(
+        buyer_fill.counterparty == seller_order.key()
+            && seller_fill.counterparty == buyer_order.key()
+            && buyer_fill.is_buyer
+            && !seller_fill.is_buyer
+            && buyer_fill.nonce == seller_fill.nonce
+            && buyer_fill.ciphertexts == seller_fill.ciphertexts,
+        ShadowSwapError::FillNotRecorded
+    );
+    Ok(buyer_fill)
+}
+
+/// Put a shielded order back on the book after a settlement did not happen
+/// 
+/// An allocation that differs from the original one means the order was
+/// already partially filled.
+fn restore_unsettled_order(order: &mut EncryptedOrder, escrow: &Escrow, now: i64) {
+    order.status = if escrow.encrypted_remaining == escrow.encrypted_amount {
+        ORDER_STATUS_ACTIVE
+    } else {
+        ORDER_STATUS_PARTIAL
+    };
+    order.updated_at = now;
+}
+
+/// Read the order book's stored volume ciphertexts
+/// 
+/// Returns `(false, zeros)` when no volume has been recorded in the current
+/// period yet; the circuits ignore the ciphertexts in that case.
+fn stored_volume_ciphertexts(order_book: &OrderBook) -> Result<(bool, [[u8; 32]; 2])> {
+    if order_book.encrypted_volume_base.is_empty() {
+        return Ok((false, [[0u8; 32]; 2]));
+    }
+    let base: [u8; 32] = order_book
+        .encrypted_volume_base
+        .as_slice()
+        .try_into()
+        .map_err(|_| ShadowSwapError::InvalidCipherPayload)?;
+    let quote: [u8; 32] = order_book
+        .encrypted_volume_quote
+        .as_slice()
+        .try_into()
+        .map_err(|_| ShadowSwapError::InvalidCipherPayload)?;
+    Ok((true, [base, quote]))
+}
+
+// ============================================================================
+// Account Structures
+// ============================================================================
+
+/// EncryptedOrder - Stores individual encrypted order data
+/// 
+/// This account stores fully encrypted order information. The on-chain program
+/// never sees plaintext order details (price, amount, side). All order data
+/// is encrypted client-side and stored as a cipher payload.
+#[account]
+pub struct EncryptedOrder {
+    /// Order owner's public key
+    pub owner: Pubkey,
+    
+    /// Order book this order belongs to
+    pub order_book: Pubkey,
+    
+    /// Encrypted order payload containing:
+    /// - Order side (buy/sell) - encrypted
+    /// - Price - encrypted
+    /// - Amount - encrypted
+    /// - Other order parameters - encrypted
+    /// Maximum size: 512 bytes for encrypted data
+    pub cipher_payload: Vec<u8>,
+    
+    /// Owner's x25519 public key the `OrderInput` ciphertexts are encrypted under
+    pub encryption_pubkey: [u8; 32],
+    
+    /// Nonce of the `OrderInput` ciphertexts
+    pub encryption_nonce: u128,
+    
+    /// Order status (1 = active, 2 = partially filled, 3 = filled, 4 = cancelled,
+    /// 5 = matched pending, 6 = amount mismatch, 7 = pending verification,
+    /// 8 = conditional)
+    /// This can be public as it doesn't reveal order details
+    pub status: u8,
+    
+    /// Encrypted remaining amount (updates as order fills)
+    pub encrypted_remaining: Vec<u8>,
+    
+    /// Escrow account holding the order's funds
+    pub escrow: Pubkey,
+    
+    /// Order creation timestamp
+    pub created_at: i64,
+    
+    /// Last update timestamp
+    pub updated_at: i64,
+    
+    /// Order ID (sequential, assigned by order book)
+    pub order_id: u64,
+    
+    /// Auction epoch the order was placed in (auction books only)
+    pub epoch: u64,
+    
+    /// Base amount filled through batch auctions so far
+    pub auction_filled: u64,
+    
+    /// Matches queued by `match_callback` and not yet settled
+    pub pending_matches: u16,
+    
+    /// Whether the order has traded through continuous matching; such
+    /// orders cannot join batch auctions, which size them by `auction_filled`
+    pub continuous_filled: bool,
+    
+    /// Fill of the last MPC match with this order
+    pub fill: RecordedFill,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// ConditionalOrder - Hidden trigger for a stop-loss / take-profit order
+/// 
+/// Points at an `EncryptedOrder` parked in `ORDER_STATUS_CONDITIONAL`. The
+/// trigger direction and price are only ever decrypted inside MPC.
+#[account]
+pub struct ConditionalOrder {
+    /// Order owner
+    pub owner: Pubkey,
+    
+    /// Order book
+    pub order_book: Pubkey,
+    
+    /// Order activated when the trigger fires
+    pub order: Pubkey,
+    
+    /// Owner's x25519 public key for the trigger ciphertexts
+    pub trigger_pubkey: [u8; 32],
+    
+    /// Nonce of the trigger ciphertexts
+    pub trigger_nonce: u128,
+    
+    /// Ciphertexts of the circuit's `TriggerInput` (direction, trigger price)
+    pub encrypted_trigger: [[u8; 32]; TRIGGER_INPUT_FIELDS],
+    
+    /// Oracle price submitted by the most recent crank
+    pub last_checked_price: u64,
+    
+    /// Time of the most recent crank
+    pub last_checked_at: i64,
+    
+    /// Time the trigger fired (0 = not yet)
+    pub triggered_at: i64,
+    
+    /// Creation timestamp
+    pub created_at: i64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// OrderBook - Manages the order book for a trading pair
+/// 
+/// For MVP, this handles SOL/USDC pair. The structure is designed to be
+/// extensible for multi-token support in future phases.
+#[account]
+pub struct OrderBook {
+    /// Authority that can manage the order book
+    pub authority: Pubkey,
+    
+    /// Base token mint (SOL for MVP)
+    pub base_mint: Pubkey,
+    
+    /// Quote token mint (USDC for MVP)
+    pub quote_mint: Pubkey,
+    
+    /// Total number of orders created (used for sequential order IDs)
+    pub order_count: u64,
+    
+    /// Number of active orders
+    pub active_orders: u64,
+    
+    /// Total trading volume (base token) - encrypted
+    pub encrypted_volume_base: Vec<u8>,
+    
+    /// Total trading volume (quote token) - encrypted
+    pub encrypted_volume_quote: Vec<u8>,
+    
+    /// Nonce of the MXE-encrypted volume ciphertexts
+    pub encrypted_volume_nonce: u128,
+    
+    /// Incremented on every volume write; stale MPC results are rejected
+    pub volume_version: u64,
+    
+    /// Start of the current volume reporting period
+    pub volume_period_start: i64,
+    
+    /// Order book creation timestamp
+    pub created_at: i64,
+    
+    /// Last trade timestamp
+    pub last_trade_at: i64,
+    
+    /// Fee basis points (e.g., 30 = 0.3%)
+    pub fee_bps: u16,
+    
+    /// Fee collector account
+    pub fee_collector: Pubkey,
+    
+    /// Minimum order size (base token, in lamports/smallest unit)
+    pub min_base_order_size: u64,
+    
+    /// Whether the order book is active
+    pub is_active: bool,
+    
+    /// Reference price oracle for pegged orders (default = none)
+    pub oracle: Pubkey,
+    
+    /// Maximum oracle price age in seconds
+    pub oracle_max_staleness: i64,
+    
+    /// Maximum oracle confidence interval, in basis points of the price
+    pub oracle_max_confidence_bps: u16,
+    
+    /// Exponent of the book's price units that oracle prices are rescaled to
+    pub oracle_price_expo: i32,
+    
+    /// Batch auction epoch length in seconds (0 = continuous matching)
+    pub auction_epoch_duration: i64,
+    
+    /// Epoch new orders are placed into
+    pub current_epoch: u64,
+    
+    /// Start of the current epoch
+    pub epoch_started_at: i64,
+    
+    /// Lowest order ID that may still join a batch auction; every order
+    /// below it is filled, cancelled or otherwise barred from auctions
+    pub auction_floor: u64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// Escrow - Holds funds for an order until it's matched or cancelled
+/// 
+/// Each order has an associated escrow account that holds either:
+/// - Base tokens (for sell orders)
+/// - Quote tokens (for buy orders)
+#[account]
+pub struct Escrow {
+    /// Order this escrow belongs to
+    pub order: Pubkey,
+    
+    /// Order owner
+    pub owner: Pubkey,
+    
+    /// Order book
+    pub order_book: Pubkey,
+    
+    /// Token account holding escrowed funds
+    /// This is a PDA-owned token account
+    pub token_account: Pubkey,
+    
+    /// Mint of the escrowed token
+    pub token_mint: Pubkey,
+    
+    /// Original encrypted amount deposited
+    pub encrypted_amount: Vec<u8>,
+    
+    /// Encrypted remaining amount (decreases as order fills)
+    /// 
+    /// For shielded orders this is the MXE-encrypted allocation reserved in
+    /// the vault (empty until allocated or after release).
+    pub encrypted_remaining: Vec<u8>,
+    
+    /// Nonce of a shielded allocation in `encrypted_remaining`
+    pub encrypted_remaining_nonce: u128,
+    
+    /// MXE-encrypted base amount of a shielded order not yet filled
+    pub encrypted_unfilled: [u8; 32],
+    
+    /// Nonce of `encrypted_unfilled`
+    pub encrypted_unfilled_nonce: u128,
+    
+    /// Shielded vault backing this escrow (default = own token account)
+    pub vault: Pubkey,
+    
+    /// Escrow creation timestamp
+    pub created_at: i64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// ShieldedVault - Pooled token account for one mint of an order book
+/// 
+/// Holds every shielded deposit of that mint. Per-user balances and per-order
+/// allocations are only tracked encrypted, in `ShieldedBalance` and `Escrow`.
+#[account]
+pub struct ShieldedVault {
+    /// Order book
+    pub order_book: Pubkey,
+    
+    /// Mint held by the vault (the book's base or quote mint)
+    pub token_mint: Pubkey,
+    
+    /// Vault-owned token account holding all deposits
+    pub token_account: Pubkey,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// ShieldedBalance - A user's encrypted free balance in a shielded vault
+#[account]
+pub struct ShieldedBalance {
+    /// Balance owner
+    pub owner: Pubkey,
+    
+    /// Vault the balance lives in
+    pub vault: Pubkey,
+    
+    /// MXE-encrypted free balance (`ShieldedAmount`)
+    pub encrypted_balance: [u8; 32],
+    
+    /// Nonce of `encrypted_balance`
+    pub balance_nonce: u128,
+    
+    /// Whether `encrypted_balance` holds a ciphertext yet
+    pub has_balance: bool,
+    
+    /// Public deposits not yet folded into the encrypted balance
+    pub pending_deposit: u64,
+    
+    /// Part of `pending_deposit` handed to the running computation
+    pub in_flight_credit: u64,
+    
+    /// Amount of the running withdrawal request
+    pub in_flight_withdrawal: u64,
+    
+    /// Whether an MPC computation on this balance is queued
+    pub busy: bool,
+    
+    /// Creation timestamp
+    pub created_at: i64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// AuctionEpoch - Orders and result of one batch auction epoch
+/// 
+/// Created when the epoch closes; `orders`, `fills` and `done` are indexed
+/// by position in the clearing batch.
+#[account]
+pub struct AuctionEpoch {
+    /// Order book
+    pub order_book: Pubkey,
+    
+    /// Epoch number
+    pub epoch: u64,
+    
+    /// Auction status (1 = closed, 2 = clearing, 3 = cleared, 4 = settled)
+    pub status: u8,
+    
+    /// Time the epoch closed
+    pub closed_at: i64,
+    
+    /// Time the clearing computation was queued
+    pub clearing_started_at: i64,
+    
+    /// Number of orders in the clearing batch
+    pub order_count: u8,
+    
+    /// Orders in the clearing batch
+    pub orders: [Pubkey; AUCTION_BATCH_SIZE],
+    
+    /// Uniform clearing price (0 = nothing crossed)
+    pub clearing_price: u64,
+    
+    /// Base amount each order fills in this epoch
+    pub fills: [u64; AUCTION_BATCH_SIZE],
+    
+    /// Whether each order is completely filled after this epoch
+    pub done: [bool; AUCTION_BATCH_SIZE],
+    
+    /// Settlement timestamp
+    pub settled_at: i64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// CallbackAuth - Authentication token for callback operations
+/// 
+/// This account is used to authorize callback operations from the keeper/matching
+/// engine. It ensures only authorized entities can trigger order matching.
+#[account]
+pub struct CallbackAuth {
+    /// Authority that can use this callback auth
+    pub authority: Pubkey,
+    
+    /// Order book this callback auth is valid for
+    pub order_book: Pubkey,
+    
+    /// Nonce to prevent replay attacks
+    pub nonce: u64,
+    
+    /// Expiration timestamp
+    pub expires_at: i64,
+    
+    /// Whether this callback auth is active
+    pub is_active: bool,
+    
+    /// Creation timestamp
+    pub created_at: i64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+// ============================================================================
+// Match Result Structures
+// ============================================================================
+
+/// Result of matching two orders from Arcium MPC
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct MatchResult {
+    /// Buyer's order account pubkey
+    pub buyer_pubkey: Pubkey,
+    
+    /// Seller's order account pubkey
+    pub seller_pubkey: Pubkey,
+    
+    /// Buyer's order ID
+    pub buyer_order_id: u64,
+    
+    /// Seller's order ID
+    pub seller_order_id: u64,
+    
+    /// Matched amount (encrypted)
+    pub encrypted_amount: Vec<u8>,
+    
+    /// Execution price (encrypted)
+    pub encrypted_price: Vec<u8>,
+    
+    /// Buyer's remaining amount after this match (encrypted)
+    pub buyer_encrypted_remaining: Vec<u8>,
+    
+    /// Seller's remaining amount after this match (encrypted)
+    pub seller_encrypted_remaining: Vec<u8>,
+}
+
+/// Encrypted `MatchOutput` for a single recipient
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct EncryptedMatchOutput {
+    /// x25519 public key the output is encrypted to (zeroed for the MXE copy)
+    pub encryption_key: [u8; 32],
+    
+    /// Encryption nonce (little-endian u128)
+    pub nonce: Vec<u8>,
+    
+    /// Matched amount (encrypted)
+    pub encrypted_matched_amount: Vec<u8>,
+    
+    /// Execution price (encrypted)
+    pub encrypted_execution_price: Vec<u8>,
+}
+
+/// MXE-encrypted `MatchOutput` of an order's last MPC match
+/// 
+/// Written on both orders by `arcium_match_callback`; a zero `counterparty`
+/// means no fill is recorded.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
+pub struct RecordedFill {
+    /// The other order of the match
+    pub counterparty: Pubkey,
+    
+    /// Whether this order was the buy side of the match
+    pub is_buyer: bool,
+    
+    /// Encryption nonce of `ciphertexts`
+    pub nonce: u128,
+    
+    /// Matched amount and execution price (encrypted)
+    pub ciphertexts: [[u8; 32]; 2],
+    
+    /// Not yet consumed by `invoke_shielded_settlement`
+    pub unsettled: bool,
+    
+    /// Settled but not yet added to the book's volume
+    pub volume_pending: bool,
+}
+
+/// Match result input for settlement (plaintext from keeper)
+/// 
+/// This struct contains the decrypted match details that the keeper bot
+/// submits to execute the trade settlement. The keeper has computed the
+/// matching off-chain and decrypted the results.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct MatchResultInput {
+    /// Buyer's order account pubkey
+    pub buyer_pubkey: Pubkey,
+    
+    /// Seller's order account pubkey
+    pub seller_pubkey: Pubkey,
+    
+    /// Matched amount in base token units (e.g., lamports for WSOL)
+    pub matched_amount: u64,
+    
+    /// Execution price: quote tokens per base token
+    /// (adjusted for decimals, e.g., USDC micro-units per WSOL lamport)
+    pub execution_price: u64,
+    
+    /// Base amount left unfilled on the buyer's order after this match
+    pub buyer_remaining: u64,
+    
+    /// Base amount left unfilled on the seller's order after this match
+    pub seller_remaining: u64,
+}
+
+/// One order's MPC input for batch auction clearing
+/// 
+/// `order_ciphertexts` must be the prefix of the order's `cipher_payload`;
+/// they are read under the key and nonce stored on the order.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AuctionOrderInput {
+    pub order_ciphertexts: [[u8; 32]; ORDER_INPUT_FIELDS],
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+/// Event emitted when orders are matched and queued for execution
+#[event]
+pub struct MatchQueued {
+    pub order_book: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub buyer_order_id: u64,
+    pub seller_order_id: u64,
+    pub encrypted_amount: Vec<u8>,
+    pub encrypted_price: Vec<u8>,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a trade is settled
+#[event]
+pub struct TradeSettled {
+    pub order_book: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub buyer_order_id: u64,
+    pub seller_order_id: u64,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub execution_price: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the MPC posted amount check completes
+#[event]
+pub struct PostedAmountVerified {
+    pub order_book: Pubkey,
+    pub order: Pubkey,
+    pub order_id: u64,
+    pub is_consistent: bool,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the MPC escrow coverage check completes
+#[event]
+pub struct EscrowCoverageVerified {
+    pub order_book: Pubkey,
+    pub order: Pubkey,
+    pub order_id: u64,
+    pub is_covered: bool,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the encrypted running volume is updated
+#[event]
+pub struct VolumeUpdated {
+    pub order_book: Pubkey,
+    pub volume_version: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the authority reveals aggregate period volume
+#[event]
+pub struct VolumeRevealed {
+    pub order_book: Pubkey,
+    pub period_start: i64,
+    pub period_end: i64,
+    pub base_volume: u64,
+    pub quote_volume: u64,
+}
+
+/// Event emitted when Arcium MPC matching completes
+#[event]
+pub struct ArciumMatchCompleted {
+    pub order_book: Pubkey,
+    pub buy_order: Pubkey,
+    pub sell_order: Pubkey,
+    pub buyer_order_id: u64,
+    pub seller_order_id: u64,
+    /// Whether the fill was recorded on both orders
+    pub recorded: bool,
+    pub buyer_output: EncryptedMatchOutput,
+    pub seller_output: EncryptedMatchOutput,
+    pub keeper_output: EncryptedMatchOutput,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a conditional order's trigger fires
+#[event]
+pub struct ConditionalOrderTriggered {
+    pub order_book: Pubkey,
+    pub order: Pubkey,
+    pub order_id: u64,
+    pub oracle_price: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when tokens are deposited into a shielded vault
+#[event]
+pub struct ShieldedDepositMade {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when two shielded orders settle inside the vaults
+/// 
+/// Fill size and price are not included; they stay encrypted.
+#[event]
+pub struct ShieldedTradeSettled {
+    pub order_book: Pubkey,
+    pub buyer_order_id: u64,
+    pub seller_order_id: u64,
+    pub buyer_filled: bool,
+    pub seller_filled: bool,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a shielded withdrawal is decided
+#[event]
+pub struct ShieldedWithdrawal {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub approved: bool,
+    pub timestamp: i64,
+}
+
+/// Event emitted when an auction epoch closes
+#[event]
+pub struct AuctionEpochClosed {
+    pub order_book: Pubkey,
+    pub epoch: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when MPC returns an epoch's clearing price
+#[event]
+pub struct AuctionCleared {
+    pub order_book: Pubkey,
+    pub epoch: u64,
+    pub clearing_price: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when an epoch's fills are settled
+#[event]
+pub struct AuctionSettled {
+    pub order_book: Pubkey,
+    pub epoch: u64,
+    pub clearing_price: u64,
+    pub base_volume: u64,
+    pub filled_orders: u64,
+    pub timestamp: i64,
+}
+
+// ============================================================================
+// Error Codes
+// ============================================================================
+
+#[error_code]
+pub enum ShadowSwapError {
+    #[msg("Order book is not active")]
+    OrderBookNotActive,
+    
+    #[msg("Invalid order status")]
+    InvalidOrderStatus,
+    
+    #[msg("Order amount too small")]
+    OrderTooSmall,
+    
+    #[msg("Invalid cipher payload")]
+    InvalidCipherPayload,
+    
+    #[msg("Unauthorized callback")]
+    UnauthorizedCallback,
+    
+    #[msg("Callback auth expired")]
+    CallbackAuthExpired,
+    
+    #[msg("Invalid escrow account")]
+    InvalidEscrow,
+    
+    #[msg("Insufficient funds in escrow")]
+    InsufficientEscrowFunds,
+    
+    #[msg("Invalid token mint")]
+    InvalidTokenMint,
+    
+    #[msg("Order not found")]
+    OrderNotFound,
+    
+    #[msg("Order already filled")]
+    OrderAlreadyFilled,
+    
+    #[msg("Order already cancelled")]
+    OrderAlreadyCancelled,
+    
+    #[msg("Invalid order book")]
+    InvalidOrderBook,
+    
+    #[msg("Invalid fee configuration")]
+    InvalidFeeConfiguration,
+    
+    #[msg("Numerical overflow")]
+    NumericalOverflow,
+    
+    #[msg("Order is not active")]
+    OrderNotActive,
+    
+    #[msg("Volume state changed since the computation was queued")]
+    StaleVolumeState,
+    
+    #[msg("No volume recorded in the current period")]
+    NoVolumeToReveal,
+    
+    #[msg("Invalid oracle configuration")]
+    InvalidOracleConfiguration,
+    
+    #[msg("Order book has no oracle configured")]
+    OracleNotConfigured,
+    
+    #[msg("Invalid oracle account")]
+    InvalidOracleAccount,
+    
+    #[msg("Oracle price is stale")]
+    StaleOraclePrice,
+    
+    #[msg("Oracle confidence interval too wide")]
+    OracleConfidenceTooWide,
+    
+    #[msg("Reference price outside the oracle confidence interval")]
+    ReferencePriceOutOfRange,
+
+    #[msg("Escrow is held in a shielded vault")]
+    ShieldedEscrow,
+
+    #[msg("Escrow is not held in this shielded vault")]
+    NotShieldedEscrow,
+
+    #[msg("Shielded balance has a computation in flight")]
+    ShieldedBalanceBusy,
+
+    #[msg("Invalid shielded vault")]
+    InvalidShieldedVault,
+
+    #[msg("Shielded order has no allocation")]
+    NoShieldedAllocation,
+
+    #[msg("Invalid auction configuration")]
+    InvalidAuctionConfiguration,
+
+    #[msg("Order book trades in batch auctions")]
+    AuctionModeActive,
+
+    #[msg("Order book does not trade in batch auctions")]
+    NotAuctionBook,
+
+    #[msg("Auction epoch has not ended")]
+    AuctionEpochNotOver,
+
+    #[msg("Invalid auction status")]
+    InvalidAuctionStatus,
+
+    #[msg("Invalid auction batch")]
+    InvalidAuctionBatch,
+
+    #[msg("Order has continuous-matching fills and cannot join an auction")]
+    ContinuousFillsInAuction,
+
+    #[msg("Auction clearing has not timed out")]
+    AuctionClearingNotExpired,
+
+    #[msg("An order cannot match against itself")]
+    SelfMatch,
+
+    #[msg("No MPC fill between these orders is recorded")]
+    FillNotRecorded,
+
+    #[msg("The recorded fill has already been settled")]
+    FillAlreadySettled,
+
+    #[msg("The recorded fill is not waiting to be added to volume")]
+    FillVolumeNotPending,
+}
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Maximum size for encrypted order payload (512 bytes)
+pub const MAX_CIPHER_PAYLOAD_SIZE: usize = 512;
+
+/// Maximum size for encrypted amount fields (64 bytes)
+pub const MAX_ENCRYPTED_AMOUNT_SIZE: usize = 64;
+
+/// Maximum size for encrypted volume fields (64 bytes)
+pub const MAX_ENCRYPTED_VOLUME_SIZE: usize = 64;
+
+/// Number of encrypted fields in the circuit's `OrderInput` (side, price, amount, timestamp, flags)
+pub const ORDER_INPUT_FIELDS: usize = 5;
+
+/// Order status: Active
+pub const ORDER_STATUS_ACTIVE: u8 = 1;
+
+/// Order status: Partially filled
+pub const ORDER_STATUS_PARTIAL: u8 = 2;
+
+/// Order status: Fully filled
+pub const ORDER_STATUS_FILLED: u8 = 3;
+
+/// Order status: Cancelled
+pub const ORDER_STATUS_CANCELLED: u8 = 4;
+
+/// Order status: Matched, pending execution
+pub const ORDER_STATUS_MATCHED_PENDING: u8 = 5;
+
+/// Order status: Encrypted amount inconsistent with escrow (cancel only)
+pub const ORDER_STATUS_AMOUNT_MISMATCH: u8 = 6;
+
+/// Order status: Placed, waiting for the MPC escrow coverage check
+pub const ORDER_STATUS_PENDING_VERIFICATION: u8 = 7;
+
+/// Order status: Escrowed, waiting for its hidden trigger to fire
+pub const ORDER_STATUS_CONDITIONAL: u8 = 8;
+
+/// Number of encrypted fields in the circuit's `TriggerInput` (direction, trigger_price)
+pub const TRIGGER_INPUT_FIELDS: usize = 2;
+
+/// Orders per batch auction clearing (fixed by the clear_batch_auction circuit)
+pub const AUCTION_BATCH_SIZE: usize = 8;
+
+/// Auction epoch statuses
+pub const AUCTION_STATUS_CLOSED: u8 = 1;
+pub const AUCTION_STATUS_CLEARING: u8 = 2;
+pub const AUCTION_STATUS_CLEARED: u8 = 3;
+pub const AUCTION_STATUS_SETTLED: u8 = 4;
+
+/// Seconds after which a clearing without an MPC callback can be expired
+pub const AUCTION_CLEARING_TIMEOUT: i64 = 600;
+
+/// Divisor applied to `amount * price` to get quote units
+/// (submit_match_results computes quote_amount = matched_amount * execution_price)
+pub const QUOTE_PRICE_SCALE: u64 = 1;
+
+/// Seeds for PDA derivation
+pub const ORDER_BOOK_SEED: &[u8] = b"order_book";
+pub const ORDER_SEED: &[u8] = b"order";
+pub const ESCROW_SEED: &[u8] = b"escrow";
+pub const CALLBACK_AUTH_SEED: &[u8] = b"callback_auth";
+pub const CONDITIONAL_ORDER_SEED: &[u8] = b"conditional_order";
+pub const SHIELDED_VAULT_SEED: &[u8] = b"shielded_vault";
+pub const SHIELDED_VAULT_TOKEN_SEED: &[u8] = b"shielded_vault_token";
+pub const SHIELDED_BALANCE_SEED: &[u8] = b"shielded_balance";
+pub const AUCTION_EPOCH_SEED: &[u8] = b"auction_epoch";
+
+// ============================================================================
+// Instruction Contexts
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitializeOrderBook<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<OrderBook>() + MAX_ENCRYPTED_VOLUME_SIZE * 2 + 72, // Extra space for Vec fields
+        seeds = [ORDER_BOOK_SEED, base_mint.key().as_ref(), quote_mint.key().as_ref()],
+        bump
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Fee collector can be any account
+    pub fee_collector: UncheckedAccount<'info>,
+    
+    /// CHECK: Base mint reference for PDA seeds
+    pub base_mint: UncheckedAccount<'info>,
+    
+    /// CHECK: Quote mint reference for PDA seeds
+    pub quote_mint: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlaceOrder<'info> {
+    #[account(
+        mut,
+        constraint = order_book.is_active @ ShadowSwapError::OrderBookNotActive
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<EncryptedOrder>() + MAX_CIPHER_PAYLOAD_SIZE + MAX_ENCRYPTED_AMOUNT_SIZE + 100,
+        seeds = [ORDER_SEED, order_book.key().as_ref(), order_book.order_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub order: Account<'info, EncryptedOrder>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<Escrow>() + MAX_ENCRYPTED_AMOUNT_SIZE * 2 + 100,
+        seeds = [ESCROW_SEED, order.key().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        init,
+        payer = owner,
+        token::mint = token_mint,
+        token::authority = escrow,
+        seeds = [b"escrow_token", order.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    pub token_mint: Account<'info, anchor_spl::token::Mint>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOrder<'info> {
+    #[account(
+        mut,
+        has_one = owner @ ShadowSwapError::UnauthorizedCallback,
+        constraint = order.status == ORDER_STATUS_ACTIVE
+            || order.status == ORDER_STATUS_PARTIAL
+            || order.status == ORDER_STATUS_PENDING_VERIFICATION
+            || order.status == ORDER_STATUS_AMOUNT_MISMATCH
+            || order.status == ORDER_STATUS_CONDITIONAL @ ShadowSwapError::InvalidOrderStatus
+    )]
+    pub order: Account<'info, EncryptedOrder>,
+    
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, order.key().as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.vault == Pubkey::default() @ ShadowSwapError::ShieldedEscrow
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(mut)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub order_book: Account<'info, OrderBook>,
+    
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct MatchOrders<'info> {
+    #[account(
+        constraint = callback_auth.is_active @ ShadowSwapError::UnauthorizedCallback,
+        constraint = callback_auth.authority == keeper.key() @ ShadowSwapError::UnauthorizedCallback,
+        seeds = [CALLBACK_AUTH_SEED, order_book.key().as_ref(), keeper.key().as_ref()],
+        bump = callback_auth.bump
+    )]
+    pub callback_auth: Account<'info, CallbackAuth>,
+    
+    #[account(
+        constraint = order_book.auction_epoch_duration == 0 @ ShadowSwapError::AuctionModeActive
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    #[account(
+        mut,
+        constraint = buy_order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook
+    )]
+    pub buy_order: Account<'info, EncryptedOrder>,
+    
+    #[account(
+        mut,
+        constraint = sell_order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook
+    )]
+    pub sell_order: Account<'info, EncryptedOrder>,
+    
+    pub keeper: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MatchCallback<'info> {
+    #[account(
+        constraint = callback_auth.is_active @ ShadowSwapError::UnauthorizedCallback,
+        constraint = callback_auth.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook,
+        seeds = [CALLBACK_AUTH_SEED, order_book.key().as_ref(), keeper.key().as_ref()],
+        bump = callback_auth.bump
+    )]
+    pub callback_auth: Account<'info, CallbackAuth>,
+    
+    #[account(
+        constraint = order_book.auction_epoch_duration == 0 @ ShadowSwapError::AuctionModeActive
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    pub keeper: Signer<'info>,
+    
+    // Matched order accounts (writable) and their escrows are passed via
+    // remaining_accounts, which allows a dynamic number of matches in a
+    // single callback
+}
+
+#[derive(Accounts)]
+pub struct ConfigureOracle<'info> {
+    #[account(
+        mut,
+        has_one = authority @ ShadowSwapError::UnauthorizedCallback
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateCallbackAuth<'info> {
+    #[account(
+        constraint = order_book.authority == authority.key() @ ShadowSwapError::UnauthorizedCallback
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<CallbackAuth>(),
+        seeds = [CALLBACK_AUTH_SEED, order_book.key().as_ref(), keeper.key().as_ref()],
+        bump
+    )]
+    pub callback_auth: Account<'info, CallbackAuth>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Keeper account to be authorized
+    pub keeper: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+// ============================================================================
+// Arcium MPC Account Contexts
+// ============================================================================
+
+/// Context for initializing the match computation definition
+/// This is auto-generated by Arcium macros
+#[derive(Accounts)]
+pub struct InitMatchCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub order_book: Account<'info, OrderBook>,
+    // Additional Arcium-required accounts will be auto-generated
+    // by the #[arcium_program] macro
+}
+
+/// Context for invoking encrypted matching computation
+/// This is auto-generated by Arcium macros
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct InvokeMatching<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub order_book: Account<'info, OrderBook>,
+    
+    #[account(
+        constraint = buy_order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook,
+        constraint = buy_order.status == ORDER_STATUS_ACTIVE
+            || buy_order.status == ORDER_STATUS_PARTIAL @ ShadowSwapError::OrderNotActive
+    )]
+    pub buy_order: Account<'info, EncryptedOrder>,
+    
+    #[account(
+        constraint = sell_order.key() != buy_order.key() @ ShadowSwapError::SelfMatch,
+        constraint = sell_order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook,
+        constraint = sell_order.status == ORDER_STATUS_ACTIVE
+            || sell_order.status == ORDER_STATUS_PARTIAL @ ShadowSwapError::OrderNotActive
+    )]
+    pub sell_order: Account<'info, EncryptedOrder>,
+    
+    /// CHECK: Oracle price account; layout is checked by `PythPriceAccount`.
+    /// Required when a non-zero reference price is supplied.
+    #[account(address = order_book.oracle @ ShadowSwapError::InvalidOracleAccount)]
+    pub oracle: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: PDA account for signing (auto-generated by Arcium)
+    #[account(
+        mut,
+        seeds = [b"sign_pda"],
+        bump
+    )]
+    pub sign_pda_account: UncheckedAccount<'info>,
+    
+    // Additional Arcium MPC-required accounts (cluster, computation, etc.)
+    // are auto-generated by the #[arcium_program] macro
+}
+
+/// Context for Arcium match callback
+/// Arcium callback accounts are auto-generated; both orders are passed as
+/// extra callback accounts by `invoke_matching`
+#[derive(Accounts)]
+pub struct ArciumMatchCallback<'info> {
+    #[account(mut)]
+    pub buy_order: Account<'info, EncryptedOrder>,
+    
+    #[account(mut)]
+    pub sell_order: Account<'info, EncryptedOrder>,
+}
+
+/// Context for initializing the verify amount computation definition
+/// This is auto-generated by Arcium macros
+#[derive(Accounts)]
+pub struct InitVerifyAmountCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub order_book: Account<'info, OrderBook>,
+    // Additional Arcium-required accounts will be auto-generated
+    // by the #[arcium_program] macro
+}
+
+/// Context for invoking the posted amount verification computation
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct InvokeVerifyPostedAmount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub order_book: Account<'info, OrderBook>,
+    
+    /// Keeper authorization; omitted when the order owner invokes
+    #[account(
+        constraint = callback_auth.is_active @ ShadowSwapError::UnauthorizedCallback,
+        constraint = callback_auth.authority == payer.key() @ ShadowSwapError::UnauthorizedCallback,
+        seeds = [CALLBACK_AUTH_SEED, order_book.key().as_ref(), payer.key().as_ref()],
+        bump = callback_auth.bump
+    )]
+    pub callback_auth: Option<Account<'info, CallbackAuth>>,
+    
+    #[account(
+        constraint = order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook
+    )]
+    pub order: Account<'info, EncryptedOrder>,
+    
+    #[account(
+        seeds = [ESCROW_SEED, order.key().as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.vault == Pubkey::default() @ ShadowSwapError::ShieldedEscrow
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        constraint = escrow_token_account.key() == escrow.token_account @ ShadowSwapError::InvalidEscrow
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: PDA account for signing (auto-generated by Arcium)
+    #[account(
+        mut,
+        seeds = [b"sign_pda"],
+        bump
+    )]
+    pub sign_pda_account: UncheckedAccount<'info>,
+    
+    // Additional Arcium MPC-required accounts (cluster, computation, etc.)
+    // are auto-generated by the #[arcium_program] macro
+}
+
+/// Context for initializing the update volume computation definition
+/// This is auto-generated by Arcium macros
+#[derive(Accounts)]
+pub struct InitUpdateVolumeCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub order_book: Account<'info, OrderBook>,
+    // Additional Arcium-required accounts will be auto-generated
+    // by the #[arcium_program] macro
+}
+
+/// Context for initializing the reveal volume computation definition
+/// This is auto-generated by Arcium macros
+#[derive(Accounts)]
+pub struct InitRevealVolumeCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub order_book: Account<'info, OrderBook>,
+    // Additional Arcium-required accounts will be auto-generated
+    // by the #[arcium_program] macro
+}
+
+/// Context for queueing an encrypted volume update (keeper only)
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct InvokeUpdateVolume<'info> {
+    #[account(
+        constraint = callback_auth.is_active @ ShadowSwapError::UnauthorizedCallback,
+        constraint = callback_auth.authority == payer.key() @ ShadowSwapError::UnauthorizedCallback,
+        seeds = [CALLBACK_AUTH_SEED, order_book.key().as_ref(), payer.key().as_ref()],
+        bump = callback_auth.bump
+    )]
+    pub callback_auth: Account<'info, CallbackAuth>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub order_book: Account<'info, OrderBook>,
+    
+    #[account(
+        constraint = buyer_order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook
+    )]
+    pub buyer_order: Account<'info, EncryptedOrder>,
+    
+    #[account(
+        constraint = seller_order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook
+    )]
+    pub seller_order: Account<'info, EncryptedOrder>,
+    
+    /// CHECK: PDA account for signing (auto-generated by Arcium)
+    #[account(
+        mut,
+        seeds = [b"sign_pda"],
+        bump
+    )]
+    pub sign_pda_account: UncheckedAccount<'info>,
+    
+    // Additional Arcium MPC-required accounts (cluster, computation, etc.)
+    // are auto-generated by the #[arcium_program] macro
+}
+
+/// Context for the volume update callback
+/// Arcium callback accounts are auto-generated; the order book and both
+/// orders are passed as extra callback accounts by `invoke_update_volume`
+#[derive(Accounts)]
+pub struct UpdateVolumeCallback<'info> {
+    #[account(mut)]
+    pub order_book: Account<'info, OrderBook>,
+    
+    #[account(mut)]
+    pub buyer_order: Account<'info, EncryptedOrder>,
+    
+    #[account(mut)]
+    pub seller_order: Account<'info, EncryptedOrder>,
+}
+
+/// Context for requesting a period volume reveal (authority only)
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct RequestVolumeReveal<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
     
-    /// Escrow account holding the order's funds
-    pub escrow: Pubkey,
+    #[account(
+        constraint = order_book.authority == payer.key() @ ShadowSwapError::UnauthorizedCallback
+    )]
+    pub order_book: Account<'info, OrderBook>,
     
-    /// Order creation timestamp
-    pub created_at: i64,
+    /// CHECK: PDA account for signing (auto-generated by Arcium)
+    #[account(
+        mut,
+        seeds = [b"sign_pda"],
+        bump
+    )]
+    pub sign_pda_account: UncheckedAccount<'info>,
     
-    /// Last update timestamp
-    pub updated_at: i64,
+    // Additional Arcium MPC-required accounts (cluster, computation, etc.)
+    // are auto-generated by the #[arcium_program] macro
+}
+
+/// Context for the period volume reveal callback
+/// Arcium callback accounts are auto-generated; the order book is passed as
+/// an extra callback account by `request_volume_reveal`
+#[derive(Accounts)]
+pub struct RevealPeriodVolumeCallback<'info> {
+    #[account(mut)]
+    pub order_book: Account<'info, OrderBook>,
+}
+
+/// Context for attaching a hidden trigger to a just-placed order
+#[derive(Accounts)]
+pub struct AttachTrigger<'info> {
+    #[account(
+        mut,
+        has_one = owner @ ShadowSwapError::UnauthorizedCallback,
+        constraint = order.status == ORDER_STATUS_PENDING_VERIFICATION @ ShadowSwapError::InvalidOrderStatus
+    )]
+    pub order: Account<'info, EncryptedOrder>,
     
-    /// Order ID (sequential, assigned by order book)
-    pub order_id: u64,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<ConditionalOrder>(),
+        seeds = [CONDITIONAL_ORDER_SEED, order.key().as_ref()],
+        bump
+    )]
+    pub conditional_order: Account<'info, ConditionalOrder>,
     
-    /// Bump seed for PDA derivation
-    pub bump: u8,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
 }
 
-/// OrderBook - Manages the order book for a trading pair
-/// 
-/// For MVP, this handles SOL/USDC pair. The structure is designed to be
-/// extensible for multi-token support in future phases.
-#[account]
-pub struct OrderBook {
-    /// Authority that can manage the order book
-    pub authority: Pubkey,
+/// Context for initializing the check trigger computation definition
+/// This is auto-generated by Arcium macros
+#[derive(Accounts)]
+pub struct InitCheckTriggerCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    // Additional Arcium-required accounts will be auto-generated
+    // by the #[arcium_program] macro
+}
+
+/// Context for the permissionless conditional order crank
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct CrankConditionalOrder<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
     
-    /// Base token mint (SOL for MVP)
-    pub base_mint: Pubkey,
+    pub order_book: Account<'info, OrderBook>,
     
-    /// Quote token mint (USDC for MVP)
-    pub quote_mint: Pubkey,
+    #[account(
+        constraint = order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook,
+        constraint = order.status == ORDER_STATUS_CONDITIONAL @ ShadowSwapError::InvalidOrderStatus
+    )]
+    pub order: Account<'info, EncryptedOrder>,
     
-    /// Total number of orders created (used for sequential order IDs)
-    pub order_count: u64,
+    #[account(
+        mut,
+        seeds = [CONDITIONAL_ORDER_SEED, order.key().as_ref()],
+        bump = conditional_order.bump
+    )]
+    pub conditional_order: Account<'info, ConditionalOrder>,
     
-    /// Number of active orders
-    pub active_orders: u64,
+    /// CHECK: Oracle price account; layout is checked by `PythPriceAccount`
+    #[account(address = order_book.oracle @ ShadowSwapError::InvalidOracleAccount)]
+    pub oracle: UncheckedAccount<'info>,
     
-    /// Total trading volume (base token) - encrypted
-    pub encrypted_volume_base: Vec<u8>,
+    /// CHECK: PDA account for signing (auto-generated by Arcium)
+    #[account(
+        mut,
+        seeds = [b"sign_pda"],
+        bump
+    )]
+    pub sign_pda_account: UncheckedAccount<'info>,
     
-    /// Total trading volume (quote token) - encrypted
-    pub encrypted_volume_quote: Vec<u8>,
+    // Additional Arcium MPC-required accounts (cluster, computation, etc.)
+    // are auto-generated by the #[arcium_program] macro
+}
+
+/// Context for the trigger check callback
+/// Arcium callback accounts are auto-generated; the conditional order and
+/// its order are passed as extra callback accounts by `crank_conditional_order`
+#[derive(Accounts)]
+pub struct CheckTriggerCallback<'info> {
+    #[account(mut)]
+    pub conditional_order: Account<'info, ConditionalOrder>,
     
-    /// Order book creation timestamp
-    pub created_at: i64,
+    #[account(mut)]
+    pub order: Account<'info, EncryptedOrder>,
+}
+
+/// Context for creating a shielded vault (order book authority only)
+#[derive(Accounts)]
+pub struct InitializeShieldedVault<'info> {
+    #[account(
+        constraint = order_book.authority == authority.key() @ ShadowSwapError::UnauthorizedCallback
+    )]
+    pub order_book: Account<'info, OrderBook>,
     
-    /// Last trade timestamp
-    pub last_trade_at: i64,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<ShieldedVault>(),
+        seeds = [SHIELDED_VAULT_SEED, order_book.key().as_ref(), token_mint.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, ShieldedVault>,
     
-    /// Fee basis points (e.g., 30 = 0.3%)
-    pub fee_bps: u16,
+    #[account(
+        init,
+        payer = authority,
+        token::mint = token_mint,
+        token::authority = vault,
+        seeds = [SHIELDED_VAULT_TOKEN_SEED, vault.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
     
-    /// Fee collector account
-    pub fee_collector: Pubkey,
+    pub token_mint: Account<'info, anchor_spl::token::Mint>,
     
-    /// Minimum order size (base token, in lamports/smallest unit)
-    pub min_base_order_size: u64,
+    #[account(mut)]
+    pub authority: Signer<'info>,
     
-    /// Whether the order book is active
-    pub is_active: bool,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Context for opening a shielded balance
+#[derive(Accounts)]
+pub struct OpenShieldedBalance<'info> {
+    pub vault: Account<'info, ShieldedVault>,
     
-    /// Bump seed for PDA derivation
-    pub bump: u8,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<ShieldedBalance>(),
+        seeds = [SHIELDED_BALANCE_SEED, vault.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub balance: Account<'info, ShieldedBalance>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
 }
 
-/// Escrow - Holds funds for an order until it's matched or cancelled
-/// 
-/// Each order has an associated escrow account that holds either:
-/// - Base tokens (for sell orders)
-/// - Quote tokens (for buy orders)
-#[account]
-pub struct Escrow {
-    /// Order this escrow belongs to
-    pub order: Pubkey,
+/// Context for depositing into a shielded vault
+#[derive(Accounts)]
+pub struct ShieldedDeposit<'info> {
+    pub vault: Account<'info, ShieldedVault>,
     
-    /// Order owner
-    pub owner: Pubkey,
+    #[account(
+        mut,
+        address = vault.token_account @ ShadowSwapError::InvalidShieldedVault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
     
-    /// Order book
-    pub order_book: Pubkey,
+    #[account(
+        mut,
+        seeds = [SHIELDED_BALANCE_SEED, vault.key().as_ref(), owner.key().as_ref()],
+        bump = balance.bump
+    )]
+    pub balance: Account<'info, ShieldedBalance>,
     
-    /// Token account holding escrowed funds
-    /// This is a PDA-owned token account
-    pub token_account: Pubkey,
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
     
-    /// Mint of the escrowed token
-    pub token_mint: Pubkey,
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+/// Context for placing an order backed by a shielded balance
+#[derive(Accounts)]
+pub struct PlaceShieldedOrder<'info> {
+    #[account(
+        mut,
+        constraint = order_book.is_active @ ShadowSwapError::OrderBookNotActive
+    )]
+    pub order_book: Account<'info, OrderBook>,
     
-    /// Original encrypted amount deposited
-    pub encrypted_amount: Vec<u8>,
+    #[account(
+        constraint = vault.order_book == order_book.key() @ ShadowSwapError::InvalidShieldedVault
+    )]
+    pub vault: Account<'info, ShieldedVault>,
     
-    /// Encrypted remaining amount (decreases as order fills)
-    pub encrypted_remaining: Vec<u8>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<EncryptedOrder>() + MAX_CIPHER_PAYLOAD_SIZE + MAX_ENCRYPTED_AMOUNT_SIZE + 100,
+        seeds = [ORDER_SEED, order_book.key().as_ref(), order_book.order_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub order: Account<'info, EncryptedOrder>,
     
-    /// Escrow creation timestamp
-    pub created_at: i64,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<Escrow>() + MAX_ENCRYPTED_AMOUNT_SIZE * 2 + 100,
+        seeds = [ESCROW_SEED, order.key().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
     
-    /// Bump seed for PDA derivation
-    pub bump: u8,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for initializing the shielded allocate computation definition
+/// This is auto-generated by Arcium macros
+#[derive(Accounts)]
+pub struct InitShieldedAllocateCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub order_book: Account<'info, OrderBook>,
+    // Additional Arcium-required accounts will be auto-generated
+    // by the #[arcium_program] macro
+}
+
+/// Context for initializing the shielded release computation definition
+/// This is auto-generated by Arcium macros
+#[derive(Accounts)]
+pub struct InitShieldedReleaseCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub order_book: Account<'info, OrderBook>,
+    // Additional Arcium-required accounts will be auto-generated
+    // by the #[arcium_program] macro
+}
+
+/// Context for initializing the shielded settle computation definition
+/// This is auto-generated by Arcium macros
+#[derive(Accounts)]
+pub struct InitShieldedSettleCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub order_book: Account<'info, OrderBook>,
+    // Additional Arcium-required accounts will be auto-generated
+    // by the #[arcium_program] macro
+}
+
+/// Context for initializing the shielded withdraw computation definition
+/// This is auto-generated by Arcium macros
+#[derive(Accounts)]
+pub struct InitShieldedWithdrawCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub order_book: Account<'info, OrderBook>,
+    // Additional Arcium-required accounts will be auto-generated
+    // by the #[arcium_program] macro
 }
 
-/// CallbackAuth - Authentication token for callback operations
-/// 
-/// This account is used to authorize callback operations from the keeper/matching
-/// engine. It ensures only authorized entities can trigger order matching.
-#[account]
-pub struct CallbackAuth {
-    /// Authority that can use this callback auth
-    pub authority: Pubkey,
+/// Context for reserving a shielded order's escrow
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct InvokeShieldedAllocation<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
     
-    /// Order book this callback auth is valid for
-    pub order_book: Pubkey,
+    pub order_book: Account<'info, OrderBook>,
     
-    /// Nonce to prevent replay attacks
-    pub nonce: u64,
+    /// Keeper authorization; omitted when the order owner invokes
+    #[account(
+        constraint = callback_auth.is_active @ ShadowSwapError::UnauthorizedCallback,
+        constraint = callback_auth.authority == payer.key() @ ShadowSwapError::UnauthorizedCallback,
+        seeds = [CALLBACK_AUTH_SEED, order_book.key().as_ref(), payer.key().as_ref()],
+        bump = callback_auth.bump
+    )]
+    pub callback_auth: Option<Account<'info, CallbackAuth>>,
     
-    /// Expiration timestamp
-    pub expires_at: i64,
+    #[account(
+        constraint = order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook,
+        constraint = order.status == ORDER_STATUS_PENDING_VERIFICATION @ ShadowSwapError::InvalidOrderStatus
+    )]
+    pub order: Account<'info, EncryptedOrder>,
     
-    /// Whether this callback auth is active
-    pub is_active: bool,
+    #[account(
+        seeds = [ESCROW_SEED, order.key().as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.vault == vault.key() @ ShadowSwapError::NotShieldedEscrow
+    )]
+    pub escrow: Account<'info, Escrow>,
     
-    /// Creation timestamp
-    pub created_at: i64,
+    pub vault: Account<'info, ShieldedVault>,
     
-    /// Bump seed for PDA derivation
-    pub bump: u8,
+    #[account(
+        mut,
+        seeds = [SHIELDED_BALANCE_SEED, vault.key().as_ref(), order.owner.as_ref()],
+        bump = balance.bump
+    )]
+    pub balance: Account<'info, ShieldedBalance>,
+    
+    /// CHECK: PDA account for signing (auto-generated by Arcium)
+    #[account(
+        mut,
+        seeds = [b"sign_pda"],
+        bump
+    )]
+    pub sign_pda_account: UncheckedAccount<'info>,
+    
+    // Additional Arcium MPC-required accounts (cluster, computation, etc.)
+    // are auto-generated by the #[arcium_program] macro
 }
 
-// ============================================================================
-// Match Result Structures
-// ============================================================================
-
-/// Result of matching two orders from Arcium MPC
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
-pub struct MatchResult {
-    /// Buyer's order account pubkey
-    pub buyer_pubkey: Pubkey,
+/// Context for the shielded allocation callback
+/// Arcium callback accounts are auto-generated; the order, escrow and balance
+/// are passed as extra callback accounts by `invoke_shielded_allocation`
+#[derive(Accounts)]
+pub struct ShieldedAllocateCallback<'info> {
+    #[account(mut)]
+    pub order: Account<'info, EncryptedOrder>,
     
-    /// Seller's order account pubkey
-    pub seller_pubkey: Pubkey,
+    #[account(mut)]
+    pub escrow: Account<'info, Escrow>,
     
-    /// Buyer's order ID
-    pub buyer_order_id: u64,
+    #[account(mut)]
+    pub balance: Account<'info, ShieldedBalance>,
+}
+
+/// Context for cancelling a shielded order
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct CancelShieldedOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
     
-    /// Seller's order ID
-    pub seller_order_id: u64,
+    #[account(mut)]
+    pub order_book: Account<'info, OrderBook>,
     
-    /// Matched amount (encrypted)
-    pub encrypted_amount: Vec<u8>,
+    #[account(
+        mut,
+        constraint = order.owner == owner.key() @ ShadowSwapError::UnauthorizedCallback,
+        constraint = order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook,
+        constraint = order.status == ORDER_STATUS_ACTIVE
+            || order.status == ORDER_STATUS_PARTIAL
+            || order.status == ORDER_STATUS_PENDING_VERIFICATION
+            || order.status == ORDER_STATUS_AMOUNT_MISMATCH
+            || order.status == ORDER_STATUS_CANCELLED
+            || order.status == ORDER_STATUS_FILLED @ ShadowSwapError::InvalidOrderStatus
+    )]
+    pub order: Account<'info, EncryptedOrder>,
     
-    /// Execution price (encrypted)
-    pub encrypted_price: Vec<u8>,
-}
-
-/// Match result input for settlement (plaintext from keeper)
-/// 
-/// This struct contains the decrypted match details that the keeper bot
-/// submits to execute the trade settlement. The keeper has computed the
-/// matching off-chain and decrypted the results.
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
-pub struct MatchResultInput {
-    /// Buyer's order account pubkey
-    pub buyer_pubkey: Pubkey,
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, order.key().as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.vault == vault.key() @ ShadowSwapError::NotShieldedEscrow
+    )]
+    pub escrow: Account<'info, Escrow>,
     
-    /// Seller's order account pubkey
-    pub seller_pubkey: Pubkey,
+    pub vault: Account<'info, ShieldedVault>,
     
-    /// Matched amount in base token units (e.g., lamports for WSOL)
-    pub matched_amount: u64,
+    #[account(
+        mut,
+        seeds = [SHIELDED_BALANCE_SEED, vault.key().as_ref(), owner.key().as_ref()],
+        bump = balance.bump
+    )]
+    pub balance: Account<'info, ShieldedBalance>,
     
-    /// Execution price: quote tokens per base token
-    /// (adjusted for decimals, e.g., USDC micro-units per WSOL lamport)
-    pub execution_price: u64,
-}
-
-// ============================================================================
-// Events
-// ============================================================================
-
-/// Event emitted when orders are matched and queued for execution
-#[event]
-pub struct MatchQueued {
-    pub order_book: Pubkey,
-    pub buyer: Pubkey,
-    pub seller: Pubkey,
-    pub buyer_order_id: u64,
-    pub seller_order_id: u64,
-    pub encrypted_amount: Vec<u8>,
-    pub encrypted_price: Vec<u8>,
-    pub timestamp: i64,
-}
-
-/// Event emitted when a trade is settled
-#[event]
-pub struct TradeSettled {
-    pub order_book: Pubkey,
-    pub buyer: Pubkey,
-    pub seller: Pubkey,
-    pub buyer_order_id: u64,
-    pub seller_order_id: u64,
-    pub base_amount: u64,
-    pub quote_amount: u64,
-    pub execution_price: u64,
-    pub timestamp: i64,
+    /// CHECK: PDA account for signing (auto-generated by Arcium)
+    #[account(
+        mut,
+        seeds = [b"sign_pda"],
+        bump
+    )]
+    pub sign_pda_account: UncheckedAccount<'info>,
+    
+    // Additional Arcium MPC-required accounts (cluster, computation, etc.)
+    // are auto-generated by the #[arcium_program] macro
 }
 
-/// Event emitted when Arcium MPC matching completes
-#[event]
-pub struct ArciumMatchCompleted {
-    pub order_book: Pubkey,
-    pub encrypted_matched_amount: Vec<u8>,
-    pub encrypted_execution_price: Vec<u8>,
-    pub nonce: Vec<u8>,
-    pub timestamp: i64,
+/// Context for the shielded release callback
+/// Arcium callback accounts are auto-generated; the escrow and balance are
+/// passed as extra callback accounts by `cancel_shielded_order`
+#[derive(Accounts)]
+pub struct ShieldedReleaseCallback<'info> {
+    #[account(mut)]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(mut)]
+    pub balance: Account<'info, ShieldedBalance>,
 }
 
-// ============================================================================
-// Error Codes
-// ============================================================================
-
-#[error_code]
-pub enum ShadowSwapError {
-    #[msg("Order book is not active")]
-    OrderBookNotActive,
-    
-    #[msg("Invalid order status")]
-    InvalidOrderStatus,
-    
-    #[msg("Order amount too small")]
-    OrderTooSmall,
-    
-    #[msg("Invalid cipher payload")]
-    InvalidCipherPayload,
+/// Context for settling two shielded orders (keeper only)
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct InvokeShieldedSettlement<'info> {
+    #[account(
+        constraint = callback_auth.is_active @ ShadowSwapError::UnauthorizedCallback,
+        constraint = callback_auth.authority == payer.key() @ ShadowSwapError::UnauthorizedCallback,
+        seeds = [CALLBACK_AUTH_SEED, order_book.key().as_ref(), payer.key().as_ref()],
+        bump = callback_auth.bump
+    )]
+    pub callback_auth: Account<'info, CallbackAuth>,
     
-    #[msg("Unauthorized callback")]
-    UnauthorizedCallback,
+    #[account(mut)]
+    pub payer: Signer<'info>,
     
-    #[msg("Callback auth expired")]
-    CallbackAuthExpired,
+    #[account(
+        constraint = order_book.auction_epoch_duration == 0 @ ShadowSwapError::AuctionModeActive
+    )]
+    pub order_book: Account<'info, OrderBook>,
     
-    #[msg("Invalid escrow account")]
-    InvalidEscrow,
+    #[account(
+        mut,
+        constraint = buyer_order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook,
+        constraint = buyer_order.status == ORDER_STATUS_ACTIVE
+            || buyer_order.status == ORDER_STATUS_PARTIAL @ ShadowSwapError::InvalidOrderStatus
+    )]
+    pub buyer_order: Account<'info, EncryptedOrder>,
     
-    #[msg("Insufficient funds in escrow")]
-    InsufficientEscrowFunds,
+    #[account(
+        mut,
+        constraint = seller_order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook,
+        constraint = seller_order.status == ORDER_STATUS_ACTIVE
+            || seller_order.status == ORDER_STATUS_PARTIAL @ ShadowSwapError::InvalidOrderStatus
+    )]
+    pub seller_order: Account<'info, EncryptedOrder>,
     
-    #[msg("Invalid token mint")]
-    InvalidTokenMint,
+    /// Buyer's escrow, allocated in the quote vault
+    #[account(
+        seeds = [ESCROW_SEED, buyer_order.key().as_ref()],
+        bump = buyer_escrow.bump,
+        constraint = buyer_escrow.vault == quote_vault.key() @ ShadowSwapError::NotShieldedEscrow
+    )]
+    pub buyer_escrow: Account<'info, Escrow>,
     
-    #[msg("Order not found")]
-    OrderNotFound,
+    /// Seller's escrow, allocated in the base vault
+    #[account(
+        seeds = [ESCROW_SEED, seller_order.key().as_ref()],
+        bump = seller_escrow.bump,
+        constraint = seller_escrow.vault == base_vault.key() @ ShadowSwapError::NotShieldedEscrow
+    )]
+    pub seller_escrow: Account<'info, Escrow>,
     
-    #[msg("Order already filled")]
-    OrderAlreadyFilled,
+    #[account(
+        seeds = [SHIELDED_VAULT_SEED, order_book.key().as_ref(), order_book.base_mint.as_ref()],
+        bump = base_vault.bump
+    )]
+    pub base_vault: Account<'info, ShieldedVault>,
     
-    #[msg("Order already cancelled")]
-    OrderAlreadyCancelled,
+    #[account(
+        seeds = [SHIELDED_VAULT_SEED, order_book.key().as_ref(), order_book.quote_mint.as_ref()],
+        bump = quote_vault.bump
+    )]
+    pub quote_vault: Account<'info, ShieldedVault>,
     
-    #[msg("Invalid order book")]
-    InvalidOrderBook,
+    /// Buyer's balance in the base vault, credited with the fill
+    #[account(
+        mut,
+        seeds = [SHIELDED_BALANCE_SEED, base_vault.key().as_ref(), buyer_order.owner.as_ref()],
+        bump = buyer_base_balance.bump
+    )]
+    pub buyer_base_balance: Account<'info, ShieldedBalance>,
     
-    #[msg("Invalid fee configuration")]
-    InvalidFeeConfiguration,
+    /// Seller's balance in the quote vault, credited with the proceeds
+    #[account(
+        mut,
+        seeds = [SHIELDED_BALANCE_SEED, quote_vault.key().as_ref(), seller_order.owner.as_ref()],
+        bump = seller_quote_balance.bump
+    )]
+    pub seller_quote_balance: Account<'info, ShieldedBalance>,
     
-    #[msg("Numerical overflow")]
-    NumericalOverflow,
+    /// CHECK: PDA account for signing (auto-generated by Arcium)
+    #[account(
+        mut,
+        seeds = [b"sign_pda"],
+        bump
+    )]
+    pub sign_pda_account: UncheckedAccount<'info>,
     
-    #[msg("Order is not active")]
-    OrderNotActive,
+    // Additional Arcium MPC-required accounts (cluster, computation, etc.)
+    // are auto-generated by the #[arcium_program] macro
 }
 
-// ============================================================================
-// Constants
-// ============================================================================
-
-/// Maximum size for encrypted order payload (512 bytes)
-pub const MAX_CIPHER_PAYLOAD_SIZE: usize = 512;
-
-/// Maximum size for encrypted amount fields (64 bytes)
-pub const MAX_ENCRYPTED_AMOUNT_SIZE: usize = 64;
-
-/// Maximum size for encrypted volume fields (64 bytes)
-pub const MAX_ENCRYPTED_VOLUME_SIZE: usize = 64;
-
-/// Order status: Active
-pub const ORDER_STATUS_ACTIVE: u8 = 1;
-
-/// Order status: Partially filled
-pub const ORDER_STATUS_PARTIAL: u8 = 2;
-
-/// Order status: Fully filled
-pub const ORDER_STATUS_FILLED: u8 = 3;
-
-/// Order status: Cancelled
-pub const ORDER_STATUS_CANCELLED: u8 = 4;
-
-/// Order status: Matched, pending execution
-pub const ORDER_STATUS_MATCHED_PENDING: u8 = 5;
-
-/// Seeds for PDA derivation
-pub const ORDER_BOOK_SEED: &[u8] = b"order_book";
-pub const ORDER_SEED: &[u8] = b"order";
-pub const ESCROW_SEED: &[u8] = b"escrow";
-pub const CALLBACK_AUTH_SEED: &[u8] = b"callback_auth";
-
-// ============================================================================
-// Instruction Contexts
-// ============================================================================
-
+/// Context for the shielded settlement callback
+/// Arcium callback accounts are auto-generated; the accounts below are
+/// passed as extra callback accounts by `invoke_shielded_settlement`
 #[derive(Accounts)]
-pub struct InitializeOrderBook<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + std::mem::size_of::<OrderBook>() + 200, // Extra space for Vec fields
-        seeds = [ORDER_BOOK_SEED, base_mint.key().as_ref(), quote_mint.key().as_ref()],
-        bump
-    )]
+pub struct ShieldedSettleCallback<'info> {
+    #[account(mut)]
     pub order_book: Account<'info, OrderBook>,
     
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub buyer_order: Account<'info, EncryptedOrder>,
     
-    /// CHECK: Fee collector can be any account
-    pub fee_collector: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub seller_order: Account<'info, EncryptedOrder>,
     
-    /// CHECK: Base mint reference for PDA seeds
-    pub base_mint: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub buyer_escrow: Account<'info, Escrow>,
     
-    /// CHECK: Quote mint reference for PDA seeds
-    pub quote_mint: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub seller_escrow: Account<'info, Escrow>,
     
-    pub system_program: Program<'info, System>,
+    #[account(mut)]
+    pub buyer_base_balance: Account<'info, ShieldedBalance>,
+    
+    #[account(mut)]
+    pub seller_quote_balance: Account<'info, ShieldedBalance>,
 }
 
+/// Context for requesting a shielded withdrawal
 #[derive(Accounts)]
-pub struct PlaceOrder<'info> {
+#[instruction(computation_offset: u64)]
+pub struct RequestShieldedWithdrawal<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub vault: Account<'info, ShieldedVault>,
+    
     #[account(
         mut,
-        constraint = order_book.is_active @ ShadowSwapError::OrderBookNotActive
+        address = vault.token_account @ ShadowSwapError::InvalidShieldedVault
     )]
-    pub order_book: Account<'info, OrderBook>,
+    pub vault_token_account: Account<'info, TokenAccount>,
     
     #[account(
-        init,
-        payer = owner,
-        space = 8 + std::mem::size_of::<EncryptedOrder>() + MAX_CIPHER_PAYLOAD_SIZE + MAX_ENCRYPTED_AMOUNT_SIZE + 100,
-        seeds = [ORDER_SEED, order_book.key().as_ref(), order_book.order_count.to_le_bytes().as_ref()],
-        bump
+        mut,
+        seeds = [SHIELDED_BALANCE_SEED, vault.key().as_ref(), payer.key().as_ref()],
+        bump = balance.bump
     )]
-    pub order: Account<'info, EncryptedOrder>,
+    pub balance: Account<'info, ShieldedBalance>,
     
     #[account(
-        init,
-        payer = owner,
-        space = 8 + std::mem::size_of::<Escrow>() + MAX_ENCRYPTED_AMOUNT_SIZE * 2 + 100,
-        seeds = [ESCROW_SEED, order.key().as_ref()],
-        bump
+        mut,
+        constraint = destination.owner == payer.key() @ ShadowSwapError::UnauthorizedCallback,
+        constraint = destination.mint == vault.token_mint @ ShadowSwapError::InvalidTokenMint
     )]
-    pub escrow: Account<'info, Escrow>,
+    pub destination: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
     
+    /// CHECK: PDA account for signing (auto-generated by Arcium)
     #[account(
-        init,
-        payer = owner,
-        token::mint = token_mint,
-        token::authority = escrow,
-        seeds = [b"escrow_token", order.key().as_ref()],
+        mut,
+        seeds = [b"sign_pda"],
         bump
     )]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+    pub sign_pda_account: UncheckedAccount<'info>,
     
-    #[account(mut)]
-    pub user_token_account: Account<'info, TokenAccount>,
+    // Additional Arcium MPC-required accounts (cluster, computation, etc.)
+    // are auto-generated by the #[arcium_program] macro
+}
+
+/// Context for the shielded withdrawal callback
+/// Arcium callback accounts are auto-generated; the accounts below are
+/// passed as extra callback accounts by `request_shielded_withdrawal`
+#[derive(Accounts)]
+pub struct ShieldedWithdrawCallback<'info> {
+    pub vault: Account<'info, ShieldedVault>,
     
-    pub token_mint: Account<'info, anchor_spl::token::Mint>,
+    #[account(
+        mut,
+        address = vault.token_account @ ShadowSwapError::InvalidShieldedVault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
     
-    #[account(mut)]
-    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        constraint = balance.vault == vault.key() @ ShadowSwapError::InvalidShieldedVault
+    )]
+    pub balance: Account<'info, ShieldedBalance>,
+    
+    #[account(
+        mut,
+        constraint = destination.owner == balance.owner @ ShadowSwapError::UnauthorizedCallback,
+        constraint = destination.mint == vault.token_mint @ ShadowSwapError::InvalidTokenMint
+    )]
+    pub destination: Account<'info, TokenAccount>,
     
     pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
+/// Context for switching a book between continuous and auction trading
 #[derive(Accounts)]
-pub struct CancelOrder<'info> {
+pub struct ConfigureAuction<'info> {
     #[account(
         mut,
-        has_one = owner @ ShadowSwapError::UnauthorizedCallback,
-        constraint = order.status == ORDER_STATUS_ACTIVE || order.status == ORDER_STATUS_PARTIAL @ ShadowSwapError::InvalidOrderStatus
+        constraint = order_book.authority == authority.key() @ ShadowSwapError::UnauthorizedCallback
     )]
-    pub order: Account<'info, EncryptedOrder>,
+    pub order_book: Account<'info, OrderBook>,
     
+    pub authority: Signer<'info>,
+}
+
+/// Context for the permissionless epoch close crank
+#[derive(Accounts)]
+pub struct CloseAuctionEpoch<'info> {
     #[account(
         mut,
-        seeds = [ESCROW_SEED, order.key().as_ref()],
-        bump = escrow.bump
+        constraint = order_book.auction_epoch_duration > 0 @ ShadowSwapError::NotAuctionBook
     )]
-    pub escrow: Account<'info, Escrow>,
+    pub order_book: Account<'info, OrderBook>,
     
-    #[account(mut)]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<AuctionEpoch>(),
+        seeds = [AUCTION_EPOCH_SEED, order_book.key().as_ref(), order_book.current_epoch.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub auction_epoch: Account<'info, AuctionEpoch>,
     
     #[account(mut)]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub payer: Signer<'info>,
     
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for initializing the clear auction computation definition
+/// This is auto-generated by Arcium macros
+#[derive(Accounts)]
+pub struct InitClearAuctionCompDef<'info> {
     #[account(mut)]
-    pub order_book: Account<'info, OrderBook>,
-    
-    pub owner: Signer<'info>,
-    pub token_program: Program<'info, Token>,
+    pub payer: Signer<'info>,
+    // Additional Arcium-required accounts will be auto-generated
+    // by the #[arcium_program] macro
 }
 
+/// Context for queueing an epoch's clearing computation
+/// 
+/// The batch's `[order, escrow]` pairs are passed (orders writable) via
+/// remaining_accounts.
 #[derive(Accounts)]
-pub struct MatchOrders<'info> {
+#[instruction(computation_offset: u64)]
+pub struct InvokeAuctionClearing<'info> {
     #[account(
         constraint = callback_auth.is_active @ ShadowSwapError::UnauthorizedCallback,
-        constraint = callback_auth.authority == keeper.key() @ ShadowSwapError::UnauthorizedCallback,
-        seeds = [CALLBACK_AUTH_SEED, order_book.key().as_ref(), keeper.key().as_ref()],
+        constraint = callback_auth.authority == payer.key() @ ShadowSwapError::UnauthorizedCallback,
+        seeds = [CALLBACK_AUTH_SEED, order_book.key().as_ref(), payer.key().as_ref()],
         bump = callback_auth.bump
     )]
     pub callback_auth: Account<'info, CallbackAuth>,
     
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(mut)]
     pub order_book: Account<'info, OrderBook>,
     
     #[account(
         mut,
-        constraint = buy_order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook
+        seeds = [AUCTION_EPOCH_SEED, order_book.key().as_ref(), auction_epoch.epoch.to_le_bytes().as_ref()],
+        bump = auction_epoch.bump,
+        constraint = auction_epoch.status == AUCTION_STATUS_CLOSED @ ShadowSwapError::InvalidAuctionStatus
     )]
-    pub buy_order: Account<'info, EncryptedOrder>,
+    pub auction_epoch: Account<'info, AuctionEpoch>,
     
+    /// CHECK: PDA account for signing (auto-generated by Arcium)
     #[account(
         mut,
-        constraint = sell_order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook
+        seeds = [b"sign_pda"],
+        bump
     )]
-    pub sell_order: Account<'info, EncryptedOrder>,
+    pub sign_pda_account: UncheckedAccount<'info>,
     
-    pub keeper: Signer<'info>,
+    // Additional Arcium MPC-required accounts (cluster, computation, etc.)
+    // are auto-generated by the #[arcium_program] macro
 }
 
+/// Context for the auction clearing callback
+/// Arcium callback accounts are auto-generated; the auction epoch is passed
+/// as an extra callback account by `invoke_auction_clearing`
 #[derive(Accounts)]
-pub struct MatchCallback<'info> {
-    #[account(
-        constraint = callback_auth.is_active @ ShadowSwapError::UnauthorizedCallback,
-        constraint = callback_auth.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook,
-        seeds = [CALLBACK_AUTH_SEED, order_book.key().as_ref(), keeper.key().as_ref()],
-        bump = callback_auth.bump
-    )]
-    pub callback_auth: Account<'info, CallbackAuth>,
-    
+pub struct ClearBatchAuctionCallback<'info> {
+    #[account(mut)]
+    pub auction_epoch: Account<'info, AuctionEpoch>,
+}
+
+/// Context for expiring a stuck auction clearing
+#[derive(Accounts)]
+pub struct ExpireAuctionClearing<'info> {
     pub order_book: Account<'info, OrderBook>,
     
-    pub keeper: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [AUCTION_EPOCH_SEED, order_book.key().as_ref(), auction_epoch.epoch.to_le_bytes().as_ref()],
+        bump = auction_epoch.bump,
+        constraint = auction_epoch.status == AUCTION_STATUS_CLEARING @ ShadowSwapError::InvalidAuctionStatus
+    )]
+    pub auction_epoch: Account<'info, AuctionEpoch>,
     
-    // NOTE: In production, matched order accounts would be passed via remaining_accounts
-    // This allows for dynamic number of matches in a single callback
+    pub cranker: Signer<'info>,
 }
 
+/// Context for settling a cleared auction epoch
+/// 
+/// Five accounts per cleared order are passed via remaining_accounts (see
+/// `settle_auction`).
 #[derive(Accounts)]
-pub struct CreateCallbackAuth<'info> {
-    #[account(
-        constraint = order_book.authority == authority.key() @ ShadowSwapError::UnauthorizedCallback
-    )]
+pub struct SettleAuction<'info> {
+    #[account(mut)]
     pub order_book: Account<'info, OrderBook>,
     
     #[account(
-        init,
-        payer = authority,
-        space = 8 + std::mem::size_of::<CallbackAuth>(),
-        seeds = [CALLBACK_AUTH_SEED, order_book.key().as_ref(), keeper.key().as_ref()],
-        bump
+        mut,
+        seeds = [AUCTION_EPOCH_SEED, order_book.key().as_ref(), auction_epoch.epoch.to_le_bytes().as_ref()],
+        bump = auction_epoch.bump,
+        constraint = auction_epoch.status == AUCTION_STATUS_CLEARED @ ShadowSwapError::InvalidAuctionStatus
     )]
-    pub callback_auth: Account<'info, CallbackAuth>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
+    pub auction_epoch: Account<'info, AuctionEpoch>,
     
-    /// CHECK: Keeper account to be authorized
-    pub keeper: UncheckedAccount<'info>,
+    pub cranker: Signer<'info>,
     
-    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
 }
 
-// ============================================================================
-// Arcium MPC Account Contexts
-// ============================================================================
-
-/// Context for initializing the match computation definition
+/// Context for initializing the verify escrow computation definition
 /// This is auto-generated by Arcium macros
 #[derive(Accounts)]
-pub struct InitMatchCompDef<'info> {
+pub struct InitVerifyEscrowCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     pub order_book: Account<'info, OrderBook>,
@@ -1131,15 +4908,41 @@ pub struct InitMatchCompDef<'info> {
     // by the #[arcium_program] macro
 }
 
-/// Context for invoking encrypted matching computation
-/// This is auto-generated by Arcium macros
+/// Context for invoking the escrow coverage computation
 #[derive(Accounts)]
 #[instruction(computation_offset: u64)]
-pub struct InvokeMatching<'info> {
+pub struct InvokeVerifyEscrowCoverage<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    
     pub order_book: Account<'info, OrderBook>,
     
+    /// Keeper authorization; omitted when the order owner invokes
+    #[account(
+        constraint = callback_auth.is_active @ ShadowSwapError::UnauthorizedCallback,
+        constraint = callback_auth.authority == payer.key() @ ShadowSwapError::UnauthorizedCallback,
+        seeds = [CALLBACK_AUTH_SEED, order_book.key().as_ref(), payer.key().as_ref()],
+        bump = callback_auth.bump
+    )]
+    pub callback_auth: Option<Account<'info, CallbackAuth>>,
+    
+    #[account(
+        constraint = order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook
+    )]
+    pub order: Account<'info, EncryptedOrder>,
+    
+    #[account(
+        seeds = [ESCROW_SEED, order.key().as_ref()],
+        bump = escrow.bump,
+        constraint = escrow.vault == Pubkey::default() @ ShadowSwapError::ShieldedEscrow
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        constraint = escrow_token_account.key() == escrow.token_account @ ShadowSwapError::InvalidEscrow
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
     /// CHECK: PDA account for signing (auto-generated by Arcium)
     #[account(
         mut,
@@ -1152,12 +4955,22 @@ pub struct InvokeMatching<'info> {
     // are auto-generated by the #[arcium_program] macro
 }
 
-/// Context for Arcium match callback
-/// This is auto-generated by Arcium macros
+/// Context for the posted amount verification callback
+/// Arcium callback accounts are auto-generated; the order is passed as an
+/// extra callback account by `invoke_verify_posted_amount`
 #[derive(Accounts)]
-pub struct ArciumMatchCallback<'info> {
-    pub order_book: Account<'info, OrderBook>,
-    // Arcium callback accounts are auto-generated
+pub struct VerifyPostedAmountCallback<'info> {
+    #[account(mut)]
+    pub order: Account<'info, EncryptedOrder>,
+}
+
+/// Context for the escrow coverage callback
+/// Arcium callback accounts are auto-generated; the order is passed as an
+/// extra callback account by `invoke_verify_escrow_coverage`
+#[derive(Accounts)]
+pub struct VerifyEscrowCoverageCallback<'info> {
+    #[account(mut)]
+    pub order: Account<'info, EncryptedOrder>,
 }
 
 #[derive(Accounts)]
@@ -1173,7 +4986,10 @@ pub struct SubmitMatchResults<'info> {
     pub callback_auth: Account<'info, CallbackAuth>,
     
     /// Order book
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = order_book.auction_epoch_duration == 0 @ ShadowSwapError::AuctionModeActive
+    )]
     pub order_book: Account<'info, OrderBook>,
     
     /// Buyer's order account
@@ -1195,7 +5011,8 @@ pub struct SubmitMatchResults<'info> {
         mut,
         seeds = [ESCROW_SEED, buyer_order.key().as_ref()],
         bump = buyer_escrow.bump,
-        constraint = buyer_escrow.order == buyer_order.key() @ ShadowSwapError::InvalidEscrow
+        constraint = buyer_escrow.order == buyer_order.key() @ ShadowSwapError::InvalidEscrow,
+        constraint = buyer_escrow.vault == Pubkey::default() @ ShadowSwapError::ShieldedEscrow
     )]
     pub buyer_escrow: Account<'info, Escrow>,
     
@@ -1204,7 +5021,8 @@ pub struct SubmitMatchResults<'info> {
         mut,
         seeds = [ESCROW_SEED, seller_order.key().as_ref()],
         bump = seller_escrow.bump,
-        constraint = seller_escrow.order == seller_order.key() @ ShadowSwapError::InvalidEscrow
+        constraint = seller_escrow.order == seller_order.key() @ ShadowSwapError::InvalidEscrow,
+        constraint = seller_escrow.vault == Pubkey::default() @ ShadowSwapError::ShieldedEscrow
     )]
     pub seller_escrow: Account<'info, Escrow>,
     
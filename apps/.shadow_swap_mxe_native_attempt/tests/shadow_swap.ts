@@ -50,6 +50,16 @@ describe("ShadowSwap - Security & Edge Case Tests", () => {
     return Buffer.from(new Array(size).fill(0).map(() => Math.floor(Math.random() * 256)));
   }
   
+  // Helper: Dummy x25519 key for the order's OrderInput ciphertexts
+  function dummyEncryptionKey(): number[] {
+    return Array.from(createDummyEncryptedAmount(32));
+  }
+  
+  // Helper: Amount moved into escrow by place_order (above the 100000 book minimum)
+  function postedAmount(amount: number = 1_000_000): anchor.BN {
+    return new anchor.BN(amount);
+  }
+  
   // Helper: Derive PDAs
   function deriveOrderPda(orderBook: PublicKey, orderCount: bigint | number): [PublicKey, number] {
     const orderCountBuffer = Buffer.alloc(8);
@@ -290,7 +300,7 @@ describe("ShadowSwap - Security & Edge Case Tests", () => {
       
       try {
         await program.methods
-          .placeOrder(cipherPayload, encryptedAmount)
+          .placeOrder(cipherPayload, encryptedAmount, postedAmount(), dummyEncryptionKey(), new anchor.BN(0))
           .accounts({
             orderBook: orderBookPda,
             order: orderPda,
@@ -329,7 +339,7 @@ describe("ShadowSwap - Security & Edge Case Tests", () => {
       const encryptedAmount1 = createDummyEncryptedAmount();
       
       await program.methods
-        .placeOrder(cipherPayload1, encryptedAmount1)
+        .placeOrder(cipherPayload1, encryptedAmount1, postedAmount(), dummyEncryptionKey(), new anchor.BN(0))
         .accounts({
           orderBook: orderBookPda,
           order: orderPda1,
@@ -351,7 +361,7 @@ describe("ShadowSwap - Security & Edge Case Tests", () => {
       // This would only happen if we try to use the same PDA
       try {
         await program.methods
-          .placeOrder(cipherPayload1, encryptedAmount1)
+          .placeOrder(cipherPayload1, encryptedAmount1, postedAmount(), dummyEncryptionKey(), new anchor.BN(0))
           .accounts({
             orderBook: orderBookPda,
             order: orderPda1, // Same PDA!
@@ -379,6 +389,67 @@ describe("ShadowSwap - Security & Edge Case Tests", () => {
       }
     });
     
+    it("❌ Should fail: Sell order below minimum base order size", async () => {
+      const orderCount = await getCurrentOrderCount();
+      const [orderPda] = deriveOrderPda(orderBookPda, orderCount);
+      const [escrowPda] = deriveEscrowPda(orderPda);
+      const [escrowTokenAccountPda] = deriveEscrowTokenAccountPda(orderPda);
+      
+      try {
+        await program.methods
+          .placeOrder(createDummyCipherPayload(), createDummyEncryptedAmount(), postedAmount(1000), dummyEncryptionKey(), new anchor.BN(0))
+          .accounts({
+            orderBook: orderBookPda,
+            order: orderPda,
+            escrow: escrowPda,
+            escrowTokenAccount: escrowTokenAccountPda,
+            userTokenAccount: userBBaseAccount,
+            tokenMint: baseMint,
+            owner: userB.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          })
+          .signers([userB])
+          .rpc();
+        
+        assert.fail("Should have failed with order too small");
+      } catch (error) {
+        console.log("✓ Correctly failed - order below book minimum");
+        assert.include(error.toString(), "OrderTooSmall");
+      }
+    });
+    
+    it("✅ Should succeed: Only the posted amount is escrowed", async () => {
+      const orderCount = await getCurrentOrderCount();
+      const [orderPda] = deriveOrderPda(orderBookPda, orderCount);
+      const [escrowPda] = deriveEscrowPda(orderPda);
+      const [escrowTokenAccountPda] = deriveEscrowTokenAccountPda(orderPda);
+      const balanceBefore = (await getAccount(provider.connection, userAQuoteAccount)).amount;
+      
+      await program.methods
+        .placeOrder(createDummyCipherPayload(), createDummyEncryptedAmount(), postedAmount(), dummyEncryptionKey(), new anchor.BN(0))
+        .accounts({
+          orderBook: orderBookPda,
+          order: orderPda,
+          escrow: escrowPda,
+          escrowTokenAccount: escrowTokenAccountPda,
+          userTokenAccount: userAQuoteAccount,
+          tokenMint: quoteMint,
+          owner: userA.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([userA])
+        .rpc();
+      
+      const escrowBalance = (await getAccount(provider.connection, escrowTokenAccountPda)).amount;
+      const balanceAfter = (await getAccount(provider.connection, userAQuoteAccount)).amount;
+      assert.equal(escrowBalance.toString(), postedAmount().toString());
+      assert.equal((balanceBefore - balanceAfter).toString(), postedAmount().toString());
+    });
+    
     it("❌ Should fail: Cipher payload too large", async () => {
       const orderCount = await getCurrentOrderCount();
       const [orderPda] = deriveOrderPda(orderBookPda, orderCount);
@@ -391,7 +462,7 @@ describe("ShadowSwap - Security & Edge Case Tests", () => {
       
       try {
         await program.methods
-          .placeOrder(oversizedPayload, encryptedAmount)
+          .placeOrder(oversizedPayload, encryptedAmount, postedAmount(), dummyEncryptionKey(), new anchor.BN(0))
           .accounts({
             orderBook: orderBookPda,
             order: orderPda,
@@ -435,7 +506,7 @@ describe("ShadowSwap - Security & Edge Case Tests", () => {
       const encryptedAmount = createDummyEncryptedAmount();
       
       await program.methods
-        .placeOrder(cipherPayload, encryptedAmount)
+        .placeOrder(cipherPayload, encryptedAmount, postedAmount(), dummyEncryptionKey(), new anchor.BN(0))
         .accounts({
           orderBook: orderBookPda,
           order: userAOrderPda,
@@ -533,7 +604,7 @@ describe("ShadowSwap - Security & Edge Case Tests", () => {
       }
     });
     
-    it("❌ Should fail: Cannot cancel matched order", async () => {
+    it("❌ Should fail: Cannot match orders still pending escrow verification", async () => {
       // Create two new orders
       let orderCount = await getCurrentOrderCount();
       const [buyOrderPda] = deriveOrderPda(orderBookPda, orderCount);
@@ -542,7 +613,7 @@ describe("ShadowSwap - Security & Edge Case Tests", () => {
       
       // Place buy order
       await program.methods
-        .placeOrder(createDummyCipherPayload(), createDummyEncryptedAmount())
+        .placeOrder(createDummyCipherPayload(), createDummyEncryptedAmount(), postedAmount(), dummyEncryptionKey(), new anchor.BN(0))
         .accounts({
           orderBook: orderBookPda,
           order: buyOrderPda,
@@ -566,7 +637,7 @@ describe("ShadowSwap - Security & Edge Case Tests", () => {
       
       // Place sell order
       await program.methods
-        .placeOrder(createDummyCipherPayload(), createDummyEncryptedAmount())
+        .placeOrder(createDummyCipherPayload(), createDummyEncryptedAmount(), postedAmount(), dummyEncryptionKey(), new anchor.BN(0))
         .accounts({
           orderBook: orderBookPda,
           order: sellOrderPda,
@@ -582,46 +653,28 @@ describe("ShadowSwap - Security & Edge Case Tests", () => {
         .signers([userB])
         .rpc();
       
-      // Match the orders (keeper action)
-      await program.methods
-        .matchOrders(createDummyEncryptedAmount())
-        .accounts({
-          callbackAuth: callbackAuthPda,
-          orderBook: orderBookPda,
-          buyOrder: buyOrderPda,
-          sellOrder: sellOrderPda,
-          keeper: keeper.publicKey,
-        })
-        .signers([keeper])
-        .rpc();
-      
-      console.log("✓ Orders matched successfully");
+      // New orders wait for the MPC escrow coverage check (status 7)
+      const buyOrder = await program.account.encryptedOrder.fetch(buyOrderPda);
+      assert.equal(buyOrder.status, 7, "Order status should be PENDING_VERIFICATION");
       
-      // Try to cancel the matched buy order
+      // The keeper cannot match them before they are verified
       try {
         await program.methods
-          .cancelOrder()
+          .matchOrders(createDummyEncryptedAmount())
           .accounts({
-            order: buyOrderPda,
-            escrow: buyEscrowPda,
-            escrowTokenAccount: buyEscrowTokenPda,
-            userTokenAccount: userAQuoteAccount,
+            callbackAuth: callbackAuthPda,
             orderBook: orderBookPda,
-            owner: userA.publicKey,
-            tokenProgram: TOKEN_PROGRAM_ID,
+            buyOrder: buyOrderPda,
+            sellOrder: sellOrderPda,
+            keeper: keeper.publicKey,
           })
-          .signers([userA])
+          .signers([keeper])
           .rpc();
         
-        assert.fail("Should have failed - cannot cancel matched order");
+        assert.fail("Should have failed - orders are pending verification");
       } catch (error) {
-        console.log("✓ Correctly failed - cannot cancel matched/partial order that keeper modified");
-        // The order status is now PARTIAL (2), not ACTIVE or PARTIAL as allowed by cancel
-        // Actually, our cancel_order allows PARTIAL, so this test needs adjustment
-        // Let me verify the order status
-        const order = await program.account.encryptedOrder.fetch(buyOrderPda);
-        console.log("  Order status after match:", order.status);
-        // Status 2 is PARTIAL which IS allowed, so this test scenario needs rethinking
+        console.log("✓ Correctly failed - cannot match unverified orders");
+        assert.include(error.toString(), "InvalidOrderStatus");
       }
     });
   });
@@ -641,7 +694,7 @@ describe("ShadowSwap - Security & Edge Case Tests", () => {
       
       // Place first order
       await program.methods
-        .placeOrder(createDummyCipherPayload(), createDummyEncryptedAmount())
+        .placeOrder(createDummyCipherPayload(), createDummyEncryptedAmount(), postedAmount(), dummyEncryptionKey(), new anchor.BN(0))
         .accounts({
           orderBook: orderBookPda,
           order: buyOrderPda,
@@ -665,7 +718,7 @@ describe("ShadowSwap - Security & Edge Case Tests", () => {
       
       // Place second order
       await program.methods
-        .placeOrder(createDummyCipherPayload(), createDummyEncryptedAmount())
+        .placeOrder(createDummyCipherPayload(), createDummyEncryptedAmount(), postedAmount(), dummyEncryptionKey(), new anchor.BN(0))
         .accounts({
           orderBook: orderBookPda,
           order: sellOrderPda,
@@ -721,7 +774,7 @@ describe("ShadowSwap - Security & Edge Case Tests", () => {
       
       // Place first order
       await program.methods
-        .placeOrder(createDummyCipherPayload(), createDummyEncryptedAmount())
+        .placeOrder(createDummyCipherPayload(), createDummyEncryptedAmount(), postedAmount(), dummyEncryptionKey(), new anchor.BN(0))
         .accounts({
           orderBook: orderBookPda,
           order: cancelledOrderPda,
@@ -760,7 +813,7 @@ describe("ShadowSwap - Security & Edge Case Tests", () => {
       
       // Place second active order
       await program.methods
-        .placeOrder(createDummyCipherPayload(), createDummyEncryptedAmount())
+        .placeOrder(createDummyCipherPayload(), createDummyEncryptedAmount(), postedAmount(), dummyEncryptionKey(), new anchor.BN(0))
         .accounts({
           orderBook: orderBookPda,
           order: activeOrderPda,
@@ -829,6 +882,456 @@ describe("ShadowSwap - Security & Edge Case Tests", () => {
     });
   });
 
+  // ============================================================================
+  // ORACLE CONFIGURATION TESTS
+  // ============================================================================
+
+  describe("configure_oracle", () => {
+    const oracle = Keypair.generate().publicKey;
+
+    it("❌ Should fail: Non-authority cannot configure the oracle", async () => {
+      try {
+        await program.methods
+          .configureOracle(oracle, new anchor.BN(60), 100, -6)
+          .accounts({
+            orderBook: orderBookPda,
+            authority: userA.publicKey,
+          })
+          .signers([userA])
+          .rpc();
+
+        assert.fail("Should have failed - signer is not the book authority");
+      } catch (error) {
+        console.log("✓ Correctly rejected oracle configuration from non-authority");
+        assert.include(error.toString(), "UnauthorizedCallback");
+      }
+    });
+
+    it("✅ Should succeed: Authority configures the reference oracle", async () => {
+      await program.methods
+        .configureOracle(oracle, new anchor.BN(60), 100, -6)
+        .accounts({
+          orderBook: orderBookPda,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      const orderBook = await program.account.orderBook.fetch(orderBookPda);
+      assert.ok(orderBook.oracle.equals(oracle));
+      assert.equal(orderBook.oracleMaxStaleness.toNumber(), 60);
+      assert.equal(orderBook.oracleMaxConfidenceBps, 100);
+      assert.equal(orderBook.oraclePriceExpo, -6);
+      console.log("✓ Oracle configured");
+    });
+  });
+
+  // ============================================================================
+  // CONDITIONAL ORDER TESTS
+  // ============================================================================
+
+  describe("attach_trigger", () => {
+    function deriveConditionalOrderPda(order: PublicKey): [PublicKey, number] {
+      return PublicKey.findProgramAddressSync(
+        [Buffer.from("conditional_order"), order.toBuffer()],
+        program.programId
+      );
+    }
+
+    // place_order followed by attach_trigger, as a single transaction
+    async function placeConditionalOrder(
+      owner: Keypair,
+      userTokenAccount: PublicKey,
+      triggerSigner: Keypair = owner
+    ) {
+      const orderCount = await getCurrentOrderCount();
+      const [order] = deriveOrderPda(orderBookPda, orderCount);
+      const [escrow] = deriveEscrowPda(order);
+      const [escrowToken] = deriveEscrowTokenAccountPda(order);
+      const [conditionalOrder] = deriveConditionalOrderPda(order);
+
+      const placeIx = await program.methods
+        .placeOrder(createDummyCipherPayload(), createDummyEncryptedAmount(), postedAmount(), dummyEncryptionKey(), new anchor.BN(0))
+        .accounts({
+          orderBook: orderBookPda,
+          order,
+          escrow,
+          escrowTokenAccount: escrowToken,
+          userTokenAccount,
+          tokenMint: quoteMint,
+          owner: owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .instruction();
+
+      await program.methods
+        .attachTrigger(
+          Array.from(createDummyEncryptedAmount()),
+          new anchor.BN(1),
+          [Array.from(createDummyEncryptedAmount()), Array.from(createDummyEncryptedAmount())]
+        )
+        .accounts({
+          order,
+          conditionalOrder,
+          owner: triggerSigner.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([placeIx])
+        .signers(triggerSigner === owner ? [owner] : [owner, triggerSigner])
+        .rpc();
+
+      return { order, escrow, escrowToken, conditionalOrder };
+    }
+
+    it("✅ Should succeed: Order with a hidden trigger is parked as conditional", async () => {
+      const { order, conditionalOrder } = await placeConditionalOrder(userA, userAQuoteAccount);
+
+      const orderAccount = await program.account.encryptedOrder.fetch(order);
+      assert.equal(orderAccount.status, 8, "Order should be conditional");
+
+      const conditional = await program.account.conditionalOrder.fetch(conditionalOrder);
+      assert.ok(conditional.order.equals(order));
+      assert.ok(conditional.owner.equals(userA.publicKey));
+      assert.equal(conditional.triggeredAt.toNumber(), 0);
+      console.log("✓ Conditional order created");
+    });
+
+    it("✅ Should succeed: Owner can cancel an untriggered conditional order", async () => {
+      const { order, escrow, escrowToken } = await placeConditionalOrder(userA, userAQuoteAccount);
+      const balanceBefore = await getAccount(provider.connection, userAQuoteAccount);
+
+      await program.methods
+        .cancelOrder()
+        .accounts({
+          order,
+          escrow,
+          escrowTokenAccount: escrowToken,
+          userTokenAccount: userAQuoteAccount,
+          orderBook: orderBookPda,
+          owner: userA.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([userA])
+        .rpc();
+
+      const orderAccount = await program.account.encryptedOrder.fetch(order);
+      assert.equal(orderAccount.status, 4, "Order should be cancelled");
+      const balanceAfter = await getAccount(provider.connection, userAQuoteAccount);
+      assert.equal(Number(balanceAfter.amount - balanceBefore.amount), postedAmount().toNumber());
+      console.log("✓ Conditional order cancelled with refund");
+    });
+
+    it("❌ Should fail: Trigger cannot be attached to another user's order", async () => {
+      try {
+        await placeConditionalOrder(userA, userAQuoteAccount, userB);
+        assert.fail("Should have failed - signer does not own the order");
+      } catch (error) {
+        console.log("✓ Correctly rejected trigger from non-owner");
+        assert.include(error.toString(), "UnauthorizedCallback");
+      }
+    });
+  });
+
+  // ============================================================================
+  // SHIELDED VAULT TESTS
+  // ============================================================================
+
+  describe("shielded vault", () => {
+    const DEPOSIT = 5_000_000;
+
+    function pda(seeds: Buffer[]): PublicKey {
+      return PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+    }
+
+    let quoteVault: PublicKey;
+    let quoteVaultToken: PublicKey;
+    let userABalance: PublicKey;
+
+    before(() => {
+      quoteVault = pda([Buffer.from("shielded_vault"), orderBookPda.toBuffer(), quoteMint.toBuffer()]);
+      quoteVaultToken = pda([Buffer.from("shielded_vault_token"), quoteVault.toBuffer()]);
+      userABalance = pda([Buffer.from("shielded_balance"), quoteVault.toBuffer(), userA.publicKey.toBuffer()]);
+    });
+
+    function initializeVault(authority: Keypair | null) {
+      const builder = program.methods
+        .initializeShieldedVault()
+        .accounts({
+          orderBook: orderBookPda,
+          vault: quoteVault,
+          vaultTokenAccount: quoteVaultToken,
+          tokenMint: quoteMint,
+          authority: authority ? authority.publicKey : provider.wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        });
+      return authority ? builder.signers([authority]).rpc() : builder.rpc();
+    }
+
+    it("❌ Should fail: Non-authority cannot create a shielded vault", async () => {
+      try {
+        await initializeVault(userA);
+        assert.fail("Should have failed - signer is not the book authority");
+      } catch (error) {
+        console.log("✓ Correctly rejected shielded vault from non-authority");
+        assert.include(error.toString(), "UnauthorizedCallback");
+      }
+    });
+
+    it("✅ Should succeed: Deposits are pooled and pending until folded in", async () => {
+      await initializeVault(null);
+
+      await program.methods
+        .openShieldedBalance()
+        .accounts({
+          vault: quoteVault,
+          balance: userABalance,
+          owner: userA.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([userA])
+        .rpc();
+
+      await program.methods
+        .shieldedDeposit(new anchor.BN(DEPOSIT))
+        .accounts({
+          vault: quoteVault,
+          vaultTokenAccount: quoteVaultToken,
+          balance: userABalance,
+          userTokenAccount: userAQuoteAccount,
+          owner: userA.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([userA])
+        .rpc();
+
+      const balance = await program.account.shieldedBalance.fetch(userABalance);
+      assert.equal(balance.pendingDeposit.toNumber(), DEPOSIT);
+      assert.isFalse(balance.hasBalance, "Nothing is encrypted before the first computation");
+      assert.isFalse(balance.busy);
+
+      const vaultToken = await getAccount(provider.connection, quoteVaultToken);
+      assert.equal(Number(vaultToken.amount), DEPOSIT);
+      console.log("✓ Shielded deposit pooled");
+    });
+
+    it("❌ Should fail: Shielded orders cannot use the per-order cancel path", async () => {
+      const orderCount = await getCurrentOrderCount();
+      const [order] = deriveOrderPda(orderBookPda, orderCount);
+      const [escrow] = deriveEscrowPda(order);
+
+      await program.methods
+        .placeShieldedOrder(createDummyCipherPayload(), createDummyEncryptedAmount(), dummyEncryptionKey(), new anchor.BN(0))
+        .accounts({
+          orderBook: orderBookPda,
+          vault: quoteVault,
+          order,
+          escrow,
+          owner: userA.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([userA])
+        .rpc();
+
+      const escrowAccount = await program.account.escrow.fetch(escrow);
+      assert.ok(escrowAccount.vault.equals(quoteVault));
+      assert.equal(escrowAccount.encryptedRemaining.length, 0, "No allocation before MPC");
+
+      try {
+        await program.methods
+          .cancelOrder()
+          .accounts({
+            order,
+            escrow,
+            escrowTokenAccount: quoteVaultToken,
+            userTokenAccount: userAQuoteAccount,
+            orderBook: orderBookPda,
+            owner: userA.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([userA])
+          .rpc();
+        assert.fail("Should have failed - escrow lives in the shielded vault");
+      } catch (error) {
+        console.log("✓ Correctly rejected per-order cancel of a shielded order");
+        assert.include(error.toString(), "ShieldedEscrow");
+      }
+    });
+
+    it("❌ Should fail: Shielded orders cannot be queued by match_callback", async () => {
+      const orders: PublicKey[] = [];
+      const orderIds: anchor.BN[] = [];
+      for (let i = 0; i < 2; i++) {
+        const orderCount = await getCurrentOrderCount();
+        const [order] = deriveOrderPda(orderBookPda, orderCount);
+        await program.methods
+          .placeShieldedOrder(createDummyCipherPayload(), createDummyEncryptedAmount(), dummyEncryptionKey(), new anchor.BN(0))
+          .accounts({
+            orderBook: orderBookPda,
+            vault: quoteVault,
+            order,
+            escrow: deriveEscrowPda(order)[0],
+            owner: userA.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([userA])
+          .rpc();
+        orders.push(order);
+        orderIds.push(new anchor.BN(orderCount.toString()));
+      }
+
+      try {
+        await program.methods
+          .matchCallback([
+            {
+              buyerPubkey: orders[0],
+              sellerPubkey: orders[1],
+              buyerOrderId: orderIds[0],
+              sellerOrderId: orderIds[1],
+              encryptedAmount: createDummyEncryptedAmount(),
+              encryptedPrice: createDummyEncryptedAmount(),
+              buyerEncryptedRemaining: createDummyEncryptedAmount(),
+              sellerEncryptedRemaining: createDummyEncryptedAmount(),
+            },
+          ])
+          .accounts({
+            callbackAuth: callbackAuthPda,
+            orderBook: orderBookPda,
+            keeper: keeper.publicKey,
+          })
+          .remainingAccounts([
+            ...orders.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false })),
+            ...orders.map((order) => ({ pubkey: deriveEscrowPda(order)[0], isWritable: false, isSigner: false })),
+          ])
+          .signers([keeper])
+          .rpc();
+        assert.fail("Should have failed - shielded orders settle inside the vaults");
+      } catch (error) {
+        console.log("✓ Correctly rejected a match of shielded orders");
+        assert.include(error.toString(), "ShieldedEscrow");
+      }
+    });
+  });
+
+  // ============================================================================
+  // BATCH AUCTION TESTS
+  // ============================================================================
+
+  describe("batch auctions", () => {
+    function auctionEpochPda(epoch: anchor.BN): PublicKey {
+      return PublicKey.findProgramAddressSync(
+        [Buffer.from("auction_epoch"), orderBookPda.toBuffer(), epoch.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0];
+    }
+
+    function configureAuction(epochDuration: number, authority: Keypair | null = null) {
+      const builder = program.methods
+        .configureAuction(new anchor.BN(epochDuration))
+        .accounts({
+          orderBook: orderBookPda,
+          authority: authority ? authority.publicKey : provider.wallet.publicKey,
+        });
+      return authority ? builder.signers([authority]).rpc() : builder.rpc();
+    }
+
+    async function closeEpoch() {
+      const orderBook = await program.account.orderBook.fetch(orderBookPda);
+      const auctionEpoch = auctionEpochPda(orderBook.currentEpoch);
+      await program.methods
+        .closeAuctionEpoch()
+        .accounts({
+          orderBook: orderBookPda,
+          auctionEpoch,
+          payer: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      return auctionEpoch;
+    }
+
+    after(async () => {
+      // Later tests use continuous matching on the shared book
+      await configureAuction(0);
+    });
+
+    it("❌ Should fail: Non-authority cannot enable auctions", async () => {
+      try {
+        await configureAuction(60, userA);
+        assert.fail("Should have failed - signer is not the book authority");
+      } catch (error) {
+        console.log("✓ Correctly rejected auction configuration from non-authority");
+        assert.include(error.toString(), "UnauthorizedCallback");
+      }
+    });
+
+    it("❌ Should fail: Continuous books have no epochs to close", async () => {
+      try {
+        await closeEpoch();
+        assert.fail("Should have failed - book trades continuously");
+      } catch (error) {
+        console.log("✓ Correctly rejected epoch close on a continuous book");
+        assert.include(error.toString(), "NotAuctionBook");
+      }
+    });
+
+    it("❌ Should fail: Epoch cannot close before its duration", async () => {
+      await configureAuction(3600);
+
+      try {
+        await closeEpoch();
+        assert.fail("Should have failed - epoch still running");
+      } catch (error) {
+        console.log("✓ Correctly rejected early epoch close");
+        assert.include(error.toString(), "AuctionEpochNotOver");
+      }
+    });
+
+    it("✅ Should succeed: Closing an epoch rolls new orders into the next", async () => {
+      await configureAuction(0);
+      await configureAuction(1);
+      await new Promise((resolve) => setTimeout(resolve, 2000));
+
+      const before = await program.account.orderBook.fetch(orderBookPda);
+      const auctionEpoch = await closeEpoch();
+
+      const epoch = await program.account.auctionEpoch.fetch(auctionEpoch);
+      assert.equal(epoch.epoch.toNumber(), before.currentEpoch.toNumber());
+      assert.equal(epoch.status, 1, "Closed, waiting for clearing");
+      assert.equal(epoch.orderCount, 0);
+
+      const after = await program.account.orderBook.fetch(orderBookPda);
+      assert.equal(after.currentEpoch.toNumber(), before.currentEpoch.toNumber() + 1);
+
+      const orderCount = await getCurrentOrderCount();
+      const [order] = deriveOrderPda(orderBookPda, orderCount);
+      const [escrow] = deriveEscrowPda(order);
+      const [escrowToken] = deriveEscrowTokenAccountPda(order);
+      await program.methods
+        .placeOrder(createDummyCipherPayload(), createDummyEncryptedAmount(), postedAmount(), dummyEncryptionKey(), new anchor.BN(0))
+        .accounts({
+          orderBook: orderBookPda,
+          order,
+          escrow,
+          escrowTokenAccount: escrowToken,
+          userTokenAccount: userAQuoteAccount,
+          tokenMint: quoteMint,
+          owner: userA.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([userA])
+        .rpc();
+
+      const placed = await program.account.encryptedOrder.fetch(order);
+      assert.equal(placed.epoch.toNumber(), after.currentEpoch.toNumber());
+      console.log("✓ Late order placed into the next epoch");
+    });
+  });
+
   // ============================================================================
   // ADDITIONAL SECURITY TESTS
   // ============================================================================
@@ -854,7 +1357,7 @@ describe("ShadowSwap - Security & Edge Case Tests", () => {
         const tokenAccount = i % 2 === 0 ? userAQuoteAccount : userBQuoteAccount;
         
         await program.methods
-          .placeOrder(createDummyCipherPayload(), createDummyEncryptedAmount())
+          .placeOrder(createDummyCipherPayload(), createDummyEncryptedAmount(), postedAmount(), dummyEncryptionKey(), new anchor.BN(0))
           .accounts({
             orderBook: orderBookPda,
             order: orderPda,
@@ -907,9 +1410,16 @@ describe("ShadowSwap - Security & Edge Case Tests", () => {
     console.log("\nTest Coverage:");
     console.log("  ✓ Insufficient funds");
     console.log("  ✓ Duplicate orders (PDA collision)");
+    console.log("  ✓ Orders below book minimum");
+    console.log("  ✓ Posted amount escrow");
     console.log("  ✓ Oversized cipher payloads");
+    console.log("  ✓ Oracle configuration access control");
+    console.log("  ✓ Conditional order triggers");
+    console.log("  ✓ Shielded vault access and deposits");
+    console.log("  ✓ Batch auction epochs");
     console.log("  ✓ Unauthorized cancellation");
     console.log("  ✓ Double cancellation");
+    console.log("  ✓ Matching unverified orders");
     console.log("  ✓ Unauthorized matching");
     console.log("  ✓ Matching cancelled orders");
     console.log("  ✓ Expired callback auth");
@@ -15,6 +15,21 @@ import {
   createAccount,
   mintTo,
 } from "@solana/spl-token";
+import {
+  awaitComputationFinalization,
+  getArciumEnv,
+  getClusterAccAddress,
+  getCompDefAccAddress,
+  getCompDefAccOffset,
+  getComputationAccAddress,
+  getExecutingPoolAccAddress,
+  getMempoolAccAddress,
+  getMXEAccAddress,
+  getMXEPublicKey,
+  RescueCipher,
+  x25519,
+} from "@arcium-hq/client";
+import { randomBytes } from "crypto";
 import { assert } from "chai";
 
 describe("Match Callback Tests", () => {
@@ -31,6 +46,8 @@ describe("Match Callback Tests", () => {
   let userA: Keypair;
   let userB: Keypair;
   let feeCollector: Keypair;
+  let userAQuoteAccount: PublicKey;
+  let userBBaseAccount: PublicKey;
 
   // Helper function to derive order PDA
   function deriveOrderPda(orderBookAddress: PublicKey, orderCount: number): [PublicKey, number] {
@@ -43,6 +60,145 @@ describe("Match Callback Tests", () => {
     );
   }
 
+  // Helper function to derive escrow PDAs
+  function deriveEscrowPdas(order: PublicKey): [PublicKey, PublicKey] {
+    const [escrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), order.toBuffer()],
+      program.programId
+    );
+    const [escrowToken] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow_token"), order.toBuffer()],
+      program.programId
+    );
+    return [escrow, escrowToken];
+  }
+
+  // Helper function to place an order, get it verified by the escrow
+  // coverage circuit and return its (now ACTIVE) PDA
+  //
+  // Buy orders escrow 1 USDC for 1000 units @ 1000; sell orders escrow
+  // 0.001 WSOL for 1_000_000 units, so both are exactly covered.
+  async function placeOrder(
+    owner: Keypair,
+    userTokenAccount: PublicKey,
+    tokenMint: PublicKey
+  ): Promise<PublicKey> {
+    const orderBook = await program.account.orderBook.fetch(orderBookPda);
+    const [orderPda] = deriveOrderPda(orderBookPda, orderBook.orderCount.toNumber());
+    const [escrowPda, escrowTokenPda] = deriveEscrowPdas(orderPda);
+
+    const isBuy = tokenMint.equals(quoteMint);
+    const orderInput = isBuy
+      ? [BigInt(0), BigInt(1000), BigInt(1000), BigInt(Date.now()), BigInt(0)]
+      : [BigInt(1), BigInt(1000), BigInt(1_000_000), BigInt(Date.now()), BigInt(0)];
+
+    // Encrypt the OrderInput fields for the MXE
+    const privateKey = x25519.utils.randomPrivateKey();
+    const publicKey = x25519.getPublicKey(privateKey);
+    const mxePublicKey = await getMXEPublicKey(provider, program.programId);
+    const cipher = new RescueCipher(x25519.getSharedSecret(privateKey, mxePublicKey!));
+    const nonce = randomBytes(16);
+    const ciphertexts = cipher.encrypt(orderInput, nonce);
+
+    const cipherPayload = Buffer.alloc(256);
+    ciphertexts.forEach((ct, idx) => Buffer.from(ct).copy(cipherPayload, idx * 32));
+
+    await program.methods
+      .placeOrder(
+        cipherPayload,
+        Buffer.from(ciphertexts[2]),
+        new anchor.BN(1_000_000),
+        Array.from(publicKey),
+        new anchor.BN(deserializeLE(nonce).toString())
+      )
+      .accounts({
+        orderBook: orderBookPda,
+        order: orderPda,
+        escrow: escrowPda,
+        escrowTokenAccount: escrowTokenPda,
+        userTokenAccount,
+        tokenMint,
+        owner: owner.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([owner])
+      .rpc();
+
+    // The owner runs the escrow coverage check so the order becomes ACTIVE
+    const arciumEnv = getArciumEnv();
+    const computationOffset = new anchor.BN(randomBytes(8), "hex");
+    await program.methods
+      .invokeVerifyEscrowCoverage(
+        computationOffset,
+        ciphertexts.map((ct) => Array.from(ct))
+      )
+      .accountsPartial({
+        payer: owner.publicKey,
+        orderBook: orderBookPda,
+        callbackAuth: null,
+        order: orderPda,
+        escrow: escrowPda,
+        escrowTokenAccount: escrowTokenPda,
+        computationAccount: getComputationAccAddress(program.programId, computationOffset),
+        clusterAccount: getClusterAccAddress(arciumEnv.arciumClusterOffset),
+        mxeAccount: getMXEAccAddress(program.programId),
+        mempoolAccount: getMempoolAccAddress(program.programId),
+        executingPool: getExecutingPoolAccAddress(program.programId),
+        compDefAccount: getCompDefAccAddress(
+          program.programId,
+          Buffer.from(getCompDefAccOffset("verify_escrow_coverage")).readUInt32LE()
+        ),
+      })
+      .signers([owner])
+      .rpc();
+    await awaitComputationFinalization(provider, computationOffset, program.programId, "confirmed");
+
+    const order = await program.account.encryptedOrder.fetch(orderPda);
+    assert.equal(order.status, 1, "Order should be ACTIVE after coverage check");
+
+    return orderPda;
+  }
+
+  function deserializeLE(bytes: Uint8Array): bigint {
+    let value = BigInt(0);
+    for (let i = bytes.length - 1; i >= 0; i--) {
+      value = (value << BigInt(8)) | BigInt(bytes[i]);
+    }
+    return value;
+  }
+
+  // Helper function to build a match result for two placed orders
+  async function buildMatchResult(buyOrder: PublicKey, sellOrder: PublicKey, fill: number) {
+    const buyer = await program.account.encryptedOrder.fetch(buyOrder);
+    const seller = await program.account.encryptedOrder.fetch(sellOrder);
+    return {
+      buyerPubkey: buyOrder,
+      sellerPubkey: sellOrder,
+      buyerOrderId: buyer.orderId,
+      sellerOrderId: seller.orderId,
+      encryptedAmount: Buffer.from(new Uint8Array(64).fill(fill)), // Mock encrypted amount
+      encryptedPrice: Buffer.from(new Uint8Array(64).fill(fill + 1)), // Mock encrypted price
+      buyerEncryptedRemaining: Buffer.from(new Uint8Array(32).fill(fill + 2)),
+      sellerEncryptedRemaining: Buffer.from(new Uint8Array(32).fill(fill + 3)),
+    };
+  }
+
+  // Remaining accounts for match results: each [buyer, seller] pair of
+  // orders followed by their escrows
+  function matchAccountMetas(orders: PublicKey[]) {
+    const metas = [];
+    for (let i = 0; i < orders.length; i += 2) {
+      const pair = [orders[i], orders[i + 1]];
+      metas.push(...pair.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false })));
+      metas.push(
+        ...pair.map((order) => ({ pubkey: deriveEscrowPdas(order)[0], isWritable: false, isSigner: false }))
+      );
+    }
+    return metas;
+  }
+
   // Helper function to derive callback auth PDA
   function deriveCallbackAuthPda(
     orderBookAddress: PublicKey,
@@ -93,6 +249,12 @@ describe("Match Callback Tests", () => {
       6
     );
 
+    // Fund users so they can place orders
+    userAQuoteAccount = await createAccount(provider.connection, userA, quoteMint, userA.publicKey);
+    userBBaseAccount = await createAccount(provider.connection, userB, baseMint, userB.publicKey);
+    await mintTo(provider.connection, userA, quoteMint, userAQuoteAccount, userA.publicKey, 1000 * 10 ** 6);
+    await mintTo(provider.connection, userA, baseMint, userBBaseAccount, userA.publicKey, 5 * 10 ** 9);
+
     // Derive order book PDA
     [orderBookPda] = PublicKey.findProgramAddressSync(
       [Buffer.from("order_book"), baseMint.toBuffer(), quoteMint.toBuffer()],
@@ -136,17 +298,11 @@ describe("Match Callback Tests", () => {
   it("Successfully calls match_callback with sample results", async () => {
     console.log("\n🧪 Testing match_callback...\n");
 
+    const buyOrder = await placeOrder(userA, userAQuoteAccount, quoteMint);
+    const sellOrder = await placeOrder(userB, userBBaseAccount, baseMint);
+
     // Create sample match results (simulating Arcium MPC output)
-    const matchResults = [
-      {
-        buyerPubkey: deriveOrderPda(orderBookPda, 0)[0],
-        sellerPubkey: deriveOrderPda(orderBookPda, 1)[0],
-        buyerOrderId: new anchor.BN(0),
-        sellerOrderId: new anchor.BN(1),
-        encryptedAmount: Buffer.from(new Uint8Array(64).fill(1)), // Mock encrypted amount
-        encryptedPrice: Buffer.from(new Uint8Array(64).fill(2)),  // Mock encrypted price
-      },
-    ];
+    const matchResults = [await buildMatchResult(buyOrder, sellOrder, 1)];
 
     console.log("📊 Match Results:");
     console.log("   Buyer Order:", matchResults[0].buyerPubkey.toString());
@@ -160,6 +316,7 @@ describe("Match Callback Tests", () => {
         orderBook: orderBookPda,
         keeper: keeper.publicKey,
       })
+      .remainingAccounts(matchAccountMetas([buyOrder, sellOrder]))
       .signers([keeper])
       .rpc();
 
@@ -176,6 +333,14 @@ describe("Match Callback Tests", () => {
     assert.isNotNull(txDetails, "Transaction should exist");
     assert.isNull(txDetails!.meta!.err, "Transaction should not have errors");
 
+    // Both orders move to MATCHED_PENDING (5) with their own remaining amount
+    const buyer = await program.account.encryptedOrder.fetch(buyOrder);
+    const seller = await program.account.encryptedOrder.fetch(sellOrder);
+    assert.equal(buyer.status, 5, "Buyer order should be MATCHED_PENDING");
+    assert.equal(seller.status, 5, "Seller order should be MATCHED_PENDING");
+    assert.deepEqual(Buffer.from(buyer.encryptedRemaining), matchResults[0].buyerEncryptedRemaining);
+    assert.deepEqual(Buffer.from(seller.encryptedRemaining), matchResults[0].sellerEncryptedRemaining);
+
     console.log("\n🎉 Test passed! Callback works correctly.");
   });
 
@@ -192,16 +357,9 @@ describe("Match Callback Tests", () => {
       )
     );
 
-    const matchResults = [
-      {
-        buyerPubkey: deriveOrderPda(orderBookPda, 0)[0],
-        sellerPubkey: deriveOrderPda(orderBookPda, 1)[0],
-        buyerOrderId: new anchor.BN(0),
-        sellerOrderId: new anchor.BN(1),
-        encryptedAmount: Buffer.from(new Uint8Array(64)),
-        encryptedPrice: Buffer.from(new Uint8Array(64)),
-      },
-    ];
+    const buyOrder = await placeOrder(userA, userAQuoteAccount, quoteMint);
+    const sellOrder = await placeOrder(userB, userBBaseAccount, baseMint);
+    const matchResults = [await buildMatchResult(buyOrder, sellOrder, 0)];
 
     try {
       await program.methods
@@ -211,6 +369,7 @@ describe("Match Callback Tests", () => {
           orderBook: orderBookPda,
           keeper: unauthorizedKeeper.publicKey,
         })
+        .remainingAccounts(matchAccountMetas([buyOrder, sellOrder]))
         .signers([unauthorizedKeeper])
         .rpc();
 
@@ -225,23 +384,14 @@ describe("Match Callback Tests", () => {
     console.log("\n🧪 Testing multiple matches...\n");
 
     // Create multiple match results
+    const orders: PublicKey[] = [];
+    for (let i = 0; i < 2; i++) {
+      orders.push(await placeOrder(userA, userAQuoteAccount, quoteMint));
+      orders.push(await placeOrder(userB, userBBaseAccount, baseMint));
+    }
     const matchResults = [
-      {
-        buyerPubkey: deriveOrderPda(orderBookPda, 0)[0],
-        sellerPubkey: deriveOrderPda(orderBookPda, 1)[0],
-        buyerOrderId: new anchor.BN(0),
-        sellerOrderId: new anchor.BN(1),
-        encryptedAmount: Buffer.from(new Uint8Array(64).fill(1)),
-        encryptedPrice: Buffer.from(new Uint8Array(64).fill(2)),
-      },
-      {
-        buyerPubkey: deriveOrderPda(orderBookPda, 2)[0],
-        sellerPubkey: deriveOrderPda(orderBookPda, 3)[0],
-        buyerOrderId: new anchor.BN(2),
-        sellerOrderId: new anchor.BN(3),
-        encryptedAmount: Buffer.from(new Uint8Array(64).fill(3)),
-        encryptedPrice: Buffer.from(new Uint8Array(64).fill(4)),
-      },
+      await buildMatchResult(orders[0], orders[1], 1),
+      await buildMatchResult(orders[2], orders[3], 3),
     ];
 
     console.log("📊 Processing", matchResults.length, "matches");
@@ -253,6 +403,7 @@ describe("Match Callback Tests", () => {
         orderBook: orderBookPda,
         keeper: keeper.publicKey,
       })
+      .remainingAccounts(matchAccountMetas(orders))
       .signers([keeper])
       .rpc();
 
@@ -267,7 +418,249 @@ describe("Match Callback Tests", () => {
     assert.isNotNull(txDetails);
     assert.isNull(txDetails!.meta!.err);
 
+    for (const order of orders) {
+      const account = await program.account.encryptedOrder.fetch(order);
+      assert.equal(account.status, 5, "Order should be MATCHED_PENDING");
+    }
+
     console.log("🎉 Multiple match test passed!");
   });
+
+  it("Matches an order carried over from an earlier result", async () => {
+    const buyOrder = await placeOrder(userA, userAQuoteAccount, quoteMint);
+    const firstSell = await placeOrder(userB, userBBaseAccount, baseMint);
+    const secondSell = await placeOrder(userB, userBBaseAccount, baseMint);
+    const matchResults = [
+      await buildMatchResult(buyOrder, firstSell, 7),
+      await buildMatchResult(buyOrder, secondSell, 9),
+    ];
+
+    await program.methods
+      .matchCallback(matchResults)
+      .accounts({
+        callbackAuth: callbackAuthPda,
+        orderBook: orderBookPda,
+        keeper: keeper.publicKey,
+      })
+      .remainingAccounts(matchAccountMetas([buyOrder, firstSell, buyOrder, secondSell]))
+      .signers([keeper])
+      .rpc();
+
+    const buyer = await program.account.encryptedOrder.fetch(buyOrder);
+    assert.equal(buyer.status, 5, "Buyer order should be MATCHED_PENDING");
+    assert.equal(buyer.pendingMatches, 2, "Buyer order is queued in both results");
+    assert.deepEqual(Buffer.from(buyer.encryptedRemaining), matchResults[1].buyerEncryptedRemaining);
+  });
+
+  it("Rejects match results for orders that are already matched", async () => {
+    const buyOrder = await placeOrder(userA, userAQuoteAccount, quoteMint);
+    const sellOrder = await placeOrder(userB, userBBaseAccount, baseMint);
+    const matchResults = [await buildMatchResult(buyOrder, sellOrder, 5)];
+
+    await program.methods
+      .matchCallback(matchResults)
+      .accounts({
+        callbackAuth: callbackAuthPda,
+        orderBook: orderBookPda,
+        keeper: keeper.publicKey,
+      })
+      .remainingAccounts(matchAccountMetas([buyOrder, sellOrder]))
+      .signers([keeper])
+      .rpc();
+
+    try {
+      await program.methods
+        .matchCallback(matchResults)
+        .accounts({
+          callbackAuth: callbackAuthPda,
+          orderBook: orderBookPda,
+          keeper: keeper.publicKey,
+        })
+        .remainingAccounts(matchAccountMetas([buyOrder, sellOrder]))
+        .signers([keeper])
+        .rpc();
+
+      assert.fail("Should have failed with OrderNotActive");
+    } catch (error: any) {
+      assert.include(error.toString(), "OrderNotActive");
+    }
+  });
+
+  it("Rejects a match of an order against itself", async () => {
+    const buyOrder = await placeOrder(userA, userAQuoteAccount, quoteMint);
+    const matchResults = [await buildMatchResult(buyOrder, buyOrder, 11)];
+
+    try {
+      await program.methods
+        .matchCallback(matchResults)
+        .accounts({
+          callbackAuth: callbackAuthPda,
+          orderBook: orderBookPda,
+          keeper: keeper.publicKey,
+        })
+        .remainingAccounts(matchAccountMetas([buyOrder, buyOrder]))
+        .signers([keeper])
+        .rpc();
+
+      assert.fail("Should have failed with SelfMatch");
+    } catch (error: any) {
+      assert.include(error.toString(), "SelfMatch");
+    }
+  });
+
+  it("Rejects a posted amount check from someone other than the owner or a keeper", async () => {
+    const buyOrder = await placeOrder(userA, userAQuoteAccount, quoteMint);
+    const [escrowPda, escrowTokenPda] = deriveEscrowPdas(buyOrder);
+    const escrow = await program.account.escrow.fetch(escrowPda);
+
+    const arciumEnv = getArciumEnv();
+    const computationOffset = new anchor.BN(randomBytes(8), "hex");
+    try {
+      await program.methods
+        .invokeVerifyPostedAmount(
+          computationOffset,
+          new anchor.BN(0),
+          Array.from(escrow.encryptedAmount.slice(0, 32))
+        )
+        .accountsPartial({
+          payer: userB.publicKey,
+          orderBook: orderBookPda,
+          callbackAuth: null,
+          order: buyOrder,
+          escrow: escrowPda,
+          escrowTokenAccount: escrowTokenPda,
+          computationAccount: getComputationAccAddress(program.programId, computationOffset),
+          clusterAccount: getClusterAccAddress(arciumEnv.arciumClusterOffset),
+          mxeAccount: getMXEAccAddress(program.programId),
+          mempoolAccount: getMempoolAccAddress(program.programId),
+          executingPool: getExecutingPoolAccAddress(program.programId),
+          compDefAccount: getCompDefAccAddress(
+            program.programId,
+            Buffer.from(getCompDefAccOffset("verify_posted_amount")).readUInt32LE()
+          ),
+        })
+        .signers([userB])
+        .rpc();
+
+      assert.fail("Should have failed with UnauthorizedCallback");
+    } catch (error: any) {
+      assert.include(error.toString(), "UnauthorizedCallback");
+    }
+  });
+
+  describe("encrypted volume", () => {
+    function compDefAddress(name: string): PublicKey {
+      return getCompDefAccAddress(program.programId, Buffer.from(getCompDefAccOffset(name)).readUInt32LE());
+    }
+
+    // Arcium accounts for queueing `name` at a fresh computation offset
+    function computationAccounts(name: string) {
+      const arciumEnv = getArciumEnv();
+      const computationOffset = new anchor.BN(randomBytes(8), "hex");
+      return {
+        computationOffset,
+        accounts: {
+          computationAccount: getComputationAccAddress(program.programId, computationOffset),
+          clusterAccount: getClusterAccAddress(arciumEnv.arciumClusterOffset),
+          mxeAccount: getMXEAccAddress(program.programId),
+          mempoolAccount: getMempoolAccAddress(program.programId),
+          executingPool: getExecutingPoolAccAddress(program.programId),
+          compDefAccount: compDefAddress(name),
+        },
+      };
+    }
+
+    function invokeUpdateVolume(buyerOrder: PublicKey, sellerOrder: PublicKey, payer: Keypair = keeper) {
+      const { computationOffset, accounts } = computationAccounts("update_volume");
+      return program.methods
+        .invokeUpdateVolume(computationOffset)
+        .accountsPartial({
+          callbackAuth: callbackAuthPda,
+          payer: payer.publicKey,
+          orderBook: orderBookPda,
+          buyerOrder,
+          sellerOrder,
+          ...accounts,
+        })
+        .signers([payer])
+        .rpc();
+    }
+
+    function requestVolumeReveal(payer?: Keypair) {
+      const { computationOffset, accounts } = computationAccounts("reveal_period_volume");
+      const builder = program.methods.requestVolumeReveal(computationOffset).accountsPartial({
+        payer: payer ? payer.publicKey : provider.wallet.publicKey,
+        orderBook: orderBookPda,
+        ...accounts,
+      });
+      return payer ? builder.signers([payer]).rpc() : builder.rpc();
+    }
+
+    it("Registers the volume computation definitions", async () => {
+      // Definitions are per program, so a previous run may have registered them
+      for (const [name, init] of [
+        ["update_volume", () => program.methods.initUpdateVolumeCompDef()],
+        ["reveal_period_volume", () => program.methods.initRevealVolumeCompDef()],
+      ] as const) {
+        const compDefAccount = compDefAddress(name);
+        if (!(await provider.connection.getAccountInfo(compDefAccount))) {
+          await init()
+            .accountsPartial({
+              payer: provider.wallet.publicKey,
+              orderBook: orderBookPda,
+              mxeAccount: getMXEAccAddress(program.programId),
+              compDefAccount,
+            })
+            .rpc();
+        }
+        assert.isNotNull(await provider.connection.getAccountInfo(compDefAccount), `${name} should be registered`);
+      }
+    });
+
+    it("Rejects a volume update for orders without a recorded fill", async () => {
+      const buyOrder = await placeOrder(userA, userAQuoteAccount, quoteMint);
+      const sellOrder = await placeOrder(userB, userBBaseAccount, baseMint);
+
+      try {
+        await invokeUpdateVolume(buyOrder, sellOrder);
+        assert.fail("Should have failed with FillNotRecorded");
+      } catch (error: any) {
+        assert.include(error.toString(), "FillNotRecorded");
+      }
+    });
+
+    it("Rejects a volume update from someone other than a keeper", async () => {
+      const buyOrder = await placeOrder(userA, userAQuoteAccount, quoteMint);
+      const sellOrder = await placeOrder(userB, userBBaseAccount, baseMint);
+
+      try {
+        await invokeUpdateVolume(buyOrder, sellOrder, userA);
+        assert.fail("Should have failed - userA has no callback auth");
+      } catch (error: any) {
+        assert.include(error.toString(), "ConstraintSeeds");
+      }
+    });
+
+    it("Rejects a volume reveal from someone other than the authority", async () => {
+      try {
+        await requestVolumeReveal(userA);
+        assert.fail("Should have failed with UnauthorizedCallback");
+      } catch (error: any) {
+        assert.include(error.toString(), "UnauthorizedCallback");
+      }
+    });
+
+    it("Rejects a volume reveal before any volume is recorded", async () => {
+      const orderBook = await program.account.orderBook.fetch(orderBookPda);
+      assert.equal(orderBook.encryptedVolumeBase.length, 0, "No fill has been counted on this book");
+
+      try {
+        await requestVolumeReveal();
+        assert.fail("Should have failed with NoVolumeToReveal");
+      } catch (error: any) {
+        assert.include(error.toString(), "NoVolumeToReveal");
+      }
+    });
+  });
 });
 
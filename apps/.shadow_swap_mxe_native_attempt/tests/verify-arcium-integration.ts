@@ -342,6 +342,8 @@ async function test7_MockMatchCallback(
         sellerOrderId: new anchor.BN(2),
         encryptedAmount: Array(64).fill(0),
         encryptedPrice: Array(64).fill(0),
+        buyerEncryptedRemaining: Array(32).fill(0),
+        sellerEncryptedRemaining: Array(32).fill(0),
       },
     ];
     
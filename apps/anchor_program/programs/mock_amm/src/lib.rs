@@ -0,0 +1,189 @@
+//! Constant-rate mock AMM for local fallback tests
+//!
+//! Implements the swap-program interface `shadow_swap::amm::SwapInterface`
+//! adapts to: `swap(amount_in, min_amount_out)` with accounts
+//! `[source, destination, authority, token_program, ..pool accounts]`.
+//! Pools trade their two mints at a fixed rate from their own vaults.
+
+// The #[program] macro expands to the deprecated `AccountInfo::realloc`
+#![allow(deprecated)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+declare_id!("8NW8dXQQ3smtPqgKuP3MgMqSBX3KVKkMRuc5gHD6idgA");
+
+#[program]
+pub mod mock_amm {
+    use super::*;
+
+    /// Create a pool paying `rate_numerator / rate_denominator` of mint B per
+    /// unit of mint A (and the inverse the other way)
+    pub fn initialize_pool(
+        ctx: Context<InitializePool>,
+        rate_numerator: u64,
+        rate_denominator: u64,
+    ) -> Result<()> {
+        require!(
+            rate_numerator > 0 && rate_denominator > 0,
+            MockAmmError::InvalidRate
+        );
+
+        let pool = &mut ctx.accounts.pool;
+        pool.mint_a = ctx.accounts.mint_a.key();
+        pool.mint_b = ctx.accounts.mint_b.key();
+        pool.rate_numerator = rate_numerator;
+        pool.rate_denominator = rate_denominator;
+        pool.bump = ctx.bumps.pool;
+        Ok(())
+    }
+
+    /// Swap `amount_in` of the source token for the pool's other token
+    pub fn swap(ctx: Context<Swap>, amount_in: u64, min_amount_out: u64) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        let (numerator, denominator) = if ctx.accounts.source.mint == pool.mint_a {
+            (pool.rate_numerator, pool.rate_denominator)
+        } else {
+            (pool.rate_denominator, pool.rate_numerator)
+        };
+        let amount_out = u64::try_from(
+            (amount_in as u128) * (numerator as u128) / (denominator as u128),
+        )
+        .map_err(|_| error!(MockAmmError::NumericalOverflow))?;
+        require!(amount_out >= min_amount_out, MockAmmError::SlippageExceeded);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.source.to_account_info(),
+                    to: ctx.accounts.input_vault.to_account_info(),
+                    authority: ctx.accounts.authority.to_account_info(),
+                },
+            ),
+            amount_in,
+        )?;
+
+        let seeds = &[
+            POOL_SEED,
+            pool.mint_a.as_ref(),
+            pool.mint_b.as_ref(),
+            &[pool.bump],
+        ];
+        let signer = &[&seeds[..]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.output_vault.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.pool.to_account_info(),
+                },
+                signer,
+            ),
+            amount_out,
+        )?;
+
+        msg!("Mock swap: {} in, {} out", amount_in, amount_out);
+        Ok(())
+    }
+}
+
+#[account]
+pub struct Pool {
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub rate_numerator: u64,
+    pub rate_denominator: u64,
+    pub bump: u8,
+}
+
+#[error_code]
+pub enum MockAmmError {
+    #[msg("Invalid pool rate")]
+    InvalidRate,
+
+    #[msg("Output below minimum")]
+    SlippageExceeded,
+
+    #[msg("Numerical overflow")]
+    NumericalOverflow,
+
+    #[msg("Token account does not belong to the pool")]
+    InvalidVault,
+}
+
+pub const POOL_SEED: &[u8] = b"pool";
+pub const POOL_VAULT_SEED: &[u8] = b"pool_vault";
+
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<Pool>(),
+        seeds = [POOL_SEED, mint_a.key().as_ref(), mint_b.key().as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        init,
+        payer = payer,
+        token::mint = mint_a,
+        token::authority = pool,
+        seeds = [POOL_VAULT_SEED, pool.key().as_ref(), mint_a.key().as_ref()],
+        bump
+    )]
+    pub vault_a: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        token::mint = mint_b,
+        token::authority = pool,
+        seeds = [POOL_VAULT_SEED, pool.key().as_ref(), mint_b.key().as_ref()],
+        bump
+    )]
+    pub vault_b: Account<'info, TokenAccount>,
+
+    pub mint_a: Account<'info, Mint>,
+    pub mint_b: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Account order is the adapter interface; pool accounts follow
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    #[account(mut)]
+    pub source: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub destination: Account<'info, TokenAccount>,
+
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+
+    pub pool: Account<'info, Pool>,
+
+    #[account(
+        mut,
+        seeds = [POOL_VAULT_SEED, pool.key().as_ref(), source.mint.as_ref()],
+        bump
+    )]
+    pub input_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [POOL_VAULT_SEED, pool.key().as_ref(), destination.mint.as_ref()],
+        bump,
+        constraint = input_vault.key() != output_vault.key() @ MockAmmError::InvalidVault
+    )]
+    pub output_vault: Account<'info, TokenAccount>,
+}
@@ -0,0 +1,93 @@
+//! AMM adapter for fallback execution
+//!
+//! `fallback_swap` executes an unmatched order against public liquidity by
+//! CPI into the swap program configured on its order book. Adapters implement
+//! `AmmAdapter` for a specific program interface; `SwapInterface` handles
+//! programs exposing the generic swap instruction (including the local
+//! `mock_amm` used in tests):
+//!
+//! - data: 8-byte Anchor discriminator of `swap`, then Borsh
+//!   `amount_in: u64, min_amount_out: u64`
+//! - accounts: `[source (w), destination (w), authority (s), token_program,
+//!   ..pool accounts]`, where pool accounts are passed through unchanged
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+
+/// Token accounts taking part in one adapter swap
+pub struct AmmSwapAccounts<'a, 'info> {
+    /// Swap program to invoke
+    pub amm_program: &'a AccountInfo<'info>,
+    /// Token account the input is taken from
+    pub source: &'a AccountInfo<'info>,
+    /// Token account the output is paid to
+    pub destination: &'a AccountInfo<'info>,
+    /// Owner of `source`; signs through `signer_seeds`
+    pub authority: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    /// Program-specific pool accounts
+    pub pool_accounts: &'a [AccountInfo<'info>],
+}
+
+/// Swaps through a specific AMM program interface
+pub trait AmmAdapter {
+    fn swap(
+        accounts: &AmmSwapAccounts,
+        amount_in: u64,
+        min_amount_out: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()>;
+}
+
+/// Generic swap-program interface
+pub struct SwapInterface;
+
+impl SwapInterface {
+    /// `sha256("global:swap")[..8]`
+    const SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+}
+
+impl AmmAdapter for SwapInterface {
+    fn swap(
+        accounts: &AmmSwapAccounts,
+        amount_in: u64,
+        min_amount_out: u64,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let mut data = Vec::with_capacity(24);
+        data.extend_from_slice(&Self::SWAP_DISCRIMINATOR);
+        data.extend_from_slice(&amount_in.to_le_bytes());
+        data.extend_from_slice(&min_amount_out.to_le_bytes());
+
+        let mut metas = vec![
+            AccountMeta::new(accounts.source.key(), false),
+            AccountMeta::new(accounts.destination.key(), false),
+            AccountMeta::new_readonly(accounts.authority.key(), true),
+            AccountMeta::new_readonly(accounts.token_program.key(), false),
+        ];
+        let mut infos = vec![
+            accounts.source.clone(),
+            accounts.destination.clone(),
+            accounts.authority.clone(),
+            accounts.token_program.clone(),
+        ];
+        for pool_account in accounts.pool_accounts {
+            metas.push(if pool_account.is_writable {
+                AccountMeta::new(pool_account.key(), false)
+            } else {
+                AccountMeta::new_readonly(pool_account.key(), false)
+            });
+            infos.push(pool_account.clone());
+        }
+        infos.push(accounts.amm_program.clone());
+
+        let instruction = Instruction {
+            program_id: accounts.amm_program.key(),
+            accounts: metas,
+            data,
+        };
+        invoke_signed(&instruction, &infos, signer_seeds)?;
+        Ok(())
+    }
+}
@@ -1,5 +1,15 @@
+// The #[program] macro expands to the deprecated `AccountInfo::realloc`
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::hash::hashv;
+use anchor_spl::token::{self, Burn, CloseAccount, Mint, MintTo, Token, TokenAccount, Transfer};
+
+pub mod amm;
+pub mod oracle;
+
+use amm::{AmmAdapter, AmmSwapAccounts, SwapInterface};
+use oracle::{OracleReader, PythPriceAccount};
 
 declare_id!("ESHkd14KmUUJthjVqKoh7JP1oVVMFJCqPPkpsrJrT5Kt");
 
@@ -23,8 +33,8 @@ pub mod shadow_swap {
         order_book.authority = ctx.accounts.authority.key();
         order_book.base_mint = base_mint;
         order_book.quote_mint = quote_mint;
-        order_book.order_count = 0;
-        order_book.active_orders = 0;
+        order_book.order_shards = 1;
+        order_book.closed_orders = 0;
         order_book.encrypted_volume_base = vec![];
         order_book.encrypted_volume_quote = vec![];
         order_book.created_at = clock.unix_timestamp;
@@ -33,8 +43,25 @@ pub mod shadow_swap {
         order_book.fee_collector = ctx.accounts.fee_collector.key();
         order_book.min_base_order_size = min_base_order_size;
         order_book.is_active = true;
+        order_book.oracle = Pubkey::default();
+        order_book.oracle_max_deviation_bps = 0;
+        order_book.oracle_max_staleness = 0;
+        order_book.oracle_price_expo = 0;
+        order_book.fallback_amm = Pubkey::default();
+        order_book.sealed_auction_count = 0;
+        order_book.rfq_count = 0;
+        order_book.live_sealed_auctions = 0;
+        order_book.open_rfqs = 0;
+        order_book.decommissioned_at = 0;
         order_book.bump = ctx.bumps.order_book;
 
+        // Shard 0, so the book takes orders right away
+        let order_shard = &mut ctx.accounts.order_shard;
+        order_shard.order_book = order_book.key();
+        order_shard.shard = 0;
+        order_shard.order_count = 0;
+        order_shard.bump = ctx.bumps.order_shard;
+
         msg!("Order book initialized: {} / {}", base_mint, quote_mint);
         Ok(())
     }
@@ -44,11 +71,29 @@ pub mod shadow_swap {
     /// This is the standard Anchor instruction that accepts encrypted order data
     /// from the client and stores it on-chain. The matching happens off-chain
     /// in the Hybrid architecture.
+    /// 
+    /// `time_in_force` (one of the `TIME_IN_FORCE_*` constants) is the only
+    /// public order parameter; side, price and quantity stay encrypted.
+    /// 
+    /// A session key can place the order for its owner by passing its
+    /// `trading_delegate`: the order belongs to the owner, `posted_amount` must
+    /// be within the delegate's per-order limit and is pulled from the
+    /// owner's token account through the SPL allowance the owner granted the
+    /// `TradingDelegate` PDA.
+    /// 
+    /// The order PDA is seeded by `order_owner` (the owner, also when a
+    /// session key signs) and its `client_order_id`, so retrying a submission
+    /// with the same ID fails instead of placing a second order, whichever key
+    /// signs the retry. The order ID comes from `order_shard`, so the `OrderBook`
+    /// is only read and submissions through different shards don't serialize
+    /// on it.
     pub fn submit_encrypted_order(
         ctx: Context<SubmitEncryptedOrder>,
+        client_order_id: [u8; 16],
         cipher_payload: Vec<u8>,
         encrypted_amount: Vec<u8>,
         posted_amount: u64,
+        time_in_force: u8,
     ) -> Result<()> {
         require!(
             ctx.accounts.order_book.is_active,
@@ -62,25 +107,39 @@ pub mod shadow_swap {
             encrypted_amount.len() <= MAX_ENCRYPTED_AMOUNT_SIZE,
             ShadowSwapError::InvalidCipherPayload
         );
+        require!(
+            time_in_force <= TIME_IN_FORCE_POST_ONLY,
+            ShadowSwapError::InvalidTimeInForce
+        );
 
-        let order_book = &mut ctx.accounts.order_book;
+        let clock = Clock::get()?;
+        let order_owner = match &ctx.accounts.trading_delegate {
+            Some(trading_delegate) => {
+                check_trading_delegate(trading_delegate, DELEGATE_SCOPE_PLACE, clock.unix_timestamp)?;
+                let limit = if ctx.accounts.token_mint.key() == ctx.accounts.order_book.quote_mint {
+                    trading_delegate.max_quote_per_order
+                } else {
+                    trading_delegate.max_base_per_order
+                };
+                require!(posted_amount <= limit, ShadowSwapError::DelegateLimitExceeded);
+                require!(
+                    ctx.accounts.user_token_account.owner == trading_delegate.owner,
+                    ShadowSwapError::UnauthorizedDelegate
+                );
+                trading_delegate.owner
+            }
+            None => ctx.accounts.owner.key(),
+        };
+
+        let order_book = &ctx.accounts.order_book;
         let order = &mut ctx.accounts.order;
         let escrow = &mut ctx.accounts.escrow;
-        let clock = Clock::get()?;
 
-        // Assign order ID
-        let order_id = order_book.order_count;
-        order_book.order_count = order_book
-            .order_count
-            .checked_add(1)
-            .ok_or(ShadowSwapError::NumericalOverflow)?;
-        order_book.active_orders = order_book
-            .active_orders
-            .checked_add(1)
-            .ok_or(ShadowSwapError::NumericalOverflow)?;
+        // Assign order ID from the shard; the book itself is not written
+        let order_id = next_order_id(&mut ctx.accounts.order_shard)?;
 
         // Initialize order
-        order.owner = ctx.accounts.owner.key();
+        order.owner = order_owner;
         order.order_book = order_book.key();
         order.cipher_payload = cipher_payload;
         order.status = ORDER_STATUS_ACTIVE;
@@ -89,48 +148,98 @@ pub mod shadow_swap {
         order.created_at = clock.unix_timestamp;
         order.updated_at = clock.unix_timestamp;
         order.order_id = order_id;
+        order.client_order_id = client_order_id;
+        order.time_in_force = time_in_force;
+        order.parent = Pubkey::default();
+        order.user_balance = Pubkey::default();
+        order.route = Pubkey::default();
         order.bump = ctx.bumps.order;
 
         // Initialize escrow
         escrow.order = order.key();
-        escrow.owner = ctx.accounts.owner.key();
+        escrow.owner = order_owner;
         escrow.order_book = order_book.key();
         escrow.token_account = ctx.accounts.escrow_token_account.key();
         escrow.token_mint = ctx.accounts.token_mint.key();
         escrow.encrypted_amount = encrypted_amount.clone();
         escrow.encrypted_remaining = encrypted_amount;
+        escrow.locked_amount = 0;
         escrow.created_at = clock.unix_timestamp;
         escrow.bump = ctx.bumps.escrow;
 
         // Transfer tokens to escrow
         // Note: In production, the amount should be validated against the encrypted_amount
-        token::transfer(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.user_token_account.to_account_info(),
-                    to: ctx.accounts.escrow_token_account.to_account_info(),
-                    authority: ctx.accounts.owner.to_account_info(),
-                },
-            ),
-            posted_amount,
-        )?;
+        match &ctx.accounts.trading_delegate {
+            Some(trading_delegate) => {
+                let seeds = &[
+                    TRADING_DELEGATE_SEED,
+                    trading_delegate.owner.as_ref(),
+                    trading_delegate.delegate.as_ref(),
+                    &[trading_delegate.bump],
+                ];
+                let signer = &[&seeds[..]];
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: ctx.accounts.user_token_account.to_account_info(),
+                            to: ctx.accounts.escrow_token_account.to_account_info(),
+                            authority: trading_delegate.to_account_info(),
+                        },
+                        signer,
+                    ),
+                    posted_amount,
+                )?;
+            }
+            None => {
+                token::transfer(
+                    CpiContext::new(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: ctx.accounts.user_token_account.to_account_info(),
+                            to: ctx.accounts.escrow_token_account.to_account_info(),
+                            authority: ctx.accounts.owner.to_account_info(),
+                        },
+                    ),
+                    posted_amount,
+                )?;
+            }
+        }
+
+        emit!(OrderPlaced {
+            order_book: order_book.key(),
+            order: order.key(),
+            owner: order_owner,
+            order_id,
+            client_order_id,
+            time_in_force,
+            timestamp: clock.unix_timestamp,
+        });
 
-        msg!("Encrypted order submitted: ID {}", order_id);
+        msg!("Encrypted order submitted: ID {} (tif {})", order_id, time_in_force);
         Ok(())
     }
 
     /// Cancel an existing order
+    /// 
+    /// Signed by the owner, or by a session key with the cancel scope passing
+    /// its `trading_delegate`. Either way the escrow is refunded to a token
+    /// account of the owner.
     pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
         let order = &mut ctx.accounts.order;
         let escrow = &ctx.accounts.escrow;
         let clock = Clock::get()?;
 
-        // Verify order ownership
-        require!(
-            order.owner == ctx.accounts.owner.key(),
-            ShadowSwapError::UnauthorizedCallback
-        );
+        // Verify order ownership, or a session key allowed to cancel
+        match &ctx.accounts.trading_delegate {
+            Some(trading_delegate) => {
+                check_trading_delegate(trading_delegate, DELEGATE_SCOPE_CANCEL, clock.unix_timestamp)?
+            }
+            None => require!(
+                order.owner == ctx.accounts.owner.key(),
+                ShadowSwapError::UnauthorizedCallback
+            ),
+        }
 
         // Verify order can be cancelled
         require!(
@@ -144,9 +253,9 @@ pub mod shadow_swap {
 
         // Update order book
         let order_book = &mut ctx.accounts.order_book;
-        order_book.active_orders = order_book
-            .active_orders
-            .checked_sub(1)
+        order_book.closed_orders = order_book
+            .closed_orders
+            .checked_add(1)
             .ok_or(ShadowSwapError::NumericalOverflow)?;
 
         // Return funds from escrow
@@ -171,581 +280,6576 @@ pub mod shadow_swap {
             ctx.accounts.escrow_token_account.amount,
         )?;
 
+        emit!(OrderCancelled {
+            order_book: order_book.key(),
+            order: order_key,
+            owner: order.owner,
+            order_id: order.order_id,
+            client_order_id: order.client_order_id,
+            timestamp: clock.unix_timestamp,
+        });
+
         msg!("Order cancelled: ID {}", order.order_id);
         Ok(())
     }
 
-    /// Create callback authorization for keeper
+    /// Amend an open order in place
     /// 
-    /// This allows the order book authority to authorize a keeper bot
-    /// to submit match results and execute settlements.
-    pub fn create_callback_auth(
-        ctx: Context<CreateCallbackAuth>,
-        expires_at: i64,
+    /// Replaces the encrypted payload and remaining amount, keeping the order
+    /// ID and escrow. `escrow_delta` tops up (> 0) or withdraws (< 0) escrowed
+    /// tokens.
+    /// 
+    /// Priority rule: matching ranks orders by `created_at`, and every
+    /// amendment resets it to now, as if the order were new. The program
+    /// cannot see inside `cipher_payload`, so it cannot tell a pure size-down
+    /// from a price change.
+    pub fn amend_order(
+        ctx: Context<AmendOrder>,
+        cipher_payload: Vec<u8>,
+        encrypted_remaining: Vec<u8>,
+        escrow_delta: i64,
     ) -> Result<()> {
-        let callback_auth = &mut ctx.accounts.callback_auth;
+        require!(
+            cipher_payload.len() <= MAX_CIPHER_PAYLOAD_SIZE,
+            ShadowSwapError::InvalidCipherPayload
+        );
+        require!(
+            encrypted_remaining.len() <= MAX_ENCRYPTED_AMOUNT_SIZE,
+            ShadowSwapError::InvalidCipherPayload
+        );
+
         let clock = Clock::get()?;
+        let delta = escrow_delta.unsigned_abs();
+
+        if escrow_delta > 0 {
+            // Top up escrow from the owner
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.user_token_account.to_account_info(),
+                        to: ctx.accounts.escrow_token_account.to_account_info(),
+                        authority: ctx.accounts.owner.to_account_info(),
+                    },
+                ),
+                delta,
+            )?;
+        } else if escrow_delta < 0 {
+            // Withdraw part of the escrow back to the owner
+            require!(
+                delta <= ctx.accounts.escrow_token_account.amount,
+                ShadowSwapError::InsufficientEscrowFunds
+            );
+
+            let order_key = ctx.accounts.order.key();
+            let seeds = &[
+                ESCROW_SEED,
+                order_key.as_ref(),
+                &[ctx.accounts.escrow.bump],
+            ];
+            let signer = &[&seeds[..]];
+
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.escrow_token_account.to_account_info(),
+                        to: ctx.accounts.user_token_account.to_account_info(),
+                        authority: ctx.accounts.escrow.to_account_info(),
+                    },
+                    signer,
+                ),
+                delta,
+            )?;
+        }
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.encrypted_remaining = encrypted_remaining.clone();
+
+        let order = &mut ctx.accounts.order;
+        order.cipher_payload = cipher_payload;
+        order.encrypted_remaining = encrypted_remaining;
+        order.updated_at = clock.unix_timestamp;
+        order.created_at = clock.unix_timestamp;
+
+        emit!(OrderAmended {
+            order_book: order.order_book,
+            order: order.key(),
+            owner: order.owner,
+            order_id: order.order_id,
+            client_order_id: order.client_order_id,
+            escrow_delta,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Order amended: ID {}", order.order_id);
+        Ok(())
+    }
 
+    /// Expire an IOC/FOK order that was not matched in the last round
+    /// 
+    /// Called by the keeper after each matching round. The order is cancelled
+    /// and its escrow refunded to the owner.
+    pub fn expire_immediate_order(ctx: Context<ExpireImmediateOrder>) -> Result<()> {
+        let clock = Clock::get()?;
         require!(
-            expires_at > clock.unix_timestamp,
+            ctx.accounts.callback_auth.expires_at > clock.unix_timestamp,
             ShadowSwapError::CallbackAuthExpired
         );
 
-        callback_auth.authority = ctx.accounts.keeper.key();
-        callback_auth.order_book = ctx.accounts.order_book.key();
-        callback_auth.nonce = 0;
-        callback_auth.expires_at = expires_at;
-        callback_auth.is_active = true;
-        callback_auth.created_at = clock.unix_timestamp;
-        callback_auth.bump = ctx.bumps.callback_auth;
+        let order = &mut ctx.accounts.order;
+        order.status = ORDER_STATUS_CANCELLED;
+        order.updated_at = clock.unix_timestamp;
 
-        msg!("Callback auth created for keeper: {}", ctx.accounts.keeper.key());
+        let order_book = &mut ctx.accounts.order_book;
+        order_book.closed_orders = order_book
+            .closed_orders
+            .checked_add(1)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+
+        let order_key = order.key();
+        let seeds = &[
+            ESCROW_SEED,
+            order_key.as_ref(),
+            &[ctx.accounts.escrow.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    to: ctx.accounts.owner_token_account.to_account_info(),
+                    authority: ctx.accounts.escrow.to_account_info(),
+                },
+                signer,
+            ),
+            ctx.accounts.escrow_token_account.amount,
+        )?;
+
+        emit!(OrderCancelled {
+            order_book: order_book.key(),
+            order: order_key,
+            owner: order.owner,
+            order_id: order.order_id,
+            client_order_id: order.client_order_id,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Immediate order expired: ID {}", order.order_id);
         Ok(())
     }
 
-    /// Submit match results and execute settlement
+    /// Create a TWAP / iceberg parent order
     /// 
-    /// This instruction is called by the authorized keeper bot after matching orders
-    /// off-chain. It performs the actual token transfers to settle the trade.
+    /// The full `schedule.total_amount` is escrowed by the parent, which
+    /// releases child orders of about `slice_size` tokens every
+    /// `release_interval` seconds through `release_child_order`. With
+    /// `slice_jitter_bps > 0` each slice is randomized within +/- that many
+    /// basis points of `slice_size`, so the escrow footprint of the children
+    /// does not reveal a fixed pattern.
     /// 
-    /// Flow:
-    /// 1. Verify keeper authorization via callback_auth
-    /// 2. Calculate transfer amounts based on matched_amount and execution_price
-    /// 3. Transfer quote tokens (USDC) from buyer's escrow to seller
-    /// 4. Transfer base tokens (WSOL) from seller's escrow to buyer
-    /// 5. Update order statuses to "Executed"
-    /// 6. Emit settlement event
-    pub fn submit_match_results<'info>(
-        ctx: Context<'_, '_, '_, 'info, SubmitMatchResults<'info>>,
-        match_input: MatchResultInput,
+    /// Children share the parent's `cipher_payload` but carry no encrypted
+    /// amount of their own: a child's size is the slice its escrow holds, and
+    /// settlement caps its fills and reported remaining at that slice.
+    pub fn create_parent_order(
+        ctx: Context<CreateParentOrder>,
+        parent_nonce: u64,
+        cipher_payload: Vec<u8>,
+        encrypted_amount: Vec<u8>,
+        schedule: ParentOrderSchedule,
     ) -> Result<()> {
-        let callback_auth = &ctx.accounts.callback_auth;
-        let clock = Clock::get()?;
-
-        // Verify callback authorization
         require!(
-            callback_auth.is_active,
-            ShadowSwapError::UnauthorizedCallback
+            cipher_payload.len() <= MAX_CIPHER_PAYLOAD_SIZE,
+            ShadowSwapError::InvalidCipherPayload
         );
         require!(
-            callback_auth.expires_at > clock.unix_timestamp,
-            ShadowSwapError::CallbackAuthExpired
+            encrypted_amount.len() <= MAX_ENCRYPTED_AMOUNT_SIZE,
+            ShadowSwapError::InvalidCipherPayload
         );
+        let ParentOrderSchedule {
+            total_amount,
+            slice_size,
+            slice_jitter_bps,
+            release_interval,
+        } = schedule;
         require!(
-            callback_auth.authority == ctx.accounts.keeper.key(),
-            ShadowSwapError::UnauthorizedCallback
+            slice_size > 0
+                && slice_size <= total_amount
+                && slice_jitter_bps < 10000
+                && release_interval >= 0,
+            ShadowSwapError::InvalidParentOrder
         );
 
-        let buyer_order = &mut ctx.accounts.buyer_order;
-        let seller_order = &mut ctx.accounts.seller_order;
+        let clock = Clock::get()?;
+        let parent = &mut ctx.accounts.parent_order;
+        parent.owner = ctx.accounts.owner.key();
+        parent.order_book = ctx.accounts.order_book.key();
+        parent.token_mint = ctx.accounts.token_mint.key();
+        parent.escrow_token_account = ctx.accounts.parent_escrow_token_account.key();
+        parent.cipher_payload = cipher_payload;
+        parent.encrypted_amount = encrypted_amount;
+        parent.total_amount = total_amount;
+        parent.released_amount = 0;
+        parent.slice_size = slice_size;
+        parent.slice_jitter_bps = slice_jitter_bps;
+        parent.release_interval = release_interval;
+        parent.next_release_at = clock.unix_timestamp;
+        parent.child_count = 0;
+        parent.status = PARENT_STATUS_ACTIVE;
+        parent.parent_nonce = parent_nonce;
+        parent.created_at = clock.unix_timestamp;
+        parent.bump = ctx.bumps.parent_order;
 
-        // Verify orders are active or partially filled
-        require!(
-            buyer_order.status == ORDER_STATUS_ACTIVE || buyer_order.status == ORDER_STATUS_PARTIAL,
-            ShadowSwapError::InvalidOrderStatus
-        );
-        require!(
-            seller_order.status == ORDER_STATUS_ACTIVE || seller_order.status == ORDER_STATUS_PARTIAL,
-            ShadowSwapError::InvalidOrderStatus
-        );
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user_token_account.to_account_info(),
+                    to: ctx.accounts.parent_escrow_token_account.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            total_amount,
+        )?;
 
-        // Parse remaining_accounts
-        // Expected order: [buyer_escrow_token, seller_escrow_token, buyer_token, seller_token]
-        require!(
-            ctx.remaining_accounts.len() == 4,
-            ShadowSwapError::InvalidTokenMint
+        msg!(
+            "Parent order created: {} in slices of {} every {}s",
+            total_amount,
+            slice_size,
+            release_interval
         );
-        
-        let buyer_escrow_token_info = ctx.remaining_accounts[0].clone();
-        let seller_escrow_token_info = ctx.remaining_accounts[1].clone();
-        let buyer_token_info = ctx.remaining_accounts[2].clone();
-        let seller_token_info = ctx.remaining_accounts[3].clone();
+        Ok(())
+    }
+
+    /// Release the next child order of a parent order
+    /// 
+    /// Permissionless crank, callable once the parent's release time has
+    /// passed. The child is an ordinary GTC `EncryptedOrder` owned by the
+    /// parent's owner, escrowing only its slice; the caller pays its rent.
+    /// Its PDA is seeded by the parent and the child's index. The parent's
+    /// encrypted amount covers the whole order, so the child's encrypted
+    /// remaining is left empty and its escrow stands in for it.
+    pub fn release_child_order(ctx: Context<ReleaseChildOrder>) -> Result<()> {
+        let clock = Clock::get()?;
+        let parent_key = ctx.accounts.parent_order.key();
+        let parent = &ctx.accounts.parent_order;
 
-        // Verify token accounts match escrow expectations
-        require!(
-            buyer_escrow_token_info.key() == ctx.accounts.buyer_escrow.token_account,
-            ShadowSwapError::InvalidEscrow
-        );
         require!(
-            seller_escrow_token_info.key() == ctx.accounts.seller_escrow.token_account,
-            ShadowSwapError::InvalidEscrow
+            clock.unix_timestamp >= parent.next_release_at,
+            ShadowSwapError::ReleaseNotDue
         );
 
-        // Calculate transfer amounts
-        // matched_amount is in base token (WSOL) smallest units (lamports)
-        // execution_price is quote tokens (USDC) smallest units per base token
-        // Convert by dividing by base decimals factor so quote_amount is in quote smallest units
-        let quote_amount_u128 = (match_input.matched_amount as u128)
-            .checked_mul(match_input.execution_price as u128)
-            .ok_or(ShadowSwapError::NumericalOverflow)?
-            .checked_div(BASE_DECIMALS_FACTOR)
+        let unreleased = parent
+            .total_amount
+            .checked_sub(parent.released_amount)
             .ok_or(ShadowSwapError::NumericalOverflow)?;
+        let slice = next_slice_amount(parent, &parent_key, clock.slot)?.min(unreleased);
 
-        let quote_amount = u64::try_from(quote_amount_u128)
-            .map_err(|_| ShadowSwapError::NumericalOverflow)?;
+        let order_book = &ctx.accounts.order_book;
+        let order = &mut ctx.accounts.order;
+        let escrow = &mut ctx.accounts.escrow;
 
-        msg!(
-            "Settling match: buyer={}, seller={}, amount={}, price={}, quote_total={}",
-            match_input.buyer_pubkey,
-            match_input.seller_pubkey,
-            match_input.matched_amount,
-            match_input.execution_price,
-            quote_amount
-        );
+        let order_id = next_order_id(&mut ctx.accounts.order_shard)?;
+        let client_order_id = child_client_order_id(parent.child_count);
 
-        // Transfer quote tokens (USDC) from buyer's escrow to seller
-        let buyer_order_key = buyer_order.key();
-        let buyer_escrow_seeds = &[
-            ESCROW_SEED,
-            buyer_order_key.as_ref(),
-            &[ctx.accounts.buyer_escrow.bump],
+        order.owner = parent.owner;
+        order.order_book = order_book.key();
+        order.cipher_payload = parent.cipher_payload.clone();
+        order.status = ORDER_STATUS_ACTIVE;
+        order.encrypted_remaining = Vec::new();
+        order.escrow = escrow.key();
+        order.created_at = clock.unix_timestamp;
+        order.updated_at = clock.unix_timestamp;
+        order.order_id = order_id;
+        order.client_order_id = client_order_id;
+        order.time_in_force = TIME_IN_FORCE_GTC;
+        order.parent = parent_key;
+        order.user_balance = Pubkey::default();
+        order.route = Pubkey::default();
+        order.bump = ctx.bumps.order;
+
+        escrow.order = order.key();
+        escrow.owner = parent.owner;
+        escrow.order_book = order_book.key();
+        escrow.token_account = ctx.accounts.escrow_token_account.key();
+        escrow.token_mint = parent.token_mint;
+        escrow.encrypted_amount = Vec::new();
+        escrow.encrypted_remaining = Vec::new();
+        escrow.locked_amount = 0;
+        escrow.created_at = clock.unix_timestamp;
+        escrow.bump = ctx.bumps.escrow;
+
+        // Move the slice from the parent's escrow into the child's
+        let order_book_key = order_book.key();
+        let owner_key = parent.owner;
+        let nonce_bytes = parent.parent_nonce.to_le_bytes();
+        let seeds = &[
+            PARENT_ORDER_SEED,
+            order_book_key.as_ref(),
+            owner_key.as_ref(),
+            nonce_bytes.as_ref(),
+            &[parent.bump],
         ];
-        let buyer_escrow_signer = &[&buyer_escrow_seeds[..]];
+        let signer = &[&seeds[..]];
 
         token::transfer(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 Transfer {
-                    from: buyer_escrow_token_info,
-                    to: seller_token_info,
-                    authority: ctx.accounts.buyer_escrow.to_account_info(),
+                    from: ctx.accounts.parent_escrow_token_account.to_account_info(),
+                    to: ctx.accounts.escrow_token_account.to_account_info(),
+                    authority: ctx.accounts.parent_order.to_account_info(),
                 },
-                buyer_escrow_signer,
+                signer,
             ),
-            quote_amount,
+            slice,
         )?;
 
-        // Transfer base tokens (WSOL) from seller's escrow to buyer
-        let seller_order_key = seller_order.key();
-        let seller_escrow_seeds = &[
-            ESCROW_SEED,
-            seller_order_key.as_ref(),
-            &[ctx.accounts.seller_escrow.bump],
-        ];
-        let seller_escrow_signer = &[&seller_escrow_seeds[..]];
+        let parent = &mut ctx.accounts.parent_order;
+        parent.released_amount = parent
+            .released_amount
+            .checked_add(slice)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+        parent.child_count = parent
+            .child_count
+            .checked_add(1)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+        parent.next_release_at = clock
+            .unix_timestamp
+            .checked_add(parent.release_interval)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+        if parent.released_amount == parent.total_amount {
+            parent.status = PARENT_STATUS_COMPLETED;
+        }
+
+        emit!(ChildOrderReleased {
+            order_book: order_book_key,
+            parent: parent_key,
+            order: ctx.accounts.order.key(),
+            order_id,
+            client_order_id,
+            amount: slice,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Child order released: ID {} ({} tokens)", order_id, slice);
+        Ok(())
+    }
 
-        let base_amount = u64::try_from(match_input.matched_amount)
-            .map_err(|_| ShadowSwapError::NumericalOverflow)?;
+    /// Cancel a parent order and refund everything not yet released
+    /// 
+    /// Children already on the book are left untouched; cancel them with
+    /// `cancel_order`.
+    pub fn cancel_parent_order(ctx: Context<CancelParentOrder>) -> Result<()> {
+        let parent = &ctx.accounts.parent_order;
+        let order_book_key = parent.order_book;
+        let owner_key = parent.owner;
+        let nonce_bytes = parent.parent_nonce.to_le_bytes();
+        let seeds = &[
+            PARENT_ORDER_SEED,
+            order_book_key.as_ref(),
+            owner_key.as_ref(),
+            nonce_bytes.as_ref(),
+            &[parent.bump],
+        ];
+        let signer = &[&seeds[..]];
 
+        let refund = ctx.accounts.parent_escrow_token_account.amount;
         token::transfer(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 Transfer {
-                    from: seller_escrow_token_info,
-                    to: buyer_token_info,
-                    authority: ctx.accounts.seller_escrow.to_account_info(),
+                    from: ctx.accounts.parent_escrow_token_account.to_account_info(),
+                    to: ctx.accounts.user_token_account.to_account_info(),
+                    authority: ctx.accounts.parent_order.to_account_info(),
                 },
-                seller_escrow_signer,
+                signer,
             ),
-            base_amount,
+            refund,
         )?;
 
-        // Update order statuses to Filled
-        buyer_order.status = ORDER_STATUS_FILLED;
-        buyer_order.updated_at = clock.unix_timestamp;
-
-        seller_order.status = ORDER_STATUS_FILLED;
-        seller_order.updated_at = clock.unix_timestamp;
+        let parent = &mut ctx.accounts.parent_order;
+        parent.status = PARENT_STATUS_CANCELLED;
+
+        msg!(
+            "Parent order cancelled after {} children, {} refunded",
+            parent.child_count,
+            refund
+        );
+        Ok(())
+    }
+
+    /// Create the book's pooled vaults backing user balances
+    ///
+    /// One base and one quote token account, both owned by the order book PDA.
+    /// Must be called by the book authority before balances can be funded.
+    pub fn initialize_book_vaults(ctx: Context<InitializeBookVaults>) -> Result<()> {
+        msg!(
+            "Book vaults initialized: base {}, quote {}",
+            ctx.accounts.base_vault.key(),
+            ctx.accounts.quote_vault.key()
+        );
+        Ok(())
+    }
+
+    /// Open the caller's trading balance on an order book
+    pub fn open_user_balance(ctx: Context<OpenUserBalance>) -> Result<()> {
+        let user_balance = &mut ctx.accounts.user_balance;
+        user_balance.owner = ctx.accounts.owner.key();
+        user_balance.order_book = ctx.accounts.order_book.key();
+        user_balance.base_free = 0;
+        user_balance.base_locked = 0;
+        user_balance.quote_free = 0;
+        user_balance.quote_locked = 0;
+        user_balance.created_at = Clock::get()?.unix_timestamp;
+        user_balance.bump = ctx.bumps.user_balance;
+
+        msg!("User balance opened for {}", user_balance.owner);
+        Ok(())
+    }
+
+    /// Deposit base or quote tokens into the caller's trading balance
+    ///
+    /// The side is taken from the mint of `user_token_account`.
+    pub fn deposit_to_balance(ctx: Context<BalanceTransfer>, amount: u64) -> Result<()> {
+        require!(amount > 0, ShadowSwapError::OrderTooSmall);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user_token_account.to_account_info(),
+                    to: ctx.accounts.book_vault.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let is_quote = ctx.accounts.book_vault.mint == ctx.accounts.order_book.quote_mint;
+        let user_balance = &mut ctx.accounts.user_balance;
+        let free = if is_quote {
+            &mut user_balance.quote_free
+        } else {
+            &mut user_balance.base_free
+        };
+        *free = free
+            .checked_add(amount)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+
+        msg!("Deposited {} (quote: {})", amount, is_quote);
+        Ok(())
+    }
+
+    /// Withdraw tokens from the caller's trading balance
+    ///
+    /// Only the free part can be withdrawn; amounts locked by open orders stay
+    /// in the vault until those orders fill or are cancelled.
+    pub fn withdraw_from_balance(ctx: Context<BalanceTransfer>, amount: u64) -> Result<()> {
+        require!(amount > 0, ShadowSwapError::OrderTooSmall);
+
+        let is_quote = ctx.accounts.book_vault.mint == ctx.accounts.order_book.quote_mint;
+        let user_balance = &mut ctx.accounts.user_balance;
+        let free = if is_quote {
+            &mut user_balance.quote_free
+        } else {
+            &mut user_balance.base_free
+        };
+        *free = free
+            .checked_sub(amount)
+            .ok_or(ShadowSwapError::InsufficientFreeBalance)?;
+
+        let order_book = &ctx.accounts.order_book;
+        let seeds = &[
+            ORDER_BOOK_SEED,
+            order_book.base_mint.as_ref(),
+            order_book.quote_mint.as_ref(),
+            &[order_book.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.book_vault.to_account_info(),
+                    to: ctx.accounts.user_token_account.to_account_info(),
+                    authority: ctx.accounts.order_book.to_account_info(),
+                },
+                signer,
+            ),
+            amount,
+        )?;
+
+        msg!("Withdrew {} (quote: {})", amount, is_quote);
+        Ok(())
+    }
+
+    /// Submit an encrypted order funded from the caller's trading balance
+    ///
+    /// Instead of a per-order escrow transfer, `locked_amount` moves from free
+    /// to locked in the `UserBalance` (quote for buys, base for sells; chosen
+    /// by `book_vault`). Only GTC and post-only orders can be balance-backed.
+    /// The order PDA and ID are assigned as in `submit_encrypted_order`.
+    pub fn submit_balance_order(
+        ctx: Context<SubmitBalanceOrder>,
+        client_order_id: [u8; 16],
+        cipher_payload: Vec<u8>,
+        encrypted_amount: Vec<u8>,
+        locked_amount: u64,
+        time_in_force: u8,
+    ) -> Result<()> {
+        require!(
+            cipher_payload.len() <= MAX_CIPHER_PAYLOAD_SIZE,
+            ShadowSwapError::InvalidCipherPayload
+        );
+        require!(
+            encrypted_amount.len() <= MAX_ENCRYPTED_AMOUNT_SIZE,
+            ShadowSwapError::InvalidCipherPayload
+        );
+        require!(
+            time_in_force == TIME_IN_FORCE_GTC || time_in_force == TIME_IN_FORCE_POST_ONLY,
+            ShadowSwapError::InvalidTimeInForce
+        );
+        require!(locked_amount > 0, ShadowSwapError::OrderTooSmall);
+
+        let order_book = &ctx.accounts.order_book;
+        let lock_quote = ctx.accounts.book_vault.mint == order_book.quote_mint;
+        lock_balance(&mut ctx.accounts.user_balance, lock_quote, locked_amount)?;
+
+        let order = &mut ctx.accounts.order;
+        let escrow = &mut ctx.accounts.escrow;
+        let clock = Clock::get()?;
+
+        let order_id = next_order_id(&mut ctx.accounts.order_shard)?;
+
+        order.owner = ctx.accounts.owner.key();
+        order.order_book = order_book.key();
+        order.cipher_payload = cipher_payload;
+        order.status = ORDER_STATUS_ACTIVE;
+        order.encrypted_remaining = encrypted_amount.clone();
+        order.escrow = escrow.key();
+        order.created_at = clock.unix_timestamp;
+        order.updated_at = clock.unix_timestamp;
+        order.order_id = order_id;
+        order.client_order_id = client_order_id;
+        order.time_in_force = time_in_force;
+        order.parent = Pubkey::default();
+        order.user_balance = ctx.accounts.user_balance.key();
+        order.route = Pubkey::default();
+        order.bump = ctx.bumps.order;
+
+        // The escrow only records the lock; tokens stay in the book vault
+        escrow.order = order.key();
+        escrow.owner = ctx.accounts.owner.key();
+        escrow.order_book = order_book.key();
+        escrow.token_account = ctx.accounts.book_vault.key();
+        escrow.token_mint = ctx.accounts.book_vault.mint;
+        escrow.encrypted_amount = encrypted_amount.clone();
+        escrow.encrypted_remaining = encrypted_amount;
+        escrow.locked_amount = locked_amount;
+        escrow.created_at = clock.unix_timestamp;
+        escrow.bump = ctx.bumps.escrow;
+
+        emit!(OrderPlaced {
+            order_book: order_book.key(),
+            order: order.key(),
+            owner: order.owner,
+            order_id,
+            client_order_id,
+            time_in_force,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Balance order submitted: ID {} (tif {})", order_id, time_in_force);
+        Ok(())
+    }
+
+    /// Cancel a balance-backed order, returning its lock to the free balance
+    pub fn cancel_balance_order(ctx: Context<CancelBalanceOrder>) -> Result<()> {
+        let clock = Clock::get()?;
+        let order_book = &mut ctx.accounts.order_book;
+        let lock_quote = ctx.accounts.escrow.token_mint == order_book.quote_mint;
+        release_balance_lock(&mut ctx.accounts.user_balance, &mut ctx.accounts.escrow, lock_quote)?;
+
+        let order = &mut ctx.accounts.order;
+        order.status = ORDER_STATUS_CANCELLED;
+        order.updated_at = clock.unix_timestamp;
+
+        order_book.closed_orders = order_book
+            .closed_orders
+            .checked_add(1)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+
+        emit!(OrderCancelled {
+            order_book: order_book.key(),
+            order: order.key(),
+            owner: order.owner,
+            order_id: order.order_id,
+            client_order_id: order.client_order_id,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Balance order cancelled: ID {}", order.order_id);
+        Ok(())
+    }
+
+    /// Settle a match between two balance-backed orders
+    ///
+    /// Same checks as `submit_match_results`, but settlement is pure account
+    /// arithmetic: the buyer's locked quote and the seller's locked base are
+    /// swapped into the counterparties' free balances with no token CPIs.
+    /// An order whose remaining amount reaches zero is filled and any lock it
+    /// has left (e.g. from price improvement) is returned to the free balance;
+    /// otherwise it stays partially filled with the rest still locked.
+    pub fn settle_balance_match(
+        ctx: Context<SettleBalanceMatch>,
+        match_input: MatchResultInput,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            ctx.accounts.callback_auth.expires_at > clock.unix_timestamp,
+            ShadowSwapError::CallbackAuthExpired
+        );
+
+        let order_book = &ctx.accounts.order_book;
+        require!(
+            ctx.accounts.buyer_escrow.token_mint == order_book.quote_mint
+                && ctx.accounts.seller_escrow.token_mint == order_book.base_mint,
+            ShadowSwapError::InvalidTokenMint
+        );
+
+        check_post_only(&ctx.accounts.buyer_order, &ctx.accounts.seller_order)?;
+        check_oracle_band(
+            order_book,
+            ctx.accounts.oracle.as_ref(),
+            match_input.execution_price,
+            clock.unix_timestamp,
+        )?;
+
+        let base_amount = match_input.matched_amount;
+        let quote_amount = quote_amount_for(base_amount, match_input.execution_price)?;
+
+        // Swap the locked amounts into the counterparties' free balances
+        let buyer_balance = &mut ctx.accounts.buyer_balance;
+        let seller_balance = &mut ctx.accounts.seller_balance;
+        let buyer_escrow = &mut ctx.accounts.buyer_escrow;
+        let seller_escrow = &mut ctx.accounts.seller_escrow;
+        consume_lock(buyer_balance, buyer_escrow, true, quote_amount)?;
+        credit_free_balance(buyer_balance, false, base_amount)?;
+        consume_lock(seller_balance, seller_escrow, false, base_amount)?;
+        credit_free_balance(seller_balance, true, quote_amount)?;
+
+        // Close out filled orders
+        let buyer_order = &mut ctx.accounts.buyer_order;
+        let seller_order = &mut ctx.accounts.seller_order;
+        let filled_orders = finish_balance_fill(
+            buyer_order,
+            buyer_balance,
+            buyer_escrow,
+            true,
+            match_input.buyer_remaining,
+            clock.unix_timestamp,
+        )? + finish_balance_fill(
+            seller_order,
+            seller_balance,
+            seller_escrow,
+            false,
+            match_input.seller_remaining,
+            clock.unix_timestamp,
+        )?;
 
-        // Update order book
         let order_book = &mut ctx.accounts.order_book;
-        order_book.active_orders = order_book
-            .active_orders
-            .checked_sub(2) // Both orders are now filled
+        order_book.closed_orders = order_book
+            .closed_orders
+            .checked_add(filled_orders)
             .ok_or(ShadowSwapError::NumericalOverflow)?;
         order_book.last_trade_at = clock.unix_timestamp;
 
-        // Emit settlement event
         emit!(TradeSettled {
             order_book: order_book.key(),
             buyer: match_input.buyer_pubkey,
             seller: match_input.seller_pubkey,
             buyer_order_id: buyer_order.order_id,
             seller_order_id: seller_order.order_id,
-            base_amount: match_input.matched_amount,
+            buyer_client_order_id: buyer_order.client_order_id,
+            seller_client_order_id: seller_order.client_order_id,
+            base_amount,
             quote_amount,
             execution_price: match_input.execution_price,
             timestamp: clock.unix_timestamp,
         });
 
         msg!(
-            "Trade settled: buyer_order={}, seller_order={}, base={}, quote={}",
+            "Balance trade settled: buyer_order={}, seller_order={}, base={}, quote={}",
             buyer_order.order_id,
             seller_order.order_id,
-            match_input.matched_amount,
+            base_amount,
             quote_amount
         );
+        Ok(())
+    }
+
+    /// Route a balance-backed order's proceeds through a second order book
+    ///
+    /// Turns the order into the first leg of a two-hop swap (A→B on the first
+    /// book, B→C on the second). Routed orders only settle through
+    /// `settle_routed_match`, which enforces `min_amount_out` of the output
+    /// token for the order's full locked amount, pro rata on partial fills.
+    /// Submit the order and its route in the same transaction.
+    pub fn create_route(ctx: Context<CreateRoute>, min_amount_out: u64) -> Result<()> {
+        require!(min_amount_out > 0, ShadowSwapError::OrderTooSmall);
+
+        let first_book = &ctx.accounts.first_order_book;
+        let second_book = &ctx.accounts.second_order_book;
+        let input_mint = ctx.accounts.escrow.token_mint;
+        let intermediate_mint = if input_mint == first_book.base_mint {
+            first_book.quote_mint
+        } else {
+            first_book.base_mint
+        };
+        let output_mint = if second_book.base_mint == intermediate_mint {
+            second_book.quote_mint
+        } else {
+            second_book.base_mint
+        };
+        require!(
+            (second_book.base_mint == intermediate_mint || second_book.quote_mint == intermediate_mint)
+                && output_mint != input_mint,
+            ShadowSwapError::InvalidRoute
+        );
+
+        let route = &mut ctx.accounts.route;
+        route.owner = ctx.accounts.owner.key();
+        route.order = ctx.accounts.order.key();
+        route.first_order_book = first_book.key();
+        route.second_order_book = second_book.key();
+        route.intermediate_mint = intermediate_mint;
+        route.output_mint = output_mint;
+        route.amount_in = ctx.accounts.escrow.locked_amount;
+        route.min_amount_out = min_amount_out;
+        route.created_at = Clock::get()?.unix_timestamp;
+        route.bump = ctx.bumps.route;
+
+        let order = &mut ctx.accounts.order;
+        order.route = route.key();
+
+        msg!(
+            "Route created for order {}: min out {} of {}",
+            order.order_id,
+            min_amount_out,
+            output_mint
+        );
+        Ok(())
+    }
+
+    /// Settle both legs of a routed order atomically
+    ///
+    /// Leg one fills the routed order against a resting balance-backed order
+    /// on the first book; the intermediate tokens it buys move from the first
+    /// book's vault to the second's and fill a resting order there, and the
+    /// output is credited to the owner's balance on the second book. Any
+    /// intermediate amount not spent by leg two is credited to the owner's
+    /// balance on the first book. Fails unless the output meets the route's
+    /// minimum for the input actually spent.
+    pub fn settle_routed_match(
+        ctx: Context<SettleRoutedMatch>,
+        route_input: RoutedMatchInput,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            ctx.accounts.first_callback_auth.expires_at > clock.unix_timestamp
+                && ctx.accounts.second_callback_auth.expires_at > clock.unix_timestamp,
+            ShadowSwapError::CallbackAuthExpired
+        );
+
+        let route = &ctx.accounts.route;
+        let first_book = &ctx.accounts.first_order_book;
+        let second_book = &ctx.accounts.second_order_book;
+
+        // The routed order pays the input token; the first counterparty pays
+        // the intermediate token and the second pays the output token
+        let user_sells_first = ctx.accounts.order_escrow.token_mint == first_book.base_mint;
+        let user_sells_second = route.intermediate_mint == second_book.base_mint;
+        require!(
+            ctx.accounts.first_counter_escrow.token_mint == route.intermediate_mint
+                && ctx.accounts.second_counter_escrow.token_mint == route.output_mint,
+            ShadowSwapError::InvalidTokenMint
+        );
+
+        if user_sells_first {
+            check_post_only(&ctx.accounts.first_counter_order, &ctx.accounts.order)?;
+        } else {
+            check_post_only(&ctx.accounts.order, &ctx.accounts.first_counter_order)?;
+        }
+        check_oracle_band(
+            first_book,
+            ctx.accounts.first_oracle.as_ref(),
+            route_input.first_execution_price,
+            clock.unix_timestamp,
+        )?;
+        check_oracle_band(
+            second_book,
+            ctx.accounts.second_oracle.as_ref(),
+            route_input.second_execution_price,
+            clock.unix_timestamp,
+        )?;
+
+        let first_quote = quote_amount_for(route_input.first_matched_amount, route_input.first_execution_price)?;
+        let (amount_in, intermediate_out) = if user_sells_first {
+            (route_input.first_matched_amount, first_quote)
+        } else {
+            (first_quote, route_input.first_matched_amount)
+        };
+        let second_quote = quote_amount_for(route_input.second_matched_amount, route_input.second_execution_price)?;
+        let (intermediate_in, amount_out) = if user_sells_second {
+            (route_input.second_matched_amount, second_quote)
+        } else {
+            (second_quote, route_input.second_matched_amount)
+        };
+        require!(
+            intermediate_in > 0 && intermediate_in <= intermediate_out,
+            ShadowSwapError::RouteLegMismatch
+        );
+
+        // amount_out / amount_in >= min_amount_out / route.amount_in
+        require!(
+            (amount_out as u128) * (route.amount_in as u128)
+                >= (route.min_amount_out as u128) * (amount_in as u128),
+            ShadowSwapError::SlippageExceeded
+        );
+
+        // Leg one: arithmetic on the first book's balances
+        let user_first_balance = &mut ctx.accounts.user_first_balance;
+        let order_escrow = &mut ctx.accounts.order_escrow;
+        consume_lock(user_first_balance, order_escrow, !user_sells_first, amount_in)?;
+        credit_free_balance(user_first_balance, user_sells_first, intermediate_out - intermediate_in)?;
+
+        let first_counter_balance = &mut ctx.accounts.first_counter_balance;
+        let first_counter_escrow = &mut ctx.accounts.first_counter_escrow;
+        consume_lock(first_counter_balance, first_counter_escrow, user_sells_first, intermediate_out)?;
+        credit_free_balance(first_counter_balance, !user_sells_first, amount_in)?;
+
+        // Pass the intermediate tokens through to the second book's vault
+        let seeds = &[
+            ORDER_BOOK_SEED,
+            first_book.base_mint.as_ref(),
+            first_book.quote_mint.as_ref(),
+            &[first_book.bump],
+        ];
+        let signer = &[&seeds[..]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.first_intermediate_vault.to_account_info(),
+                    to: ctx.accounts.second_intermediate_vault.to_account_info(),
+                    authority: ctx.accounts.first_order_book.to_account_info(),
+                },
+                signer,
+            ),
+            intermediate_in,
+        )?;
+
+        // Leg two: arithmetic on the second book's balances
+        let second_counter_balance = &mut ctx.accounts.second_counter_balance;
+        let second_counter_escrow = &mut ctx.accounts.second_counter_escrow;
+        consume_lock(second_counter_balance, second_counter_escrow, user_sells_second, amount_out)?;
+        credit_free_balance(second_counter_balance, !user_sells_second, intermediate_in)?;
+        credit_free_balance(&mut ctx.accounts.user_second_balance, user_sells_second, amount_out)?;
+
+        // Close out filled orders
+        let order = &mut ctx.accounts.order;
+        let first_counter_order = &mut ctx.accounts.first_counter_order;
+        let first_filled = finish_balance_fill(
+            order,
+            user_first_balance,
+            order_escrow,
+            !user_sells_first,
+            route_input.order_remaining,
+            clock.unix_timestamp,
+        )? + finish_balance_fill(
+            first_counter_order,
+            first_counter_balance,
+            first_counter_escrow,
+            user_sells_first,
+            route_input.first_counter_remaining,
+            clock.unix_timestamp,
+        )?;
+        let second_counter_order = &mut ctx.accounts.second_counter_order;
+        let second_filled = finish_balance_fill(
+            second_counter_order,
+            second_counter_balance,
+            second_counter_escrow,
+            user_sells_second,
+            route_input.second_counter_remaining,
+            clock.unix_timestamp,
+        )?;
+
+        let first_book = &mut ctx.accounts.first_order_book;
+        first_book.closed_orders = first_book
+            .closed_orders
+            .checked_add(first_filled)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+        first_book.last_trade_at = clock.unix_timestamp;
+
+        let second_book = &mut ctx.accounts.second_order_book;
+        second_book.closed_orders = second_book
+            .closed_orders
+            .checked_add(second_filled)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+        second_book.last_trade_at = clock.unix_timestamp;
+
+        emit!(RouteSettled {
+            route: ctx.accounts.route.key(),
+            owner: ctx.accounts.route.owner,
+            order_id: order.order_id,
+            client_order_id: order.client_order_id,
+            first_order_book: first_book.key(),
+            second_order_book: second_book.key(),
+            amount_in,
+            intermediate_amount: intermediate_in,
+            amount_out,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!(
+            "Routed trade settled: order={}, in={}, via={}, out={}",
+            order.order_id,
+            amount_in,
+            intermediate_in,
+            amount_out
+        );
+        Ok(())
+    }
+
+    /// Set the swap program `fallback_swap` executes through
+    /// 
+    /// The program must expose the `amm::SwapInterface` instruction;
+    /// `Pubkey::default()` disables fallback execution on this book.
+    pub fn configure_fallback_amm(ctx: Context<ConfigureFallbackAmm>, amm_program: Pubkey) -> Result<()> {
+        ctx.accounts.order_book.fallback_amm = amm_program;
+
+        msg!("Fallback AMM configured: {}", amm_program);
+        Ok(())
+    }
+
+    /// Opt an escrow-funded order into AMM fallback execution
+    /// 
+    /// After `deadline` the owner or an authorized keeper can swap the whole
+    /// escrow against public liquidity with `fallback_swap`.
+    /// `limit_commitment` is `sha256(limit_price as u64 LE || salt)`; the limit
+    /// stays private until the fallback reveals it.
+    pub fn set_fallback(
+        ctx: Context<SetFallback>,
+        deadline: i64,
+        limit_commitment: [u8; 32],
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(deadline > clock.unix_timestamp, ShadowSwapError::InvalidFallback);
+
+        let fallback = &mut ctx.accounts.fallback;
+        fallback.order = ctx.accounts.order.key();
+        fallback.owner = ctx.accounts.owner.key();
+        fallback.deadline = deadline;
+        fallback.limit_commitment = limit_commitment;
+        fallback.created_at = clock.unix_timestamp;
+        fallback.bump = ctx.bumps.fallback;
+
+        msg!("Fallback set for order {}: deadline {}", ctx.accounts.order.order_id, deadline);
+        Ok(())
+    }
+
+    /// Execute an unmatched order against the book's fallback AMM
+    /// 
+    /// Callable by the owner or a keeper authorized on the book once the
+    /// order's fallback deadline has passed. The caller reveals the order's
+    /// limit price (checked against the commitment), which sets the minimum
+    /// output for the whole escrow; the owner's token account must receive at
+    /// least that much or the swap fails. The order is then filled.
+    pub fn fallback_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FallbackSwap<'info>>,
+        limit_price: u64,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp >= ctx.accounts.fallback.deadline,
+            ShadowSwapError::FallbackNotDue
+        );
+
+        // Only the owner or a keeper authorized on this book
+        let caller = ctx.accounts.caller.key();
+        if caller != ctx.accounts.order.owner {
+            let callback_auth = ctx
+                .accounts
+                .callback_auth
+                .as_ref()
+                .ok_or(ShadowSwapError::UnauthorizedCallback)?;
+            require!(
+                callback_auth.is_active
+                    && callback_auth.authority == caller
+                    && callback_auth.expires_at > clock.unix_timestamp,
+                ShadowSwapError::UnauthorizedCallback
+            );
+        }
+
+        require!(
+            limit_price > 0
+                && fallback_commitment(limit_price, &salt) == ctx.accounts.fallback.limit_commitment,
+            ShadowSwapError::InvalidFallbackReveal
+        );
+
+        // Minimum output for the whole escrow at the revealed limit
+        let order_book = &ctx.accounts.order_book;
+        let sells_base = ctx.accounts.escrow.token_mint == order_book.base_mint;
+        let output_mint = if sells_base {
+            order_book.quote_mint
+        } else {
+            order_book.base_mint
+        };
+        require!(
+            ctx.accounts.owner_token_account.mint == output_mint,
+            ShadowSwapError::InvalidTokenMint
+        );
+
+        let amount_in = ctx.accounts.escrow_token_account.amount;
+        require!(amount_in > 0, ShadowSwapError::InsufficientEscrowFunds);
+        let min_amount_out = if sells_base {
+            quote_amount_for(amount_in, limit_price)?
+        } else {
+            base_amount_for(amount_in, limit_price)?
+        };
+
+        let balance_before = ctx.accounts.owner_token_account.amount;
+        let order_key = ctx.accounts.order.key();
+        let seeds = &[
+            ESCROW_SEED,
+            order_key.as_ref(),
+            &[ctx.accounts.escrow.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        SwapInterface::swap(
+            &AmmSwapAccounts {
+                amm_program: &ctx.accounts.amm_program.to_account_info(),
+                source: &ctx.accounts.escrow_token_account.to_account_info(),
+                destination: &ctx.accounts.owner_token_account.to_account_info(),
+                authority: &ctx.accounts.escrow.to_account_info(),
+                token_program: &ctx.accounts.token_program.to_account_info(),
+                pool_accounts: ctx.remaining_accounts,
+            },
+            amount_in,
+            min_amount_out,
+            signer,
+        )?;
+
+        // Trust balances, not the AMM
+        ctx.accounts.escrow_token_account.reload()?;
+        ctx.accounts.owner_token_account.reload()?;
+        require!(
+            ctx.accounts.escrow_token_account.amount == 0,
+            ShadowSwapError::IncompleteFallbackSwap
+        );
+        let amount_out = ctx
+            .accounts
+            .owner_token_account
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+        require!(amount_out >= min_amount_out, ShadowSwapError::SlippageExceeded);
+
+        let order = &mut ctx.accounts.order;
+        order.status = ORDER_STATUS_FILLED;
+        order.updated_at = clock.unix_timestamp;
+
+        let order_book = &mut ctx.accounts.order_book;
+        order_book.closed_orders = order_book
+            .closed_orders
+            .checked_add(1)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+        order_book.last_trade_at = clock.unix_timestamp;
+
+        emit!(FallbackExecuted {
+            order_book: order_book.key(),
+            order: order_key,
+            owner: order.owner,
+            order_id: order.order_id,
+            client_order_id: order.client_order_id,
+            amount_in,
+            amount_out,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!(
+            "Fallback swap executed: order={}, in={}, out={}",
+            order.order_id,
+            amount_in,
+            amount_out
+        );
+        Ok(())
+    }
+
+    /// Create the book's LP backstop vault
+    /// 
+    /// LPs deposit base and quote for vault shares; the keeper can fill
+    /// unmatched orders against the vault at the oracle price plus or minus
+    /// `spread_bps`. Requires the book to have an oracle.
+    pub fn initialize_lp_vault(ctx: Context<InitializeLpVault>, params: LpVaultParams) -> Result<()> {
+        params.validate()?;
+
+        let lp_vault = &mut ctx.accounts.lp_vault;
+        lp_vault.order_book = ctx.accounts.order_book.key();
+        lp_vault.share_mint = ctx.accounts.share_mint.key();
+        lp_vault.base_vault = ctx.accounts.base_vault.key();
+        lp_vault.quote_vault = ctx.accounts.quote_vault.key();
+        lp_vault.pending_shares = ctx.accounts.pending_shares.key();
+        lp_vault.params = params;
+        lp_vault.withdrawal_count = 0;
+        lp_vault.next_withdrawal = 0;
+        lp_vault.created_at = Clock::get()?.unix_timestamp;
+        lp_vault.bump = ctx.bumps.lp_vault;
+
+        msg!(
+            "LP vault initialized: spread {} bps, max inventory {} bps",
+            params.spread_bps,
+            params.max_inventory_bps
+        );
+        Ok(())
+    }
+
+    /// Update the LP vault's spread, exposure cap, fee share and withdrawal delay
+    pub fn configure_lp_vault(ctx: Context<ConfigureLpVault>, params: LpVaultParams) -> Result<()> {
+        params.validate()?;
+        ctx.accounts.lp_vault.params = params;
+
+        msg!("LP vault configured: spread {} bps", params.spread_bps);
+        Ok(())
+    }
+
+    /// Deposit base and/or quote into the LP vault for shares
+    /// 
+    /// Deposits and the vault are valued in quote at the oracle price, so
+    /// either side (or both, in any ratio) can be deposited.
+    pub fn deposit_liquidity(
+        ctx: Context<DepositLiquidity>,
+        base_amount: u64,
+        quote_amount: u64,
+    ) -> Result<()> {
+        require!(base_amount > 0 || quote_amount > 0, ShadowSwapError::OrderTooSmall);
+
+        let clock = Clock::get()?;
+        let price = oracle_reference_price(&ctx.accounts.order_book, &ctx.accounts.oracle, clock.unix_timestamp)?;
+        let deposit_value = lp_inventory_value(base_amount, quote_amount, price)?;
+        let vault_value = lp_inventory_value(
+            ctx.accounts.base_vault.amount,
+            ctx.accounts.quote_vault.amount,
+            price,
+        )?;
+        let supply = ctx.accounts.share_mint.supply as u128;
+        let shares = if supply == 0 || vault_value == 0 {
+            deposit_value
+        } else {
+            deposit_value
+                .checked_mul(supply)
+                .ok_or(ShadowSwapError::NumericalOverflow)?
+                / vault_value
+        };
+        let shares = u64::try_from(shares).map_err(|_| error!(ShadowSwapError::NumericalOverflow))?;
+        require!(shares > 0, ShadowSwapError::OrderTooSmall);
+
+        for (amount, from, to) in [
+            (base_amount, &ctx.accounts.lp_base_account, &ctx.accounts.base_vault),
+            (quote_amount, &ctx.accounts.lp_quote_account, &ctx.accounts.quote_vault),
+        ] {
+            if amount > 0 {
+                token::transfer(
+                    CpiContext::new(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: from.to_account_info(),
+                            to: to.to_account_info(),
+                            authority: ctx.accounts.lp.to_account_info(),
+                        },
+                    ),
+                    amount,
+                )?;
+            }
+        }
+
+        let order_book_key = ctx.accounts.order_book.key();
+        let seeds = &[
+            LP_VAULT_SEED,
+            order_book_key.as_ref(),
+            &[ctx.accounts.lp_vault.bump],
+        ];
+        let signer = &[&seeds[..]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.share_mint.to_account_info(),
+                    to: ctx.accounts.lp_share_account.to_account_info(),
+                    authority: ctx.accounts.lp_vault.to_account_info(),
+                },
+                signer,
+            ),
+            shares,
+        )?;
+
+        emit!(LiquidityDeposited {
+            lp_vault: ctx.accounts.lp_vault.key(),
+            lp: ctx.accounts.lp.key(),
+            base_amount,
+            quote_amount,
+            shares,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Liquidity deposited: base {}, quote {}, shares {}", base_amount, quote_amount, shares);
+        Ok(())
+    }
+
+    /// Queue a withdrawal of LP shares
+    /// 
+    /// The shares move into the vault's pending account and keep their claim
+    /// on the vault until `process_lp_withdrawal` pays them out, in request
+    /// order, once `withdrawal_delay` has passed.
+    pub fn request_lp_withdrawal(ctx: Context<RequestLpWithdrawal>, shares: u64) -> Result<()> {
+        require!(shares > 0, ShadowSwapError::OrderTooSmall);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.lp_share_account.to_account_info(),
+                    to: ctx.accounts.pending_shares.to_account_info(),
+                    authority: ctx.accounts.lp.to_account_info(),
+                },
+            ),
+            shares,
+        )?;
+
+        let lp_vault = &mut ctx.accounts.lp_vault;
+        let request = &mut ctx.accounts.withdrawal_request;
+        request.lp_vault = lp_vault.key();
+        request.owner = ctx.accounts.lp.key();
+        request.index = lp_vault.withdrawal_count;
+        request.shares = shares;
+        request.base_destination = ctx.accounts.base_destination.key();
+        request.quote_destination = ctx.accounts.quote_destination.key();
+        request.requested_at = Clock::get()?.unix_timestamp;
+        request.bump = ctx.bumps.withdrawal_request;
+
+        lp_vault.withdrawal_count = lp_vault
+            .withdrawal_count
+            .checked_add(1)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+
+        msg!("LP withdrawal #{} queued: {} shares", request.index, shares);
+        Ok(())
+    }
+
+    /// Pay out the next queued LP withdrawal
+    /// 
+    /// Permissionless crank. Pays the request's pro-rata share of both vault
+    /// balances to the destinations recorded at request time, burns the
+    /// shares and closes the request. A request that cannot be paid is moved
+    /// aside with `skip_lp_withdrawal`.
+    pub fn process_lp_withdrawal(ctx: Context<ProcessLpWithdrawal>) -> Result<()> {
+        let clock = Clock::get()?;
+        let request = &ctx.accounts.withdrawal_request;
+        check_withdrawal_due(&ctx.accounts.lp_vault, request, clock.unix_timestamp)?;
+
+        let (base_out, quote_out) = lp_withdrawal_payout(
+            request.shares,
+            ctx.accounts.share_mint.supply,
+            ctx.accounts.base_vault.amount,
+            ctx.accounts.quote_vault.amount,
+        )?;
+        pay_lp_withdrawal(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.lp_vault,
+            &ctx.accounts.share_mint.to_account_info(),
+            &ctx.accounts.pending_shares.to_account_info(),
+            request.shares,
+            [
+                (base_out, ctx.accounts.base_vault.to_account_info(), ctx.accounts.base_destination.to_account_info()),
+                (quote_out, ctx.accounts.quote_vault.to_account_info(), ctx.accounts.quote_destination.to_account_info()),
+            ],
+        )?;
+
+        emit!(LiquidityWithdrawn {
+            lp_vault: ctx.accounts.lp_vault.key(),
+            lp: request.owner,
+            shares: request.shares,
+            base_amount: base_out,
+            quote_amount: quote_out,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!(
+            "LP withdrawal #{} processed: base {}, quote {}",
+            request.index,
+            base_out,
+            quote_out
+        );
+
+        let lp_vault = &mut ctx.accounts.lp_vault;
+        lp_vault.next_withdrawal = lp_vault
+            .next_withdrawal
+            .checked_add(1)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+        Ok(())
+    }
+
+    /// Park the next queued LP withdrawal so the queue can move past it
+    /// 
+    /// Permissionless crank for a due request that `process_lp_withdrawal`
+    /// cannot pay, e.g. because a destination was closed or frozen. The
+    /// shares are redeemed as in `process_lp_withdrawal`, but the payout goes
+    /// to claim accounts owned by the request (funded by the cranker) and the
+    /// LP collects it with `claim_lp_withdrawal`.
+    pub fn skip_lp_withdrawal(ctx: Context<SkipLpWithdrawal>) -> Result<()> {
+        let clock = Clock::get()?;
+        let request = &ctx.accounts.withdrawal_request;
+        check_withdrawal_due(&ctx.accounts.lp_vault, request, clock.unix_timestamp)?;
+
+        let (base_out, quote_out) = lp_withdrawal_payout(
+            request.shares,
+            ctx.accounts.share_mint.supply,
+            ctx.accounts.base_vault.amount,
+            ctx.accounts.quote_vault.amount,
+        )?;
+        pay_lp_withdrawal(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.lp_vault,
+            &ctx.accounts.share_mint.to_account_info(),
+            &ctx.accounts.pending_shares.to_account_info(),
+            request.shares,
+            [
+                (base_out, ctx.accounts.base_vault.to_account_info(), ctx.accounts.base_claim.to_account_info()),
+                (quote_out, ctx.accounts.quote_vault.to_account_info(), ctx.accounts.quote_claim.to_account_info()),
+            ],
+        )?;
+
+        emit!(LpWithdrawalParked {
+            lp_vault: ctx.accounts.lp_vault.key(),
+            lp: request.owner,
+            index: request.index,
+            shares: request.shares,
+            base_amount: base_out,
+            quote_amount: quote_out,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!(
+            "LP withdrawal #{} parked: base {}, quote {}",
+            request.index,
+            base_out,
+            quote_out
+        );
+
+        let lp_vault = &mut ctx.accounts.lp_vault;
+        lp_vault.next_withdrawal = lp_vault
+            .next_withdrawal
+            .checked_add(1)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+        Ok(())
+    }
+
+    /// Collect a parked LP withdrawal
+    /// 
+    /// The LP names new destination accounts. The claim accounts and the
+    /// request are closed to the LP.
+    pub fn claim_lp_withdrawal(ctx: Context<ClaimLpWithdrawal>) -> Result<()> {
+        let request = &ctx.accounts.withdrawal_request;
+        let lp_vault_key = ctx.accounts.lp_vault.key();
+        let index_bytes = request.index.to_le_bytes();
+        let seeds = &[
+            LP_WITHDRAWAL_SEED,
+            lp_vault_key.as_ref(),
+            index_bytes.as_ref(),
+            &[request.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let base_amount = ctx.accounts.base_claim.amount;
+        let quote_amount = ctx.accounts.quote_claim.amount;
+        for (amount, claim, destination) in [
+            (base_amount, ctx.accounts.base_claim.to_account_info(), ctx.accounts.base_destination.to_account_info()),
+            (quote_amount, ctx.accounts.quote_claim.to_account_info(), ctx.accounts.quote_destination.to_account_info()),
+        ] {
+            if amount > 0 {
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: claim.clone(),
+                            to: destination,
+                            authority: request.to_account_info(),
+                        },
+                        signer,
+                    ),
+                    amount,
+                )?;
+            }
+            token::close_account(CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                CloseAccount {
+                    account: claim,
+                    destination: ctx.accounts.owner.to_account_info(),
+                    authority: request.to_account_info(),
+                },
+                signer,
+            ))?;
+        }
+
+        emit!(LiquidityWithdrawn {
+            lp_vault: lp_vault_key,
+            lp: request.owner,
+            shares: request.shares,
+            base_amount,
+            quote_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Parked LP withdrawal #{} claimed", request.index);
+        Ok(())
+    }
+
+    /// Fill an unmatched order against the LP vault
+    /// 
+    /// Keeper-only. The vault trades at the oracle price plus `spread_bps`
+    /// when the order buys and minus it when the order sells. The limit is
+    /// encrypted, so the program cannot check it: the keeper asserts the
+    /// order's limit allows that price, as it does for matches.
+    /// The order pays from its escrow and receives its proceeds less
+    /// `fee_bps`, of which `lp_fee_share_bps` stays in the vault and the rest
+    /// goes to the fee collector. Fails if the side the vault receives would
+    /// exceed `max_inventory_bps` of the vault's value. Post-only orders are
+    /// never backstopped and IOC/FOK orders must fill completely; other
+    /// orders keep resting with whatever remains. An order filled completely
+    /// (`order_remaining == 0`) gets the rest of its escrow back through
+    /// `owner_refund_account`.
+    pub fn backstop_fill(ctx: Context<BackstopFill>, matched_amount: u64, order_remaining: u64) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            ctx.accounts.callback_auth.expires_at > clock.unix_timestamp,
+            ShadowSwapError::CallbackAuthExpired
+        );
+        require!(matched_amount > 0, ShadowSwapError::OrderTooSmall);
+
+        let time_in_force = ctx.accounts.order.time_in_force;
+        require!(
+            time_in_force != TIME_IN_FORCE_POST_ONLY,
+            ShadowSwapError::PostOnlyWouldTake
+        );
+        require!(
+            !is_immediate(time_in_force) || order_remaining == 0,
+            ShadowSwapError::FillOrKillNotFilled
+        );
+
+        // Vault price: oracle +/- spread, against the order
+        let order_book = &ctx.accounts.order_book;
+        let params = ctx.accounts.lp_vault.params;
+        let reference_price = oracle_reference_price(order_book, &ctx.accounts.oracle, clock.unix_timestamp)?;
+        let spread = u64::try_from((reference_price as u128) * (params.spread_bps as u128) / 10_000)
+            .map_err(|_| error!(ShadowSwapError::NumericalOverflow))?;
+        let order_buys = ctx.accounts.escrow.token_mint == order_book.quote_mint;
+        let execution_price = if order_buys {
+            reference_price.checked_add(spread)
+        } else {
+            reference_price.checked_sub(spread)
+        }
+        .ok_or(ShadowSwapError::NumericalOverflow)?;
+
+        check_child_slice(
+            &ctx.accounts.order,
+            order_buys,
+            ctx.accounts.escrow_token_account.amount,
+            matched_amount,
+            order_remaining,
+            execution_price,
+        )?;
+
+        let quote_amount = quote_amount_for(matched_amount, execution_price)?;
+        let (amount_in, proceeds, proceeds_mint) = if order_buys {
+            (quote_amount, matched_amount, order_book.base_mint)
+        } else {
+            (matched_amount, quote_amount, order_book.quote_mint)
+        };
+        require!(
+            ctx.accounts.owner_token_account.mint == proceeds_mint
+                && ctx.accounts.fee_collector_token_account.mint == proceeds_mint,
+            ShadowSwapError::InvalidTokenMint
+        );
+
+        let fee = bps_of(proceeds, order_book.fee_bps)?;
+        let lp_fee = bps_of(fee, params.lp_fee_share_bps)?;
+        let protocol_fee = fee - lp_fee;
+
+        let (vault_in, vault_out) = if order_buys {
+            (&ctx.accounts.lp_quote_vault, &ctx.accounts.lp_base_vault)
+        } else {
+            (&ctx.accounts.lp_base_vault, &ctx.accounts.lp_quote_vault)
+        };
+        require!(
+            vault_out.amount >= proceeds - lp_fee,
+            ShadowSwapError::InsufficientLiquidity
+        );
+
+        // Order pays the vault from its escrow
+        let order_key = ctx.accounts.order.key();
+        let escrow_seeds = &[
+            ESCROW_SEED,
+            order_key.as_ref(),
+            &[ctx.accounts.escrow.bump],
+        ];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    to: vault_in.to_account_info(),
+                    authority: ctx.accounts.escrow.to_account_info(),
+                },
+                &[&escrow_seeds[..]],
+            ),
+            amount_in,
+        )?;
+
+        // Vault pays the proceeds less fees, and the protocol's fee share
+        let order_book_key = order_book.key();
+        let vault_seeds = &[
+            LP_VAULT_SEED,
+            order_book_key.as_ref(),
+            &[ctx.accounts.lp_vault.bump],
+        ];
+        let vault_signer = &[&vault_seeds[..]];
+        for (amount, to) in [
+            (proceeds - fee, &ctx.accounts.owner_token_account),
+            (protocol_fee, &ctx.accounts.fee_collector_token_account),
+        ] {
+            if amount > 0 {
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: vault_out.to_account_info(),
+                            to: to.to_account_info(),
+                            authority: ctx.accounts.lp_vault.to_account_info(),
+                        },
+                        vault_signer,
+                    ),
+                    amount,
+                )?;
+            }
+        }
+
+        // Inventory cap on the side the vault just took on
+        ctx.accounts.lp_base_vault.reload()?;
+        ctx.accounts.lp_quote_vault.reload()?;
+        let base_value = quote_amount_for(ctx.accounts.lp_base_vault.amount, reference_price)? as u128;
+        let quote_value = ctx.accounts.lp_quote_vault.amount as u128;
+        let received_value = if order_buys { quote_value } else { base_value };
+        require!(
+            received_value * 10_000 <= (base_value + quote_value) * params.max_inventory_bps as u128,
+            ShadowSwapError::InventoryCapExceeded
+        );
+
+        // A filled order never rests: refund whatever is left in its escrow
+        if order_remaining == 0 {
+            let refund_account = ctx
+                .accounts
+                .owner_refund_account
+                .as_ref()
+                .ok_or(ShadowSwapError::MissingRefundAccount)?;
+            ctx.accounts.escrow_token_account.reload()?;
+            let leftover = ctx.accounts.escrow_token_account.amount;
+            if leftover > 0 {
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: ctx.accounts.escrow_token_account.to_account_info(),
+                            to: refund_account.to_account_info(),
+                            authority: ctx.accounts.escrow.to_account_info(),
+                        },
+                        &[&escrow_seeds[..]],
+                    ),
+                    leftover,
+                )?;
+            }
+        }
+
+        let order = &mut ctx.accounts.order;
+        order.updated_at = clock.unix_timestamp;
+        let order_book = &mut ctx.accounts.order_book;
+        if order_remaining == 0 {
+            order.status = ORDER_STATUS_FILLED;
+            order_book.closed_orders = order_book
+                .closed_orders
+                .checked_add(1)
+                .ok_or(ShadowSwapError::NumericalOverflow)?;
+        } else {
+            order.status = ORDER_STATUS_PARTIAL;
+        }
+        order_book.last_trade_at = clock.unix_timestamp;
+
+        emit!(BackstopFilled {
+            order_book: order_book.key(),
+            order: order_key,
+            order_id: order.order_id,
+            client_order_id: order.client_order_id,
+            base_amount: matched_amount,
+            quote_amount,
+            execution_price,
+            lp_fee,
+            protocol_fee,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!(
+            "Backstop fill: order={}, base={}, quote={}, price={}",
+            order.order_id,
+            matched_amount,
+            quote_amount,
+            execution_price
+        );
+        Ok(())
+    }
+
+    /// Open a commit-reveal auction round on an order book
+    /// 
+    /// Commit-reveal rounds need no MPC: orders commit to a hash of their
+    /// terms with a public deposit during the commit window, reveal them
+    /// during the reveal window and are cleared on-chain at one uniform
+    /// price. Deposits are pooled in the round's base / quote vaults.
+    pub fn open_sealed_auction(ctx: Context<OpenSealedAuction>, params: SealedAuctionParams) -> Result<()> {
+        params.validate()?;
+
+        let clock = Clock::get()?;
+        let commit_end = clock
+            .unix_timestamp
+            .checked_add(params.commit_duration)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+        let reveal_end = commit_end
+            .checked_add(params.reveal_duration)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+
+        let order_book = &mut ctx.accounts.order_book;
+        let auction = &mut ctx.accounts.auction;
+        auction.order_book = order_book.key();
+        auction.round = order_book.sealed_auction_count;
+        auction.base_vault = ctx.accounts.base_vault.key();
+        auction.quote_vault = ctx.accounts.quote_vault.key();
+        auction.commit_end = commit_end;
+        auction.reveal_end = reveal_end;
+        auction.penalty_bps = params.penalty_bps;
+        auction.status = SEALED_AUCTION_STATUS_OPEN;
+        auction.commit_count = 0;
+        auction.settled_count = 0;
+        auction.bids = Vec::new();
+        auction.clearing_price = 0;
+        auction.volume = 0;
+        auction.created_at = clock.unix_timestamp;
+        auction.bump = ctx.bumps.auction;
+
+        order_book.sealed_auction_count = order_book
+            .sealed_auction_count
+            .checked_add(1)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+        order_book.live_sealed_auctions += 1;
+
+        msg!(
+            "Sealed auction {} opened: commits until {}, reveals until {}",
+            auction.round,
+            commit_end,
+            reveal_end
+        );
+        Ok(())
+    }
+
+    /// Commit to a sealed order and deposit its funds
+    /// 
+    /// `commitment` is `sha256(side as u8 || price as u64 LE || amount as u64 LE
+    /// || salt)`. The deposit (base tokens for sells, quote tokens for buys)
+    /// must cover the order once revealed; buys should deposit
+    /// `amount * price` rounded up.
+    pub fn commit_sealed_order(
+        ctx: Context<CommitSealedOrder>,
+        commitment: [u8; 32],
+        deposit: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let auction = &mut ctx.accounts.auction;
+        require!(
+            clock.unix_timestamp < auction.commit_end,
+            ShadowSwapError::SealedAuctionPhase
+        );
+        require!(
+            auction.commit_count < MAX_SEALED_ORDERS as u64,
+            ShadowSwapError::SealedAuctionFull
+        );
+        require!(deposit > 0, ShadowSwapError::OrderTooSmall);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user_token_account.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            deposit,
+        )?;
+
+        let sealed_order = &mut ctx.accounts.sealed_order;
+        sealed_order.auction = auction.key();
+        sealed_order.owner = ctx.accounts.owner.key();
+        sealed_order.index = auction.commit_count;
+        sealed_order.commitment = commitment;
+        sealed_order.deposit_mint = ctx.accounts.vault.mint;
+        sealed_order.deposit = deposit;
+        sealed_order.revealed = false;
+        sealed_order.bid_index = 0;
+        sealed_order.committed_at = clock.unix_timestamp;
+        sealed_order.bump = ctx.bumps.sealed_order;
+
+        auction.commit_count += 1;
+
+        msg!("Sealed order {} committed to auction {}", sealed_order.index, auction.round);
+        Ok(())
+    }
+
+    /// Reveal a committed sealed order during the reveal window
+    /// 
+    /// The terms must hash to the commitment, the order must meet the book
+    /// minimum and the deposit must be in the right token and cover it.
+    /// Orders that are never revealed are refunded minus the round's penalty.
+    pub fn reveal_sealed_order(
+        ctx: Context<RevealSealedOrder>,
+        side: u8,
+        price: u64,
+        amount: u64,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let auction = &mut ctx.accounts.auction;
+        require!(
+            clock.unix_timestamp >= auction.commit_end && clock.unix_timestamp < auction.reveal_end,
+            ShadowSwapError::SealedAuctionPhase
+        );
+
+        let sealed_order = &mut ctx.accounts.sealed_order;
+        require!(
+            sealed_order_commitment(side, price, amount, &salt) == sealed_order.commitment,
+            ShadowSwapError::InvalidSealedReveal
+        );
+
+        let order_book = &ctx.accounts.order_book;
+        require!(
+            (side == ORDER_SIDE_BUY || side == ORDER_SIDE_SELL)
+                && price > 0
+                && amount > 0
+                && amount >= order_book.min_base_order_size,
+            ShadowSwapError::InvalidSealedReveal
+        );
+
+        let (deposit_mint, required) = if side == ORDER_SIDE_BUY {
+            (order_book.quote_mint, quote_amount_ceil(amount, price)?)
+        } else {
+            (order_book.base_mint, amount)
+        };
+        require!(
+            sealed_order.deposit_mint == deposit_mint && sealed_order.deposit >= required,
+            ShadowSwapError::InsufficientEscrowFunds
+        );
+
+        sealed_order.revealed = true;
+        sealed_order.bid_index = auction.bids.len() as u8;
+        auction.bids.push(SealedBid {
+            side,
+            price,
+            amount,
+            filled: 0,
+        });
+
+        msg!("Sealed order {} revealed", sealed_order.index);
+        Ok(())
+    }
+
+    /// Clear a commit-reveal round once its reveal window has closed
+    /// 
+    /// Permissionless. Computes the uniform clearing price and every revealed
+    /// order's fill from the revealed orders (see `clear_uniform_price`).
+    pub fn clear_sealed_auction(ctx: Context<ClearSealedAuction>) -> Result<()> {
+        let clock = Clock::get()?;
+        let auction = &mut ctx.accounts.auction;
+        require!(
+            clock.unix_timestamp >= auction.reveal_end,
+            ShadowSwapError::SealedAuctionPhase
+        );
+
+        let (clearing_price, volume) = clear_uniform_price(&mut auction.bids)?;
+        auction.clearing_price = clearing_price;
+        auction.volume = volume;
+        auction.status = SEALED_AUCTION_STATUS_CLEARED;
+
+        let order_book = &mut ctx.accounts.order_book;
+        if volume > 0 {
+            order_book.last_trade_at = clock.unix_timestamp;
+        }
+        // A round nobody committed to has nothing left to settle
+        if auction.commit_count == 0 {
+            order_book.live_sealed_auctions -= 1;
+        }
+
+        emit!(SealedAuctionCleared {
+            order_book: auction.order_book,
+            auction: auction.key(),
+            round: auction.round,
+            clearing_price,
+            volume,
+            committed: auction.commit_count,
+            revealed: auction.bids.len() as u64,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!(
+            "Sealed auction {} cleared: {} base at {}",
+            auction.round,
+            volume,
+            clearing_price
+        );
+        Ok(())
+    }
+
+    /// Pay out one sealed order of a cleared round and close it
+    /// 
+    /// Permissionless. Revealed orders receive their fill at the clearing
+    /// price plus the unused deposit; buys pay the fill rounded up, sells
+    /// receive it rounded down. Unrevealed orders get their deposit back
+    /// minus `penalty_bps`, which goes to the book's fee collector.
+    pub fn settle_sealed_order(ctx: Context<SettleSealedOrder>) -> Result<()> {
+        let auction = &ctx.accounts.auction;
+        let sealed_order = &ctx.accounts.sealed_order;
+        let deposit = sealed_order.deposit;
+        let deposit_is_base = sealed_order.deposit_mint == ctx.accounts.base_vault.mint;
+
+        let (filled, base_out, quote_out, penalty) = if sealed_order.revealed {
+            let bid = auction.bids[sealed_order.bid_index as usize];
+            if bid.side == ORDER_SIDE_BUY {
+                let cost = quote_amount_ceil(bid.filled, auction.clearing_price)?;
+                (bid.filled, bid.filled, deposit - cost, 0)
+            } else {
+                let proceeds = quote_amount_for(bid.filled, auction.clearing_price)?;
+                (bid.filled, deposit - bid.filled, proceeds, 0)
+            }
+        } else {
+            let penalty = bps_of(deposit, auction.penalty_bps)?;
+            if deposit_is_base {
+                (0, deposit - penalty, 0, penalty)
+            } else {
+                (0, 0, deposit - penalty, penalty)
+            }
+        };
+
+        let order_book_key = ctx.accounts.order_book.key();
+        let round_bytes = auction.round.to_le_bytes();
+        let seeds = &[
+            SEALED_AUCTION_SEED,
+            order_book_key.as_ref(),
+            round_bytes.as_ref(),
+            &[auction.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let penalty_vault = if deposit_is_base {
+            &ctx.accounts.base_vault
+        } else {
+            &ctx.accounts.quote_vault
+        };
+        let mut payouts = vec![
+            (base_out, &ctx.accounts.base_vault, ctx.accounts.owner_base_account.to_account_info()),
+            (quote_out, &ctx.accounts.quote_vault, ctx.accounts.owner_quote_account.to_account_info()),
+        ];
+        if penalty > 0 {
+            let fee_collector_token_account = ctx
+                .accounts
+                .fee_collector_token_account
+                .as_ref()
+                .ok_or(ShadowSwapError::InvalidFeeConfiguration)?;
+            require!(
+                fee_collector_token_account.owner == ctx.accounts.order_book.fee_collector
+                    && fee_collector_token_account.mint == sealed_order.deposit_mint,
+                ShadowSwapError::InvalidFeeConfiguration
+            );
+            payouts.push((penalty, penalty_vault, fee_collector_token_account.to_account_info()));
+        }
+
+        for (amount, vault, to) in payouts {
+            if amount > 0 {
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: vault.to_account_info(),
+                            to,
+                            authority: ctx.accounts.auction.to_account_info(),
+                        },
+                        signer,
+                    ),
+                    amount,
+                )?;
+            }
+        }
+
+        let clock = Clock::get()?;
+        emit!(SealedOrderSettled {
+            auction: ctx.accounts.auction.key(),
+            owner: sealed_order.owner,
+            index: sealed_order.index,
+            revealed: sealed_order.revealed,
+            filled,
+            base_out,
+            quote_out,
+            penalty,
+            timestamp: clock.unix_timestamp,
+        });
+
+        let auction = &mut ctx.accounts.auction;
+        auction.settled_count += 1;
+        if auction.settled_count == auction.commit_count {
+            ctx.accounts.order_book.live_sealed_auctions -= 1;
+        }
+
+        msg!(
+            "Sealed order {} settled: filled {}, penalty {}",
+            ctx.accounts.sealed_order.index,
+            filled,
+            penalty
+        );
+        Ok(())
+    }
+
+    /// Whitelist a market maker for RFQs on an order book
+    /// 
+    /// `encryption_key` is the maker's x25519 public key; takers encrypt
+    /// request sizes to it.
+    pub fn add_rfq_maker(ctx: Context<AddRfqMaker>, encryption_key: [u8; 32]) -> Result<()> {
+        let rfq_maker = &mut ctx.accounts.rfq_maker;
+        rfq_maker.order_book = ctx.accounts.order_book.key();
+        rfq_maker.maker = ctx.accounts.maker.key();
+        rfq_maker.encryption_key = encryption_key;
+        rfq_maker.added_at = Clock::get()?.unix_timestamp;
+        rfq_maker.bump = ctx.bumps.rfq_maker;
+
+        msg!("RFQ maker whitelisted: {}", rfq_maker.maker);
+        Ok(())
+    }
+
+    /// Remove a market maker from the RFQ whitelist
+    /// 
+    /// Quotes the maker already posted stay valid until withdrawn or expired.
+    pub fn remove_rfq_maker(ctx: Context<RemoveRfqMaker>) -> Result<()> {
+        msg!("RFQ maker removed: {}", ctx.accounts.rfq_maker.maker);
+        Ok(())
+    }
+
+    /// Request quotes from a set of whitelisted makers
+    /// 
+    /// `encrypted_size` holds one ciphertext of the requested size per maker,
+    /// in `makers` order, each encrypted to that maker's `encryption_key`.
+    /// `deposit` funds the taker's escrow (quote tokens for buys, base for
+    /// sells) and caps what the taker can trade; the rest is refunded on
+    /// acceptance. Remaining accounts: the `RfqMaker` account of each maker.
+    pub fn request_quote<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RequestQuote<'info>>,
+        side: u8,
+        makers: Vec<Pubkey>,
+        encrypted_size: Vec<u8>,
+        deposit: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let order_book = &mut ctx.accounts.order_book;
+        require!(
+            (side == ORDER_SIDE_BUY || side == ORDER_SIDE_SELL)
+                && !makers.is_empty()
+                && makers.len() <= MAX_RFQ_MAKERS
+                && expires_at > clock.unix_timestamp,
+            ShadowSwapError::InvalidRfq
+        );
+        require!(
+            encrypted_size.len() <= MAX_CIPHER_PAYLOAD_SIZE,
+            ShadowSwapError::InvalidCipherPayload
+        );
+        require!(deposit > 0, ShadowSwapError::OrderTooSmall);
+
+        let deposit_mint = if side == ORDER_SIDE_BUY {
+            order_book.quote_mint
+        } else {
+            order_book.base_mint
+        };
+        require!(
+            ctx.accounts.token_mint.key() == deposit_mint,
+            ShadowSwapError::InvalidTokenMint
+        );
+
+        // Every maker must be distinct and currently whitelisted on this book
+        require!(
+            ctx.remaining_accounts.len() == makers.len(),
+            ShadowSwapError::RfqMakerNotWhitelisted
+        );
+        for (i, (maker, maker_info)) in makers.iter().zip(ctx.remaining_accounts).enumerate() {
+            let rfq_maker: Account<'info, RfqMaker> = Account::try_from(maker_info)?;
+            require!(
+                rfq_maker.order_book == order_book.key() && rfq_maker.maker == *maker,
+                ShadowSwapError::RfqMakerNotWhitelisted
+            );
+            require!(!makers[..i].contains(maker), ShadowSwapError::InvalidRfq);
+        }
+
+        let rfq_id = order_book.rfq_count;
+        order_book.rfq_count = order_book
+            .rfq_count
+            .checked_add(1)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+        order_book.open_rfqs += 1;
+
+        let request = &mut ctx.accounts.request;
+        let escrow = &mut ctx.accounts.escrow;
+        request.order_book = order_book.key();
+        request.taker = ctx.accounts.taker.key();
+        request.rfq_id = rfq_id;
+        request.side = side;
+        request.makers = makers;
+        request.encrypted_size = encrypted_size;
+        request.escrow = escrow.key();
+        request.expires_at = expires_at;
+        request.status = RFQ_STATUS_OPEN;
+        request.quote_count = 0;
+        request.accepted_quote = Pubkey::default();
+        request.created_at = clock.unix_timestamp;
+        request.bump = ctx.bumps.request;
+
+        escrow.order = request.key();
+        escrow.owner = ctx.accounts.taker.key();
+        escrow.order_book = order_book.key();
+        escrow.token_account = ctx.accounts.escrow_token_account.key();
+        escrow.token_mint = deposit_mint;
+        escrow.encrypted_amount = Vec::new();
+        escrow.encrypted_remaining = Vec::new();
+        escrow.locked_amount = 0;
+        escrow.created_at = clock.unix_timestamp;
+        escrow.bump = ctx.bumps.escrow;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user_token_account.to_account_info(),
+                    to: ctx.accounts.escrow_token_account.to_account_info(),
+                    authority: ctx.accounts.taker.to_account_info(),
+                },
+            ),
+            deposit,
+        )?;
+
+        emit!(RfqRequested {
+            order_book: order_book.key(),
+            request: request.key(),
+            taker: request.taker,
+            rfq_id,
+            side,
+            makers: request.makers.clone(),
+            expires_at,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("RFQ {} requested from {} makers", rfq_id, request.makers.len());
+        Ok(())
+    }
+
+    /// Answer an RFQ with a quote
+    /// 
+    /// `encrypted_quote` carries the quoted price, size and salt encrypted to
+    /// the taker; `terms_commitment` is `sha256(price as u64 LE || amount as
+    /// u64 LE || salt)`. `deposit` funds the maker's escrow with the other
+    /// side of the trade (base when the taker buys, quote when it sells).
+    pub fn submit_rfq_quote(
+        ctx: Context<SubmitRfqQuote>,
+        encrypted_quote: Vec<u8>,
+        terms_commitment: [u8; 32],
+        deposit: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let request = &mut ctx.accounts.request;
+        require!(
+            clock.unix_timestamp < request.expires_at,
+            ShadowSwapError::RfqExpired
+        );
+        require!(
+            request.makers.contains(&ctx.accounts.maker.key()),
+            ShadowSwapError::RfqMakerNotWhitelisted
+        );
+        require!(
+            expires_at > clock.unix_timestamp,
+            ShadowSwapError::InvalidRfqQuote
+        );
+        require!(
+            encrypted_quote.len() <= MAX_CIPHER_PAYLOAD_SIZE,
+            ShadowSwapError::InvalidCipherPayload
+        );
+        require!(deposit > 0, ShadowSwapError::OrderTooSmall);
+
+        let order_book = &ctx.accounts.order_book;
+        let deposit_mint = if request.side == ORDER_SIDE_BUY {
+            order_book.base_mint
+        } else {
+            order_book.quote_mint
+        };
+        require!(
+            ctx.accounts.token_mint.key() == deposit_mint,
+            ShadowSwapError::InvalidTokenMint
+        );
+
+        let quote = &mut ctx.accounts.quote;
+        let escrow = &mut ctx.accounts.escrow;
+        quote.request = request.key();
+        quote.maker = ctx.accounts.maker.key();
+        quote.encrypted_quote = encrypted_quote;
+        quote.terms_commitment = terms_commitment;
+        quote.escrow = escrow.key();
+        quote.expires_at = expires_at;
+        quote.created_at = clock.unix_timestamp;
+        quote.bump = ctx.bumps.quote;
+
+        escrow.order = quote.key();
+        escrow.owner = ctx.accounts.maker.key();
+        escrow.order_book = order_book.key();
+        escrow.token_account = ctx.accounts.escrow_token_account.key();
+        escrow.token_mint = deposit_mint;
+        escrow.encrypted_amount = Vec::new();
+        escrow.encrypted_remaining = Vec::new();
+        escrow.locked_amount = 0;
+        escrow.created_at = clock.unix_timestamp;
+        escrow.bump = ctx.bumps.escrow;
+
+        request.quote_count = request
+            .quote_count
+            .checked_add(1)
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.user_token_account.to_account_info(),
+                    to: ctx.accounts.escrow_token_account.to_account_info(),
+                    authority: ctx.accounts.maker.to_account_info(),
+                },
+            ),
+            deposit,
+        )?;
+
+        emit!(RfqQuoted {
+            request: request.key(),
+            quote: quote.key(),
+            maker: quote.maker,
+            expires_at,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Quote submitted for RFQ {} by {}", request.rfq_id, quote.maker);
+        Ok(())
+    }
+
+    /// Accept a quote, settling the trade from both escrows atomically
+    /// 
+    /// The taker reveals the quoted terms, which must match the quote's
+    /// commitment. Base moves from the seller's escrow to the buyer and
+    /// `amount * price` quote from the buyer's escrow to the seller, subject
+    /// to the book's oracle band; both escrows then refund what is left.
+    pub fn accept_rfq_quote(
+        ctx: Context<AcceptRfqQuote>,
+        price: u64,
+        amount: u64,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let request = &ctx.accounts.request;
+        let quote = &ctx.accounts.quote;
+        require!(
+            clock.unix_timestamp < request.expires_at && clock.unix_timestamp < quote.expires_at,
+            ShadowSwapError::RfqExpired
+        );
+        require!(
+            rfq_terms_commitment(price, amount, &salt) == quote.terms_commitment,
+            ShadowSwapError::InvalidRfqQuote
+        );
+
+        let order_book = &ctx.accounts.order_book;
+        require!(
+            amount > 0 && amount >= order_book.min_base_order_size,
+            ShadowSwapError::OrderTooSmall
+        );
+        check_oracle_band(order_book, ctx.accounts.oracle.as_ref(), price, clock.unix_timestamp)?;
+
+        let quote_amount = quote_amount_for(amount, price)?;
+        let taker_buys = request.side == ORDER_SIDE_BUY;
+        let (buyer, seller) = if taker_buys {
+            (request.taker, quote.maker)
+        } else {
+            (quote.maker, request.taker)
+        };
+        let (buyer_escrow, buyer_escrow_token, seller_escrow, seller_escrow_token) = if taker_buys {
+            (
+                &ctx.accounts.request_escrow,
+                &ctx.accounts.request_escrow_token_account,
+                &ctx.accounts.quote_escrow,
+                &ctx.accounts.quote_escrow_token_account,
+            )
+        } else {
+            (
+                &ctx.accounts.quote_escrow,
+                &ctx.accounts.quote_escrow_token_account,
+                &ctx.accounts.request_escrow,
+                &ctx.accounts.request_escrow_token_account,
+            )
+        };
+        require!(
+            buyer_escrow_token.amount >= quote_amount && seller_escrow_token.amount >= amount,
+            ShadowSwapError::InsufficientEscrowFunds
+        );
+
+        let (buyer_base, seller_quote, taker_refund, maker_refund) = if taker_buys {
+            (
+                &ctx.accounts.taker_base_account,
+                &ctx.accounts.maker_quote_account,
+                &ctx.accounts.taker_quote_account,
+                &ctx.accounts.maker_base_account,
+            )
+        } else {
+            (
+                &ctx.accounts.maker_base_account,
+                &ctx.accounts.taker_quote_account,
+                &ctx.accounts.taker_base_account,
+                &ctx.accounts.maker_quote_account,
+            )
+        };
+
+        let token_program = ctx.accounts.token_program.to_account_info();
+        escrow_transfer(&token_program, buyer_escrow, buyer_escrow_token, seller_quote, quote_amount)?;
+        escrow_transfer(&token_program, seller_escrow, seller_escrow_token, buyer_base, amount)?;
+
+        // Refund what is left of both deposits
+        for (escrow, escrow_token, refund) in [
+            (&ctx.accounts.request_escrow, &ctx.accounts.request_escrow_token_account, taker_refund),
+            (&ctx.accounts.quote_escrow, &ctx.accounts.quote_escrow_token_account, maker_refund),
+        ] {
+            let sent = if escrow.key() == buyer_escrow.key() { quote_amount } else { amount };
+            escrow_transfer(&token_program, escrow, escrow_token, refund, escrow_token.amount - sent)?;
+        }
+
+        let request = &mut ctx.accounts.request;
+        request.status = RFQ_STATUS_FILLED;
+        request.accepted_quote = ctx.accounts.quote.key();
+
+        let order_book = &mut ctx.accounts.order_book;
+        order_book.last_trade_at = clock.unix_timestamp;
+        order_book.open_rfqs -= 1;
+
+        emit!(RfqFilled {
+            order_book: order_book.key(),
+            request: request.key(),
+            quote: request.accepted_quote,
+            rfq_id: request.rfq_id,
+            taker: request.taker,
+            maker: ctx.accounts.quote.maker,
+            buyer,
+            seller,
+            base_amount: amount,
+            quote_amount,
+            execution_price: price,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!(
+            "RFQ {} filled: base={}, quote={}, price={}",
+            request.rfq_id,
+            amount,
+            quote_amount,
+            price
+        );
+        Ok(())
+    }
+
+    /// Withdraw a quote, refunding the maker's escrow and closing the quote
+    /// 
+    /// Makers can pull a quote at any time. An accepted quote's escrow is
+    /// already empty, so withdrawing it only reclaims the rent.
+    pub fn withdraw_rfq_quote(ctx: Context<WithdrawRfqQuote>) -> Result<()> {
+        escrow_transfer(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.escrow,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.maker_token_account,
+            ctx.accounts.escrow_token_account.amount,
+        )?;
+
+        msg!("Quote withdrawn from RFQ {}", ctx.accounts.request.rfq_id);
+        Ok(())
+    }
+
+    /// Cancel an open RFQ, refunding the taker's escrow
+    pub fn cancel_rfq_request(ctx: Context<CancelRfqRequest>) -> Result<()> {
+        escrow_transfer(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.escrow,
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.taker_token_account,
+            ctx.accounts.escrow_token_account.amount,
+        )?;
+
+        let request = &mut ctx.accounts.request;
+        request.status = RFQ_STATUS_CANCELLED;
+        ctx.accounts.order_book.open_rfqs -= 1;
+
+        msg!("RFQ {} cancelled", request.rfq_id);
+        Ok(())
+    }
+
+    /// Authorize a session key to trade on the caller's behalf
+    /// 
+    /// `scopes` is a mask of `DELEGATE_SCOPE_*` bits; there is no scope that
+    /// withdraws funds. Per-order limits cap the escrow posted by each order
+    /// the delegate places, per token (0 = none in that token). To fund those
+    /// orders the owner also approves the `TradingDelegate` PDA as SPL
+    /// delegate of their token accounts, which caps the total.
+    pub fn authorize_trading_delegate(
+        ctx: Context<AuthorizeTradingDelegate>,
+        scopes: u8,
+        expires_at: i64,
+        max_base_per_order: u64,
+        max_quote_per_order: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            scopes != 0 && scopes & !DELEGATE_SCOPE_ALL == 0 && expires_at > clock.unix_timestamp,
+            ShadowSwapError::InvalidTradingDelegate
+        );
+
+        let trading_delegate = &mut ctx.accounts.trading_delegate;
+        trading_delegate.owner = ctx.accounts.owner.key();
+        trading_delegate.delegate = ctx.accounts.delegate.key();
+        trading_delegate.scopes = scopes;
+        trading_delegate.expires_at = expires_at;
+        trading_delegate.max_base_per_order = max_base_per_order;
+        trading_delegate.max_quote_per_order = max_quote_per_order;
+        trading_delegate.created_at = clock.unix_timestamp;
+        trading_delegate.bump = ctx.bumps.trading_delegate;
+
+        msg!(
+            "Trading delegate {} authorized (scopes {}, expires {})",
+            trading_delegate.delegate,
+            scopes,
+            expires_at
+        );
+        Ok(())
+    }
+
+    /// Revoke a session key
+    /// 
+    /// Closes the `TradingDelegate`; SPL allowances granted to it become
+    /// unusable, though the owner may still want to revoke them.
+    pub fn revoke_trading_delegate(ctx: Context<RevokeTradingDelegate>) -> Result<()> {
+        msg!("Trading delegate {} revoked", ctx.accounts.trading_delegate.delegate);
+        Ok(())
+    }
+
+    /// Cancel several of the caller's escrow-funded orders at once
+    /// 
+    /// Remaining accounts come in groups of four per order:
+    /// `[order, escrow, escrow_token_account, refund_token_account]`, where
+    /// the refund account is the caller's account for the escrowed mint.
+    /// Every escrow is refunded in full; one bad group fails the whole batch.
+    pub fn cancel_orders<'info>(ctx: Context<'_, '_, 'info, 'info, CancelOrders<'info>>) -> Result<()> {
+        let clock = Clock::get()?;
+        let (order_ids, client_order_ids) = cancel_order_batch(
+            &ctx.accounts.token_program.to_account_info(),
+            &mut ctx.accounts.order_book,
+            Some(ctx.accounts.owner.key()),
+            ctx.remaining_accounts,
+            clock.unix_timestamp,
+        )?;
+
+        emit!(OrdersBulkCancelled {
+            order_book: ctx.accounts.order_book.key(),
+            caller: ctx.accounts.owner.key(),
+            order_ids: order_ids.clone(),
+            client_order_ids,
+            forced: false,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Cancelled {} orders", order_ids.len());
+        Ok(())
+    }
+
+    /// Cancel any escrow-funded orders of a book (authority only)
+    /// 
+    /// Meant for winding a book down. Same remaining accounts as
+    /// `cancel_orders`, but orders may belong to anyone and each refund
+    /// account must be owned by that order's owner, never the authority's.
+    pub fn force_cancel_orders<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ForceCancelOrders<'info>>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let (order_ids, client_order_ids) = cancel_order_batch(
+            &ctx.accounts.token_program.to_account_info(),
+            &mut ctx.accounts.order_book,
+            None,
+            ctx.remaining_accounts,
+            clock.unix_timestamp,
+        )?;
+
+        emit!(OrdersBulkCancelled {
+            order_book: ctx.accounts.order_book.key(),
+            caller: ctx.accounts.authority.key(),
+            order_ids: order_ids.clone(),
+            client_order_ids,
+            forced: true,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Force-cancelled {} orders", order_ids.len());
+        Ok(())
+    }
+
+    /// Start retiring an order book (authority only)
+    /// 
+    /// Stops new orders for good and opens the book to the permissionless
+    /// `wind_down_orders` crank. In-flight matches can still settle.
+    pub fn start_decommission(ctx: Context<StartDecommission>) -> Result<()> {
+        let clock = Clock::get()?;
+        let order_book = &mut ctx.accounts.order_book;
+        order_book.is_active = false;
+        order_book.decommissioned_at = clock.unix_timestamp;
+
+        emit!(DecommissionStarted {
+            order_book: order_book.key(),
+            closed_orders: order_book.closed_orders,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!(
+            "Order book decommissioning: {} orders closed so far",
+            order_book.closed_orders
+        );
+        Ok(())
+    }
+
+    /// Refund and close orders of a decommissioning book (permissionless)
+    /// 
+    /// Remaining accounts come in groups of five per order:
+    /// `[order, escrow, escrow_token_account, refund, owner]`. For an
+    /// escrow-funded order `refund` is the owner's token account for the
+    /// escrowed mint; the escrow is refunded and its token account closed.
+    /// For a balance-backed order `escrow_token_account` is the book vault and
+    /// `refund` the owner's `UserBalance`, which gets its lock back. The order
+    /// and escrow accounts are then closed, their rent going to `owner`.
+    pub fn wind_down_orders<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WindDownOrders<'info>>,
+    ) -> Result<()> {
+        let groups = ctx.remaining_accounts.chunks_exact(5);
+        require!(
+            !ctx.remaining_accounts.is_empty() && groups.remainder().is_empty(),
+            ShadowSwapError::InvalidWindDown
+        );
+
+        let clock = Clock::get()?;
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let order_book = &mut ctx.accounts.order_book;
+        let mut order_ids = Vec::with_capacity(groups.len());
+        let mut client_order_ids = Vec::with_capacity(groups.len());
+        let mut refunded = 0u64;
+        for group in groups {
+            let (order_info, escrow_info, escrow_token_info, refund_info, owner_info) =
+                (&group[0], &group[1], &group[2], &group[3], &group[4]);
+
+            let order: Account<'info, EncryptedOrder> = Account::try_from(order_info)?;
+            require!(
+                order.order_book == order_book.key(),
+                ShadowSwapError::InvalidOrderBook
+            );
+            require!(
+                order.status != ORDER_STATUS_MATCHED_PENDING,
+                ShadowSwapError::InvalidOrderStatus
+            );
+            let mut escrow: Account<'info, Escrow> = Account::try_from(escrow_info)?;
+            require!(
+                escrow_info.key() == order.escrow && escrow_token_info.key() == escrow.token_account,
+                ShadowSwapError::InvalidEscrow
+            );
+            require!(
+                owner_info.key() == order.owner,
+                ShadowSwapError::InvalidRefundAccount
+            );
+
+            if order.user_balance == Pubkey::default() {
+                refunded += refund_escrow_remainder(&token_program, &escrow, escrow_token_info, refund_info)?;
+
+                let seeds = &[ESCROW_SEED, escrow.order.as_ref(), &[escrow.bump]];
+                let signer = &[&seeds[..]];
+                token::close_account(CpiContext::new_with_signer(
+                    token_program.clone(),
+                    CloseAccount {
+                        account: escrow_token_info.clone(),
+                        destination: owner_info.clone(),
+                        authority: escrow.to_account_info(),
+                    },
+                    signer,
+                ))?;
+            } else {
+                require!(
+                    refund_info.key() == order.user_balance,
+                    ShadowSwapError::InvalidRefundAccount
+                );
+                let mut user_balance: Account<'info, UserBalance> = Account::try_from(refund_info)?;
+                let lock_quote = escrow.token_mint == order_book.quote_mint;
+                release_balance_lock(&mut user_balance, &mut escrow, lock_quote)?;
+                user_balance.exit(&crate::ID)?;
+            }
+
+            if order.status == ORDER_STATUS_ACTIVE || order.status == ORDER_STATUS_PARTIAL {
+                order_book.closed_orders = order_book
+                    .closed_orders
+                    .checked_add(1)
+                    .ok_or(ShadowSwapError::NumericalOverflow)?;
+            }
+
+            order_ids.push(order.order_id);
+            client_order_ids.push(order.client_order_id);
+            order.close(owner_info.clone())?;
+            escrow.close(owner_info.clone())?;
+        }
+
+        emit!(OrdersWoundDown {
+            order_book: order_book.key(),
+            order_ids: order_ids.clone(),
+            client_order_ids,
+            refunded,
+            closed_orders: order_book.closed_orders,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!(
+            "Wound down {} orders, {} orders closed so far",
+            order_ids.len(),
+            order_book.closed_orders
+        );
+        Ok(())
+    }
+
+    /// Close a fully wound-down order book (authority only)
+    /// 
+    /// Remaining accounts are all of the book's order shards, in index order.
+    /// Their order counts must add up to `closed_orders` (no active orders
+    /// left). Both `book_vault` PDAs must be empty, so users withdraw their
+    /// balances first; vaults that were never initialized are skipped. The
+    /// book must have no LP vault, no sealed auction round left to settle
+    /// and no open RFQ. Shard, vault and book rent goes to the authority.
+    pub fn close_order_book<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseOrderBook<'info>>,
+    ) -> Result<()> {
+        let order_book = &ctx.accounts.order_book;
+        require!(
+            ctx.remaining_accounts.len() == order_book.order_shards as usize,
+            ShadowSwapError::InvalidOrderShard
+        );
+        require!(
+            order_book.live_sealed_auctions == 0,
+            ShadowSwapError::BookHasOpenAuctions
+        );
+        require!(order_book.open_rfqs == 0, ShadowSwapError::BookHasOpenRfqs);
+
+        let mut placed_orders = 0u64;
+        for (index, shard_info) in ctx.remaining_accounts.iter().enumerate() {
+            let order_shard: Account<'info, OrderShard> = Account::try_from(shard_info)?;
+            require!(
+                order_shard.order_book == order_book.key() && order_shard.shard as usize == index,
+                ShadowSwapError::InvalidOrderShard
+            );
+            placed_orders = placed_orders
+                .checked_add(order_shard.order_count)
+                .ok_or(ShadowSwapError::NumericalOverflow)?;
+            order_shard.close(ctx.accounts.authority.to_account_info())?;
+        }
+        require!(
+            placed_orders == order_book.closed_orders,
+            ShadowSwapError::BookHasActiveOrders
+        );
+
+        let seeds = &[
+            ORDER_BOOK_SEED,
+            order_book.base_mint.as_ref(),
+            order_book.quote_mint.as_ref(),
+            &[order_book.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let mut vaults_closed = 0u8;
+        for vault_info in [
+            ctx.accounts.base_vault.to_account_info(),
+            ctx.accounts.quote_vault.to_account_info(),
+        ] {
+            if vault_info.data_is_empty() {
+                continue;
+            }
+            let vault = TokenAccount::try_deserialize(&mut &vault_info.try_borrow_data()?[..])?;
+            require!(vault.amount == 0, ShadowSwapError::BookVaultNotEmpty);
+            token::close_account(CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                CloseAccount {
+                    account: vault_info,
+                    destination: ctx.accounts.authority.to_account_info(),
+                    authority: order_book.to_account_info(),
+                },
+                signer,
+            ))?;
+            vaults_closed += 1;
+        }
+
+        let clock = Clock::get()?;
+        emit!(OrderBookClosed {
+            order_book: order_book.key(),
+            authority: ctx.accounts.authority.key(),
+            vaults_closed,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Order book closed");
+        Ok(())
+    }
+
+    /// Add an order ID shard to a book (authority only)
+    /// 
+    /// Shards are numbered in creation order; `initialize_order_book` creates
+    /// shard 0. Clients spread submissions across shards (e.g. by owner key)
+    /// so concurrent orders don't contend for one counter.
+    pub fn add_order_shard(ctx: Context<AddOrderShard>) -> Result<()> {
+        let order_book = &mut ctx.accounts.order_book;
+        require!(
+            order_book.order_shards < MAX_ORDER_SHARDS,
+            ShadowSwapError::OrderShardLimit
+        );
+
+        let order_shard = &mut ctx.accounts.order_shard;
+        order_shard.order_book = order_book.key();
+        order_shard.shard = order_book.order_shards;
+        order_shard.order_count = 0;
+        order_shard.bump = ctx.bumps.order_shard;
+        order_book.order_shards += 1;
+
+        msg!("Order shard {} added", order_shard.shard);
+        Ok(())
+    }
+
+    /// Configure the oracle price band used to sanity check settlements
+    /// 
+    /// `oracle` is a Pyth price account; `Pubkey::default()` disables the
+    /// check. Settlement prices are compared to the oracle price rescaled to
+    /// `10^price_expo` units (e.g. -6 when execution prices are USDC
+    /// micro-units per whole base token).
+    pub fn configure_oracle(
+        ctx: Context<ConfigureOracle>,
+        oracle: Pubkey,
+        max_deviation_bps: u16,
+        max_staleness: i64,
+        price_expo: i32,
+    ) -> Result<()> {
+        require!(
+            max_deviation_bps <= 10000 && max_staleness >= 0,
+            ShadowSwapError::InvalidOracleConfiguration
+        );
+
+        let order_book = &mut ctx.accounts.order_book;
+        order_book.oracle = oracle;
+        order_book.oracle_max_deviation_bps = max_deviation_bps;
+        order_book.oracle_max_staleness = max_staleness;
+        order_book.oracle_price_expo = price_expo;
+
+        msg!(
+            "Oracle configured: {} (max deviation {} bps, max staleness {}s)",
+            oracle,
+            max_deviation_bps,
+            max_staleness
+        );
+        Ok(())
+    }
+
+    /// Create callback authorization for keeper
+    /// 
+    /// This allows the order book authority to authorize a keeper bot
+    /// to submit match results and execute settlements.
+    pub fn create_callback_auth(
+        ctx: Context<CreateCallbackAuth>,
+        expires_at: i64,
+    ) -> Result<()> {
+        let callback_auth = &mut ctx.accounts.callback_auth;
+        let clock = Clock::get()?;
+
+        require!(
+            expires_at > clock.unix_timestamp,
+            ShadowSwapError::CallbackAuthExpired
+        );
+
+        callback_auth.authority = ctx.accounts.keeper.key();
+        callback_auth.order_book = ctx.accounts.order_book.key();
+        callback_auth.nonce = 0;
+        callback_auth.expires_at = expires_at;
+        callback_auth.is_active = true;
+        callback_auth.created_at = clock.unix_timestamp;
+        callback_auth.bump = ctx.bumps.callback_auth;
+
+        msg!("Callback auth created for keeper: {}", ctx.accounts.keeper.key());
+        Ok(())
+    }
+
+    /// Submit match results and execute settlement
+    /// 
+    /// This instruction is called by the authorized keeper bot after matching orders
+    /// off-chain. It performs the actual token transfers to settle the trade.
+    /// 
+    /// Flow:
+    /// 1. Verify keeper authorization via callback_auth
+    /// 2. Check time-in-force rules (post-only must be the maker)
+    /// 3. Check execution_price against the oracle band (if configured)
+    /// 4. Calculate transfer amounts based on matched_amount and execution_price
+    /// 5. Transfer quote tokens (USDC) from buyer's escrow to seller
+    /// 6. Transfer base tokens (WSOL) from seller's escrow to buyer
+    /// 7. Update order statuses; IOC/FOK orders are closed out and the rest
+    ///    of their escrow refunded (FOK orders must fill completely)
+    /// 8. Emit settlement event
+    /// 
+    /// remaining_accounts: [buyer_escrow_token, seller_escrow_token,
+    /// buyer_token, seller_token] followed, when either order is IOC/FOK, by
+    /// [buyer_refund_token (quote), seller_refund_token (base)].
+    pub fn submit_match_results<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SubmitMatchResults<'info>>,
+        match_input: MatchResultInput,
+    ) -> Result<()> {
+        let callback_auth = &ctx.accounts.callback_auth;
+        let clock = Clock::get()?;
+
+        // Verify callback authorization
+        require!(
+            callback_auth.is_active,
+            ShadowSwapError::UnauthorizedCallback
+        );
+        require!(
+            callback_auth.expires_at > clock.unix_timestamp,
+            ShadowSwapError::CallbackAuthExpired
+        );
+        require!(
+            callback_auth.authority == ctx.accounts.keeper.key(),
+            ShadowSwapError::UnauthorizedCallback
+        );
+
+        let buyer_order = &mut ctx.accounts.buyer_order;
+        let seller_order = &mut ctx.accounts.seller_order;
+
+        // Verify orders are active or partially filled
+        require!(
+            buyer_order.status == ORDER_STATUS_ACTIVE || buyer_order.status == ORDER_STATUS_PARTIAL,
+            ShadowSwapError::InvalidOrderStatus
+        );
+        require!(
+            seller_order.status == ORDER_STATUS_ACTIVE || seller_order.status == ORDER_STATUS_PARTIAL,
+            ShadowSwapError::InvalidOrderStatus
+        );
+
+        // Post-only orders must be the maker (the earlier order sets the price)
+        check_post_only(buyer_order, seller_order)?;
+
+        // Final statuses per time-in-force (fails the whole settlement if an
+        // FOK order would be partially filled)
+        let buyer_status = status_after_fill(buyer_order.time_in_force, match_input.buyer_remaining)?;
+        let seller_status = status_after_fill(seller_order.time_in_force, match_input.seller_remaining)?;
+
+        // Parse remaining_accounts
+        // Expected order: [buyer_escrow_token, seller_escrow_token, buyer_token, seller_token,
+        //                  (buyer_refund_token, seller_refund_token)]
+        require!(
+            ctx.remaining_accounts.len() == 4 || ctx.remaining_accounts.len() == 6,
+            ShadowSwapError::InvalidTokenMint
+        );
+        
+        let buyer_escrow_token_info = ctx.remaining_accounts[0].clone();
+        let seller_escrow_token_info = ctx.remaining_accounts[1].clone();
+        let buyer_token_info = ctx.remaining_accounts[2].clone();
+        let seller_token_info = ctx.remaining_accounts[3].clone();
+
+        // Verify token accounts match escrow expectations
+        require!(
+            buyer_escrow_token_info.key() == ctx.accounts.buyer_escrow.token_account,
+            ShadowSwapError::InvalidEscrow
+        );
+        require!(
+            seller_escrow_token_info.key() == ctx.accounts.seller_escrow.token_account,
+            ShadowSwapError::InvalidEscrow
+        );
+
+        // Reject fills priced too far from the oracle
+        check_oracle_band(
+            &ctx.accounts.order_book,
+            ctx.accounts.oracle.as_ref(),
+            match_input.execution_price,
+            clock.unix_timestamp,
+        )?;
+
+        // Children of parent orders cannot trade past their escrowed slice
+        let buyer_escrowed = Account::<TokenAccount>::try_from(&ctx.remaining_accounts[0])?.amount;
+        let seller_escrowed = Account::<TokenAccount>::try_from(&ctx.remaining_accounts[1])?.amount;
+        check_child_slice(
+            buyer_order,
+            true,
+            buyer_escrowed,
+            match_input.matched_amount,
+            match_input.buyer_remaining,
+            match_input.execution_price,
+        )?;
+        check_child_slice(
+            seller_order,
+            false,
+            seller_escrowed,
+            match_input.matched_amount,
+            match_input.seller_remaining,
+            match_input.execution_price,
+        )?;
+
+        // Calculate transfer amounts
+        let quote_amount = quote_amount_for(match_input.matched_amount, match_input.execution_price)?;
+
+        msg!(
+            "Settling match: buyer={}, seller={}, amount={}, price={}, quote_total={}",
+            match_input.buyer_pubkey,
+            match_input.seller_pubkey,
+            match_input.matched_amount,
+            match_input.execution_price,
+            quote_amount
+        );
+
+        // Transfer quote tokens (USDC) from buyer's escrow to seller
+        let buyer_order_key = buyer_order.key();
+        let buyer_escrow_seeds = &[
+            ESCROW_SEED,
+            buyer_order_key.as_ref(),
+            &[ctx.accounts.buyer_escrow.bump],
+        ];
+        let buyer_escrow_signer = &[&buyer_escrow_seeds[..]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: buyer_escrow_token_info,
+                    to: seller_token_info,
+                    authority: ctx.accounts.buyer_escrow.to_account_info(),
+                },
+                buyer_escrow_signer,
+            ),
+            quote_amount,
+        )?;
+
+        // Transfer base tokens (WSOL) from seller's escrow to buyer
+        let seller_order_key = seller_order.key();
+        let seller_escrow_seeds = &[
+            ESCROW_SEED,
+            seller_order_key.as_ref(),
+            &[ctx.accounts.seller_escrow.bump],
+        ];
+        let seller_escrow_signer = &[&seller_escrow_seeds[..]];
+
+        let base_amount = match_input.matched_amount;
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: seller_escrow_token_info,
+                    to: buyer_token_info,
+                    authority: ctx.accounts.seller_escrow.to_account_info(),
+                },
+                seller_escrow_signer,
+            ),
+            base_amount,
+        )?;
+
+        // IOC/FOK orders never rest: refund whatever is left in their escrow
+        let token_program_info = ctx.accounts.token_program.to_account_info();
+        if is_immediate(buyer_order.time_in_force) {
+            let refund_info = ctx
+                .remaining_accounts
+                .get(4)
+                .ok_or(ShadowSwapError::MissingRefundAccount)?;
+            refund_escrow_remainder(
+                &token_program_info,
+                &ctx.accounts.buyer_escrow,
+                &ctx.remaining_accounts[0],
+                refund_info,
+            )?;
+        }
+        if is_immediate(seller_order.time_in_force) {
+            let refund_info = ctx
+                .remaining_accounts
+                .get(5)
+                .ok_or(ShadowSwapError::MissingRefundAccount)?;
+            refund_escrow_remainder(
+                &token_program_info,
+                &ctx.accounts.seller_escrow,
+                &ctx.remaining_accounts[1],
+                refund_info,
+            )?;
+        }
+
+        // Update order statuses
+        buyer_order.status = buyer_status;
+        buyer_order.updated_at = clock.unix_timestamp;
+
+        seller_order.status = seller_status;
+        seller_order.updated_at = clock.unix_timestamp;
+
+        // Update order book
+        let order_book = &mut ctx.accounts.order_book;
+        order_book.closed_orders = order_book
+            .closed_orders
+            .checked_add(2) // Both orders are now filled or cancelled
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+        order_book.last_trade_at = clock.unix_timestamp;
+
+        // Emit settlement event
+        emit!(TradeSettled {
+            order_book: order_book.key(),
+            buyer: match_input.buyer_pubkey,
+            seller: match_input.seller_pubkey,
+            buyer_order_id: buyer_order.order_id,
+            seller_order_id: seller_order.order_id,
+            buyer_client_order_id: buyer_order.client_order_id,
+            seller_client_order_id: seller_order.client_order_id,
+            base_amount: match_input.matched_amount,
+            quote_amount,
+            execution_price: match_input.execution_price,
+            timestamp: clock.unix_timestamp,
+        });
+
+        // IOC remainders were refunded above: report those orders as cancelled
+        for (order, order_key) in [(&buyer_order, buyer_order_key), (&seller_order, seller_order_key)] {
+            if order.status == ORDER_STATUS_CANCELLED {
+                emit!(OrderCancelled {
+                    order_book: order_book.key(),
+                    order: order_key,
+                    owner: order.owner,
+                    order_id: order.order_id,
+                    client_order_id: order.client_order_id,
+                    timestamp: clock.unix_timestamp,
+                });
+            }
+        }
+
+        msg!(
+            "Trade settled: buyer_order={}, seller_order={}, base={}, quote={}",
+            buyer_order.order_id,
+            seller_order.order_id,
+            match_input.matched_amount,
+            quote_amount
+        );
+
+        Ok(())
+    }
+}
+
+/// Reject a match where a post-only order would be the taker
+///
+/// The earlier order is the maker and sets the price.
+fn check_post_only(buyer_order: &EncryptedOrder, seller_order: &EncryptedOrder) -> Result<()> {
+    let buyer_is_maker = buyer_order.created_at < seller_order.created_at;
+    require!(
+        buyer_order.time_in_force != TIME_IN_FORCE_POST_ONLY || buyer_is_maker,
+        ShadowSwapError::PostOnlyWouldTake
+    );
+    require!(
+        seller_order.time_in_force != TIME_IN_FORCE_POST_ONLY || !buyer_is_maker,
+        ShadowSwapError::PostOnlyWouldTake
+    );
+    Ok(())
+}
+
+/// Reject an execution price too far from the book's oracle, if it has one
+fn check_oracle_band(
+    order_book: &OrderBook,
+    oracle: Option<&UncheckedAccount>,
+    execution_price: u64,
+    now: i64,
+) -> Result<()> {
+    if order_book.oracle == Pubkey::default() {
+        return Ok(());
+    }
+
+    let oracle_info = oracle.ok_or(ShadowSwapError::OracleNotConfigured)?;
+    let reference_price = oracle_reference_price(order_book, oracle_info, now)?;
+    let deviation = oracle::deviation_bps(execution_price, reference_price)?;
+    require!(
+        deviation <= order_book.oracle_max_deviation_bps as u64,
+        ShadowSwapError::PriceOutsideOracleBand
+    );
+    Ok(())
+}
+
+/// Quote amount paid for `matched_amount` base units at `execution_price`
+///
+/// matched_amount is in base token (WSOL) smallest units (lamports) and
+/// execution_price is quote tokens (USDC) smallest units per base token, so
+/// dividing by the base decimals factor gives quote smallest units.
+fn quote_amount_for(matched_amount: u64, execution_price: u64) -> Result<u64> {
+    let quote_amount = (matched_amount as u128)
+        .checked_mul(execution_price as u128)
+        .ok_or(ShadowSwapError::NumericalOverflow)?
+        .checked_div(BASE_DECIMALS_FACTOR)
+        .ok_or(ShadowSwapError::NumericalOverflow)?;
+    u64::try_from(quote_amount).map_err(|_| error!(ShadowSwapError::NumericalOverflow))
+}
+
+/// Move `amount` of a user balance from free to locked
+fn lock_balance(user_balance: &mut UserBalance, quote: bool, amount: u64) -> Result<()> {
+    let (free, locked) = if quote {
+        (&mut user_balance.quote_free, &mut user_balance.quote_locked)
+    } else {
+        (&mut user_balance.base_free, &mut user_balance.base_locked)
+    };
+    *free = free
+        .checked_sub(amount)
+        .ok_or(ShadowSwapError::InsufficientFreeBalance)?;
+    *locked = locked
+        .checked_add(amount)
+        .ok_or(ShadowSwapError::NumericalOverflow)?;
+    Ok(())
+}
+
+/// Return whatever an order still has locked to the owner's free balance
+fn release_balance_lock(user_balance: &mut UserBalance, escrow: &mut Escrow, quote: bool) -> Result<()> {
+    let amount = escrow.locked_amount;
+    let (free, locked) = if quote {
+        (&mut user_balance.quote_free, &mut user_balance.quote_locked)
+    } else {
+        (&mut user_balance.base_free, &mut user_balance.base_locked)
+    };
+    *locked = locked
+        .checked_sub(amount)
+        .ok_or(ShadowSwapError::NumericalOverflow)?;
+    *free = free
+        .checked_add(amount)
+        .ok_or(ShadowSwapError::NumericalOverflow)?;
+    escrow.locked_amount = 0;
+    Ok(())
+}
+
+/// Pay `amount` out of an order's balance lock
+fn consume_lock(user_balance: &mut UserBalance, escrow: &mut Escrow, quote: bool, amount: u64) -> Result<()> {
+    escrow.locked_amount = escrow
+        .locked_amount
+        .checked_sub(amount)
+        .ok_or(ShadowSwapError::InsufficientEscrowFunds)?;
+    let locked = if quote {
+        &mut user_balance.quote_locked
+    } else {
+        &mut user_balance.base_locked
+    };
+    *locked = locked
+        .checked_sub(amount)
+        .ok_or(ShadowSwapError::NumericalOverflow)?;
+    Ok(())
+}
+
+/// Credit `amount` to the free side of a user balance
+fn credit_free_balance(user_balance: &mut UserBalance, quote: bool, amount: u64) -> Result<()> {
+    let free = if quote {
+        &mut user_balance.quote_free
+    } else {
+        &mut user_balance.base_free
+    };
+    *free = free
+        .checked_add(amount)
+        .ok_or(ShadowSwapError::NumericalOverflow)?;
+    Ok(())
+}
+
+/// Update a balance-backed order after a fill
+///
+/// With nothing remaining the order is filled and its leftover lock returned;
+/// otherwise it stays partially filled. Returns 1 if the order was filled.
+fn finish_balance_fill(
+    order: &mut EncryptedOrder,
+    user_balance: &mut UserBalance,
+    escrow: &mut Escrow,
+    quote: bool,
+    remaining: u64,
+    now: i64,
+) -> Result<u64> {
+    order.updated_at = now;
+    if remaining == 0 {
+        release_balance_lock(user_balance, escrow, quote)?;
+        order.status = ORDER_STATUS_FILLED;
+        Ok(1)
+    } else {
+        order.status = ORDER_STATUS_PARTIAL;
+        Ok(0)
+    }
+}
+
+/// Base units bought with `quote_amount` at `execution_price`
+fn base_amount_for(quote_amount: u64, execution_price: u64) -> Result<u64> {
+    let base_amount = (quote_amount as u128)
+        .checked_mul(BASE_DECIMALS_FACTOR)
+        .ok_or(ShadowSwapError::NumericalOverflow)?
+        .checked_div(execution_price as u128)
+        .ok_or(ShadowSwapError::NumericalOverflow)?;
+    u64::try_from(base_amount).map_err(|_| error!(ShadowSwapError::NumericalOverflow))
+}
+
+/// Commitment to an order's fallback limit price
+fn fallback_commitment(limit_price: u64, salt: &[u8; 32]) -> [u8; 32] {
+    hashv(&[&limit_price.to_le_bytes(), salt]).to_bytes()
+}
+
+/// Book's oracle price in execution price units, rejecting stale prices
+fn oracle_reference_price(order_book: &OrderBook, oracle_info: &AccountInfo, now: i64) -> Result<u64> {
+    require!(
+        order_book.oracle != Pubkey::default(),
+        ShadowSwapError::OracleNotConfigured
+    );
+    let oracle_price = PythPriceAccount::read_price(oracle_info)?;
+    oracle_price.ensure_fresh(now, order_book.oracle_max_staleness)?;
+    oracle_price.scaled_to(order_book.oracle_price_expo)
+}
+
+/// Value in quote units of a base/quote inventory at `price`
+fn lp_inventory_value(base_amount: u64, quote_amount: u64, price: u64) -> Result<u128> {
+    Ok(quote_amount_for(base_amount, price)? as u128 + quote_amount as u128)
+}
+
+/// `bps` basis points of `amount`, rounded down
+fn bps_of(amount: u64, bps: u16) -> Result<u64> {
+    u64::try_from((amount as u128) * (bps as u128) / 10_000)
+        .map_err(|_| error!(ShadowSwapError::NumericalOverflow))
+}
+
+/// Quote amount for `base_amount` at `price`, rounded up
+///
+/// Used where the quote side pays, so rounding never favours the payer.
+fn quote_amount_ceil(base_amount: u64, price: u64) -> Result<u64> {
+    let quote_amount = (base_amount as u128)
+        .checked_mul(price as u128)
+        .and_then(|value| value.checked_add(BASE_DECIMALS_FACTOR - 1))
+        .ok_or(ShadowSwapError::NumericalOverflow)?
+        / BASE_DECIMALS_FACTOR;
+    u64::try_from(quote_amount).map_err(|_| error!(ShadowSwapError::NumericalOverflow))
+}
+
+/// Commitment to a sealed order's terms
+fn sealed_order_commitment(side: u8, price: u64, amount: u64, salt: &[u8; 32]) -> [u8; 32] {
+    hashv(&[&[side], &price.to_le_bytes(), &amount.to_le_bytes(), salt]).to_bytes()
+}
+
+/// Whether a revealed order trades at `price`
+fn bid_crosses(bid: &SealedBid, price: u64) -> bool {
+    if bid.side == ORDER_SIDE_BUY {
+        bid.price >= price
+    } else {
+        bid.price <= price
+    }
+}
+
+/// Uniform-price clearing of revealed sealed orders
+///
+/// The clearing price is the revealed limit price that maximises matched
+/// volume, then minimises the demand / supply imbalance, then is lowest.
+/// Buys at or above it and sells at or below it trade; the larger side is
+/// filled pro rata to size, with rounding dust handed out in reveal order so
+/// both sides fill exactly the matched volume. Sets each bid's `filled` and
+/// returns `(clearing_price, volume)`, or `(0, 0)` when nothing crosses.
+fn clear_uniform_price(bids: &mut [SealedBid]) -> Result<(u64, u64)> {
+    // (price, volume, imbalance, demand, supply)
+    let mut best: Option<(u64, u64, u64, u64, u64)> = None;
+
+    for candidate in bids.iter().map(|bid| bid.price) {
+        let mut demand = 0u64;
+        let mut supply = 0u64;
+        for bid in bids.iter().filter(|bid| bid_crosses(bid, candidate)) {
+            let total = if bid.side == ORDER_SIDE_BUY {
+                &mut demand
+            } else {
+                &mut supply
+            };
+            *total = total
+                .checked_add(bid.amount)
+                .ok_or(ShadowSwapError::NumericalOverflow)?;
+        }
+
+        let volume = demand.min(supply);
+        if volume == 0 {
+            continue;
+        }
+        let imbalance = demand.abs_diff(supply);
+        let better = match best {
+            None => true,
+            Some((price, best_volume, best_imbalance, _, _)) => {
+                volume > best_volume
+                    || (volume == best_volume
+                        && (imbalance < best_imbalance
+                            || (imbalance == best_imbalance && candidate < price)))
+            }
+        };
+        if better {
+            best = Some((candidate, volume, imbalance, demand, supply));
+        }
+    }
+
+    let Some((clearing_price, volume, _, demand, supply)) = best else {
+        return Ok((0, 0));
+    };
+
+    // Dust left to hand out per side, indexed by `side`
+    let mut dust = [volume, volume];
+    for bid in bids.iter_mut().filter(|bid| bid_crosses(bid, clearing_price)) {
+        let side_total = if bid.side == ORDER_SIDE_BUY { demand } else { supply };
+        bid.filled = u64::try_from((bid.amount as u128) * (volume as u128) / (side_total as u128))
+            .map_err(|_| error!(ShadowSwapError::NumericalOverflow))?;
+        dust[bid.side as usize] -= bid.filled;
+    }
+    for bid in bids.iter_mut().filter(|bid| bid_crosses(bid, clearing_price)) {
+        let extra = (bid.amount - bid.filled).min(dust[bid.side as usize]);
+        bid.filled += extra;
+        dust[bid.side as usize] -= extra;
+    }
+
+    Ok((clearing_price, volume))
+}
+
+/// Commitment to an RFQ quote's terms
+fn rfq_terms_commitment(price: u64, amount: u64, salt: &[u8; 32]) -> [u8; 32] {
+    hashv(&[&price.to_le_bytes(), &amount.to_le_bytes(), salt]).to_bytes()
+}
+
+/// Transfer `amount` out of an escrow-owned token account
+fn escrow_transfer<'info>(
+    token_program: &AccountInfo<'info>,
+    escrow: &Account<'info, Escrow>,
+    escrow_token: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let seeds = &[ESCROW_SEED, escrow.order.as_ref(), &[escrow.bump]];
+    let signer = &[&seeds[..]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            Transfer {
+                from: escrow_token.to_account_info(),
+                to: to.to_account_info(),
+                authority: escrow.to_account_info(),
+            },
+            signer,
+        ),
+        amount,
+    )
+}
+
+/// Check a session key may act with `scope` at `now`
+///
+/// The caller's account constraints already tie the delegate to the signer
+/// and the order owner.
+fn check_trading_delegate(trading_delegate: &TradingDelegate, scope: u8, now: i64) -> Result<()> {
+    require!(
+        trading_delegate.scopes & scope == scope,
+        ShadowSwapError::UnauthorizedDelegate
+    );
+    require!(
+        trading_delegate.expires_at > now,
+        ShadowSwapError::DelegateExpired
+    );
+    Ok(())
+}
+
+/// Cancel a batch of escrow-funded orders passed as remaining accounts
+///
+/// Takes `[order, escrow, escrow_token_account, refund_token_account]` per
+/// order. When `owner` is set every order must belong to it. Refunds always
+/// go to the order owner (checked by `refund_escrow_remainder`). Returns the
+/// cancelled order IDs and their client order IDs.
+fn cancel_order_batch<'info>(
+    token_program: &AccountInfo<'info>,
+    order_book: &mut Account<'info, OrderBook>,
+    owner: Option<Pubkey>,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    now: i64,
+) -> Result<(Vec<u64>, Vec<[u8; 16]>)> {
+    let groups = remaining_accounts.chunks_exact(4);
+    require!(
+        !remaining_accounts.is_empty() && groups.remainder().is_empty(),
+        ShadowSwapError::InvalidBulkCancel
+    );
+
+    let mut order_ids = Vec::with_capacity(groups.len());
+    let mut client_order_ids = Vec::with_capacity(groups.len());
+    for group in groups {
+        let mut order: Account<'info, EncryptedOrder> = Account::try_from(&group[0])?;
+        require!(
+            order.order_book == order_book.key(),
+            ShadowSwapError::InvalidOrderBook
+        );
+        require!(
+            owner.unwrap_or(order.owner) == order.owner,
+            ShadowSwapError::UnauthorizedCallback
+        );
+        require!(
+            order.status == ORDER_STATUS_ACTIVE || order.status == ORDER_STATUS_PARTIAL,
+            ShadowSwapError::InvalidOrderStatus
+        );
+        require!(
+            order.user_balance == Pubkey::default(),
+            ShadowSwapError::BalanceBackedOrder
+        );
+
+        let escrow: Account<'info, Escrow> = Account::try_from(&group[1])?;
+        require!(
+            group[1].key() == order.escrow && group[2].key() == escrow.token_account,
+            ShadowSwapError::InvalidEscrow
+        );
+        refund_escrow_remainder(token_program, &escrow, &group[2], &group[3])?;
+
+        order.status = ORDER_STATUS_CANCELLED;
+        order.updated_at = now;
+        order.exit(&crate::ID)?;
+        order_ids.push(order.order_id);
+        client_order_ids.push(order.client_order_id);
+    }
+
+    order_book.closed_orders = order_book
+        .closed_orders
+        .checked_add(order_ids.len() as u64)
+        .ok_or(ShadowSwapError::NumericalOverflow)?;
+    Ok((order_ids, client_order_ids))
+}
+
+/// Take the next order ID from a shard
+/// 
+/// IDs interleave across shards (`count * MAX_ORDER_SHARDS + shard`), so
+/// they stay unique book-wide without a shared counter.
+fn next_order_id(order_shard: &mut OrderShard) -> Result<u64> {
+    let order_id = order_shard
+        .order_count
+        .checked_mul(MAX_ORDER_SHARDS as u64)
+        .and_then(|id| id.checked_add(order_shard.shard as u64))
+        .ok_or(ShadowSwapError::NumericalOverflow)?;
+    order_shard.order_count = order_shard
+        .order_count
+        .checked_add(1)
+        .ok_or(ShadowSwapError::NumericalOverflow)?;
+    Ok(order_id)
+}
+
+/// Client order ID of a parent's child order: the child index, little-endian
+fn child_client_order_id(child_index: u64) -> [u8; 16] {
+    let mut client_order_id = [0u8; 16];
+    client_order_id[..8].copy_from_slice(&child_index.to_le_bytes());
+    client_order_id
+}
+
+/// Whether the time-in-force closes the order out after its first match
+fn is_immediate(time_in_force: u8) -> bool {
+    time_in_force == TIME_IN_FORCE_IOC || time_in_force == TIME_IN_FORCE_FOK
+}
+
+/// Status of an order after a fill leaving `remaining` base units unfilled
+/// 
+/// FOK orders must fill completely; the unfilled part of an IOC order is
+/// cancelled. GTC and post-only orders keep the existing settle-as-filled
+/// behaviour.
+fn status_after_fill(time_in_force: u8, remaining: u64) -> Result<u8> {
+    match time_in_force {
+        TIME_IN_FORCE_FOK => {
+            require!(remaining == 0, ShadowSwapError::FillOrKillNotFilled);
+            Ok(ORDER_STATUS_FILLED)
+        }
+        TIME_IN_FORCE_IOC if remaining > 0 => Ok(ORDER_STATUS_CANCELLED),
+        _ => Ok(ORDER_STATUS_FILLED),
+    }
+}
+
+/// Cap a child order's fill and remaining at its escrowed slice
+/// 
+/// Children carry no encrypted amount of their own, so what they can still
+/// trade is whatever their escrow covers at the execution price. The keeper
+/// may not fill or leave open more than that; other orders pass unchecked.
+fn check_child_slice(
+    order: &EncryptedOrder,
+    order_buys: bool,
+    escrowed: u64,
+    matched_amount: u64,
+    remaining: u64,
+    execution_price: u64,
+) -> Result<()> {
+    if order.parent == Pubkey::default() {
+        return Ok(());
+    }
+    let base = matched_amount
+        .checked_add(remaining)
+        .ok_or(ShadowSwapError::NumericalOverflow)?;
+    let needed = if order_buys {
+        quote_amount_for(base, execution_price)?
+    } else {
+        base
+    };
+    require!(needed <= escrowed, ShadowSwapError::ChildSliceExceeded);
+    Ok(())
+}
+
+/// Size of the next child slice of a parent order
+/// 
+/// Without jitter this is `slice_size`. With jitter the slice is drawn from
+/// `slice_size * (1 +/- slice_jitter_bps / 10000)` using a hash of the parent,
+/// its child count and the current slot. This is only meant to break up the
+/// visible escrow pattern, not to be unpredictable to validators.
+fn next_slice_amount(parent: &ParentOrder, parent_key: &Pubkey, slot: u64) -> Result<u64> {
+    if parent.slice_jitter_bps == 0 {
+        return Ok(parent.slice_size);
+    }
+
+    let hash = anchor_lang::solana_program::hash::hashv(&[
+        parent_key.as_ref(),
+        &parent.child_count.to_le_bytes(),
+        &slot.to_le_bytes(),
+    ]);
+    let roll = u64::from_le_bytes(hash.to_bytes()[..8].try_into().unwrap());
+
+    let max_jitter = (parent.slice_size as u128)
+        .checked_mul(parent.slice_jitter_bps as u128)
+        .ok_or(ShadowSwapError::NumericalOverflow)?
+        / 10_000;
+    // Uniform in [slice_size - max_jitter, slice_size + max_jitter]
+    let offset = (roll as u128) % (max_jitter * 2 + 1);
+    let slice = (parent.slice_size as u128 + offset)
+        .checked_sub(max_jitter)
+        .ok_or(ShadowSwapError::NumericalOverflow)?;
+    u64::try_from(slice.max(1)).map_err(|_| error!(ShadowSwapError::NumericalOverflow))
+}
+
+/// Transfer the tokens left in an order's escrow back to the order owner
+/// Require `request` to be the vault's next withdrawal and past its delay
+fn check_withdrawal_due(lp_vault: &LpVault, request: &LpWithdrawalRequest, now: i64) -> Result<()> {
+    require!(
+        request.index == lp_vault.next_withdrawal,
+        ShadowSwapError::WithdrawalNotNext
+    );
+    require!(
+        now >= request.requested_at.saturating_add(lp_vault.params.withdrawal_delay),
+        ShadowSwapError::WithdrawalNotReady
+    );
+    Ok(())
+}
+
+/// Base and quote paid for `shares` out of `supply`: the pro-rata share of
+/// each vault balance, rounded down
+fn lp_withdrawal_payout(shares: u64, supply: u64, base_balance: u64, quote_balance: u64) -> Result<(u64, u64)> {
+    let pro_rata = |balance: u64| -> Result<u64> {
+        u64::try_from((balance as u128) * (shares as u128) / (supply as u128))
+            .map_err(|_| error!(ShadowSwapError::NumericalOverflow))
+    };
+    Ok((pro_rata(base_balance)?, pro_rata(quote_balance)?))
+}
+
+/// Burn a withdrawal's pending shares and move its payout out of the vault
+///
+/// Each payout is `(amount, vault token account, receiving account)`.
+fn pay_lp_withdrawal<'info>(
+    token_program: &AccountInfo<'info>,
+    lp_vault: &Account<'info, LpVault>,
+    share_mint: &AccountInfo<'info>,
+    pending_shares: &AccountInfo<'info>,
+    shares: u64,
+    payouts: [(u64, AccountInfo<'info>, AccountInfo<'info>); 2],
+) -> Result<()> {
+    let seeds = &[LP_VAULT_SEED, lp_vault.order_book.as_ref(), &[lp_vault.bump]];
+    let signer = &[&seeds[..]];
+
+    token::burn(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            Burn {
+                mint: share_mint.clone(),
+                from: pending_shares.clone(),
+                authority: lp_vault.to_account_info(),
+            },
+            signer,
+        ),
+        shares,
+    )?;
+
+    for (amount, from, to) in payouts {
+        if amount > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    token_program.clone(),
+                    Transfer {
+                        from,
+                        to,
+                        authority: lp_vault.to_account_info(),
+                    },
+                    signer,
+                ),
+                amount,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn refund_escrow_remainder<'info>(
+    token_program: &AccountInfo<'info>,
+    escrow: &Account<'info, Escrow>,
+    escrow_token_info: &'info AccountInfo<'info>,
+    refund_info: &'info AccountInfo<'info>,
+) -> Result<u64> {
+    let escrow_token: Account<'info, TokenAccount> = Account::try_from(escrow_token_info)?;
+    let refund_token: Account<'info, TokenAccount> = Account::try_from(refund_info)?;
+    require!(
+        refund_token.owner == escrow.owner && refund_token.mint == escrow.token_mint,
+        ShadowSwapError::InvalidRefundAccount
+    );
+
+    let amount = escrow_token.amount;
+    if amount == 0 {
+        return Ok(0);
+    }
+
+    let seeds = &[ESCROW_SEED, escrow.order.as_ref(), &[escrow.bump]];
+    let signer = &[&seeds[..]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            Transfer {
+                from: escrow_token_info.clone(),
+                to: refund_info.clone(),
+                authority: escrow.to_account_info(),
+            },
+            signer,
+        ),
+        amount,
+    )?;
+
+    msg!("Refunded {} from escrow of order {}", amount, escrow.order);
+    Ok(amount)
+}
+
+// ============================================================================
+// Account Structures
+// ============================================================================
+
+/// EncryptedOrder - Stores individual encrypted order data
+/// 
+/// This account stores fully encrypted order information. The on-chain program
+/// never sees plaintext order details (price, amount, side). All order data
+/// is encrypted client-side and stored as a cipher payload.
+#[account]
+pub struct EncryptedOrder {
+    /// Order owner's public key
+    pub owner: Pubkey,
+    
+    /// Order book this order belongs to
+    pub order_book: Pubkey,
+    
+    /// Encrypted order payload containing:
+    /// - Order side (buy/sell) - encrypted
+    /// - Price - encrypted
+    /// - Amount - encrypted
+    /// - Other order parameters - encrypted
+    /// Maximum size: 512 bytes for encrypted data
+    pub cipher_payload: Vec<u8>,
+    
+    /// Order status (1 = active, 2 = partially filled, 3 = filled, 4 = cancelled)
+    /// This can be public as it doesn't reveal order details
+    pub status: u8,
+    
+    /// Encrypted remaining amount (updates as order fills)
+    pub encrypted_remaining: Vec<u8>,
+    
+    /// Escrow account holding the order's funds
+    pub escrow: Pubkey,
+    
+    /// Order creation timestamp
+    pub created_at: i64,
+    
+    /// Last update timestamp
+    pub updated_at: i64,
+    
+    /// Order ID (unique per book, assigned by an `OrderShard`)
+    pub order_id: u64,
+    
+    /// Client-chosen order ID the order PDA is seeded with, making
+    /// submission idempotent (child orders: the child index, little-endian)
+    pub client_order_id: [u8; 16],
+    
+    /// Time-in-force (0 = GTC, 1 = IOC, 2 = FOK, 3 = post-only)
+    /// Public so settlement can enforce it; side and quantity stay encrypted
+    pub time_in_force: u8,
+    
+    /// Parent order that released this order (default = standalone order)
+    pub parent: Pubkey,
+    
+    /// User balance the order is funded from (default = own escrow tokens)
+    pub user_balance: Pubkey,
+    
+    /// Route the order's proceeds pass through (default = not routed)
+    pub route: Pubkey,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// ParentOrder - Escrows a large TWAP / iceberg order
+/// 
+/// Holds the full amount and releases it as ordinary child `EncryptedOrder`s
+/// so only slice-sized escrows ever appear on the book.
+#[account]
+pub struct ParentOrder {
+    /// Order owner
+    pub owner: Pubkey,
+    
+    /// Order book children are released into
+    pub order_book: Pubkey,
+    
+    /// Mint of the escrowed token
+    pub token_mint: Pubkey,
+    
+    /// Token account holding the unreleased amount (owned by this PDA)
+    pub escrow_token_account: Pubkey,
+    
+    /// Encrypted order payload copied to every child
+    pub cipher_payload: Vec<u8>,
+    
+    /// Encrypted amount copied to every child
+    pub encrypted_amount: Vec<u8>,
+    
+    /// Total amount escrowed at creation
+    pub total_amount: u64,
+    
+    /// Amount moved into children so far
+    pub released_amount: u64,
+    
+    /// Target amount escrowed by each child
+    pub slice_size: u64,
+    
+    /// Random slice variation in basis points of `slice_size` (0 = fixed)
+    pub slice_jitter_bps: u16,
+    
+    /// Seconds between child releases
+    pub release_interval: i64,
+    
+    /// Earliest time the next child can be released
+    pub next_release_at: i64,
+    
+    /// Number of children released
+    pub child_count: u64,
+    
+    /// Parent status (1 = active, 2 = completed, 3 = cancelled)
+    pub status: u8,
+    
+    /// Client-chosen nonce, part of the PDA seeds
+    pub parent_nonce: u64,
+    
+    /// Creation timestamp
+    pub created_at: i64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// OrderBook - Manages the order book for a trading pair
+/// 
+/// For MVP, this handles SOL/USDC pair. The structure is designed to be
+/// extensible for multi-token support in future phases.
+#[account]
+pub struct OrderBook {
+    /// Authority that can manage the order book
+    pub authority: Pubkey,
+    
+    /// Base token mint (SOL for MVP)
+    pub base_mint: Pubkey,
+    
+    /// Quote token mint (USDC for MVP)
+    pub quote_mint: Pubkey,
+    
+    /// Number of order ID shards (`OrderShard` PDAs `0..order_shards`)
+    pub order_shards: u8,
+    
+    /// Orders filled, cancelled or wound down; the book's active orders are
+    /// its shards' `order_count` total minus this
+    pub closed_orders: u64,
+    
+    /// Total trading volume (base token) - encrypted
+    pub encrypted_volume_base: Vec<u8>,
+    
+    /// Total trading volume (quote token) - encrypted
+    pub encrypted_volume_quote: Vec<u8>,
+    
+    /// Order book creation timestamp
+    pub created_at: i64,
+    
+    /// Last trade timestamp
+    pub last_trade_at: i64,
+    
+    /// Fee basis points (e.g., 30 = 0.3%)
+    pub fee_bps: u16,
+    
+    /// Fee collector account
+    pub fee_collector: Pubkey,
+    
+    /// Minimum order size (base token, in lamports/smallest unit)
+    pub min_base_order_size: u64,
+    
+    /// Whether the order book is active
+    pub is_active: bool,
+    
+    /// Price oracle used to band settlement prices (default = no check)
+    pub oracle: Pubkey,
+    
+    /// Maximum execution price deviation from the oracle, in basis points
+    pub oracle_max_deviation_bps: u16,
+    
+    /// Maximum oracle price age in seconds
+    pub oracle_max_staleness: i64,
+    
+    /// Exponent of the book's execution price units
+    pub oracle_price_expo: i32,
+    
+    /// Swap program unmatched orders can fall back to (default = disabled)
+    pub fallback_amm: Pubkey,
+    
+    /// Number of commit-reveal auction rounds opened
+    pub sealed_auction_count: u64,
+    
+    /// Number of RFQs requested
+    pub rfq_count: u64,
+    
+    /// Sealed auction rounds opened and not yet fully settled
+    pub live_sealed_auctions: u64,
+    
+    /// RFQs neither filled nor cancelled
+    pub open_rfqs: u64,
+    
+    /// When decommissioning started (0 = live book)
+    pub decommissioned_at: i64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// Escrow - Holds funds for an order until it's matched or cancelled
+/// 
+/// Each order has an associated escrow account that holds either:
+/// - Base tokens (for sell orders)
+/// - Quote tokens (for buy orders)
+#[account]
+pub struct Escrow {
+    /// Order this escrow belongs to
+    pub order: Pubkey,
+    
+    /// Order owner
+    pub owner: Pubkey,
+    
+    /// Order book
+    pub order_book: Pubkey,
+    
+    /// Token account holding escrowed funds
+    /// This is a PDA-owned token account
+    pub token_account: Pubkey,
+    
+    /// Mint of the escrowed token
+    pub token_mint: Pubkey,
+    
+    /// Original encrypted amount deposited
+    pub encrypted_amount: Vec<u8>,
+    
+    /// Encrypted remaining amount (decreases as order fills)
+    pub encrypted_remaining: Vec<u8>,
+    
+    /// Amount locked in the owner's `UserBalance` (balance-backed orders only)
+    pub locked_amount: u64,
+    
+    /// Escrow creation timestamp
+    pub created_at: i64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// UserBalance - A user's trading balance on one order book
+///
+/// Tokens sit in the book's pooled vaults. Balance-backed orders lock part of
+/// the free balance instead of funding their own escrow, and settlement
+/// between them only moves amounts between these accounts.
+#[account]
+pub struct UserBalance {
+    /// Balance owner
+    pub owner: Pubkey,
+    
+    /// Order book
+    pub order_book: Pubkey,
+    
+    /// Base tokens available to withdraw or lock
+    pub base_free: u64,
+    
+    /// Base tokens locked by open sell orders
+    pub base_locked: u64,
+    
+    /// Quote tokens available to withdraw or lock
+    pub quote_free: u64,
+    
+    /// Quote tokens locked by open buy orders
+    pub quote_locked: u64,
+    
+    /// Creation timestamp
+    pub created_at: i64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// Route - Sends a balance-backed order's proceeds through a second book
+/// 
+/// Created against an order on the first book; settlement swaps the input
+/// token for the intermediate there and the intermediate for the output
+/// token on the second book in one instruction.
+#[account]
+pub struct Route {
+    /// Route owner (owner of the routed order)
+    pub owner: Pubkey,
+    
+    /// Routed order on the first book
+    pub order: Pubkey,
+    
+    /// Book of the first leg (input → intermediate)
+    pub first_order_book: Pubkey,
+    
+    /// Book of the second leg (intermediate → output)
+    pub second_order_book: Pubkey,
+    
+    /// Token passed from the first leg to the second
+    pub intermediate_mint: Pubkey,
+    
+    /// Token the owner receives
+    pub output_mint: Pubkey,
+    
+    /// Input amount locked by the order when the route was created
+    pub amount_in: u64,
+    
+    /// Minimum output for the full `amount_in`
+    pub min_amount_out: u64,
+    
+    /// Creation timestamp
+    pub created_at: i64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// FallbackConfig - Lets an order execute against an AMM after a deadline
+#[account]
+pub struct FallbackConfig {
+    /// Order the fallback applies to
+    pub order: Pubkey,
+    
+    /// Order owner
+    pub owner: Pubkey,
+    
+    /// Earliest time `fallback_swap` may run
+    pub deadline: i64,
+    
+    /// sha256(limit_price LE || salt), revealed by `fallback_swap`
+    pub limit_commitment: [u8; 32],
+    
+    /// Creation timestamp
+    pub created_at: i64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// LpVault - Backstop liquidity for one order book
+/// 
+/// Holds LP-deposited base and quote that the keeper can trade against
+/// unmatched orders. Ownership is tracked by an SPL share mint.
+#[account]
+pub struct LpVault {
+    /// Order book the vault backstops
+    pub order_book: Pubkey,
+    
+    /// Mint of the vault's share tokens (authority: this PDA)
+    pub share_mint: Pubkey,
+    
+    /// Base token inventory (owned by this PDA)
+    pub base_vault: Pubkey,
+    
+    /// Quote token inventory (owned by this PDA)
+    pub quote_vault: Pubkey,
+    
+    /// Shares of queued withdrawals (owned by this PDA)
+    pub pending_shares: Pubkey,
+    
+    /// Spread, exposure cap, fee share and withdrawal delay
+    pub params: LpVaultParams,
+    
+    /// Number of withdrawals ever queued (next request index)
+    pub withdrawal_count: u64,
+    
+    /// Index of the next withdrawal to process
+    pub next_withdrawal: u64,
+    
+    /// Creation timestamp
+    pub created_at: i64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// LpWithdrawalRequest - One queued LP withdrawal
+#[account]
+pub struct LpWithdrawalRequest {
+    /// Vault the shares are redeemed from
+    pub lp_vault: Pubkey,
+    
+    /// LP that queued the withdrawal (receives the closed account's rent)
+    pub owner: Pubkey,
+    
+    /// Position in the vault's withdrawal queue
+    pub index: u64,
+    
+    /// Shares held in the vault's pending account
+    pub shares: u64,
+    
+    /// Token account receiving the base payout
+    pub base_destination: Pubkey,
+    
+    /// Token account receiving the quote payout
+    pub quote_destination: Pubkey,
+    
+    /// Request timestamp
+    pub requested_at: i64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// SealedAuction - One commit-reveal auction round of an order book
+/// 
+/// Revealed orders are kept in `bids` in reveal order; their fills are set
+/// when the round is cleared.
+#[account]
+pub struct SealedAuction {
+    /// Order book
+    pub order_book: Pubkey,
+    
+    /// Round number on the order book
+    pub round: u64,
+    
+    /// Round-owned token account pooling base deposits
+    pub base_vault: Pubkey,
+    
+    /// Round-owned token account pooling quote deposits
+    pub quote_vault: Pubkey,
+    
+    /// End of the commit window
+    pub commit_end: i64,
+    
+    /// End of the reveal window
+    pub reveal_end: i64,
+    
+    /// Share of an unrevealed order's deposit forfeited, in basis points
+    pub penalty_bps: u16,
+    
+    /// Round status (1 = open, 2 = cleared)
+    pub status: u8,
+    
+    /// Number of committed orders
+    pub commit_count: u64,
+    
+    /// Number of orders paid out after clearing
+    pub settled_count: u64,
+    
+    /// Revealed orders
+    pub bids: Vec<SealedBid>,
+    
+    /// Uniform clearing price (0 = nothing crossed)
+    pub clearing_price: u64,
+    
+    /// Base amount matched at the clearing price
+    pub volume: u64,
+    
+    /// Creation timestamp
+    pub created_at: i64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// SealedOrder - A committed order in a commit-reveal round
+#[account]
+pub struct SealedOrder {
+    /// Auction round
+    pub auction: Pubkey,
+    
+    /// Order owner
+    pub owner: Pubkey,
+    
+    /// Commit sequence number within the round
+    pub index: u64,
+    
+    /// `sha256(side || price LE || amount LE || salt)`
+    pub commitment: [u8; 32],
+    
+    /// Mint of the deposit (base for sells, quote for buys)
+    pub deposit_mint: Pubkey,
+    
+    /// Amount deposited into the round's vault
+    pub deposit: u64,
+    
+    /// Whether the order was revealed
+    pub revealed: bool,
+    
+    /// Position in the round's `bids` once revealed
+    pub bid_index: u8,
+    
+    /// Commit timestamp
+    pub committed_at: i64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// RfqMaker - A market maker whitelisted for RFQs on an order book
+#[account]
+pub struct RfqMaker {
+    /// Order book
+    pub order_book: Pubkey,
+    
+    /// Maker wallet
+    pub maker: Pubkey,
+    
+    /// Maker's x25519 public key request sizes are encrypted to
+    pub encryption_key: [u8; 32],
+    
+    /// Whitelisting timestamp
+    pub added_at: i64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// RfqRequest - A taker's request for quotes
+/// 
+/// The taker's deposit sits in an `Escrow` seeded by the request.
+#[account]
+pub struct RfqRequest {
+    /// Order book
+    pub order_book: Pubkey,
+    
+    /// Taker wallet
+    pub taker: Pubkey,
+    
+    /// Sequential RFQ ID on the order book
+    pub rfq_id: u64,
+    
+    /// Taker side (0 = buy, 1 = sell)
+    pub side: u8,
+    
+    /// Makers asked to quote
+    pub makers: Vec<Pubkey>,
+    
+    /// Requested size, one ciphertext per maker in `makers` order
+    pub encrypted_size: Vec<u8>,
+    
+    /// Taker's escrow
+    pub escrow: Pubkey,
+    
+    /// Quotes can be submitted and accepted until this timestamp
+    pub expires_at: i64,
+    
+    /// Request status (1 = open, 2 = filled, 3 = cancelled)
+    pub status: u8,
+    
+    /// Number of quotes received
+    pub quote_count: u64,
+    
+    /// Quote the taker accepted (default = none)
+    pub accepted_quote: Pubkey,
+    
+    /// Creation timestamp
+    pub created_at: i64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// RfqQuote - A maker's answer to an RFQ
+/// 
+/// The maker's deposit sits in an `Escrow` seeded by the quote.
+#[account]
+pub struct RfqQuote {
+    /// RFQ being answered
+    pub request: Pubkey,
+    
+    /// Maker wallet
+    pub maker: Pubkey,
+    
+    /// Price, size and salt encrypted to the taker
+    pub encrypted_quote: Vec<u8>,
+    
+    /// `sha256(price LE || amount LE || salt)`
+    pub terms_commitment: [u8; 32],
+    
+    /// Maker's escrow
+    pub escrow: Pubkey,
+    
+    /// Quote can be accepted until this timestamp
+    pub expires_at: i64,
+    
+    /// Creation timestamp
+    pub created_at: i64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// TradingDelegate - A session key allowed to trade for an owner
+#[account]
+pub struct TradingDelegate {
+    /// Owner whose orders the delegate manages
+    pub owner: Pubkey,
+    
+    /// Session key
+    pub delegate: Pubkey,
+    
+    /// Allowed actions (`DELEGATE_SCOPE_*` bits)
+    pub scopes: u8,
+    
+    /// Authorization expiry timestamp
+    pub expires_at: i64,
+    
+    /// Maximum base escrow per placed order
+    pub max_base_per_order: u64,
+    
+    /// Maximum quote escrow per placed order
+    pub max_quote_per_order: u64,
+    
+    /// Creation timestamp
+    pub created_at: i64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// OrderShard - One of a book's order ID counters
+/// 
+/// Placing an order bumps a shard instead of the `OrderBook`, so orders
+/// taking IDs from different shards never write-lock the same account.
+#[account]
+pub struct OrderShard {
+    /// Order book the shard assigns IDs for
+    pub order_book: Pubkey,
+    
+    /// Shard index (`0..MAX_ORDER_SHARDS`)
+    pub shard: u8,
+    
+    /// Orders placed through this shard
+    pub order_count: u64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+/// CallbackAuth - Authentication token for callback operations
+/// 
+/// This account is used to authorize callback operations from the keeper/matching
+/// engine. It ensures only authorized entities can trigger order matching.
+#[account]
+pub struct CallbackAuth {
+    /// Authority that can use this callback auth
+    pub authority: Pubkey,
+    
+    /// Order book this callback auth is valid for
+    pub order_book: Pubkey,
+    
+    /// Nonce to prevent replay attacks
+    pub nonce: u64,
+    
+    /// Expiration timestamp
+    pub expires_at: i64,
+    
+    /// Whether this callback auth is active
+    pub is_active: bool,
+    
+    /// Creation timestamp
+    pub created_at: i64,
+    
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+// ============================================================================
+// Match Result Structures
+// ============================================================================
+
+/// Match result input for settlement (plaintext from keeper)
+/// 
+/// This struct contains the decrypted match details that the keeper bot
+/// submits to execute the trade settlement. The keeper has computed the
+/// matching off-chain and decrypted the results.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct MatchResultInput {
+    /// Buyer's order account pubkey
+    pub buyer_pubkey: Pubkey,
+    
+    /// Seller's order account pubkey
+    pub seller_pubkey: Pubkey,
+    
+    /// Matched amount in base token units (e.g., lamports for WSOL)
+    pub matched_amount: u64,
+    
+    /// Execution price: quote tokens per base token
+    /// (adjusted for decimals, e.g., USDC micro-units per WSOL lamport)
+    pub execution_price: u64,
+    
+    /// Base amount left unfilled on the buy order after this match
+    pub buyer_remaining: u64,
+    
+    /// Base amount left unfilled on the sell order after this match
+    pub seller_remaining: u64,
+}
+
+// ============================================================================
+// Parent Order Structures
+// ============================================================================
+
+/// Release schedule of a TWAP / iceberg parent order
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ParentOrderSchedule {
+    /// Total amount escrowed (quote tokens for buys, base tokens for sells)
+    pub total_amount: u64,
+    
+    /// Target amount escrowed by each child order
+    pub slice_size: u64,
+    
+    /// Random slice variation in basis points of `slice_size` (0 = fixed)
+    pub slice_jitter_bps: u16,
+    
+    /// Seconds between child releases (0 = release as fast as cranked)
+    pub release_interval: i64,
+}
+
+// ============================================================================
+// LP Vault Structures
+// ============================================================================
+
+/// Pricing, risk and withdrawal settings of an LP vault
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct LpVaultParams {
+    /// Distance from the oracle price the vault trades at, in basis points
+    pub spread_bps: u16,
+    
+    /// Maximum share of the vault's value either side may reach after a
+    /// backstop fill, in basis points (5000-10000)
+    pub max_inventory_bps: u16,
+    
+    /// Share of the book's `fee_bps` on backstop fills kept by the vault
+    pub lp_fee_share_bps: u16,
+    
+    /// Seconds a queued withdrawal waits before it can be processed
+    pub withdrawal_delay: i64,
+}
+
+impl LpVaultParams {
+    fn validate(&self) -> Result<()> {
+        require!(
+            self.spread_bps < 10_000
+                && (5_000..=10_000).contains(&self.max_inventory_bps)
+                && self.lp_fee_share_bps <= 10_000
+                && self.withdrawal_delay >= 0,
+            ShadowSwapError::InvalidLpVaultConfiguration
+        );
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Sealed Auction Structures
+// ============================================================================
+
+/// Windows and penalty of a commit-reveal auction round
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct SealedAuctionParams {
+    /// Seconds orders can be committed after the round opens
+    pub commit_duration: i64,
+    
+    /// Seconds orders can be revealed after the commit window
+    pub reveal_duration: i64,
+    
+    /// Share of an unrevealed order's deposit forfeited, in basis points
+    pub penalty_bps: u16,
+}
+
+impl SealedAuctionParams {
+    fn validate(&self) -> Result<()> {
+        require!(
+            self.commit_duration > 0 && self.reveal_duration > 0 && self.penalty_bps <= 10_000,
+            ShadowSwapError::InvalidSealedAuction
+        );
+        Ok(())
+    }
+}
+
+/// A revealed sealed order
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct SealedBid {
+    /// 0 = buy, 1 = sell
+    pub side: u8,
+    
+    /// Limit price (quote units per base token)
+    pub price: u64,
+    
+    /// Base amount
+    pub amount: u64,
+    
+    /// Base amount filled at the clearing price
+    pub filled: u64,
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+/// Event emitted when an order is placed
+#[event]
+pub struct OrderPlaced {
+    pub order_book: Pubkey,
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub order_id: u64,
+    pub client_order_id: [u8; 16],
+    pub time_in_force: u8,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a single order is cancelled
+#[event]
+pub struct OrderCancelled {
+    pub order_book: Pubkey,
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub order_id: u64,
+    pub client_order_id: [u8; 16],
+    pub timestamp: i64,
+}
+
+/// Event emitted when a trade is settled
+#[event]
+pub struct TradeSettled {
+    pub order_book: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub buyer_order_id: u64,
+    pub seller_order_id: u64,
+    /// Client order IDs of both orders
+    pub buyer_client_order_id: [u8; 16],
+    pub seller_client_order_id: [u8; 16],
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub execution_price: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when an order is amended in place
+#[event]
+pub struct OrderAmended {
+    pub order_book: Pubkey,
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub order_id: u64,
+    pub client_order_id: [u8; 16],
+    pub escrow_delta: i64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when both legs of a routed order settle
+#[event]
+pub struct RouteSettled {
+    pub route: Pubkey,
+    pub owner: Pubkey,
+    pub order_id: u64,
+    pub client_order_id: [u8; 16],
+    pub first_order_book: Pubkey,
+    pub second_order_book: Pubkey,
+    pub amount_in: u64,
+    pub intermediate_amount: u64,
+    pub amount_out: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when an order is executed against the fallback AMM
+#[event]
+pub struct FallbackExecuted {
+    pub order_book: Pubkey,
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub order_id: u64,
+    pub client_order_id: [u8; 16],
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when an LP deposits into a backstop vault
+#[event]
+pub struct LiquidityDeposited {
+    pub lp_vault: Pubkey,
+    pub lp: Pubkey,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub shares: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a queued LP withdrawal is paid out
+#[event]
+pub struct LiquidityWithdrawn {
+    pub lp_vault: Pubkey,
+    pub lp: Pubkey,
+    pub shares: u64,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a due LP withdrawal is moved to its claim accounts
+#[event]
+pub struct LpWithdrawalParked {
+    pub lp_vault: Pubkey,
+    pub lp: Pubkey,
+    pub index: u64,
+    pub shares: u64,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when an order is filled against a backstop vault
+#[event]
+pub struct BackstopFilled {
+    pub order_book: Pubkey,
+    pub order: Pubkey,
+    pub order_id: u64,
+    pub client_order_id: [u8; 16],
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub execution_price: u64,
+    pub lp_fee: u64,
+    pub protocol_fee: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a commit-reveal round is cleared
+#[event]
+pub struct SealedAuctionCleared {
+    pub order_book: Pubkey,
+    pub auction: Pubkey,
+    pub round: u64,
+    pub clearing_price: u64,
+    pub volume: u64,
+    pub committed: u64,
+    pub revealed: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a sealed order is paid out
+#[event]
+pub struct SealedOrderSettled {
+    pub auction: Pubkey,
+    pub owner: Pubkey,
+    pub index: u64,
+    pub revealed: bool,
+    pub filled: u64,
+    pub base_out: u64,
+    pub quote_out: u64,
+    pub penalty: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a taker requests quotes
+#[event]
+pub struct RfqRequested {
+    pub order_book: Pubkey,
+    pub request: Pubkey,
+    pub taker: Pubkey,
+    pub rfq_id: u64,
+    pub side: u8,
+    pub makers: Vec<Pubkey>,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a maker answers an RFQ
+#[event]
+pub struct RfqQuoted {
+    pub request: Pubkey,
+    pub quote: Pubkey,
+    pub maker: Pubkey,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a taker accepts a quote and both sides settle
+#[event]
+pub struct RfqFilled {
+    pub order_book: Pubkey,
+    pub request: Pubkey,
+    pub quote: Pubkey,
+    pub rfq_id: u64,
+    pub taker: Pubkey,
+    pub maker: Pubkey,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub execution_price: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when orders are cancelled in bulk
+#[event]
+pub struct OrdersBulkCancelled {
+    pub order_book: Pubkey,
+    pub caller: Pubkey,
+    pub order_ids: Vec<u64>,
+    pub client_order_ids: Vec<[u8; 16]>,
+    /// Cancelled by the book authority rather than the owner
+    pub forced: bool,
+    pub timestamp: i64,
+}
+
+/// Event emitted when an order book starts decommissioning
+#[event]
+pub struct DecommissionStarted {
+    pub order_book: Pubkey,
+    /// Orders closed on the book so far
+    pub closed_orders: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted for each wind-down crank of a decommissioning book
+#[event]
+pub struct OrdersWoundDown {
+    pub order_book: Pubkey,
+    pub order_ids: Vec<u64>,
+    pub client_order_ids: Vec<[u8; 16]>,
+    /// Tokens refunded from escrows in this crank
+    pub refunded: u64,
+    /// Orders closed on the book so far
+    pub closed_orders: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a decommissioned order book is closed
+#[event]
+pub struct OrderBookClosed {
+    pub order_book: Pubkey,
+    pub authority: Pubkey,
+    pub vaults_closed: u8,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a parent order releases a child order
+#[event]
+pub struct ChildOrderReleased {
+    pub order_book: Pubkey,
+    pub parent: Pubkey,
+    pub order: Pubkey,
+    pub order_id: u64,
+    pub client_order_id: [u8; 16],
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Match result input for routed settlement (plaintext from keeper)
+/// 
+/// Both legs are in base units of their own book; execution prices are quote
+/// per base as in `MatchResultInput`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct RoutedMatchInput {
+    /// Base amount matched on the first book
+    pub first_matched_amount: u64,
+    
+    /// Execution price on the first book
+    pub first_execution_price: u64,
+    
+    /// Base amount left unfilled on the routed order after this match
+    pub order_remaining: u64,
+    
+    /// Base amount left unfilled on the first book's counterparty order
+    pub first_counter_remaining: u64,
+    
+    /// Base amount matched on the second book
+    pub second_matched_amount: u64,
+    
+    /// Execution price on the second book
+    pub second_execution_price: u64,
+    
+    /// Base amount left unfilled on the second book's counterparty order
+    pub second_counter_remaining: u64,
+}
+
+// ============================================================================
+// Error Codes
+// ============================================================================
+
+#[error_code]
+pub enum ShadowSwapError {
+    #[msg("Order book is not active")]
+    OrderBookNotActive,
+    
+    #[msg("Invalid order status")]
+    InvalidOrderStatus,
+    
+    #[msg("Order amount too small")]
+    OrderTooSmall,
+    
+    #[msg("Invalid cipher payload")]
+    InvalidCipherPayload,
+    
+    #[msg("Unauthorized callback")]
+    UnauthorizedCallback,
+    
+    #[msg("Callback auth expired")]
+    CallbackAuthExpired,
+    
+    #[msg("Invalid escrow account")]
+    InvalidEscrow,
+    
+    #[msg("Insufficient funds in escrow")]
+    InsufficientEscrowFunds,
+    
+    #[msg("Invalid token mint")]
+    InvalidTokenMint,
+    
+    #[msg("Order not found")]
+    OrderNotFound,
+    
+    #[msg("Order already filled")]
+    OrderAlreadyFilled,
+    
+    #[msg("Order already cancelled")]
+    OrderAlreadyCancelled,
+    
+    #[msg("Invalid order book")]
+    InvalidOrderBook,
+    
+    #[msg("Invalid fee configuration")]
+    InvalidFeeConfiguration,
+    
+    #[msg("Numerical overflow")]
+    NumericalOverflow,
+    
+    #[msg("Order is not active")]
+    OrderNotActive,
+    
+    #[msg("Invalid oracle configuration")]
+    InvalidOracleConfiguration,
+    
+    #[msg("Order book oracle account missing")]
+    OracleNotConfigured,
+    
+    #[msg("Invalid oracle account")]
+    InvalidOracleAccount,
+    
+    #[msg("Oracle price is stale")]
+    StaleOraclePrice,
+    
+    #[msg("Execution price outside oracle band")]
+    PriceOutsideOracleBand,
+    
+    #[msg("Invalid time-in-force")]
+    InvalidTimeInForce,
+    
+    #[msg("Fill-or-kill order cannot be partially filled")]
+    FillOrKillNotFilled,
+    
+    #[msg("Post-only order cannot take liquidity")]
+    PostOnlyWouldTake,
+    
+    #[msg("Refund token account missing")]
+    MissingRefundAccount,
+    
+    #[msg("Invalid refund token account")]
+    InvalidRefundAccount,
+    
+    #[msg("Invalid parent order parameters")]
+    InvalidParentOrder,
+    
+    #[msg("Parent order is not active")]
+    ParentOrderNotActive,
+    
+    #[msg("Next child order is not due yet")]
+    ReleaseNotDue,
+
+    #[msg("Order is funded from a user balance")]
+    BalanceBackedOrder,
+
+    #[msg("Order is not funded from a user balance")]
+    NotBalanceBackedOrder,
+
+    #[msg("Insufficient free balance")]
+    InsufficientFreeBalance,
+
+    #[msg("Buyer and seller balances are the same account")]
+    SelfTrade,
+
+    #[msg("Second book does not trade the route's intermediate token")]
+    InvalidRoute,
+
+    #[msg("Order is routed through another book")]
+    RoutedOrder,
+
+    #[msg("Order is not routed")]
+    NotRoutedOrder,
+
+    #[msg("Second leg spends more than the first leg bought")]
+    RouteLegMismatch,
+
+    #[msg("Route output below the minimum")]
+    SlippageExceeded,
+
+    #[msg("Invalid fallback parameters")]
+    InvalidFallback,
+
+    #[msg("Order book has no fallback AMM")]
+    FallbackNotConfigured,
+
+    #[msg("Fallback deadline has not passed")]
+    FallbackNotDue,
+
+    #[msg("Revealed limit does not match the commitment")]
+    InvalidFallbackReveal,
+
+    #[msg("AMM did not take the whole escrow")]
+    IncompleteFallbackSwap,
+
+    #[msg("Invalid LP vault configuration")]
+    InvalidLpVaultConfiguration,
+
+    #[msg("Account does not belong to the LP vault")]
+    InvalidLpVault,
+
+    #[msg("LP vault cannot cover the fill")]
+    InsufficientLiquidity,
+
+    #[msg("Fill would exceed the LP vault's inventory cap")]
+    InventoryCapExceeded,
+
+    #[msg("Earlier LP withdrawals are still queued")]
+    WithdrawalNotNext,
+
+    #[msg("LP withdrawal delay has not passed")]
+    WithdrawalNotReady,
+
+    #[msg("Invalid sealed auction configuration")]
+    InvalidSealedAuction,
+
+    #[msg("Outside the sealed auction's commit or reveal window")]
+    SealedAuctionPhase,
+
+    #[msg("Sealed auction has no room for more orders")]
+    SealedAuctionFull,
+
+    #[msg("Revealed order does not match its commitment")]
+    InvalidSealedReveal,
+
+    #[msg("Invalid sealed auction status")]
+    InvalidSealedAuctionStatus,
+
+    #[msg("Invalid RFQ request")]
+    InvalidRfq,
+
+    #[msg("Maker is not whitelisted for this RFQ")]
+    RfqMakerNotWhitelisted,
+
+    #[msg("RFQ or quote has expired")]
+    RfqExpired,
+
+    #[msg("RFQ is not open")]
+    RfqNotOpen,
+
+    #[msg("Invalid RFQ quote")]
+    InvalidRfqQuote,
+
+    #[msg("Invalid trading delegate configuration")]
+    InvalidTradingDelegate,
+
+    #[msg("Delegate is not authorized for this action")]
+    UnauthorizedDelegate,
+
+    #[msg("Trading delegate has expired")]
+    DelegateExpired,
+
+    #[msg("Order exceeds the delegate's per-order limit")]
+    DelegateLimitExceeded,
+
+    #[msg("Bulk cancel needs [order, escrow, escrow token, refund] accounts per order")]
+    InvalidBulkCancel,
+
+    #[msg("Order book is already decommissioning")]
+    BookDecommissioning,
+
+    #[msg("Order book is not decommissioning")]
+    BookNotDecommissioning,
+
+    #[msg("Wind-down needs [order, escrow, escrow token, refund, owner] accounts per order")]
+    InvalidWindDown,
+
+    #[msg("Order book still has active orders")]
+    BookHasActiveOrders,
+
+    #[msg("Book vault is not empty")]
+    BookVaultNotEmpty,
+
+    #[msg("Order shard does not belong to this order book")]
+    InvalidOrderShard,
+
+    #[msg("Order book already has the maximum number of order shards")]
+    OrderShardLimit,
+
+    #[msg("Fill exceeds the child order's escrowed slice")]
+    ChildSliceExceeded,
+
+    #[msg("Order book has an LP vault")]
+    BookHasLpVault,
+
+    #[msg("Order book has sealed auction rounds left to settle")]
+    BookHasOpenAuctions,
+
+    #[msg("Order book has open RFQs")]
+    BookHasOpenRfqs,
+}
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Maximum size for encrypted order payload (512 bytes)
+pub const MAX_CIPHER_PAYLOAD_SIZE: usize = 512;
+
+/// Maximum size for encrypted amount fields (64 bytes)
+pub const MAX_ENCRYPTED_AMOUNT_SIZE: usize = 64;
+
+/// Maximum size for encrypted volume fields (64 bytes)
+pub const MAX_ENCRYPTED_VOLUME_SIZE: usize = 64;
+
+/// Order status: Active
+pub const ORDER_STATUS_ACTIVE: u8 = 1;
+
+/// Order status: Partially filled
+pub const ORDER_STATUS_PARTIAL: u8 = 2;
+
+/// Order status: Fully filled
+pub const ORDER_STATUS_FILLED: u8 = 3;
+
+/// Order status: Cancelled
+pub const ORDER_STATUS_CANCELLED: u8 = 4;
+
+/// Order status: Matched, pending execution
+pub const ORDER_STATUS_MATCHED_PENDING: u8 = 5;
+
+/// Time-in-force: good till cancelled (rests on the book)
+pub const TIME_IN_FORCE_GTC: u8 = 0;
+
+/// Time-in-force: immediate or cancel (remainder cancelled after one round)
+pub const TIME_IN_FORCE_IOC: u8 = 1;
+
+/// Time-in-force: fill or kill (fills completely in one match or is cancelled)
+pub const TIME_IN_FORCE_FOK: u8 = 2;
+
+/// Time-in-force: post-only (must be the maker in any match)
+pub const TIME_IN_FORCE_POST_ONLY: u8 = 3;
+
+/// Parent order status: Releasing children
+pub const PARENT_STATUS_ACTIVE: u8 = 1;
+
+/// Parent order status: Fully released
+pub const PARENT_STATUS_COMPLETED: u8 = 2;
+
+/// Parent order status: Cancelled, unreleased amount refunded
+pub const PARENT_STATUS_CANCELLED: u8 = 3;
+
+/// Order sides of revealed sealed orders
+pub const ORDER_SIDE_BUY: u8 = 0;
+pub const ORDER_SIDE_SELL: u8 = 1;
+
+/// Commit-reveal round statuses
+pub const SEALED_AUCTION_STATUS_OPEN: u8 = 1;
+pub const SEALED_AUCTION_STATUS_CLEARED: u8 = 2;
+
+/// Maximum committed orders per commit-reveal round
+pub const MAX_SEALED_ORDERS: usize = 32;
+
+/// RFQ request statuses
+pub const RFQ_STATUS_OPEN: u8 = 1;
+pub const RFQ_STATUS_FILLED: u8 = 2;
+pub const RFQ_STATUS_CANCELLED: u8 = 3;
+
+/// Maximum makers one RFQ can be sent to
+pub const MAX_RFQ_MAKERS: usize = 8;
+
+/// Trading delegate scopes (there is deliberately no withdraw scope)
+pub const DELEGATE_SCOPE_PLACE: u8 = 1;
+pub const DELEGATE_SCOPE_CANCEL: u8 = 2;
+pub const DELEGATE_SCOPE_ALL: u8 = DELEGATE_SCOPE_PLACE | DELEGATE_SCOPE_CANCEL;
+
+/// Maximum order ID shards per book
+pub const MAX_ORDER_SHARDS: u8 = 16;
+
+/// Seeds for PDA derivation
+pub const ORDER_BOOK_SEED: &[u8] = b"order_book";
+pub const ORDER_SEED: &[u8] = b"order";
+pub const ESCROW_SEED: &[u8] = b"escrow";
+pub const CALLBACK_AUTH_SEED: &[u8] = b"callback_auth";
+pub const PARENT_ORDER_SEED: &[u8] = b"parent_order";
+pub const PARENT_ESCROW_TOKEN_SEED: &[u8] = b"parent_escrow_token";
+pub const USER_BALANCE_SEED: &[u8] = b"user_balance";
+pub const BOOK_VAULT_SEED: &[u8] = b"book_vault";
+pub const ROUTE_SEED: &[u8] = b"route";
+pub const FALLBACK_SEED: &[u8] = b"fallback";
+pub const LP_VAULT_SEED: &[u8] = b"lp_vault";
+pub const LP_SHARE_MINT_SEED: &[u8] = b"lp_share_mint";
+pub const LP_VAULT_TOKEN_SEED: &[u8] = b"lp_vault_token";
+pub const LP_WITHDRAWAL_SEED: &[u8] = b"lp_withdrawal";
+pub const LP_CLAIM_SEED: &[u8] = b"lp_claim";
+pub const SEALED_AUCTION_SEED: &[u8] = b"sealed_auction";
+pub const SEALED_VAULT_SEED: &[u8] = b"sealed_vault";
+pub const SEALED_ORDER_SEED: &[u8] = b"sealed_order";
+pub const RFQ_MAKER_SEED: &[u8] = b"rfq_maker";
+pub const RFQ_REQUEST_SEED: &[u8] = b"rfq_request";
+pub const RFQ_QUOTE_SEED: &[u8] = b"rfq_quote";
+pub const TRADING_DELEGATE_SEED: &[u8] = b"trading_delegate";
+pub const ORDER_SHARD_SEED: &[u8] = b"order_shard";
+
+// ============================================================================
+// Instruction Contexts
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitializeOrderBook<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<OrderBook>() + 200, // Extra space for Vec fields
+        seeds = [ORDER_BOOK_SEED, base_mint.key().as_ref(), quote_mint.key().as_ref()],
+        bump
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// The book's first order ID counter
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<OrderShard>(),
+        seeds = [ORDER_SHARD_SEED, order_book.key().as_ref(), &[0u8]],
+        bump
+    )]
+    pub order_shard: Account<'info, OrderShard>,
+    
+    /// CHECK: Fee collector can be any account
+    pub fee_collector: UncheckedAccount<'info>,
+    
+    /// CHECK: Base mint reference for PDA seeds
+    pub base_mint: UncheckedAccount<'info>,
+    
+    /// CHECK: Quote mint reference for PDA seeds
+    pub quote_mint: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(client_order_id: [u8; 16])]
+pub struct SubmitEncryptedOrder<'info> {
+    #[account(
+        constraint = order_book.is_active @ ShadowSwapError::OrderBookNotActive
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    /// Counter the order ID is taken from
+    #[account(
+        mut,
+        has_one = order_book @ ShadowSwapError::InvalidOrderShard
+    )]
+    pub order_shard: Account<'info, OrderShard>,
+    
+    /// CHECK: Owner the order is placed for, which seeds its PDA: the signer,
+    /// or the owner behind `trading_delegate`
+    #[account(
+        constraint = order_owner.key()
+            == trading_delegate.as_ref().map_or(owner.key(), |delegate| delegate.owner)
+            @ ShadowSwapError::UnauthorizedDelegate
+    )]
+    pub order_owner: UncheckedAccount<'info>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<EncryptedOrder>() + MAX_CIPHER_PAYLOAD_SIZE + MAX_ENCRYPTED_AMOUNT_SIZE + 100,
+        seeds = [ORDER_SEED, order_book.key().as_ref(), order_owner.key().as_ref(), client_order_id.as_ref()],
+        bump
+    )]
+    pub order: Account<'info, EncryptedOrder>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<Escrow>() + MAX_ENCRYPTED_AMOUNT_SIZE * 2 + 100,
+        seeds = [ESCROW_SEED, order.key().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        init,
+        payer = owner,
+        token::mint = token_mint,
+        token::authority = escrow,
+        seeds = [b"escrow_token", order.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    pub token_mint: Account<'info, anchor_spl::token::Mint>,
+    
+    /// Order owner, or a session key acting through `trading_delegate`
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    
+    /// Session key authorization when `owner` is a delegate
+    #[account(
+        seeds = [TRADING_DELEGATE_SEED, trading_delegate.owner.as_ref(), owner.key().as_ref()],
+        bump = trading_delegate.bump
+    )]
+    pub trading_delegate: Option<Account<'info, TradingDelegate>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOrder<'info> {
+    #[account(
+        mut,
+        constraint = order.status == ORDER_STATUS_ACTIVE || order.status == ORDER_STATUS_PARTIAL @ ShadowSwapError::InvalidOrderStatus,
+        constraint = order.user_balance == Pubkey::default() @ ShadowSwapError::BalanceBackedOrder
+    )]
+    pub order: Account<'info, EncryptedOrder>,
+    
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, order.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(mut)]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    /// Owner's token account receiving the refund
+    #[account(
+        mut,
+        constraint = user_token_account.owner == order.owner @ ShadowSwapError::InvalidRefundAccount
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut, address = order.order_book @ ShadowSwapError::InvalidOrderBook)]
+    pub order_book: Account<'info, OrderBook>,
+    
+    /// Order owner, or a session key acting through `trading_delegate`
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    
+    /// Session key authorization when `owner` is a delegate
+    #[account(
+        seeds = [TRADING_DELEGATE_SEED, order.owner.as_ref(), owner.key().as_ref()],
+        bump = trading_delegate.bump
+    )]
+    pub trading_delegate: Option<Account<'info, TradingDelegate>>,
+}
+
+#[derive(Accounts)]
+pub struct AmendOrder<'info> {
+    #[account(
+        mut,
+        has_one = owner @ ShadowSwapError::UnauthorizedCallback,
+        constraint = order.status == ORDER_STATUS_ACTIVE || order.status == ORDER_STATUS_PARTIAL @ ShadowSwapError::InvalidOrderStatus,
+        constraint = order.user_balance == Pubkey::default() @ ShadowSwapError::BalanceBackedOrder
+    )]
+    pub order: Account<'info, EncryptedOrder>,
+    
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, order.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        address = escrow.token_account @ ShadowSwapError::InvalidEscrow
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = user_token_account.mint == escrow.token_mint @ ShadowSwapError::InvalidTokenMint
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireImmediateOrder<'info> {
+    /// Callback authorization - verifies keeper is authorized
+    #[account(
+        constraint = callback_auth.is_active @ ShadowSwapError::UnauthorizedCallback,
+        constraint = callback_auth.authority == keeper.key() @ ShadowSwapError::UnauthorizedCallback,
+        seeds = [CALLBACK_AUTH_SEED, order_book.key().as_ref(), keeper.key().as_ref()],
+        bump = callback_auth.bump
+    )]
+    pub callback_auth: Account<'info, CallbackAuth>,
+    
+    #[account(mut)]
+    pub order_book: Account<'info, OrderBook>,
+    
+    #[account(
+        mut,
+        constraint = order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook,
+        constraint = order.status == ORDER_STATUS_ACTIVE @ ShadowSwapError::InvalidOrderStatus,
+        constraint = is_immediate(order.time_in_force) @ ShadowSwapError::InvalidTimeInForce
+    )]
+    pub order: Account<'info, EncryptedOrder>,
+    
+    #[account(
+        seeds = [ESCROW_SEED, order.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        address = escrow.token_account @ ShadowSwapError::InvalidEscrow
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == order.owner @ ShadowSwapError::InvalidRefundAccount,
+        constraint = owner_token_account.mint == escrow.token_mint @ ShadowSwapError::InvalidRefundAccount
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+    
+    pub keeper: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(parent_nonce: u64)]
+pub struct CreateParentOrder<'info> {
+    #[account(
+        constraint = order_book.is_active @ ShadowSwapError::OrderBookNotActive
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<ParentOrder>() + MAX_CIPHER_PAYLOAD_SIZE + MAX_ENCRYPTED_AMOUNT_SIZE,
+        seeds = [PARENT_ORDER_SEED, order_book.key().as_ref(), owner.key().as_ref(), parent_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub parent_order: Account<'info, ParentOrder>,
+    
+    #[account(
+        init,
+        payer = owner,
+        token::mint = token_mint,
+        token::authority = parent_order,
+        seeds = [PARENT_ESCROW_TOKEN_SEED, parent_order.key().as_ref()],
+        bump
+    )]
+    pub parent_escrow_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    pub token_mint: Account<'info, anchor_spl::token::Mint>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseChildOrder<'info> {
+    #[account(
+        constraint = order_book.is_active @ ShadowSwapError::OrderBookNotActive
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    /// Counter the order ID is taken from
+    #[account(
+        mut,
+        has_one = order_book @ ShadowSwapError::InvalidOrderShard
+    )]
+    pub order_shard: Account<'info, OrderShard>,
+    
+    #[account(
+        mut,
+        constraint = parent_order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook,
+        constraint = parent_order.status == PARENT_STATUS_ACTIVE @ ShadowSwapError::ParentOrderNotActive
+    )]
+    pub parent_order: Account<'info, ParentOrder>,
+    
+    #[account(
+        mut,
+        address = parent_order.escrow_token_account @ ShadowSwapError::InvalidEscrow
+    )]
+    pub parent_escrow_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<EncryptedOrder>() + MAX_CIPHER_PAYLOAD_SIZE + MAX_ENCRYPTED_AMOUNT_SIZE + 100,
+        seeds = [ORDER_SEED, order_book.key().as_ref(), parent_order.key().as_ref(), parent_order.child_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub order: Account<'info, EncryptedOrder>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<Escrow>() + MAX_ENCRYPTED_AMOUNT_SIZE * 2 + 100,
+        seeds = [ESCROW_SEED, order.key().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        init,
+        payer = payer,
+        token::mint = token_mint,
+        token::authority = escrow,
+        seeds = [b"escrow_token", order.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    #[account(address = parent_order.token_mint @ ShadowSwapError::InvalidTokenMint)]
+    pub token_mint: Account<'info, anchor_spl::token::Mint>,
+    
+    /// Anyone can crank; pays rent for the child accounts
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct CancelParentOrder<'info> {
+    #[account(
+        mut,
+        has_one = owner @ ShadowSwapError::UnauthorizedCallback,
+        constraint = parent_order.status == PARENT_STATUS_ACTIVE @ ShadowSwapError::ParentOrderNotActive
+    )]
+    pub parent_order: Account<'info, ParentOrder>,
+    
+    #[account(
+        mut,
+        address = parent_order.escrow_token_account @ ShadowSwapError::InvalidEscrow
+    )]
+    pub parent_escrow_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = user_token_account.mint == parent_order.token_mint @ ShadowSwapError::InvalidTokenMint
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeBookVaults<'info> {
+    #[account(
+        constraint = order_book.authority == authority.key() @ ShadowSwapError::UnauthorizedCallback
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = base_mint,
+        token::authority = order_book,
+        seeds = [BOOK_VAULT_SEED, order_book.key().as_ref(), base_mint.key().as_ref()],
+        bump
+    )]
+    pub base_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = quote_mint,
+        token::authority = order_book,
+        seeds = [BOOK_VAULT_SEED, order_book.key().as_ref(), quote_mint.key().as_ref()],
+        bump
+    )]
+    pub quote_vault: Account<'info, TokenAccount>,
+    
+    #[account(address = order_book.base_mint @ ShadowSwapError::InvalidTokenMint)]
+    pub base_mint: Account<'info, anchor_spl::token::Mint>,
+    
+    #[account(address = order_book.quote_mint @ ShadowSwapError::InvalidTokenMint)]
+    pub quote_mint: Account<'info, anchor_spl::token::Mint>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct OpenUserBalance<'info> {
+    pub order_book: Account<'info, OrderBook>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<UserBalance>(),
+        seeds = [USER_BALANCE_SEED, order_book.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub user_balance: Account<'info, UserBalance>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BalanceTransfer<'info> {
+    pub order_book: Account<'info, OrderBook>,
+    
+    #[account(
+        mut,
+        has_one = owner @ ShadowSwapError::UnauthorizedCallback,
+        seeds = [USER_BALANCE_SEED, order_book.key().as_ref(), owner.key().as_ref()],
+        bump = user_balance.bump
+    )]
+    pub user_balance: Account<'info, UserBalance>,
+    
+    /// Book vault of the token being moved (base or quote)
+    #[account(
+        mut,
+        seeds = [BOOK_VAULT_SEED, order_book.key().as_ref(), user_token_account.mint.as_ref()],
+        bump
+    )]
+    pub book_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = user_token_account.owner == owner.key() @ ShadowSwapError::UnauthorizedCallback
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(client_order_id: [u8; 16])]
+pub struct SubmitBalanceOrder<'info> {
+    #[account(
+        constraint = order_book.is_active @ ShadowSwapError::OrderBookNotActive
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    /// Counter the order ID is taken from
+    #[account(
+        mut,
+        has_one = order_book @ ShadowSwapError::InvalidOrderShard
+    )]
+    pub order_shard: Account<'info, OrderShard>,
+    
+    #[account(
+        mut,
+        has_one = owner @ ShadowSwapError::UnauthorizedCallback,
+        seeds = [USER_BALANCE_SEED, order_book.key().as_ref(), owner.key().as_ref()],
+        bump = user_balance.bump
+    )]
+    pub user_balance: Account<'info, UserBalance>,
+    
+    /// Book vault of the locked token (quote for buys, base for sells)
+    #[account(
+        seeds = [BOOK_VAULT_SEED, order_book.key().as_ref(), book_vault.mint.as_ref()],
+        bump
+    )]
+    pub book_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<EncryptedOrder>() + MAX_CIPHER_PAYLOAD_SIZE + MAX_ENCRYPTED_AMOUNT_SIZE + 100,
+        seeds = [ORDER_SEED, order_book.key().as_ref(), owner.key().as_ref(), client_order_id.as_ref()],
+        bump
+    )]
+    pub order: Account<'info, EncryptedOrder>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<Escrow>() + MAX_ENCRYPTED_AMOUNT_SIZE * 2 + 100,
+        seeds = [ESCROW_SEED, order.key().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelBalanceOrder<'info> {
+    #[account(
+        mut,
+        has_one = owner @ ShadowSwapError::UnauthorizedCallback,
+        constraint = order.status == ORDER_STATUS_ACTIVE || order.status == ORDER_STATUS_PARTIAL @ ShadowSwapError::InvalidOrderStatus,
+        constraint = order.user_balance != Pubkey::default() @ ShadowSwapError::NotBalanceBackedOrder
+    )]
+    pub order: Account<'info, EncryptedOrder>,
+    
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, order.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        address = order.user_balance @ ShadowSwapError::NotBalanceBackedOrder
+    )]
+    pub user_balance: Account<'info, UserBalance>,
+    
+    #[account(
+        mut,
+        address = order.order_book @ ShadowSwapError::InvalidOrderBook
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettleBalanceMatch<'info> {
+    /// Callback authorization - verifies keeper is authorized
+    #[account(
+        constraint = callback_auth.is_active @ ShadowSwapError::UnauthorizedCallback,
+        constraint = callback_auth.authority == keeper.key() @ ShadowSwapError::UnauthorizedCallback,
+        seeds = [CALLBACK_AUTH_SEED, order_book.key().as_ref(), keeper.key().as_ref()],
+        bump = callback_auth.bump
+    )]
+    pub callback_auth: Account<'info, CallbackAuth>,
+    
+    #[account(mut)]
+    pub order_book: Account<'info, OrderBook>,
+    
+    #[account(
+        mut,
+        constraint = buyer_order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook,
+        constraint = buyer_order.status == ORDER_STATUS_ACTIVE || buyer_order.status == ORDER_STATUS_PARTIAL @ ShadowSwapError::InvalidOrderStatus,
+        constraint = buyer_order.route == Pubkey::default() @ ShadowSwapError::RoutedOrder
+    )]
+    pub buyer_order: Account<'info, EncryptedOrder>,
+    
+    #[account(
+        mut,
+        constraint = seller_order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook,
+        constraint = seller_order.status == ORDER_STATUS_ACTIVE || seller_order.status == ORDER_STATUS_PARTIAL @ ShadowSwapError::InvalidOrderStatus,
+        constraint = seller_order.route == Pubkey::default() @ ShadowSwapError::RoutedOrder
+    )]
+    pub seller_order: Account<'info, EncryptedOrder>,
+    
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, buyer_order.key().as_ref()],
+        bump = buyer_escrow.bump
+    )]
+    pub buyer_escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, seller_order.key().as_ref()],
+        bump = seller_escrow.bump
+    )]
+    pub seller_escrow: Account<'info, Escrow>,
+    
+    /// Buyer's balance (pays locked quote, receives base)
+    #[account(
+        mut,
+        address = buyer_order.user_balance @ ShadowSwapError::NotBalanceBackedOrder
+    )]
+    pub buyer_balance: Account<'info, UserBalance>,
+    
+    /// Seller's balance (pays locked base, receives quote)
+    #[account(
+        mut,
+        address = seller_order.user_balance @ ShadowSwapError::NotBalanceBackedOrder,
+        constraint = seller_balance.key() != buyer_balance.key() @ ShadowSwapError::SelfTrade
+    )]
+    pub seller_balance: Account<'info, UserBalance>,
+    
+    pub keeper: Signer<'info>,
+    
+    /// CHECK: Price oracle configured on the order book; layout is checked by
+    /// `PythPriceAccount`. Required when the book has an oracle.
+    #[account(address = order_book.oracle @ ShadowSwapError::InvalidOracleAccount)]
+    pub oracle: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct CreateRoute<'info> {
+    #[account(
+        mut,
+        has_one = owner @ ShadowSwapError::UnauthorizedCallback,
+        constraint = order.status == ORDER_STATUS_ACTIVE @ ShadowSwapError::InvalidOrderStatus,
+        constraint = order.user_balance != Pubkey::default() @ ShadowSwapError::NotBalanceBackedOrder
+    )]
+    pub order: Account<'info, EncryptedOrder>,
+    
+    #[account(
+        seeds = [ESCROW_SEED, order.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(address = order.order_book @ ShadowSwapError::InvalidOrderBook)]
+    pub first_order_book: Account<'info, OrderBook>,
+    
+    #[account(
+        constraint = second_order_book.is_active @ ShadowSwapError::OrderBookNotActive,
+        constraint = second_order_book.key() != first_order_book.key() @ ShadowSwapError::InvalidRoute
+    )]
+    pub second_order_book: Account<'info, OrderBook>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<Route>(),
+        seeds = [ROUTE_SEED, order.key().as_ref()],
+        bump
+    )]
+    pub route: Account<'info, Route>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleRoutedMatch<'info> {
+    /// Keeper authorization on the first book
+    #[account(
+        constraint = first_callback_auth.is_active @ ShadowSwapError::UnauthorizedCallback,
+        constraint = first_callback_auth.authority == keeper.key() @ ShadowSwapError::UnauthorizedCallback,
+        seeds = [CALLBACK_AUTH_SEED, first_order_book.key().as_ref(), keeper.key().as_ref()],
+        bump = first_callback_auth.bump
+    )]
+    pub first_callback_auth: Box<Account<'info, CallbackAuth>>,
+    
+    /// Keeper authorization on the second book
+    #[account(
+        constraint = second_callback_auth.is_active @ ShadowSwapError::UnauthorizedCallback,
+        constraint = second_callback_auth.authority == keeper.key() @ ShadowSwapError::UnauthorizedCallback,
+        seeds = [CALLBACK_AUTH_SEED, second_order_book.key().as_ref(), keeper.key().as_ref()],
+        bump = second_callback_auth.bump
+    )]
+    pub second_callback_auth: Box<Account<'info, CallbackAuth>>,
+    
+    #[account(mut, address = route.first_order_book @ ShadowSwapError::InvalidOrderBook)]
+    pub first_order_book: Box<Account<'info, OrderBook>>,
+    
+    #[account(mut, address = route.second_order_book @ ShadowSwapError::InvalidOrderBook)]
+    pub second_order_book: Box<Account<'info, OrderBook>>,
+    
+    #[account(
+        seeds = [ROUTE_SEED, order.key().as_ref()],
+        bump = route.bump
+    )]
+    pub route: Box<Account<'info, Route>>,
+    
+    /// The routed order (first leg, owner's side)
+    #[account(
+        mut,
+        constraint = order.route == route.key() @ ShadowSwapError::NotRoutedOrder,
+        constraint = order.status == ORDER_STATUS_ACTIVE || order.status == ORDER_STATUS_PARTIAL @ ShadowSwapError::InvalidOrderStatus
+    )]
+    pub order: Box<Account<'info, EncryptedOrder>>,
+    
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, order.key().as_ref()],
+        bump = order_escrow.bump
+    )]
+    pub order_escrow: Box<Account<'info, Escrow>>,
+    
+    /// Owner's balance on the first book (pays the input token)
+    #[account(
+        mut,
+        address = order.user_balance @ ShadowSwapError::NotBalanceBackedOrder
+    )]
+    pub user_first_balance: Box<Account<'info, UserBalance>>,
+    
+    /// Owner's balance on the second book (receives the output token)
+    #[account(
+        mut,
+        seeds = [USER_BALANCE_SEED, second_order_book.key().as_ref(), route.owner.as_ref()],
+        bump = user_second_balance.bump
+    )]
+    pub user_second_balance: Box<Account<'info, UserBalance>>,
+    
+    #[account(
+        mut,
+        constraint = first_counter_order.order_book == first_order_book.key() @ ShadowSwapError::InvalidOrderBook,
+        constraint = first_counter_order.status == ORDER_STATUS_ACTIVE || first_counter_order.status == ORDER_STATUS_PARTIAL @ ShadowSwapError::InvalidOrderStatus,
+        constraint = first_counter_order.route == Pubkey::default() @ ShadowSwapError::RoutedOrder
+    )]
+    pub first_counter_order: Box<Account<'info, EncryptedOrder>>,
+    
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, first_counter_order.key().as_ref()],
+        bump = first_counter_escrow.bump
+    )]
+    pub first_counter_escrow: Box<Account<'info, Escrow>>,
+    
+    #[account(
+        mut,
+        address = first_counter_order.user_balance @ ShadowSwapError::NotBalanceBackedOrder,
+        constraint = first_counter_balance.key() != user_first_balance.key() @ ShadowSwapError::SelfTrade
+    )]
+    pub first_counter_balance: Box<Account<'info, UserBalance>>,
+    
+    #[account(
+        mut,
+        constraint = second_counter_order.order_book == second_order_book.key() @ ShadowSwapError::InvalidOrderBook,
+        constraint = second_counter_order.status == ORDER_STATUS_ACTIVE || second_counter_order.status == ORDER_STATUS_PARTIAL @ ShadowSwapError::InvalidOrderStatus,
+        constraint = second_counter_order.route == Pubkey::default() @ ShadowSwapError::RoutedOrder
+    )]
+    pub second_counter_order: Box<Account<'info, EncryptedOrder>>,
+    
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, second_counter_order.key().as_ref()],
+        bump = second_counter_escrow.bump
+    )]
+    pub second_counter_escrow: Box<Account<'info, Escrow>>,
+    
+    #[account(
+        mut,
+        address = second_counter_order.user_balance @ ShadowSwapError::NotBalanceBackedOrder,
+        constraint = second_counter_balance.key() != user_second_balance.key() @ ShadowSwapError::SelfTrade
+    )]
+    pub second_counter_balance: Box<Account<'info, UserBalance>>,
+    
+    /// First book's vault of the intermediate token
+    #[account(
+        mut,
+        seeds = [BOOK_VAULT_SEED, first_order_book.key().as_ref(), route.intermediate_mint.as_ref()],
+        bump
+    )]
+    pub first_intermediate_vault: Box<Account<'info, TokenAccount>>,
+    
+    /// Second book's vault of the intermediate token
+    #[account(
+        mut,
+        seeds = [BOOK_VAULT_SEED, second_order_book.key().as_ref(), route.intermediate_mint.as_ref()],
+        bump
+    )]
+    pub second_intermediate_vault: Box<Account<'info, TokenAccount>>,
+    
+    pub keeper: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    
+    /// CHECK: First book's price oracle; layout is checked by
+    /// `PythPriceAccount`. Required when the book has an oracle.
+    #[account(address = first_order_book.oracle @ ShadowSwapError::InvalidOracleAccount)]
+    pub first_oracle: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Second book's price oracle; layout is checked by
+    /// `PythPriceAccount`. Required when the book has an oracle.
+    #[account(address = second_order_book.oracle @ ShadowSwapError::InvalidOracleAccount)]
+    pub second_oracle: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureFallbackAmm<'info> {
+    #[account(
+        mut,
+        constraint = order_book.authority == authority.key() @ ShadowSwapError::UnauthorizedCallback
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetFallback<'info> {
+    #[account(
+        has_one = owner @ ShadowSwapError::UnauthorizedCallback,
+        constraint = order.status == ORDER_STATUS_ACTIVE || order.status == ORDER_STATUS_PARTIAL @ ShadowSwapError::InvalidOrderStatus,
+        constraint = order.user_balance == Pubkey::default() @ ShadowSwapError::BalanceBackedOrder
+    )]
+    pub order: Account<'info, EncryptedOrder>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<FallbackConfig>(),
+        seeds = [FALLBACK_SEED, order.key().as_ref()],
+        bump
+    )]
+    pub fallback: Account<'info, FallbackConfig>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FallbackSwap<'info> {
+    #[account(
+        mut,
+        constraint = order.status == ORDER_STATUS_ACTIVE || order.status == ORDER_STATUS_PARTIAL @ ShadowSwapError::InvalidOrderStatus,
+        constraint = order.user_balance == Pubkey::default() @ ShadowSwapError::BalanceBackedOrder
+    )]
+    pub order: Account<'info, EncryptedOrder>,
+    
+    #[account(
+        seeds = [ESCROW_SEED, order.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    
+    #[account(
+        mut,
+        address = escrow.token_account @ ShadowSwapError::InvalidEscrow
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [FALLBACK_SEED, order.key().as_ref()],
+        bump = fallback.bump
+    )]
+    pub fallback: Account<'info, FallbackConfig>,
+    
+    #[account(
+        mut,
+        address = order.order_book @ ShadowSwapError::InvalidOrderBook
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    /// Owner's token account receiving the swap output
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == order.owner @ ShadowSwapError::UnauthorizedCallback
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Swap program configured on the order book; invoked through
+    /// `amm::SwapInterface`, with its pool accounts passed as remaining accounts
+    #[account(
+        executable,
+        constraint = order_book.fallback_amm != Pubkey::default() @ ShadowSwapError::FallbackNotConfigured,
+        address = order_book.fallback_amm @ ShadowSwapError::FallbackNotConfigured
+    )]
+    pub amm_program: UncheckedAccount<'info>,
+    
+    /// Keeper authorization; required unless the owner is calling
+    #[account(
+        seeds = [CALLBACK_AUTH_SEED, order_book.key().as_ref(), caller.key().as_ref()],
+        bump = callback_auth.bump
+    )]
+    pub callback_auth: Option<Account<'info, CallbackAuth>>,
+    
+    pub caller: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
 
-        Ok(())
-    }
+#[derive(Accounts)]
+pub struct InitializeLpVault<'info> {
+    #[account(
+        constraint = order_book.authority == authority.key() @ ShadowSwapError::UnauthorizedCallback,
+        constraint = order_book.oracle != Pubkey::default() @ ShadowSwapError::OracleNotConfigured
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<LpVault>(),
+        seeds = [LP_VAULT_SEED, order_book.key().as_ref()],
+        bump
+    )]
+    pub lp_vault: Box<Account<'info, LpVault>>,
+    
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = quote_mint.decimals,
+        mint::authority = lp_vault,
+        seeds = [LP_SHARE_MINT_SEED, lp_vault.key().as_ref()],
+        bump
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = base_mint,
+        token::authority = lp_vault,
+        seeds = [LP_VAULT_TOKEN_SEED, lp_vault.key().as_ref(), base_mint.key().as_ref()],
+        bump
+    )]
+    pub base_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = quote_mint,
+        token::authority = lp_vault,
+        seeds = [LP_VAULT_TOKEN_SEED, lp_vault.key().as_ref(), quote_mint.key().as_ref()],
+        bump
+    )]
+    pub quote_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = share_mint,
+        token::authority = lp_vault,
+        seeds = [LP_VAULT_TOKEN_SEED, lp_vault.key().as_ref(), share_mint.key().as_ref()],
+        bump
+    )]
+    pub pending_shares: Box<Account<'info, TokenAccount>>,
+    
+    #[account(address = order_book.base_mint @ ShadowSwapError::InvalidTokenMint)]
+    pub base_mint: Box<Account<'info, Mint>>,
+    
+    #[account(address = order_book.quote_mint @ ShadowSwapError::InvalidTokenMint)]
+    pub quote_mint: Box<Account<'info, Mint>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 }
 
-// ============================================================================
-// Account Structures
-// ============================================================================
+#[derive(Accounts)]
+pub struct ConfigureLpVault<'info> {
+    #[account(
+        constraint = order_book.authority == authority.key() @ ShadowSwapError::UnauthorizedCallback
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    #[account(
+        mut,
+        seeds = [LP_VAULT_SEED, order_book.key().as_ref()],
+        bump = lp_vault.bump
+    )]
+    pub lp_vault: Account<'info, LpVault>,
+    
+    pub authority: Signer<'info>,
+}
 
-/// EncryptedOrder - Stores individual encrypted order data
-/// 
-/// This account stores fully encrypted order information. The on-chain program
-/// never sees plaintext order details (price, amount, side). All order data
-/// is encrypted client-side and stored as a cipher payload.
-#[account]
-pub struct EncryptedOrder {
-    /// Order owner's public key
-    pub owner: Pubkey,
+#[derive(Accounts)]
+pub struct DepositLiquidity<'info> {
+    pub order_book: Box<Account<'info, OrderBook>>,
     
-    /// Order book this order belongs to
-    pub order_book: Pubkey,
+    #[account(
+        seeds = [LP_VAULT_SEED, order_book.key().as_ref()],
+        bump = lp_vault.bump
+    )]
+    pub lp_vault: Box<Account<'info, LpVault>>,
     
-    /// Encrypted order payload containing:
-    /// - Order side (buy/sell) - encrypted
-    /// - Price - encrypted
-    /// - Amount - encrypted
-    /// - Other order parameters - encrypted
-    /// Maximum size: 512 bytes for encrypted data
-    pub cipher_payload: Vec<u8>,
+    #[account(mut, address = lp_vault.share_mint @ ShadowSwapError::InvalidLpVault)]
+    pub share_mint: Box<Account<'info, Mint>>,
     
-    /// Order status (1 = active, 2 = partially filled, 3 = filled, 4 = cancelled)
-    /// This can be public as it doesn't reveal order details
-    pub status: u8,
+    #[account(mut, address = lp_vault.base_vault @ ShadowSwapError::InvalidLpVault)]
+    pub base_vault: Box<Account<'info, TokenAccount>>,
     
-    /// Encrypted remaining amount (updates as order fills)
-    pub encrypted_remaining: Vec<u8>,
+    #[account(mut, address = lp_vault.quote_vault @ ShadowSwapError::InvalidLpVault)]
+    pub quote_vault: Box<Account<'info, TokenAccount>>,
     
-    /// Escrow account holding the order's funds
-    pub escrow: Pubkey,
+    #[account(mut)]
+    pub lp_base_account: Box<Account<'info, TokenAccount>>,
     
-    /// Order creation timestamp
-    pub created_at: i64,
+    #[account(mut)]
+    pub lp_quote_account: Box<Account<'info, TokenAccount>>,
     
-    /// Last update timestamp
-    pub updated_at: i64,
+    /// LP's token account receiving the new shares
+    #[account(mut)]
+    pub lp_share_account: Box<Account<'info, TokenAccount>>,
     
-    /// Order ID (sequential, assigned by order book)
-    pub order_id: u64,
+    /// CHECK: Price oracle configured on the order book; layout is checked by
+    /// `PythPriceAccount`
+    #[account(address = order_book.oracle @ ShadowSwapError::InvalidOracleAccount)]
+    pub oracle: UncheckedAccount<'info>,
     
-    /// Bump seed for PDA derivation
-    pub bump: u8,
+    pub lp: Signer<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
-/// OrderBook - Manages the order book for a trading pair
-/// 
-/// For MVP, this handles SOL/USDC pair. The structure is designed to be
-/// extensible for multi-token support in future phases.
-#[account]
-pub struct OrderBook {
-    /// Authority that can manage the order book
-    pub authority: Pubkey,
+#[derive(Accounts)]
+pub struct RequestLpWithdrawal<'info> {
+    #[account(mut)]
+    pub lp_vault: Box<Account<'info, LpVault>>,
     
-    /// Base token mint (SOL for MVP)
-    pub base_mint: Pubkey,
+    #[account(
+        init,
+        payer = lp,
+        space = 8 + std::mem::size_of::<LpWithdrawalRequest>(),
+        seeds = [LP_WITHDRAWAL_SEED, lp_vault.key().as_ref(), lp_vault.withdrawal_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub withdrawal_request: Box<Account<'info, LpWithdrawalRequest>>,
     
-    /// Quote token mint (USDC for MVP)
-    pub quote_mint: Pubkey,
+    #[account(mut)]
+    pub lp_share_account: Box<Account<'info, TokenAccount>>,
     
-    /// Total number of orders created (used for sequential order IDs)
-    pub order_count: u64,
+    #[account(mut, address = lp_vault.pending_shares @ ShadowSwapError::InvalidLpVault)]
+    pub pending_shares: Box<Account<'info, TokenAccount>>,
     
-    /// Number of active orders
-    pub active_orders: u64,
+    #[account(address = lp_vault.base_vault @ ShadowSwapError::InvalidLpVault)]
+    pub base_vault: Box<Account<'info, TokenAccount>>,
     
-    /// Total trading volume (base token) - encrypted
-    pub encrypted_volume_base: Vec<u8>,
+    #[account(address = lp_vault.quote_vault @ ShadowSwapError::InvalidLpVault)]
+    pub quote_vault: Box<Account<'info, TokenAccount>>,
     
-    /// Total trading volume (quote token) - encrypted
-    pub encrypted_volume_quote: Vec<u8>,
+    /// Token account receiving the base payout
+    #[account(
+        constraint = base_destination.owner == lp.key() @ ShadowSwapError::UnauthorizedCallback,
+        constraint = base_destination.mint == base_vault.mint @ ShadowSwapError::InvalidTokenMint
+    )]
+    pub base_destination: Box<Account<'info, TokenAccount>>,
     
-    /// Order book creation timestamp
-    pub created_at: i64,
+    /// Token account receiving the quote payout
+    #[account(
+        constraint = quote_destination.owner == lp.key() @ ShadowSwapError::UnauthorizedCallback,
+        constraint = quote_destination.mint == quote_vault.mint @ ShadowSwapError::InvalidTokenMint
+    )]
+    pub quote_destination: Box<Account<'info, TokenAccount>>,
     
-    /// Last trade timestamp
-    pub last_trade_at: i64,
+    #[account(mut)]
+    pub lp: Signer<'info>,
     
-    /// Fee basis points (e.g., 30 = 0.3%)
-    pub fee_bps: u16,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProcessLpWithdrawal<'info> {
+    #[account(mut)]
+    pub lp_vault: Box<Account<'info, LpVault>>,
     
-    /// Fee collector account
-    pub fee_collector: Pubkey,
+    #[account(
+        mut,
+        close = owner,
+        has_one = lp_vault @ ShadowSwapError::InvalidLpVault,
+        has_one = owner @ ShadowSwapError::UnauthorizedCallback,
+        has_one = base_destination @ ShadowSwapError::InvalidRefundAccount,
+        has_one = quote_destination @ ShadowSwapError::InvalidRefundAccount
+    )]
+    pub withdrawal_request: Box<Account<'info, LpWithdrawalRequest>>,
     
-    /// Minimum order size (base token, in lamports/smallest unit)
-    pub min_base_order_size: u64,
+    #[account(mut, address = lp_vault.share_mint @ ShadowSwapError::InvalidLpVault)]
+    pub share_mint: Box<Account<'info, Mint>>,
     
-    /// Whether the order book is active
-    pub is_active: bool,
+    #[account(mut, address = lp_vault.pending_shares @ ShadowSwapError::InvalidLpVault)]
+    pub pending_shares: Box<Account<'info, TokenAccount>>,
     
-    /// Bump seed for PDA derivation
-    pub bump: u8,
+    #[account(mut, address = lp_vault.base_vault @ ShadowSwapError::InvalidLpVault)]
+    pub base_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(mut, address = lp_vault.quote_vault @ ShadowSwapError::InvalidLpVault)]
+    pub quote_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub base_destination: Box<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub quote_destination: Box<Account<'info, TokenAccount>>,
+    
+    /// CHECK: LP that queued the request; only receives its rent
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+    
+    pub cranker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
-/// Escrow - Holds funds for an order until it's matched or cancelled
-/// 
-/// Each order has an associated escrow account that holds either:
-/// - Base tokens (for sell orders)
-/// - Quote tokens (for buy orders)
-#[account]
-pub struct Escrow {
-    /// Order this escrow belongs to
-    pub order: Pubkey,
+#[derive(Accounts)]
+pub struct SkipLpWithdrawal<'info> {
+    #[account(mut)]
+    pub lp_vault: Box<Account<'info, LpVault>>,
     
-    /// Order owner
-    pub owner: Pubkey,
+    #[account(has_one = lp_vault @ ShadowSwapError::InvalidLpVault)]
+    pub withdrawal_request: Box<Account<'info, LpWithdrawalRequest>>,
     
-    /// Order book
-    pub order_book: Pubkey,
+    #[account(mut, address = lp_vault.share_mint @ ShadowSwapError::InvalidLpVault)]
+    pub share_mint: Box<Account<'info, Mint>>,
     
-    /// Token account holding escrowed funds
-    /// This is a PDA-owned token account
-    pub token_account: Pubkey,
+    #[account(mut, address = lp_vault.pending_shares @ ShadowSwapError::InvalidLpVault)]
+    pub pending_shares: Box<Account<'info, TokenAccount>>,
     
-    /// Mint of the escrowed token
-    pub token_mint: Pubkey,
+    #[account(mut, address = lp_vault.base_vault @ ShadowSwapError::InvalidLpVault)]
+    pub base_vault: Box<Account<'info, TokenAccount>>,
     
-    /// Original encrypted amount deposited
-    pub encrypted_amount: Vec<u8>,
+    #[account(mut, address = lp_vault.quote_vault @ ShadowSwapError::InvalidLpVault)]
+    pub quote_vault: Box<Account<'info, TokenAccount>>,
+    
+    /// Parked base payout (owned by the request PDA)
+    #[account(
+        init,
+        payer = cranker,
+        token::mint = base_mint,
+        token::authority = withdrawal_request,
+        seeds = [LP_CLAIM_SEED, withdrawal_request.key().as_ref(), base_mint.key().as_ref()],
+        bump
+    )]
+    pub base_claim: Box<Account<'info, TokenAccount>>,
+    
+    /// Parked quote payout (owned by the request PDA)
+    #[account(
+        init,
+        payer = cranker,
+        token::mint = quote_mint,
+        token::authority = withdrawal_request,
+        seeds = [LP_CLAIM_SEED, withdrawal_request.key().as_ref(), quote_mint.key().as_ref()],
+        bump
+    )]
+    pub quote_claim: Box<Account<'info, TokenAccount>>,
+    
+    #[account(address = base_vault.mint @ ShadowSwapError::InvalidTokenMint)]
+    pub base_mint: Box<Account<'info, Mint>>,
+    
+    #[account(address = quote_vault.mint @ ShadowSwapError::InvalidTokenMint)]
+    pub quote_mint: Box<Account<'info, Mint>>,
+    
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimLpWithdrawal<'info> {
+    pub lp_vault: Box<Account<'info, LpVault>>,
+    
+    #[account(
+        mut,
+        close = owner,
+        has_one = lp_vault @ ShadowSwapError::InvalidLpVault,
+        has_one = owner @ ShadowSwapError::UnauthorizedCallback,
+        seeds = [LP_WITHDRAWAL_SEED, lp_vault.key().as_ref(), withdrawal_request.index.to_le_bytes().as_ref()],
+        bump = withdrawal_request.bump
+    )]
+    pub withdrawal_request: Box<Account<'info, LpWithdrawalRequest>>,
+    
+    #[account(
+        mut,
+        seeds = [LP_CLAIM_SEED, withdrawal_request.key().as_ref(), base_claim.mint.as_ref()],
+        bump,
+        constraint = base_claim.mint != quote_claim.mint @ ShadowSwapError::InvalidTokenMint
+    )]
+    pub base_claim: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [LP_CLAIM_SEED, withdrawal_request.key().as_ref(), quote_claim.mint.as_ref()],
+        bump
+    )]
+    pub quote_claim: Box<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub base_destination: Box<Account<'info, TokenAccount>>,
     
-    /// Encrypted remaining amount (decreases as order fills)
-    pub encrypted_remaining: Vec<u8>,
+    #[account(mut)]
+    pub quote_destination: Box<Account<'info, TokenAccount>>,
     
-    /// Escrow creation timestamp
-    pub created_at: i64,
+    #[account(mut)]
+    pub owner: Signer<'info>,
     
-    /// Bump seed for PDA derivation
-    pub bump: u8,
+    pub token_program: Program<'info, Token>,
 }
 
-/// CallbackAuth - Authentication token for callback operations
-/// 
-/// This account is used to authorize callback operations from the keeper/matching
-/// engine. It ensures only authorized entities can trigger order matching.
-#[account]
-pub struct CallbackAuth {
-    /// Authority that can use this callback auth
-    pub authority: Pubkey,
+#[derive(Accounts)]
+pub struct BackstopFill<'info> {
+    /// Callback authorization - verifies keeper is authorized
+    #[account(
+        constraint = callback_auth.is_active @ ShadowSwapError::UnauthorizedCallback,
+        constraint = callback_auth.authority == keeper.key() @ ShadowSwapError::UnauthorizedCallback,
+        seeds = [CALLBACK_AUTH_SEED, order_book.key().as_ref(), keeper.key().as_ref()],
+        bump = callback_auth.bump
+    )]
+    pub callback_auth: Box<Account<'info, CallbackAuth>>,
     
-    /// Order book this callback auth is valid for
-    pub order_book: Pubkey,
+    #[account(mut)]
+    pub order_book: Box<Account<'info, OrderBook>>,
     
-    /// Nonce to prevent replay attacks
-    pub nonce: u64,
+    #[account(
+        seeds = [LP_VAULT_SEED, order_book.key().as_ref()],
+        bump = lp_vault.bump
+    )]
+    pub lp_vault: Box<Account<'info, LpVault>>,
     
-    /// Expiration timestamp
-    pub expires_at: i64,
+    #[account(mut, address = lp_vault.base_vault @ ShadowSwapError::InvalidLpVault)]
+    pub lp_base_vault: Box<Account<'info, TokenAccount>>,
     
-    /// Whether this callback auth is active
-    pub is_active: bool,
+    #[account(mut, address = lp_vault.quote_vault @ ShadowSwapError::InvalidLpVault)]
+    pub lp_quote_vault: Box<Account<'info, TokenAccount>>,
     
-    /// Creation timestamp
-    pub created_at: i64,
+    #[account(
+        mut,
+        constraint = order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook,
+        constraint = order.status == ORDER_STATUS_ACTIVE || order.status == ORDER_STATUS_PARTIAL @ ShadowSwapError::InvalidOrderStatus,
+        constraint = order.user_balance == Pubkey::default() @ ShadowSwapError::BalanceBackedOrder
+    )]
+    pub order: Box<Account<'info, EncryptedOrder>>,
     
-    /// Bump seed for PDA derivation
-    pub bump: u8,
-}
-
-// ============================================================================
-// Match Result Structures
-// ============================================================================
-
-/// Match result input for settlement (plaintext from keeper)
-/// 
-/// This struct contains the decrypted match details that the keeper bot
-/// submits to execute the trade settlement. The keeper has computed the
-/// matching off-chain and decrypted the results.
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
-pub struct MatchResultInput {
-    /// Buyer's order account pubkey
-    pub buyer_pubkey: Pubkey,
+    #[account(
+        seeds = [ESCROW_SEED, order.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
     
-    /// Seller's order account pubkey
-    pub seller_pubkey: Pubkey,
+    #[account(
+        mut,
+        address = escrow.token_account @ ShadowSwapError::InvalidEscrow
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
     
-    /// Matched amount in base token units (e.g., lamports for WSOL)
-    pub matched_amount: u64,
+    /// Order owner's token account receiving the proceeds
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == order.owner @ ShadowSwapError::UnauthorizedCallback
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
     
-    /// Execution price: quote tokens per base token
-    /// (adjusted for decimals, e.g., USDC micro-units per WSOL lamport)
-    pub execution_price: u64,
-}
-
-// ============================================================================
-// Events
-// ============================================================================
-
-/// Event emitted when a trade is settled
-#[event]
-pub struct TradeSettled {
-    pub order_book: Pubkey,
-    pub buyer: Pubkey,
-    pub seller: Pubkey,
-    pub buyer_order_id: u64,
-    pub seller_order_id: u64,
-    pub base_amount: u64,
-    pub quote_amount: u64,
-    pub execution_price: u64,
-    pub timestamp: i64,
+    /// Order owner's token account for the escrowed mint; required when the
+    /// fill completes the order
+    #[account(
+        mut,
+        constraint = owner_refund_account.owner == order.owner
+            && owner_refund_account.mint == escrow.token_mint @ ShadowSwapError::InvalidRefundAccount
+    )]
+    pub owner_refund_account: Option<Box<Account<'info, TokenAccount>>>,
+    
+    /// Fee collector's token account receiving the protocol fee share
+    #[account(
+        mut,
+        constraint = fee_collector_token_account.owner == order_book.fee_collector @ ShadowSwapError::InvalidFeeConfiguration
+    )]
+    pub fee_collector_token_account: Box<Account<'info, TokenAccount>>,
+    
+    /// CHECK: Price oracle configured on the order book; layout is checked by
+    /// `PythPriceAccount`
+    #[account(address = order_book.oracle @ ShadowSwapError::InvalidOracleAccount)]
+    pub oracle: UncheckedAccount<'info>,
+    
+    pub keeper: Signer<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
-// ============================================================================
-// Error Codes
-// ============================================================================
-
-#[error_code]
-pub enum ShadowSwapError {
-    #[msg("Order book is not active")]
-    OrderBookNotActive,
+#[derive(Accounts)]
+pub struct OpenSealedAuction<'info> {
+    #[account(
+        mut,
+        constraint = order_book.authority == authority.key() @ ShadowSwapError::UnauthorizedCallback,
+        constraint = order_book.is_active @ ShadowSwapError::OrderBookNotActive
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
     
-    #[msg("Invalid order status")]
-    InvalidOrderStatus,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<SealedAuction>() + MAX_SEALED_ORDERS * std::mem::size_of::<SealedBid>(),
+        seeds = [SEALED_AUCTION_SEED, order_book.key().as_ref(), order_book.sealed_auction_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub auction: Box<Account<'info, SealedAuction>>,
     
-    #[msg("Order amount too small")]
-    OrderTooSmall,
+    #[account(
+        init,
+        payer = authority,
+        token::mint = base_mint,
+        token::authority = auction,
+        seeds = [SEALED_VAULT_SEED, auction.key().as_ref(), base_mint.key().as_ref()],
+        bump
+    )]
+    pub base_vault: Box<Account<'info, TokenAccount>>,
     
-    #[msg("Invalid cipher payload")]
-    InvalidCipherPayload,
+    #[account(
+        init,
+        payer = authority,
+        token::mint = quote_mint,
+        token::authority = auction,
+        seeds = [SEALED_VAULT_SEED, auction.key().as_ref(), quote_mint.key().as_ref()],
+        bump
+    )]
+    pub quote_vault: Box<Account<'info, TokenAccount>>,
     
-    #[msg("Unauthorized callback")]
-    UnauthorizedCallback,
+    #[account(address = order_book.base_mint @ ShadowSwapError::InvalidTokenMint)]
+    pub base_mint: Box<Account<'info, Mint>>,
     
-    #[msg("Callback auth expired")]
-    CallbackAuthExpired,
+    #[account(address = order_book.quote_mint @ ShadowSwapError::InvalidTokenMint)]
+    pub quote_mint: Box<Account<'info, Mint>>,
     
-    #[msg("Invalid escrow account")]
-    InvalidEscrow,
+    #[account(mut)]
+    pub authority: Signer<'info>,
     
-    #[msg("Insufficient funds in escrow")]
-    InsufficientEscrowFunds,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct CommitSealedOrder<'info> {
+    #[account(
+        mut,
+        constraint = auction.status == SEALED_AUCTION_STATUS_OPEN @ ShadowSwapError::InvalidSealedAuctionStatus
+    )]
+    pub auction: Box<Account<'info, SealedAuction>>,
     
-    #[msg("Invalid token mint")]
-    InvalidTokenMint,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<SealedOrder>(),
+        seeds = [SEALED_ORDER_SEED, auction.key().as_ref(), auction.commit_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub sealed_order: Box<Account<'info, SealedOrder>>,
     
-    #[msg("Order not found")]
-    OrderNotFound,
+    /// Round vault for the deposit: base for sells, quote for buys
+    #[account(
+        mut,
+        constraint = vault.key() == auction.base_vault || vault.key() == auction.quote_vault @ ShadowSwapError::InvalidEscrow
+    )]
+    pub vault: Box<Account<'info, TokenAccount>>,
     
-    #[msg("Order already filled")]
-    OrderAlreadyFilled,
+    #[account(mut)]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
     
-    #[msg("Order already cancelled")]
-    OrderAlreadyCancelled,
+    #[account(mut)]
+    pub owner: Signer<'info>,
     
-    #[msg("Invalid order book")]
-    InvalidOrderBook,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevealSealedOrder<'info> {
+    #[account(address = auction.order_book @ ShadowSwapError::InvalidOrderBook)]
+    pub order_book: Box<Account<'info, OrderBook>>,
     
-    #[msg("Invalid fee configuration")]
-    InvalidFeeConfiguration,
+    #[account(
+        mut,
+        constraint = auction.status == SEALED_AUCTION_STATUS_OPEN @ ShadowSwapError::InvalidSealedAuctionStatus
+    )]
+    pub auction: Box<Account<'info, SealedAuction>>,
     
-    #[msg("Numerical overflow")]
-    NumericalOverflow,
+    #[account(
+        mut,
+        has_one = auction @ ShadowSwapError::InvalidSealedAuction,
+        has_one = owner @ ShadowSwapError::UnauthorizedCallback,
+        constraint = !sealed_order.revealed @ ShadowSwapError::InvalidSealedReveal
+    )]
+    pub sealed_order: Box<Account<'info, SealedOrder>>,
     
-    #[msg("Order is not active")]
-    OrderNotActive,
+    pub owner: Signer<'info>,
 }
 
-// ============================================================================
-// Constants
-// ============================================================================
-
-/// Maximum size for encrypted order payload (512 bytes)
-pub const MAX_CIPHER_PAYLOAD_SIZE: usize = 512;
-
-/// Maximum size for encrypted amount fields (64 bytes)
-pub const MAX_ENCRYPTED_AMOUNT_SIZE: usize = 64;
-
-/// Maximum size for encrypted volume fields (64 bytes)
-pub const MAX_ENCRYPTED_VOLUME_SIZE: usize = 64;
-
-/// Order status: Active
-pub const ORDER_STATUS_ACTIVE: u8 = 1;
-
-/// Order status: Partially filled
-pub const ORDER_STATUS_PARTIAL: u8 = 2;
-
-/// Order status: Fully filled
-pub const ORDER_STATUS_FILLED: u8 = 3;
-
-/// Order status: Cancelled
-pub const ORDER_STATUS_CANCELLED: u8 = 4;
-
-/// Order status: Matched, pending execution
-pub const ORDER_STATUS_MATCHED_PENDING: u8 = 5;
-
-/// Seeds for PDA derivation
-pub const ORDER_BOOK_SEED: &[u8] = b"order_book";
-pub const ORDER_SEED: &[u8] = b"order";
-pub const ESCROW_SEED: &[u8] = b"escrow";
-pub const CALLBACK_AUTH_SEED: &[u8] = b"callback_auth";
+#[derive(Accounts)]
+pub struct ClearSealedAuction<'info> {
+    #[account(mut, address = auction.order_book @ ShadowSwapError::InvalidOrderBook)]
+    pub order_book: Box<Account<'info, OrderBook>>,
+    
+    #[account(
+        mut,
+        constraint = auction.status == SEALED_AUCTION_STATUS_OPEN @ ShadowSwapError::InvalidSealedAuctionStatus
+    )]
+    pub auction: Box<Account<'info, SealedAuction>>,
+    
+    pub cranker: Signer<'info>,
+}
 
-// ============================================================================
-// Instruction Contexts
-// ============================================================================
+#[derive(Accounts)]
+pub struct SettleSealedOrder<'info> {
+    #[account(mut, address = auction.order_book @ ShadowSwapError::InvalidOrderBook)]
+    pub order_book: Box<Account<'info, OrderBook>>,
+    
+    #[account(
+        mut,
+        constraint = auction.status == SEALED_AUCTION_STATUS_CLEARED @ ShadowSwapError::InvalidSealedAuctionStatus
+    )]
+    pub auction: Box<Account<'info, SealedAuction>>,
+    
+    #[account(
+        mut,
+        close = owner,
+        has_one = auction @ ShadowSwapError::InvalidSealedAuction,
+        has_one = owner @ ShadowSwapError::UnauthorizedCallback
+    )]
+    pub sealed_order: Box<Account<'info, SealedOrder>>,
+    
+    #[account(mut, address = auction.base_vault @ ShadowSwapError::InvalidEscrow)]
+    pub base_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(mut, address = auction.quote_vault @ ShadowSwapError::InvalidEscrow)]
+    pub quote_vault: Box<Account<'info, TokenAccount>>,
+    
+    /// Owner's base token account
+    #[account(
+        mut,
+        constraint = owner_base_account.owner == owner.key() @ ShadowSwapError::UnauthorizedCallback,
+        constraint = owner_base_account.mint == order_book.base_mint @ ShadowSwapError::InvalidTokenMint
+    )]
+    pub owner_base_account: Box<Account<'info, TokenAccount>>,
+    
+    /// Owner's quote token account
+    #[account(
+        mut,
+        constraint = owner_quote_account.owner == owner.key() @ ShadowSwapError::UnauthorizedCallback,
+        constraint = owner_quote_account.mint == order_book.quote_mint @ ShadowSwapError::InvalidTokenMint
+    )]
+    pub owner_quote_account: Box<Account<'info, TokenAccount>>,
+    
+    /// Fee collector's token account receiving the penalty of an unrevealed
+    /// order; required only when there is one
+    #[account(mut)]
+    pub fee_collector_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    
+    /// CHECK: Order owner; receives the payout and the order's rent
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+    
+    pub cranker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
 
 #[derive(Accounts)]
-pub struct InitializeOrderBook<'info> {
+pub struct AddRfqMaker<'info> {
+    #[account(
+        constraint = order_book.authority == authority.key() @ ShadowSwapError::UnauthorizedCallback
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
     #[account(
         init,
         payer = authority,
-        space = 8 + std::mem::size_of::<OrderBook>() + 200, // Extra space for Vec fields
-        seeds = [ORDER_BOOK_SEED, base_mint.key().as_ref(), quote_mint.key().as_ref()],
+        space = 8 + std::mem::size_of::<RfqMaker>(),
+        seeds = [RFQ_MAKER_SEED, order_book.key().as_ref(), maker.key().as_ref()],
         bump
     )]
+    pub rfq_maker: Account<'info, RfqMaker>,
+    
+    /// CHECK: Maker wallet being whitelisted
+    pub maker: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveRfqMaker<'info> {
+    #[account(
+        constraint = order_book.authority == authority.key() @ ShadowSwapError::UnauthorizedCallback
+    )]
     pub order_book: Account<'info, OrderBook>,
     
+    #[account(
+        mut,
+        close = authority,
+        seeds = [RFQ_MAKER_SEED, order_book.key().as_ref(), rfq_maker.maker.as_ref()],
+        bump = rfq_maker.bump
+    )]
+    pub rfq_maker: Account<'info, RfqMaker>,
+    
     #[account(mut)]
     pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RequestQuote<'info> {
+    #[account(
+        mut,
+        constraint = order_book.is_active @ ShadowSwapError::OrderBookNotActive
+    )]
+    pub order_book: Box<Account<'info, OrderBook>>,
     
-    /// CHECK: Fee collector can be any account
-    pub fee_collector: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = taker,
+        space = 8 + std::mem::size_of::<RfqRequest>() + MAX_RFQ_MAKERS * 32 + MAX_CIPHER_PAYLOAD_SIZE,
+        seeds = [RFQ_REQUEST_SEED, order_book.key().as_ref(), order_book.rfq_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub request: Box<Account<'info, RfqRequest>>,
     
-    /// CHECK: Base mint reference for PDA seeds
-    pub base_mint: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = taker,
+        space = 8 + std::mem::size_of::<Escrow>() + MAX_ENCRYPTED_AMOUNT_SIZE * 2 + 100,
+        seeds = [ESCROW_SEED, request.key().as_ref()],
+        bump
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
     
-    /// CHECK: Quote mint reference for PDA seeds
-    pub quote_mint: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = taker,
+        token::mint = token_mint,
+        token::authority = escrow,
+        seeds = [b"escrow_token", request.key().as_ref()],
+        bump
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+    
+    pub token_mint: Box<Account<'info, Mint>>,
+    
+    #[account(mut)]
+    pub taker: Signer<'info>,
     
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-pub struct SubmitEncryptedOrder<'info> {
+pub struct SubmitRfqQuote<'info> {
+    #[account(address = request.order_book @ ShadowSwapError::InvalidOrderBook)]
+    pub order_book: Box<Account<'info, OrderBook>>,
+    
+    /// Maker's whitelist entry; must still exist when quoting
+    #[account(
+        seeds = [RFQ_MAKER_SEED, order_book.key().as_ref(), maker.key().as_ref()],
+        bump = rfq_maker.bump
+    )]
+    pub rfq_maker: Box<Account<'info, RfqMaker>>,
+    
     #[account(
         mut,
-        constraint = order_book.is_active @ ShadowSwapError::OrderBookNotActive
+        constraint = request.status == RFQ_STATUS_OPEN @ ShadowSwapError::RfqNotOpen
     )]
-    pub order_book: Account<'info, OrderBook>,
+    pub request: Box<Account<'info, RfqRequest>>,
     
     #[account(
         init,
-        payer = owner,
-        space = 8 + std::mem::size_of::<EncryptedOrder>() + MAX_CIPHER_PAYLOAD_SIZE + MAX_ENCRYPTED_AMOUNT_SIZE + 100,
-        seeds = [ORDER_SEED, order_book.key().as_ref(), order_book.order_count.to_le_bytes().as_ref()],
+        payer = maker,
+        space = 8 + std::mem::size_of::<RfqQuote>() + MAX_CIPHER_PAYLOAD_SIZE,
+        seeds = [RFQ_QUOTE_SEED, request.key().as_ref(), maker.key().as_ref()],
         bump
     )]
-    pub order: Account<'info, EncryptedOrder>,
+    pub quote: Box<Account<'info, RfqQuote>>,
     
     #[account(
         init,
-        payer = owner,
+        payer = maker,
         space = 8 + std::mem::size_of::<Escrow>() + MAX_ENCRYPTED_AMOUNT_SIZE * 2 + 100,
-        seeds = [ESCROW_SEED, order.key().as_ref()],
+        seeds = [ESCROW_SEED, quote.key().as_ref()],
         bump
     )]
-    pub escrow: Account<'info, Escrow>,
+    pub escrow: Box<Account<'info, Escrow>>,
     
     #[account(
         init,
-        payer = owner,
+        payer = maker,
         token::mint = token_mint,
         token::authority = escrow,
-        seeds = [b"escrow_token", order.key().as_ref()],
+        seeds = [b"escrow_token", quote.key().as_ref()],
         bump
     )]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
     
     #[account(mut)]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
     
-    pub token_mint: Account<'info, anchor_spl::token::Mint>,
+    pub token_mint: Box<Account<'info, Mint>>,
     
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub maker: Signer<'info>,
     
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
@@ -753,27 +6857,164 @@ pub struct SubmitEncryptedOrder<'info> {
 }
 
 #[derive(Accounts)]
-pub struct CancelOrder<'info> {
+pub struct AcceptRfqQuote<'info> {
+    #[account(mut, address = request.order_book @ ShadowSwapError::InvalidOrderBook)]
+    pub order_book: Box<Account<'info, OrderBook>>,
+    
     #[account(
         mut,
-        has_one = owner @ ShadowSwapError::UnauthorizedCallback,
-        constraint = order.status == ORDER_STATUS_ACTIVE || order.status == ORDER_STATUS_PARTIAL @ ShadowSwapError::InvalidOrderStatus
+        has_one = taker @ ShadowSwapError::UnauthorizedCallback,
+        constraint = request.status == RFQ_STATUS_OPEN @ ShadowSwapError::RfqNotOpen
     )]
-    pub order: Account<'info, EncryptedOrder>,
+    pub request: Box<Account<'info, RfqRequest>>,
+    
+    #[account(has_one = request @ ShadowSwapError::InvalidRfqQuote)]
+    pub quote: Box<Account<'info, RfqQuote>>,
+    
+    #[account(address = request.escrow @ ShadowSwapError::InvalidEscrow)]
+    pub request_escrow: Box<Account<'info, Escrow>>,
+    
+    #[account(mut, address = request_escrow.token_account @ ShadowSwapError::InvalidEscrow)]
+    pub request_escrow_token_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(address = quote.escrow @ ShadowSwapError::InvalidEscrow)]
+    pub quote_escrow: Box<Account<'info, Escrow>>,
+    
+    #[account(mut, address = quote_escrow.token_account @ ShadowSwapError::InvalidEscrow)]
+    pub quote_escrow_token_account: Box<Account<'info, TokenAccount>>,
     
     #[account(
         mut,
-        seeds = [ESCROW_SEED, order.key().as_ref()],
-        bump = escrow.bump
+        constraint = taker_base_account.owner == taker.key() @ ShadowSwapError::InvalidRefundAccount,
+        constraint = taker_base_account.mint == order_book.base_mint @ ShadowSwapError::InvalidTokenMint
     )]
-    pub escrow: Account<'info, Escrow>,
+    pub taker_base_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = taker_quote_account.owner == taker.key() @ ShadowSwapError::InvalidRefundAccount,
+        constraint = taker_quote_account.mint == order_book.quote_mint @ ShadowSwapError::InvalidTokenMint
+    )]
+    pub taker_quote_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = maker_base_account.owner == quote.maker @ ShadowSwapError::InvalidRefundAccount,
+        constraint = maker_base_account.mint == order_book.base_mint @ ShadowSwapError::InvalidTokenMint
+    )]
+    pub maker_base_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = maker_quote_account.owner == quote.maker @ ShadowSwapError::InvalidRefundAccount,
+        constraint = maker_quote_account.mint == order_book.quote_mint @ ShadowSwapError::InvalidTokenMint
+    )]
+    pub maker_quote_account: Box<Account<'info, TokenAccount>>,
+    
+    /// CHECK: Price oracle configured on the order book; layout is checked by
+    /// `PythPriceAccount`. Required when the book has an oracle.
+    #[account(address = order_book.oracle @ ShadowSwapError::InvalidOracleAccount)]
+    pub oracle: Option<UncheckedAccount<'info>>,
+    
+    pub taker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawRfqQuote<'info> {
+    pub request: Box<Account<'info, RfqRequest>>,
+    
+    #[account(
+        mut,
+        close = maker,
+        has_one = request @ ShadowSwapError::InvalidRfqQuote,
+        has_one = maker @ ShadowSwapError::UnauthorizedCallback
+    )]
+    pub quote: Box<Account<'info, RfqQuote>>,
+    
+    #[account(address = quote.escrow @ ShadowSwapError::InvalidEscrow)]
+    pub escrow: Box<Account<'info, Escrow>>,
+    
+    #[account(mut, address = escrow.token_account @ ShadowSwapError::InvalidEscrow)]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = maker_token_account.owner == maker.key() @ ShadowSwapError::InvalidRefundAccount,
+        constraint = maker_token_account.mint == escrow.token_mint @ ShadowSwapError::InvalidRefundAccount
+    )]
+    pub maker_token_account: Box<Account<'info, TokenAccount>>,
     
     #[account(mut)]
-    pub escrow_token_account: Account<'info, TokenAccount>,
+    pub maker: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRfqRequest<'info> {
+    #[account(mut, address = request.order_book @ ShadowSwapError::InvalidOrderBook)]
+    pub order_book: Box<Account<'info, OrderBook>>,
+    
+    #[account(
+        mut,
+        has_one = taker @ ShadowSwapError::UnauthorizedCallback,
+        constraint = request.status == RFQ_STATUS_OPEN @ ShadowSwapError::RfqNotOpen
+    )]
+    pub request: Box<Account<'info, RfqRequest>>,
+    
+    #[account(address = request.escrow @ ShadowSwapError::InvalidEscrow)]
+    pub escrow: Box<Account<'info, Escrow>>,
+    
+    #[account(mut, address = escrow.token_account @ ShadowSwapError::InvalidEscrow)]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = taker_token_account.owner == taker.key() @ ShadowSwapError::InvalidRefundAccount,
+        constraint = taker_token_account.mint == escrow.token_mint @ ShadowSwapError::InvalidRefundAccount
+    )]
+    pub taker_token_account: Box<Account<'info, TokenAccount>>,
+    
+    pub taker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct AuthorizeTradingDelegate<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + std::mem::size_of::<TradingDelegate>(),
+        seeds = [TRADING_DELEGATE_SEED, owner.key().as_ref(), delegate.key().as_ref()],
+        bump
+    )]
+    pub trading_delegate: Account<'info, TradingDelegate>,
+    
+    /// CHECK: Session key being authorized
+    pub delegate: UncheckedAccount<'info>,
     
     #[account(mut)]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeTradingDelegate<'info> {
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner @ ShadowSwapError::UnauthorizedCallback
+    )]
+    pub trading_delegate: Account<'info, TradingDelegate>,
     
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOrders<'info> {
     #[account(mut)]
     pub order_book: Account<'info, OrderBook>,
     
@@ -781,6 +7022,116 @@ pub struct CancelOrder<'info> {
     pub token_program: Program<'info, Token>,
 }
 
+#[derive(Accounts)]
+pub struct ForceCancelOrders<'info> {
+    #[account(
+        mut,
+        constraint = order_book.authority == authority.key() @ ShadowSwapError::UnauthorizedCallback
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct StartDecommission<'info> {
+    #[account(
+        mut,
+        constraint = order_book.authority == authority.key() @ ShadowSwapError::UnauthorizedCallback,
+        constraint = order_book.decommissioned_at == 0 @ ShadowSwapError::BookDecommissioning
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WindDownOrders<'info> {
+    #[account(
+        mut,
+        constraint = order_book.decommissioned_at != 0 @ ShadowSwapError::BookNotDecommissioning
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    pub cranker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CloseOrderBook<'info> {
+    #[account(
+        mut,
+        close = authority,
+        constraint = order_book.authority == authority.key() @ ShadowSwapError::UnauthorizedCallback,
+        constraint = order_book.decommissioned_at != 0 @ ShadowSwapError::BookNotDecommissioning
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    /// CHECK: Base `book_vault` PDA; closed if it was initialized
+    #[account(
+        mut,
+        seeds = [BOOK_VAULT_SEED, order_book.key().as_ref(), order_book.base_mint.as_ref()],
+        bump
+    )]
+    pub base_vault: UncheckedAccount<'info>,
+    
+    /// CHECK: Quote `book_vault` PDA; closed if it was initialized
+    #[account(
+        mut,
+        seeds = [BOOK_VAULT_SEED, order_book.key().as_ref(), order_book.quote_mint.as_ref()],
+        bump
+    )]
+    pub quote_vault: UncheckedAccount<'info>,
+    
+    /// CHECK: The book's `LpVault` PDA; must not exist
+    #[account(
+        seeds = [LP_VAULT_SEED, order_book.key().as_ref()],
+        bump,
+        constraint = lp_vault.data_is_empty() @ ShadowSwapError::BookHasLpVault
+    )]
+    pub lp_vault: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct AddOrderShard<'info> {
+    #[account(
+        mut,
+        constraint = order_book.authority == authority.key() @ ShadowSwapError::UnauthorizedCallback
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<OrderShard>(),
+        seeds = [ORDER_SHARD_SEED, order_book.key().as_ref(), order_book.order_shards.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub order_shard: Account<'info, OrderShard>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureOracle<'info> {
+    #[account(
+        mut,
+        constraint = order_book.authority == authority.key() @ ShadowSwapError::UnauthorizedCallback
+    )]
+    pub order_book: Account<'info, OrderBook>,
+    
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct CreateCallbackAuth<'info> {
     #[account(
@@ -825,14 +7176,16 @@ pub struct SubmitMatchResults<'info> {
     /// Buyer's order account
     #[account(
         mut,
-        constraint = buyer_order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook
+        constraint = buyer_order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook,
+        constraint = buyer_order.user_balance == Pubkey::default() @ ShadowSwapError::BalanceBackedOrder
     )]
     pub buyer_order: Account<'info, EncryptedOrder>,
     
     /// Seller's order account
     #[account(
         mut,
-        constraint = seller_order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook
+        constraint = seller_order.order_book == order_book.key() @ ShadowSwapError::InvalidOrderBook,
+        constraint = seller_order.user_balance == Pubkey::default() @ ShadowSwapError::BalanceBackedOrder
     )]
     pub seller_order: Account<'info, EncryptedOrder>,
     
@@ -857,6 +7210,11 @@ pub struct SubmitMatchResults<'info> {
     /// Keeper account (authorized via callback_auth)
     pub keeper: Signer<'info>,
     
+    /// CHECK: Price oracle configured on the order book; layout is checked by
+    /// `PythPriceAccount`. Required when the book has an oracle.
+    #[account(address = order_book.oracle @ ShadowSwapError::InvalidOracleAccount)]
+    pub oracle: Option<UncheckedAccount<'info>>,
+    
     /// Token program for CPI calls
     pub token_program: Program<'info, Token>,
 }
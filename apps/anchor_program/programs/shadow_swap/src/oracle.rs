@@ -0,0 +1,113 @@
+//! Oracle price reading for settlement price bands
+//!
+//! Order books can optionally point at an external price account. Readers
+//! implement `OracleReader` for a specific account layout; `PythPriceAccount`
+//! handles Pyth v2 price accounts (and the mock accounts used in tests).
+
+use anchor_lang::prelude::*;
+
+use crate::ShadowSwapError;
+
+/// Price reported by an oracle: `price * 10^expo`
+#[derive(Clone, Copy, Debug)]
+pub struct OraclePrice {
+    pub price: i64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+/// Reads an `OraclePrice` from a provider-specific account
+pub trait OracleReader {
+    fn read_price(account: &AccountInfo) -> Result<OraclePrice>;
+}
+
+/// Pyth v2 price account
+pub struct PythPriceAccount;
+
+impl PythPriceAccount {
+    const MAGIC: u32 = 0xa1b2_c3d4;
+    const ACCOUNT_TYPE_PRICE: u32 = 3;
+    const STATUS_TRADING: u32 = 1;
+
+    // Byte offsets into the price account
+    const MAGIC_OFFSET: usize = 0;
+    const ACCOUNT_TYPE_OFFSET: usize = 8;
+    const EXPO_OFFSET: usize = 20;
+    const TIMESTAMP_OFFSET: usize = 96;
+    const AGG_PRICE_OFFSET: usize = 208;
+    const AGG_STATUS_OFFSET: usize = 224;
+    const MIN_LEN: usize = 240;
+}
+
+impl OracleReader for PythPriceAccount {
+    fn read_price(account: &AccountInfo) -> Result<OraclePrice> {
+        let data = account.try_borrow_data()?;
+        require!(
+            data.len() >= Self::MIN_LEN,
+            ShadowSwapError::InvalidOracleAccount
+        );
+        require!(
+            read_u32(&data, Self::MAGIC_OFFSET) == Self::MAGIC
+                && read_u32(&data, Self::ACCOUNT_TYPE_OFFSET) == Self::ACCOUNT_TYPE_PRICE,
+            ShadowSwapError::InvalidOracleAccount
+        );
+        require!(
+            read_u32(&data, Self::AGG_STATUS_OFFSET) == Self::STATUS_TRADING,
+            ShadowSwapError::StaleOraclePrice
+        );
+
+        Ok(OraclePrice {
+            price: read_u64(&data, Self::AGG_PRICE_OFFSET) as i64,
+            expo: read_u32(&data, Self::EXPO_OFFSET) as i32,
+            publish_time: read_u64(&data, Self::TIMESTAMP_OFFSET) as i64,
+        })
+    }
+}
+
+impl OraclePrice {
+    /// Reject prices published more than `max_staleness` seconds before `now`
+    pub fn ensure_fresh(&self, now: i64, max_staleness: i64) -> Result<()> {
+        require!(
+            now.saturating_sub(self.publish_time) <= max_staleness,
+            ShadowSwapError::StaleOraclePrice
+        );
+        Ok(())
+    }
+
+    /// Price rescaled to `10^target_expo` units
+    pub fn scaled_to(&self, target_expo: i32) -> Result<u64> {
+        require!(self.price > 0, ShadowSwapError::InvalidOracleAccount);
+
+        let shift = self.expo - target_expo;
+        let factor = 10u128
+            .checked_pow(shift.unsigned_abs())
+            .ok_or(ShadowSwapError::NumericalOverflow)?;
+        let scaled = if shift >= 0 {
+            (self.price as u128)
+                .checked_mul(factor)
+                .ok_or(ShadowSwapError::NumericalOverflow)?
+        } else {
+            self.price as u128 / factor
+        };
+        u64::try_from(scaled).map_err(|_| error!(ShadowSwapError::NumericalOverflow))
+    }
+}
+
+/// Distance of `price` from `reference`, in basis points of `reference`
+pub fn deviation_bps(price: u64, reference: u64) -> Result<u64> {
+    require!(reference > 0, ShadowSwapError::InvalidOracleAccount);
+
+    let deviation = (price.abs_diff(reference) as u128)
+        .checked_mul(10_000)
+        .ok_or(ShadowSwapError::NumericalOverflow)?
+        / reference as u128;
+    Ok(u64::try_from(deviation).unwrap_or(u64::MAX))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
@@ -1,5 +1,6 @@
 const anchor = require('@coral-xyz/anchor');
 const { Connection, PublicKey, Keypair } = require('@solana/web3.js');
+const { TOKEN_PROGRAM_ID, getAssociatedTokenAddressSync } = require('@solana/spl-token');
 const fs = require('fs');
 const os = require('os');
 
@@ -30,29 +31,61 @@ async function main() {
   const allOrders = await program.account.encryptedOrder.all();
   console.log(`   Found ${allOrders.length} orders\n`);
   
+  // Only our open, escrow-funded orders can go through cancel_orders
+  const ours = allOrders.filter(
+    (order) =>
+      order.account.owner.toString() === wallet.publicKey.toString() &&
+      (order.account.status === 1 || order.account.status === 2) &&
+      order.account.userBalance.equals(PublicKey.default)
+  );
+  
   let cancelled = 0;
   let failed = 0;
   
-  for (const order of allOrders) {
+  // cancel_orders takes [order, escrow, escrow token, refund] per order of
+  // one book; five orders keep a transaction under the size limit
+  const BATCH_SIZE = 5;
+  const byBook = new Map();
+  for (const order of ours) {
+    const book = order.account.orderBook.toString();
+    byBook.set(book, [...(byBook.get(book) || []), order]);
+  }
+  const batches = [];
+  for (const orders of byBook.values()) {
+    for (let i = 0; i < orders.length; i += BATCH_SIZE) {
+      batches.push(orders.slice(i, i + BATCH_SIZE));
+    }
+  }
+  
+  for (const batch of batches) {
     try {
-      // Only cancel if owner matches
-      if (order.account.owner.toString() === wallet.publicKey.toString()) {
-        await program.methods
-          .cancelOrder()
-          .accounts({
-            order: order.publicKey,
-            authority: wallet.publicKey,
-          })
-          .rpc();
-        
-        console.log(`✅ Cancelled order: ${order.publicKey.toString()}`);
-        cancelled++;
-      } else {
-        console.log(`⏭️  Skipped order (not owner): ${order.publicKey.toString()}`);
+      const remainingAccounts = [];
+      for (const order of batch) {
+        const escrow = await program.account.escrow.fetch(order.account.escrow);
+        const refund = getAssociatedTokenAddressSync(escrow.tokenMint, wallet.publicKey);
+        remainingAccounts.push(
+          { pubkey: order.publicKey, isSigner: false, isWritable: true },
+          { pubkey: order.account.escrow, isSigner: false, isWritable: false },
+          { pubkey: escrow.tokenAccount, isSigner: false, isWritable: true },
+          { pubkey: refund, isSigner: false, isWritable: true }
+        );
       }
+      
+      await program.methods
+        .cancelOrders()
+        .accounts({
+          orderBook: batch[0].account.orderBook,
+          owner: wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(remainingAccounts)
+        .rpc();
+      
+      batch.forEach((order) => console.log(`✅ Cancelled order: ${order.publicKey.toString()}`));
+      cancelled += batch.length;
     } catch (error) {
-      console.log(`❌ Failed to cancel ${order.publicKey.toString()}: ${error.message}`);
-      failed++;
+      console.log(`❌ Failed to cancel batch starting at ${batch[0].publicKey.toString()}: ${error.message}`);
+      failed += batch.length;
     }
   }
   
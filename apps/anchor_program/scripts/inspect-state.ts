@@ -107,7 +107,7 @@ async function main() {
     console.log(`  ${colorize('Authority:', 'bright')}           ${(orderBook as any).authority.toString()}`);
     console.log(`  ${colorize('Base Mint:', 'bright')}           ${(orderBook as any).baseMint.toString()}`);
     console.log(`  ${colorize('Quote Mint:', 'bright')}          ${(orderBook as any).quoteMint.toString()}`);
-    console.log(`  ${colorize('Order Count:', 'bright')}         ${(orderBook as any).orderCount.toString()}`);
+    console.log(`  ${colorize('Order Shards:', 'bright')}        ${(orderBook as any).orderShards}`);
     console.log(`  ${colorize('Fee (bps):', 'bright')}           ${(orderBook as any).feeBps}`);
     console.log(`  ${colorize('Min Base Order Size:', 'bright')} ${(orderBook as any).minBaseOrderSize.toString()}`);
   } catch (err: any) {
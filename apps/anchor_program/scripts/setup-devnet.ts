@@ -47,6 +47,11 @@ async function main() {
   console.log("📍 Derived Accounts:");
   console.log(`   Order Book: ${orderBook.toString()}`);
 
+  const [orderShard] = PublicKey.findProgramAddressSync(
+    [Buffer.from("order_shard"), orderBook.toBuffer(), Buffer.from([0])],
+    program.programId
+  );
+
   // Check if order book already exists
   try {
     const orderBookAccount = await program.account.orderBook.fetch(orderBook);
@@ -54,7 +59,7 @@ async function main() {
     console.log(`   Base Mint: ${orderBookAccount.baseMint.toString()}`);
     console.log(`   Quote Mint: ${orderBookAccount.quoteMint.toString()}`);
     console.log(`   Fee: ${orderBookAccount.feeBps} bps (${(orderBookAccount.feeBps / 100).toFixed(2)}%)`);
-    console.log(`   Active Orders: ${orderBookAccount.activeOrders.toString()}`);
+    console.log(`   Order Shards: ${orderBookAccount.orderShards}`);
   } catch (error) {
     console.log("\n📦 Initializing order book...");
     
@@ -67,6 +72,7 @@ async function main() {
           new anchor.BN(1_000_000) // 0.001 SOL minimum order size
         )
         .accounts({
+          orderShard,
           authority: provider.wallet.publicKey,
           feeCollector: provider.wallet.publicKey, // For now, use same wallet
           baseMint: SOL_MINT,
@@ -92,6 +98,23 @@ async function main() {
     }
   }
 
+  // New books come with shard 0; books created before that need it added
+  const orderBookAccount = await program.account.orderBook.fetch(orderBook);
+  if (orderBookAccount.orderShards === 0) {
+    console.log("\n🧮 Adding order shard...");
+    const tx = await program.methods
+      .addOrderShard()
+      .accounts({
+        orderBook,
+        orderShard,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+    console.log(`   ✅ Order shard added!`);
+    console.log(`   Transaction: ${tx}`);
+    await provider.connection.confirmTransaction(tx);
+  }
+
   // Setup keeper authorization
   console.log("\n🔐 Setting up keeper authorization...");
   
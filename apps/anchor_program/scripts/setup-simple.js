@@ -37,17 +37,23 @@ async function main() {
     program.programId
   );
 
+  const [orderShard] = PublicKey.findProgramAddressSync(
+    [Buffer.from("order_shard"), orderBook.toBuffer(), Buffer.from([0])],
+    program.programId
+  );
+
   console.log(`Order Book PDA: ${orderBook.toString()}`);
 
   try {
     const ob = await program.account.orderBook.fetch(orderBook);
     console.log("\n✅ Order book already exists!");
-    console.log(`   Active Orders: ${ob.activeOrders.toString()}`);
+    console.log(`   Order Shards: ${ob.orderShards}`);
   } catch {
     console.log("\n📦 Initializing order book...");
     const tx = await program.methods
       .initializeOrderBook(SOL_MINT, USDC_MINT, 30, new anchor.BN(1_000_000))
       .accounts({
+        orderShard,
         authority: provider.wallet.publicKey,
         feeCollector: provider.wallet.publicKey,
         baseMint: SOL_MINT,
@@ -58,6 +64,18 @@ async function main() {
     await provider.connection.confirmTransaction(tx);
   }
 
+  // New books come with shard 0; books created before that need it added
+  const ob = await program.account.orderBook.fetch(orderBook);
+  if (ob.orderShards === 0) {
+    console.log("\n🧮 Adding order shard...");
+    const tx = await program.methods
+      .addOrderShard()
+      .accounts({ orderBook, orderShard, authority: provider.wallet.publicKey })
+      .rpc();
+    console.log(`✅ Order shard added! TX: ${tx}`);
+    await provider.connection.confirmTransaction(tx);
+  }
+
   const keeperPubkey = provider.wallet.publicKey;
   const [callbackAuth] = PublicKey.findProgramAddressSync(
     [Buffer.from("callback_auth"), orderBook.toBuffer(), keeperPubkey.toBuffer()],
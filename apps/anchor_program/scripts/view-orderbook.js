@@ -34,7 +34,7 @@ async function viewOrderBook() {
     console.log(`Base Mint:       ${orderBook.baseMint.toString()}`);
     console.log(`Quote Mint:      ${orderBook.quoteMint.toString()}`);
     console.log(`Authority:       ${orderBook.authority.toString()}`);
-    console.log(`Order Count:     ${orderBook.orderCount.toString()}`);
+    console.log(`Order Shards:    ${orderBook.orderShards}`);
     console.log(`Min Base Size:   ${orderBook.minBaseOrderSize.toString()}`);
     const feeBps = orderBook.feeBps ?? orderBook.feeRateBps ?? 'n/a';
     console.log(`Fee Rate (bps):  ${feeBps}`);
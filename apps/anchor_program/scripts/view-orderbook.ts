@@ -38,10 +38,10 @@ async function viewOrderBook() {
     console.log(`Base Mint:       ${orderBook.baseMint.toString()}`);
     console.log(`Quote Mint:      ${orderBook.quoteMint.toString()}`);
     console.log(`Authority:       ${orderBook.authority.toString()}`);
-    console.log(`Order Count:     ${orderBook.orderCount.toString()}`);
+    console.log(`Order Shards:    ${orderBook.orderShards}`);
     console.log(`Min Base Size:   ${orderBook.minBaseOrderSize.toString()}`);
     console.log(`Fee Rate (bps):  ${orderBook.feeRateBps}`);
-    console.log(`Active Orders:   ${orderBook.orderCount.toString()}`);
+    console.log(`Closed Orders:   ${orderBook.closedOrders.toString()}`);
     console.log("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
 
     // Fetch all orders
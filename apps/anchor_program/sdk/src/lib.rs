@@ -0,0 +1,70 @@
+//! Client-side helpers for ShadowSwap
+//!
+//! PDA derivation for the program's accounts and a route finder for swaps
+//! that have no direct order book.
+
+use anchor_lang::prelude::Pubkey;
+
+pub mod router;
+
+pub use router::{find_routes, min_amount_out, Market, RouteLeg, Side, SwapRoute};
+
+/// Order book PDA for a base/quote pair
+pub fn order_book_address(base_mint: &Pubkey, quote_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[shadow_swap::ORDER_BOOK_SEED, base_mint.as_ref(), quote_mint.as_ref()],
+        &shadow_swap::ID,
+    )
+    .0
+}
+
+/// A user's trading balance on an order book
+pub fn user_balance_address(order_book: &Pubkey, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[shadow_swap::USER_BALANCE_SEED, order_book.as_ref(), owner.as_ref()],
+        &shadow_swap::ID,
+    )
+    .0
+}
+
+/// An order book's pooled vault for one of its mints
+pub fn book_vault_address(order_book: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[shadow_swap::BOOK_VAULT_SEED, order_book.as_ref(), mint.as_ref()],
+        &shadow_swap::ID,
+    )
+    .0
+}
+
+/// One of an order book's order ID shards
+pub fn order_shard_address(order_book: &Pubkey, shard: u8) -> Pubkey {
+    Pubkey::find_program_address(
+        &[shadow_swap::ORDER_SHARD_SEED, order_book.as_ref(), &[shard]],
+        &shadow_swap::ID,
+    )
+    .0
+}
+
+/// Order PDA for an owner's client order ID (also when a session key signs)
+pub fn order_address(order_book: &Pubkey, owner: &Pubkey, client_order_id: &[u8; 16]) -> Pubkey {
+    Pubkey::find_program_address(
+        &[shadow_swap::ORDER_SEED, order_book.as_ref(), owner.as_ref(), client_order_id],
+        &shadow_swap::ID,
+    )
+    .0
+}
+
+/// Shard an owner's orders should take IDs from, spreading owners evenly
+pub fn order_shard_for(owner: &Pubkey, order_shards: u8) -> u8 {
+    owner.to_bytes()[0] % order_shards.max(1)
+}
+
+/// Escrow PDA of an order
+pub fn escrow_address(order: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[shadow_swap::ESCROW_SEED, order.as_ref()], &shadow_swap::ID).0
+}
+
+/// Route PDA attached to a routed order
+pub fn route_address(order: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[shadow_swap::ROUTE_SEED, order.as_ref()], &shadow_swap::ID).0
+}
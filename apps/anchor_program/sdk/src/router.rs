@@ -0,0 +1,214 @@
+//! Route finder for cross-book swaps
+//!
+//! Order books are keyed by (base_mint, quote_mint), so a swap between two
+//! tokens either has a book of its own or goes through an intermediate token
+//! shared by two books. Two-leg routes map onto `create_route` /
+//! `settle_routed_match`: the first leg is a balance-backed order on the
+//! first book and the second leg fills against the second book.
+
+use anchor_lang::prelude::Pubkey;
+use shadow_swap::OrderBook;
+
+/// An order book the router may use
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Market {
+    pub order_book: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+}
+
+impl Market {
+    pub fn new(order_book: Pubkey, book: &OrderBook) -> Self {
+        Self {
+            order_book,
+            base_mint: book.base_mint,
+            quote_mint: book.quote_mint,
+        }
+    }
+
+    /// The leg swapping `input_mint` on this book, if it trades that token
+    fn leg_from(&self, input_mint: &Pubkey) -> Option<RouteLeg> {
+        let (side, output_mint) = if *input_mint == self.base_mint {
+            (Side::Sell, self.quote_mint)
+        } else if *input_mint == self.quote_mint {
+            (Side::Buy, self.base_mint)
+        } else {
+            return None;
+        };
+
+        Some(RouteLeg {
+            order_book: self.order_book,
+            side,
+            input_mint: *input_mint,
+            output_mint,
+        })
+    }
+}
+
+/// Order side on a leg's book
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    /// Pay quote, receive base
+    Buy,
+    /// Pay base, receive quote
+    Sell,
+}
+
+/// One swap on one order book
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RouteLeg {
+    pub order_book: Pubkey,
+    pub side: Side,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+}
+
+/// A direct swap (one leg) or a routed swap (two legs)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SwapRoute {
+    pub legs: Vec<RouteLeg>,
+}
+
+impl SwapRoute {
+    /// Token passed between the legs of a routed swap
+    pub fn intermediate_mint(&self) -> Option<Pubkey> {
+        match self.legs.as_slice() {
+            [first, _] => Some(first.output_mint),
+            _ => None,
+        }
+    }
+}
+
+/// Every route from `input_mint` to `output_mint` over `markets`
+///
+/// Direct swaps come first, followed by two-leg routes through a shared
+/// intermediate token. Routes never revisit the input token.
+pub fn find_routes(markets: &[Market], input_mint: &Pubkey, output_mint: &Pubkey) -> Vec<SwapRoute> {
+    if input_mint == output_mint {
+        return Vec::new();
+    }
+
+    let mut direct = Vec::new();
+    let mut routed = Vec::new();
+
+    for first_market in markets {
+        let Some(first) = first_market.leg_from(input_mint) else {
+            continue;
+        };
+
+        if first.output_mint == *output_mint {
+            direct.push(SwapRoute { legs: vec![first] });
+            continue;
+        }
+
+        for second_market in markets {
+            if second_market.order_book == first_market.order_book {
+                continue;
+            }
+            if let Some(second) = second_market.leg_from(&first.output_mint) {
+                if second.output_mint == *output_mint {
+                    routed.push(SwapRoute {
+                        legs: vec![first, second],
+                    });
+                }
+            }
+        }
+    }
+
+    direct.extend(routed);
+    direct
+}
+
+/// Minimum output for `create_route` given a quoted output and slippage
+pub fn min_amount_out(expected_amount_out: u64, slippage_bps: u16) -> u64 {
+    let slippage_bps = slippage_bps.min(10_000) as u128;
+    ((expected_amount_out as u128) * (10_000 - slippage_bps) / 10_000) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(base_mint: Pubkey, quote_mint: Pubkey) -> Market {
+        Market {
+            order_book: Pubkey::new_unique(),
+            base_mint,
+            quote_mint,
+        }
+    }
+
+    #[test]
+    fn direct_routes_come_before_two_leg_routes() {
+        let (sol, usdc, usdt) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let sol_usdt = market(sol, usdt);
+        let usdc_usdt = market(usdc, usdt);
+        let sol_usdc = market(sol, usdc);
+
+        let routes = find_routes(&[sol_usdt, usdc_usdt, sol_usdc], &sol, &usdc);
+
+        assert_eq!(routes.len(), 2);
+        assert_eq!(
+            routes[0].legs,
+            vec![RouteLeg {
+                order_book: sol_usdc.order_book,
+                side: Side::Sell,
+                input_mint: sol,
+                output_mint: usdc,
+            }]
+        );
+        assert_eq!(routes[0].intermediate_mint(), None);
+        assert_eq!(
+            routes[1].legs,
+            vec![
+                RouteLeg {
+                    order_book: sol_usdt.order_book,
+                    side: Side::Sell,
+                    input_mint: sol,
+                    output_mint: usdt,
+                },
+                RouteLeg {
+                    order_book: usdc_usdt.order_book,
+                    side: Side::Buy,
+                    input_mint: usdt,
+                    output_mint: usdc,
+                },
+            ]
+        );
+        assert_eq!(routes[1].intermediate_mint(), Some(usdt));
+    }
+
+    #[test]
+    fn routes_never_use_a_book_twice() {
+        let (sol, usdc, usdt) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let sol_usdc = market(sol, usdc);
+        // A second entry for the same book (e.g. stale market data) must not
+        // be chained onto itself
+        let same_book = Market {
+            order_book: sol_usdc.order_book,
+            base_mint: usdt,
+            quote_mint: usdc,
+        };
+
+        assert!(find_routes(&[sol_usdc, same_book], &sol, &usdt).is_empty());
+    }
+
+    #[test]
+    fn same_input_and_output_has_no_route() {
+        let (sol, usdc) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert!(find_routes(&[market(sol, usdc)], &sol, &sol).is_empty());
+    }
+
+    #[test]
+    fn min_amount_out_applies_slippage() {
+        assert_eq!(min_amount_out(1_000_000, 0), 1_000_000);
+        assert_eq!(min_amount_out(1_000_000, 50), 995_000);
+        assert_eq!(min_amount_out(u64::MAX, 1), (u64::MAX as u128 * 9_999 / 10_000) as u64);
+    }
+
+    #[test]
+    fn min_amount_out_clamps_slippage_at_100_percent() {
+        assert_eq!(min_amount_out(1_000_000, 10_000), 0);
+        assert_eq!(min_amount_out(1_000_000, u16::MAX), 0);
+    }
+}
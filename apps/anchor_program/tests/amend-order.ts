@@ -0,0 +1,122 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { ShadowSwap } from "../target/types/shadow_swap";
+import { PublicKey, Keypair } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createAccount,
+  mintTo,
+  getAccount,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import { Book, PlacedOrder, airdrop, createMints, createOrderBook, placeOrder } from "./helpers";
+
+/**
+ * amend_order tests
+ *
+ * Priority rule: every amendment resets `created_at`.
+ */
+describe("ShadowSwap - Order Amendment", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.ShadowSwap as Program<ShadowSwap>;
+
+  let baseMint: PublicKey;
+  let quoteMint: PublicKey;
+  let book: Book;
+
+  let trader: Keypair;
+  let other: Keypair;
+  let traderQuoteAccount: PublicKey;
+
+  function placeBuy(postedAmount: number): Promise<PlacedOrder> {
+    return placeOrder(book, trader, quoteMint, traderQuoteAccount, postedAmount);
+  }
+
+  function amend(placed: PlacedOrder, escrowDelta: number, owner: Keypair = trader) {
+    return program.methods
+      .amendOrder(Buffer.alloc(128, 9), Buffer.alloc(32, 8), new anchor.BN(escrowDelta))
+      .accounts({
+        order: placed.order,
+        escrow: placed.escrow,
+        escrowTokenAccount: placed.escrowToken,
+        userTokenAccount: traderQuoteAccount,
+        owner: owner.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([owner])
+      .rpc();
+  }
+
+  function sleep(ms: number) {
+    return new Promise((resolve) => setTimeout(resolve, ms));
+  }
+
+  before(async () => {
+    trader = Keypair.generate();
+    other = Keypair.generate();
+    await airdrop(trader, other);
+
+    ({ baseMint, quoteMint } = await createMints(trader));
+    traderQuoteAccount = await createAccount(provider.connection, trader, quoteMint, trader.publicKey);
+    await mintTo(provider.connection, trader, quoteMint, traderQuoteAccount, trader, 10_000 * 10 ** 6);
+
+    book = await createOrderBook(baseMint, quoteMint);
+  });
+
+  it("✅ Should succeed: Withdrawal resets priority and returns escrow", async () => {
+    const placed = await placeBuy(200_000_000);
+    const before = await program.account.encryptedOrder.fetch(placed.order);
+    await sleep(1500);
+
+    await amend(placed, -50_000_000);
+
+    const after = await program.account.encryptedOrder.fetch(placed.order);
+    assert.equal(after.orderId.toNumber(), before.orderId.toNumber());
+    assert.isAbove(after.createdAt.toNumber(), before.createdAt.toNumber(), "priority should reset");
+    assert.isAbove(after.updatedAt.toNumber(), before.updatedAt.toNumber());
+    assert.deepEqual(Buffer.from(after.cipherPayload), Buffer.alloc(128, 9));
+    assert.deepEqual(Buffer.from(after.encryptedRemaining), Buffer.alloc(32, 8));
+
+    const escrow = await getAccount(provider.connection, placed.escrowToken);
+    assert.equal(Number(escrow.amount), 150_000_000);
+  });
+
+  it("✅ Should succeed: Top-up resets priority", async () => {
+    const placed = await placeBuy(200_000_000);
+    const before = await program.account.encryptedOrder.fetch(placed.order);
+    await sleep(1500);
+
+    await amend(placed, 25_000_000);
+
+    const after = await program.account.encryptedOrder.fetch(placed.order);
+    assert.equal(after.orderId.toNumber(), before.orderId.toNumber());
+    assert.isAbove(after.createdAt.toNumber(), before.createdAt.toNumber(), "priority should reset");
+
+    const escrow = await getAccount(provider.connection, placed.escrowToken);
+    assert.equal(Number(escrow.amount), 225_000_000);
+  });
+
+  it("❌ Should fail: Withdraw more than the escrow holds", async () => {
+    const placed = await placeBuy(200_000_000);
+
+    try {
+      await amend(placed, -200_000_001);
+      assert.fail("Should have failed - escrow too small");
+    } catch (error) {
+      assert.include(error.toString(), "InsufficientEscrowFunds");
+    }
+  });
+
+  it("❌ Should fail: Only the owner can amend", async () => {
+    const placed = await placeBuy(200_000_000);
+
+    try {
+      await amend(placed, 0, other);
+      assert.fail("Should have failed - not the order owner");
+    } catch (error) {
+      assert.include(error.toString(), "UnauthorizedCallback");
+    }
+  });
+});
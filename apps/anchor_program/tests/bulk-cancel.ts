@@ -0,0 +1,142 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { ShadowSwap } from "../target/types/shadow_swap";
+import {
+  PublicKey,
+  Keypair,
+  LAMPORTS_PER_SOL,
+  AccountMeta,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createAccount,
+  mintTo,
+  getAccount,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import { Book, PlacedOrder, airdrop, createMints, createOrderBook, placeOrder } from "./helpers";
+
+/**
+ * Bulk cancel tests
+ *
+ * A trader cancels two of its three sell orders in one transaction; the book
+ * authority then force-cancels the last one, refunding the trader.
+ */
+describe("ShadowSwap - Bulk Cancel", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.ShadowSwap as Program<ShadowSwap>;
+
+  const ORDER_AMOUNT = 200_000_000;
+  const FUNDED = 2 * LAMPORTS_PER_SOL;
+
+  let baseMint: PublicKey;
+  let quoteMint: PublicKey;
+  let book: Book;
+
+  let trader: Keypair;
+  let traderBaseAccount: PublicKey;
+  let authorityBaseAccount: PublicKey;
+  const orders: PlacedOrder[] = [];
+
+  function cancelGroup(placed: PlacedOrder, refund: PublicKey): AccountMeta[] {
+    return [
+      { pubkey: placed.order, isSigner: false, isWritable: true },
+      { pubkey: placed.escrow, isSigner: false, isWritable: false },
+      { pubkey: placed.escrowToken, isSigner: false, isWritable: true },
+      { pubkey: refund, isSigner: false, isWritable: true },
+    ];
+  }
+
+  async function forceCancel(refund: PublicKey) {
+    await program.methods
+      .forceCancelOrders()
+      .accounts({
+        orderBook: book.orderBook,
+        authority: provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(cancelGroup(orders[2], refund))
+      .rpc();
+  }
+
+  before(async () => {
+    trader = Keypair.generate();
+    await airdrop(trader);
+
+    ({ baseMint, quoteMint } = await createMints(trader));
+    traderBaseAccount = await createAccount(provider.connection, trader, baseMint, trader.publicKey);
+    authorityBaseAccount = await createAccount(provider.connection, trader, baseMint, provider.wallet.publicKey);
+    await mintTo(provider.connection, trader, baseMint, traderBaseAccount, trader, FUNDED);
+
+    book = await createOrderBook(baseMint, quoteMint, provider.wallet.publicKey);
+
+    for (let i = 0; i < 3; i++) {
+      orders.push(await placeOrder(book, trader, baseMint, traderBaseAccount, ORDER_AMOUNT));
+    }
+  });
+
+  it("❌ Should fail: Incomplete account group", async () => {
+    try {
+      await program.methods
+        .cancelOrders()
+        .accounts({
+          orderBook: book.orderBook,
+          owner: trader.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(cancelGroup(orders[0], traderBaseAccount).slice(0, 3))
+        .signers([trader])
+        .rpc();
+      assert.fail("Should have failed - missing refund account");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidBulkCancel");
+    }
+  });
+
+  it("✅ Should succeed: Trader cancels two orders in one transaction", async () => {
+    await program.methods
+      .cancelOrders()
+      .accounts({
+        orderBook: book.orderBook,
+        owner: trader.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        ...cancelGroup(orders[0], traderBaseAccount),
+        ...cancelGroup(orders[1], traderBaseAccount),
+      ])
+      .signers([trader])
+      .rpc();
+
+    for (const placed of orders.slice(0, 2)) {
+      const order = await program.account.encryptedOrder.fetch(placed.order);
+      assert.equal(order.status, 4);
+    }
+    const state = await program.account.orderBook.fetch(book.orderBook);
+    assert.equal(state.closedOrders.toNumber(), 2);
+    assert.equal(
+      Number((await getAccount(provider.connection, traderBaseAccount)).amount),
+      FUNDED - ORDER_AMOUNT
+    );
+  });
+
+  it("❌ Should fail: Force cancel refunding the authority", async () => {
+    try {
+      await forceCancel(authorityBaseAccount);
+      assert.fail("Should have failed - refund must go to the owner");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidRefundAccount");
+    }
+  });
+
+  it("✅ Should succeed: Authority force-cancels, refunding the owner", async () => {
+    await forceCancel(traderBaseAccount);
+
+    const order = await program.account.encryptedOrder.fetch(orders[2].order);
+    assert.equal(order.status, 4);
+    assert.equal(Number((await getAccount(provider.connection, traderBaseAccount)).amount), FUNDED);
+    assert.equal(Number((await getAccount(provider.connection, authorityBaseAccount)).amount), 0);
+  });
+});
@@ -0,0 +1,290 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { ShadowSwap } from "../target/types/shadow_swap";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  LAMPORTS_PER_SOL,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createAccount,
+  mintTo,
+  getAccount,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import {
+  Book,
+  pda,
+  orderAddress,
+  newClientOrderId,
+  airdrop,
+  createMints,
+  createOrderBook,
+  placeOrder,
+} from "./helpers";
+
+/**
+ * Order book decommissioning tests
+ *
+ * The trader rests an escrow-funded sell and a balance-backed buy; the book
+ * is decommissioned, both are wound down by a third-party cranker and the
+ * book is closed once the trader has withdrawn its balance.
+ */
+describe("ShadowSwap - Decommissioning", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.ShadowSwap as Program<ShadowSwap>;
+
+  const SELL_AMOUNT = 500_000_000;
+  const DEPOSIT = 100_000_000;
+  const LOCK = 40_000_000;
+
+  let baseMint: PublicKey;
+  let quoteMint: PublicKey;
+  let book: Book;
+  let baseVault: PublicKey;
+  let quoteVault: PublicKey;
+  let userBalance: PublicKey;
+
+  let trader: Keypair;
+  let cranker: Keypair;
+  let traderBaseAccount: PublicKey;
+  let traderQuoteAccount: PublicKey;
+  let sellOrder: PublicKey;
+  let balanceOrder: PublicKey;
+
+  async function windDown(groups: PublicKey[][]) {
+    await program.methods
+      .windDownOrders()
+      .accounts({
+        orderBook: book.orderBook,
+        cranker: cranker.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(
+        groups.flat().map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }))
+      )
+      .signers([cranker])
+      .rpc();
+  }
+
+  async function closeBook() {
+    await program.methods
+      .closeOrderBook()
+      .accounts({
+        orderBook: book.orderBook,
+        baseVault,
+        quoteVault,
+        lpVault: pda([Buffer.from("lp_vault"), book.orderBook.toBuffer()]),
+        authority: provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([{ pubkey: book.orderShard, isSigner: false, isWritable: true }])
+      .rpc();
+  }
+
+  before(async () => {
+    trader = Keypair.generate();
+    cranker = Keypair.generate();
+    await airdrop(trader, cranker);
+
+    ({ baseMint, quoteMint } = await createMints(trader));
+    traderBaseAccount = await createAccount(provider.connection, trader, baseMint, trader.publicKey);
+    traderQuoteAccount = await createAccount(provider.connection, trader, quoteMint, trader.publicKey);
+    await mintTo(provider.connection, trader, baseMint, traderBaseAccount, trader, LAMPORTS_PER_SOL);
+    await mintTo(provider.connection, trader, quoteMint, traderQuoteAccount, trader, DEPOSIT);
+
+    book = await createOrderBook(baseMint, quoteMint, provider.wallet.publicKey);
+
+    baseVault = pda([Buffer.from("book_vault"), book.orderBook.toBuffer(), baseMint.toBuffer()]);
+    quoteVault = pda([Buffer.from("book_vault"), book.orderBook.toBuffer(), quoteMint.toBuffer()]);
+    await program.methods
+      .initializeBookVaults()
+      .accounts({
+        orderBook: book.orderBook,
+        baseVault,
+        quoteVault,
+        baseMint,
+        quoteMint,
+        authority: provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    userBalance = pda([Buffer.from("user_balance"), book.orderBook.toBuffer(), trader.publicKey.toBuffer()]);
+    await program.methods
+      .openUserBalance()
+      .accounts({
+        orderBook: book.orderBook,
+        userBalance,
+        owner: trader.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([trader])
+      .rpc();
+    await program.methods
+      .depositToBalance(new anchor.BN(DEPOSIT))
+      .accounts({
+        orderBook: book.orderBook,
+        userBalance,
+        bookVault: quoteVault,
+        userTokenAccount: traderQuoteAccount,
+        owner: trader.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([trader])
+      .rpc();
+
+    sellOrder = (await placeOrder(book, trader, baseMint, traderBaseAccount, SELL_AMOUNT)).order;
+    const clientOrderId = newClientOrderId();
+    balanceOrder = orderAddress(book.orderBook, trader.publicKey, clientOrderId);
+    await program.methods
+      .submitBalanceOrder(Array.from(clientOrderId), Buffer.alloc(128, 1), Buffer.alloc(32, 2), new anchor.BN(LOCK), 0)
+      .accounts({
+        orderBook: book.orderBook,
+        orderShard: book.orderShard,
+        userBalance,
+        bookVault: quoteVault,
+        order: balanceOrder,
+        escrow: pda([Buffer.from("escrow"), balanceOrder.toBuffer()]),
+        owner: trader.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([trader])
+      .rpc();
+  });
+
+  function sellGroup(): PublicKey[] {
+    return [
+      sellOrder,
+      pda([Buffer.from("escrow"), sellOrder.toBuffer()]),
+      pda([Buffer.from("escrow_token"), sellOrder.toBuffer()]),
+      traderBaseAccount,
+      trader.publicKey,
+    ];
+  }
+
+  function balanceGroup(): PublicKey[] {
+    return [
+      balanceOrder,
+      pda([Buffer.from("escrow"), balanceOrder.toBuffer()]),
+      quoteVault,
+      userBalance,
+      trader.publicKey,
+    ];
+  }
+
+  it("❌ Should fail: Cancel an order against another book", async () => {
+    const other = await createMints(trader);
+    const otherBook = await createOrderBook(other.baseMint, other.quoteMint);
+    try {
+      await program.methods
+        .cancelOrder()
+        .accounts({
+          order: sellOrder,
+          escrow: pda([Buffer.from("escrow"), sellOrder.toBuffer()]),
+          escrowTokenAccount: pda([Buffer.from("escrow_token"), sellOrder.toBuffer()]),
+          userTokenAccount: traderBaseAccount,
+          orderBook: otherBook.orderBook,
+          owner: trader.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tradingDelegate: null,
+        })
+        .signers([trader])
+        .rpc();
+      assert.fail("Should have failed - order is on another book");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidOrderBook");
+    }
+  });
+
+  it("❌ Should fail: Wind down a live book", async () => {
+    try {
+      await windDown([sellGroup()]);
+      assert.fail("Should have failed - book is not decommissioning");
+    } catch (error) {
+      assert.include(error.toString(), "BookNotDecommissioning");
+    }
+  });
+
+  it("✅ Should succeed: Authority starts decommissioning and new orders stop", async () => {
+    await program.methods
+      .startDecommission()
+      .accounts({
+        orderBook: book.orderBook,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const state = await program.account.orderBook.fetch(book.orderBook);
+    assert.isFalse(state.isActive);
+    assert.isAbove(state.decommissionedAt.toNumber(), 0);
+
+    try {
+      await placeOrder(book, trader, baseMint, traderBaseAccount, SELL_AMOUNT);
+      assert.fail("Should have failed - book is decommissioning");
+    } catch (error) {
+      assert.include(error.toString(), "OrderBookNotActive");
+    }
+  });
+
+  it("❌ Should fail: Close a book with active orders", async () => {
+    try {
+      await closeBook();
+      assert.fail("Should have failed - orders still active");
+    } catch (error) {
+      assert.include(error.toString(), "BookHasActiveOrders");
+    }
+  });
+
+  it("✅ Should succeed: Cranker refunds and closes every order", async () => {
+    await windDown([sellGroup(), balanceGroup()]);
+
+    const state = await program.account.orderBook.fetch(book.orderBook);
+    assert.equal(state.closedOrders.toNumber(), 2);
+    assert.equal(Number((await getAccount(provider.connection, traderBaseAccount)).amount), LAMPORTS_PER_SOL);
+
+    const balance = await program.account.userBalance.fetch(userBalance);
+    assert.equal(balance.quoteFree.toNumber(), DEPOSIT);
+    assert.equal(balance.quoteLocked.toNumber(), 0);
+
+    for (const account of [...sellGroup().slice(0, 3), ...balanceGroup().slice(0, 2)]) {
+      assert.isNull(await provider.connection.getAccountInfo(account));
+    }
+  });
+
+  it("❌ Should fail: Close a book whose vault still holds balances", async () => {
+    try {
+      await closeBook();
+      assert.fail("Should have failed - quote vault not empty");
+    } catch (error) {
+      assert.include(error.toString(), "BookVaultNotEmpty");
+    }
+  });
+
+  it("✅ Should succeed: Book closes once balances are withdrawn", async () => {
+    await program.methods
+      .withdrawFromBalance(new anchor.BN(DEPOSIT))
+      .accounts({
+        orderBook: book.orderBook,
+        userBalance,
+        bookVault: quoteVault,
+        userTokenAccount: traderQuoteAccount,
+        owner: trader.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([trader])
+      .rpc();
+
+    await closeBook();
+
+    for (const account of [book.orderBook, book.orderShard, baseVault, quoteVault]) {
+      assert.isNull(await provider.connection.getAccountInfo(account));
+    }
+  });
+});
@@ -0,0 +1,207 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { ShadowSwap } from "../target/types/shadow_swap";
+import { MockAmm } from "../target/types/mock_amm";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createAccount,
+  mintTo,
+  getAccount,
+} from "@solana/spl-token";
+import { createHash, randomBytes } from "crypto";
+import { assert } from "chai";
+import {
+  Book,
+  PlacedOrder,
+  pda,
+  airdrop,
+  createMints,
+  createOrderBook,
+  placeOrder,
+} from "./helpers";
+
+/**
+ * AMM fallback tests
+ *
+ * An unmatched escrow-funded order is swapped through the book's fallback
+ * AMM (the constant-rate mock) after its deadline, at no worse than the
+ * revealed limit price.
+ */
+describe("ShadowSwap - AMM Fallback", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.ShadowSwap as Program<ShadowSwap>;
+  const amm = anchor.workspace.MockAmm as Program<MockAmm>;
+
+  const AMOUNT = 1_000_000_000; // 1 base token
+  const LIMIT_PRICE = 100_000_000; // 100 quote per base
+  const AMM_OUT = 110_000_000; // mock pool pays 110 quote per base
+
+  let baseMint: PublicKey;
+  let quoteMint: PublicKey;
+  let book: Book;
+  let pool: PublicKey;
+
+  let trader: Keypair;
+  let stranger: Keypair;
+  let traderBaseAccount: PublicKey;
+  let traderQuoteAccount: PublicKey;
+
+  interface FallbackOrder extends PlacedOrder {
+    salt: Buffer;
+  }
+
+  function poolVault(mint: PublicKey): PublicKey {
+    return pda([Buffer.from("pool_vault"), pool.toBuffer(), mint.toBuffer()], amm.programId);
+  }
+
+  function commitment(limitPrice: number, salt: Buffer): number[] {
+    const price = new anchor.BN(limitPrice).toArrayLike(Buffer, "le", 8);
+    return Array.from(createHash("sha256").update(Buffer.concat([price, salt])).digest());
+  }
+
+  async function chainTime(): Promise<number> {
+    return (await provider.connection.getBlockTime(await provider.connection.getSlot())) ?? Math.floor(Date.now() / 1000);
+  }
+
+  async function placeWithFallback(deadlineIn: number): Promise<FallbackOrder> {
+    const placed = await placeOrder(book, trader, baseMint, traderBaseAccount, AMOUNT);
+    const salt = randomBytes(32);
+
+    await program.methods
+      .setFallback(new anchor.BN((await chainTime()) + deadlineIn), commitment(LIMIT_PRICE, salt))
+      .accounts({
+        order: placed.order,
+        fallback: pda([Buffer.from("fallback"), placed.order.toBuffer()]),
+        owner: trader.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([trader])
+      .rpc();
+
+    return { ...placed, salt };
+  }
+
+  async function fallbackSwap(placed: FallbackOrder, limitPrice: number, caller: Keypair = trader) {
+    await program.methods
+      .fallbackSwap(new anchor.BN(limitPrice), Array.from(placed.salt))
+      .accounts({
+        order: placed.order,
+        escrow: placed.escrow,
+        escrowTokenAccount: placed.escrowToken,
+        fallback: pda([Buffer.from("fallback"), placed.order.toBuffer()]),
+        orderBook: book.orderBook,
+        ownerTokenAccount: traderQuoteAccount,
+        ammProgram: amm.programId,
+        callbackAuth: null,
+        caller: caller.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        { pubkey: pool, isSigner: false, isWritable: false },
+        { pubkey: poolVault(baseMint), isSigner: false, isWritable: true },
+        { pubkey: poolVault(quoteMint), isSigner: false, isWritable: true },
+      ])
+      .signers([caller])
+      .rpc();
+  }
+
+  before(async () => {
+    trader = Keypair.generate();
+    stranger = Keypair.generate();
+
+    await airdrop(trader, stranger);
+
+    ({ baseMint, quoteMint } = await createMints(trader));
+    traderBaseAccount = await createAccount(provider.connection, trader, baseMint, trader.publicKey);
+    traderQuoteAccount = await createAccount(provider.connection, trader, quoteMint, trader.publicKey);
+    await mintTo(provider.connection, trader, baseMint, traderBaseAccount, trader, 10 * AMOUNT);
+
+    book = await createOrderBook(baseMint, quoteMint);
+
+    // 1 base (1e9 units) -> 110 quote (110e6 units)
+    pool = pda([Buffer.from("pool"), baseMint.toBuffer(), quoteMint.toBuffer()], amm.programId);
+    await amm.methods
+      .initializePool(new anchor.BN(11), new anchor.BN(100))
+      .accounts({
+        pool,
+        vaultA: poolVault(baseMint),
+        vaultB: poolVault(quoteMint),
+        mintA: baseMint,
+        mintB: quoteMint,
+        payer: provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+    await mintTo(provider.connection, trader, quoteMint, poolVault(quoteMint), trader, 10 * AMM_OUT);
+
+    await program.methods
+      .configureFallbackAmm(amm.programId)
+      .accounts({
+        orderBook: book.orderBook,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+  });
+
+  it("❌ Should fail: Fallback before the deadline", async () => {
+    const placed = await placeWithFallback(3600);
+
+    try {
+      await fallbackSwap(placed, LIMIT_PRICE);
+      assert.fail("Should have failed - deadline not reached");
+    } catch (error) {
+      assert.include(error.toString(), "FallbackNotDue");
+    }
+  });
+
+  describe("after the deadline", () => {
+    let placed: FallbackOrder;
+
+    before(async () => {
+      placed = await placeWithFallback(2);
+      await new Promise((resolve) => setTimeout(resolve, 4000));
+    });
+
+    it("❌ Should fail: Revealed limit does not match the commitment", async () => {
+      try {
+        await fallbackSwap(placed, LIMIT_PRICE - 1);
+        assert.fail("Should have failed - wrong limit revealed");
+      } catch (error) {
+        assert.include(error.toString(), "InvalidFallbackReveal");
+      }
+    });
+
+    it("❌ Should fail: Caller is neither the owner nor a keeper", async () => {
+      try {
+        await fallbackSwap(placed, LIMIT_PRICE, stranger);
+        assert.fail("Should have failed - stranger has no callback auth");
+      } catch (error) {
+        assert.include(error.toString(), "UnauthorizedCallback");
+      }
+    });
+
+    it("✅ Should succeed: Owner swaps the escrow through the AMM", async () => {
+      const before = await getAccount(provider.connection, traderQuoteAccount);
+
+      await fallbackSwap(placed, LIMIT_PRICE);
+
+      const after = await getAccount(provider.connection, traderQuoteAccount);
+      assert.equal(Number(after.amount - before.amount), AMM_OUT);
+
+      const escrow = await getAccount(provider.connection, placed.escrowToken);
+      assert.equal(Number(escrow.amount), 0);
+
+      const order = await program.account.encryptedOrder.fetch(placed.order);
+      assert.equal(order.status, 3);
+    });
+  });
+});
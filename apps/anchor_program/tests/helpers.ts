@@ -0,0 +1,152 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { ShadowSwap } from "../target/types/shadow_swap";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  LAMPORTS_PER_SOL,
+} from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, createMint } from "@solana/spl-token";
+
+/**
+ * Setup shared by the ShadowSwap test suites
+ *
+ * Each suite creates its own mints and book, then places escrow-funded
+ * orders whose client order IDs come from one counter.
+ */
+
+export interface Book {
+  orderBook: PublicKey;
+  orderShard: PublicKey;
+}
+
+export interface PlacedOrder {
+  order: PublicKey;
+  escrow: PublicKey;
+  escrowToken: PublicKey;
+  clientOrderId: Buffer;
+}
+
+export interface PlaceOrderOptions {
+  timeInForce?: number;
+  /** Session key that signs for the owner, with its trading delegate PDA */
+  delegate?: { key: Keypair; tradingDelegate: PublicKey };
+  /** Reuse a client order ID instead of taking the next one */
+  clientOrderId?: Buffer;
+}
+
+let nextClientOrderId = 0;
+
+function program(): Program<ShadowSwap> {
+  return anchor.workspace.ShadowSwap as Program<ShadowSwap>;
+}
+
+export function pda(seeds: Buffer[], programId: PublicKey = program().programId): PublicKey {
+  return PublicKey.findProgramAddressSync(seeds, programId)[0];
+}
+
+export function orderShardAddress(orderBook: PublicKey, shard = 0): PublicKey {
+  return pda([Buffer.from("order_shard"), orderBook.toBuffer(), Buffer.from([shard])]);
+}
+
+export function orderAddress(orderBook: PublicKey, owner: PublicKey, clientOrderId: Buffer): PublicKey {
+  return pda([Buffer.from("order"), orderBook.toBuffer(), owner.toBuffer(), clientOrderId]);
+}
+
+/** Next client order ID: the counter, little-endian */
+export function newClientOrderId(): Buffer {
+  const clientOrderId = Buffer.alloc(16);
+  clientOrderId.writeUInt32LE(nextClientOrderId++);
+  return clientOrderId;
+}
+
+export async function airdrop(...accounts: Keypair[]) {
+  const connection = anchor.getProvider().connection;
+  for (const account of accounts) {
+    await connection.confirmTransaction(
+      await connection.requestAirdrop(account.publicKey, 10 * LAMPORTS_PER_SOL)
+    );
+  }
+}
+
+/** A 9-decimal base mint and a 6-decimal quote mint, both minted by `payer` */
+export async function createMints(payer: Keypair): Promise<{ baseMint: PublicKey; quoteMint: PublicKey }> {
+  const connection = anchor.getProvider().connection;
+  const baseMint = await createMint(connection, payer, payer.publicKey, null, 9);
+  const quoteMint = await createMint(connection, payer, payer.publicKey, null, 6);
+  return { baseMint, quoteMint };
+}
+
+/** Initialize the book for a pair, authorized by the provider wallet */
+export async function createOrderBook(
+  baseMint: PublicKey,
+  quoteMint: PublicKey,
+  feeCollector: PublicKey = Keypair.generate().publicKey
+): Promise<Book> {
+  const orderBook = pda([Buffer.from("order_book"), baseMint.toBuffer(), quoteMint.toBuffer()]);
+  const orderShard = orderShardAddress(orderBook);
+  await program()
+    .methods.initializeOrderBook(baseMint, quoteMint, 30, new anchor.BN(100000))
+    .accounts({
+      orderBook,
+      orderShard,
+      authority: anchor.getProvider().publicKey,
+      feeCollector,
+      baseMint,
+      quoteMint,
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc();
+  return { orderBook, orderShard };
+}
+
+/**
+ * Place an escrow-funded order for `owner` on the book's shard
+ *
+ * `owner` signs unless `options.delegate` does, in which case the owner's
+ * public key is enough.
+ */
+export async function placeOrder(
+  book: Book,
+  owner: Keypair | PublicKey,
+  tokenMint: PublicKey,
+  userTokenAccount: PublicKey,
+  postedAmount: number,
+  options: PlaceOrderOptions = {}
+): Promise<PlacedOrder> {
+  const ownerKey = owner instanceof Keypair ? owner.publicKey : owner;
+  const signer = options.delegate?.key ?? (owner as Keypair);
+  const clientOrderId = options.clientOrderId ?? newClientOrderId();
+  const order = orderAddress(book.orderBook, ownerKey, clientOrderId);
+  const escrow = pda([Buffer.from("escrow"), order.toBuffer()]);
+  const escrowToken = pda([Buffer.from("escrow_token"), order.toBuffer()]);
+
+  await program()
+    .methods.submitEncryptedOrder(
+      Array.from(clientOrderId),
+      Buffer.alloc(128, 1),
+      Buffer.alloc(32, 2),
+      new anchor.BN(postedAmount),
+      options.timeInForce ?? 0
+    )
+    .accounts({
+      orderBook: book.orderBook,
+      orderShard: book.orderShard,
+      orderOwner: ownerKey,
+      order,
+      escrow,
+      escrowTokenAccount: escrowToken,
+      userTokenAccount,
+      tokenMint,
+      owner: signer.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      tradingDelegate: options.delegate?.tradingDelegate ?? null,
+    })
+    .signers([signer])
+    .rpc();
+
+  return { order, escrow, escrowToken, clientOrderId };
+}
@@ -0,0 +1,352 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { ShadowSwap } from "../target/types/shadow_swap";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  LAMPORTS_PER_SOL,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createAccount,
+  mintTo,
+  getAccount,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import { Book, pda, airdrop, createMints, createOrderBook, placeOrder } from "./helpers";
+
+/**
+ * LP backstop vault tests
+ *
+ * Uses the mock Pyth SOL/USD account (150.00 USD, see oracle-bands.ts). The
+ * vault is seeded with 1 SOL and 150 USDC and backstops sell orders at the
+ * oracle price minus a 1% spread.
+ */
+describe("ShadowSwap - LP Backstop Vault", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.ShadowSwap as Program<ShadowSwap>;
+
+  const MOCK_ORACLE = new PublicKey("8D4fsMzmfikR9b5pBkiteJ9p4BHhoKzi8VzJAuyXdsa7");
+  const PRICE_EXPO = -6;
+  const NEVER_STALE = new anchor.BN(10 * 365 * 24 * 3600);
+  const QUOTE_DEPOSIT = 150_000_000; // 150 USDC
+  const PARAMS = {
+    spreadBps: 100,
+    maxInventoryBps: 6000,
+    lpFeeShareBps: 5000,
+    withdrawalDelay: new anchor.BN(3600),
+  };
+
+  let baseMint: PublicKey;
+  let quoteMint: PublicKey;
+  let book: Book;
+  let callbackAuthPda: PublicKey;
+  let lpVault: PublicKey;
+  let shareMint: PublicKey;
+
+  let lp: Keypair;
+  let seller: Keypair;
+  let keeper: Keypair;
+  let feeCollector: Keypair;
+  let lpBaseAccount: PublicKey;
+  let lpQuoteAccount: PublicKey;
+  let lpShareAccount: PublicKey;
+  let sellerBaseAccount: PublicKey;
+  let sellerQuoteAccount: PublicKey;
+  let feeCollectorQuoteAccount: PublicKey;
+
+  function vaultToken(mint: PublicKey): PublicKey {
+    return pda([Buffer.from("lp_vault_token"), lpVault.toBuffer(), mint.toBuffer()]);
+  }
+
+  function withdrawalRequestAddress(index: number): PublicKey {
+    return pda([
+      Buffer.from("lp_withdrawal"),
+      lpVault.toBuffer(),
+      new anchor.BN(index).toArrayLike(Buffer, "le", 8),
+    ]);
+  }
+
+  async function backstop(amount: number, posted: number = amount) {
+    const placed = await placeOrder(book, seller, baseMint, sellerBaseAccount, posted);
+
+    await program.methods
+      .backstopFill(new anchor.BN(amount), new anchor.BN(0))
+      .accounts({
+        callbackAuth: callbackAuthPda,
+        orderBook: book.orderBook,
+        lpVault,
+        lpBaseVault: vaultToken(baseMint),
+        lpQuoteVault: vaultToken(quoteMint),
+        order: placed.order,
+        escrow: placed.escrow,
+        escrowTokenAccount: placed.escrowToken,
+        ownerTokenAccount: sellerQuoteAccount,
+        ownerRefundAccount: sellerBaseAccount,
+        feeCollectorTokenAccount: feeCollectorQuoteAccount,
+        oracle: MOCK_ORACLE,
+        keeper: keeper.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([keeper])
+      .rpc();
+
+    return placed;
+  }
+
+  before(async () => {
+    lp = Keypair.generate();
+    seller = Keypair.generate();
+    keeper = Keypair.generate();
+    feeCollector = Keypair.generate();
+
+    await airdrop(lp, seller, keeper);
+
+    ({ baseMint, quoteMint } = await createMints(lp));
+
+    lpBaseAccount = await createAccount(provider.connection, lp, baseMint, lp.publicKey);
+    lpQuoteAccount = await createAccount(provider.connection, lp, quoteMint, lp.publicKey);
+    sellerBaseAccount = await createAccount(provider.connection, seller, baseMint, seller.publicKey);
+    sellerQuoteAccount = await createAccount(provider.connection, seller, quoteMint, seller.publicKey);
+    feeCollectorQuoteAccount = await createAccount(provider.connection, lp, quoteMint, feeCollector.publicKey);
+
+    await mintTo(provider.connection, lp, baseMint, lpBaseAccount, lp, 10 * LAMPORTS_PER_SOL);
+    await mintTo(provider.connection, lp, quoteMint, lpQuoteAccount, lp, 10 * QUOTE_DEPOSIT);
+    await mintTo(provider.connection, lp, baseMint, sellerBaseAccount, lp, 10 * LAMPORTS_PER_SOL);
+
+    book = await createOrderBook(baseMint, quoteMint, feeCollector.publicKey);
+
+    await program.methods
+      .configureOracle(MOCK_ORACLE, 500, NEVER_STALE, PRICE_EXPO)
+      .accounts({
+        orderBook: book.orderBook,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    callbackAuthPda = pda([
+      Buffer.from("callback_auth"),
+      book.orderBook.toBuffer(),
+      keeper.publicKey.toBuffer(),
+    ]);
+    await program.methods
+      .createCallbackAuth(new anchor.BN(Math.floor(Date.now() / 1000) + 86400))
+      .accounts({
+        orderBook: book.orderBook,
+        callbackAuth: callbackAuthPda,
+        authority: provider.wallet.publicKey,
+        keeper: keeper.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    lpVault = pda([Buffer.from("lp_vault"), book.orderBook.toBuffer()]);
+    shareMint = pda([Buffer.from("lp_share_mint"), lpVault.toBuffer()]);
+  });
+
+  function initializeAccounts() {
+    return {
+      orderBook: book.orderBook,
+      lpVault,
+      shareMint,
+      baseVault: vaultToken(baseMint),
+      quoteVault: vaultToken(quoteMint),
+      pendingShares: vaultToken(shareMint),
+      baseMint,
+      quoteMint,
+      authority: provider.wallet.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    };
+  }
+
+  it("❌ Should fail: Inventory cap below 50%", async () => {
+    try {
+      await program.methods
+        .initializeLpVault({ ...PARAMS, maxInventoryBps: 4000 })
+        .accounts(initializeAccounts())
+        .rpc();
+      assert.fail("Should have failed - invalid inventory cap");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidLpVaultConfiguration");
+    }
+  });
+
+  it("✅ Should succeed: Authority initializes the vault", async () => {
+    await program.methods.initializeLpVault(PARAMS).accounts(initializeAccounts()).rpc();
+
+    const vault = await program.account.lpVault.fetch(lpVault);
+    assert.ok(vault.orderBook.equals(book.orderBook));
+    assert.ok(vault.shareMint.equals(shareMint));
+    assert.equal(vault.params.spreadBps, PARAMS.spreadBps);
+    assert.equal(vault.withdrawalCount.toNumber(), 0);
+
+    lpShareAccount = await createAccount(provider.connection, lp, shareMint, lp.publicKey);
+  });
+
+  it("✅ Should succeed: Deposit mints shares at the oracle value", async () => {
+    await program.methods
+      .depositLiquidity(new anchor.BN(LAMPORTS_PER_SOL), new anchor.BN(QUOTE_DEPOSIT))
+      .accounts({
+        orderBook: book.orderBook,
+        lpVault,
+        shareMint,
+        baseVault: vaultToken(baseMint),
+        quoteVault: vaultToken(quoteMint),
+        lpBaseAccount,
+        lpQuoteAccount,
+        lpShareAccount,
+        oracle: MOCK_ORACLE,
+        lp: lp.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([lp])
+      .rpc();
+
+    // 1 SOL at 150 USDC + 150 USDC
+    const shares = await getAccount(provider.connection, lpShareAccount);
+    assert.equal(Number(shares.amount), 2 * QUOTE_DEPOSIT);
+  });
+
+  it("✅ Should succeed: Vault backstops a sell order below the oracle", async () => {
+    await backstop(LAMPORTS_PER_SOL / 10);
+
+    // 0.1 SOL at 148.50 = 14.85 USDC, less the 0.3% book fee
+    const proceeds = 14_850_000;
+    const fee = Math.floor((proceeds * 30) / 10_000);
+    const sellerQuote = await getAccount(provider.connection, sellerQuoteAccount);
+    assert.equal(Number(sellerQuote.amount), proceeds - fee);
+
+    const collected = await getAccount(provider.connection, feeCollectorQuoteAccount);
+    assert.equal(Number(collected.amount), fee - Math.floor(fee / 2));
+
+    const baseVault = await getAccount(provider.connection, vaultToken(baseMint));
+    assert.equal(Number(baseVault.amount), (LAMPORTS_PER_SOL * 11) / 10);
+  });
+
+  it("❌ Should fail: Fill pushes base inventory over the cap", async () => {
+    try {
+      await backstop(LAMPORTS_PER_SOL / 2);
+      assert.fail("Should have failed - inventory cap exceeded");
+    } catch (error) {
+      assert.include(error.toString(), "InventoryCapExceeded");
+    }
+  });
+
+  it("✅ Should succeed: Filled order gets the rest of its escrow back", async () => {
+    const before = await getAccount(provider.connection, sellerBaseAccount);
+    const placed = await backstop(LAMPORTS_PER_SOL / 100, LAMPORTS_PER_SOL / 50);
+
+    const after = await getAccount(provider.connection, sellerBaseAccount);
+    assert.equal(Number(before.amount) - Number(after.amount), LAMPORTS_PER_SOL / 100);
+
+    const escrowToken = await getAccount(provider.connection, placed.escrowToken);
+    assert.equal(Number(escrowToken.amount), 0);
+  });
+
+  it("❌ Should fail: Withdrawal destinations swapped between mints", async () => {
+    const withdrawalRequest = withdrawalRequestAddress(0);
+    try {
+      await program.methods
+        .requestLpWithdrawal(new anchor.BN(QUOTE_DEPOSIT))
+        .accounts({
+          lpVault,
+          withdrawalRequest,
+          lpShareAccount,
+          pendingShares: vaultToken(shareMint),
+          baseVault: vaultToken(baseMint),
+          quoteVault: vaultToken(quoteMint),
+          baseDestination: lpQuoteAccount,
+          quoteDestination: lpBaseAccount,
+          lp: lp.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([lp])
+        .rpc();
+      assert.fail("Should have failed - destination mint mismatch");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidTokenMint");
+    }
+  });
+
+  it("❌ Should fail: Withdrawal processed before the delay", async () => {
+    const withdrawalRequest = withdrawalRequestAddress(0);
+    await program.methods
+      .requestLpWithdrawal(new anchor.BN(QUOTE_DEPOSIT))
+      .accounts({
+        lpVault,
+        withdrawalRequest,
+        lpShareAccount,
+        pendingShares: vaultToken(shareMint),
+        baseVault: vaultToken(baseMint),
+        quoteVault: vaultToken(quoteMint),
+        baseDestination: lpBaseAccount,
+        quoteDestination: lpQuoteAccount,
+        lp: lp.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([lp])
+      .rpc();
+
+    const pending = await getAccount(provider.connection, vaultToken(shareMint));
+    assert.equal(Number(pending.amount), QUOTE_DEPOSIT);
+
+    try {
+      await program.methods
+        .processLpWithdrawal()
+        .accounts({
+          lpVault,
+          withdrawalRequest,
+          shareMint,
+          pendingShares: vaultToken(shareMint),
+          baseVault: vaultToken(baseMint),
+          quoteVault: vaultToken(quoteMint),
+          baseDestination: lpBaseAccount,
+          quoteDestination: lpQuoteAccount,
+          owner: lp.publicKey,
+          cranker: keeper.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([keeper])
+        .rpc();
+      assert.fail("Should have failed - withdrawal delay not elapsed");
+    } catch (error) {
+      assert.include(error.toString(), "WithdrawalNotReady");
+    }
+  });
+
+  it("❌ Should fail: Withdrawal skipped before the delay", async () => {
+    const withdrawalRequest = withdrawalRequestAddress(0);
+    try {
+      await program.methods
+        .skipLpWithdrawal()
+        .accounts({
+          lpVault,
+          withdrawalRequest,
+          shareMint,
+          pendingShares: vaultToken(shareMint),
+          baseVault: vaultToken(baseMint),
+          quoteVault: vaultToken(quoteMint),
+          baseClaim: pda([Buffer.from("lp_claim"), withdrawalRequest.toBuffer(), baseMint.toBuffer()]),
+          quoteClaim: pda([Buffer.from("lp_claim"), withdrawalRequest.toBuffer(), quoteMint.toBuffer()]),
+          baseMint,
+          quoteMint,
+          cranker: keeper.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([keeper])
+        .rpc();
+      assert.fail("Should have failed - withdrawal delay not elapsed");
+    } catch (error) {
+      assert.include(error.toString(), "WithdrawalNotReady");
+    }
+  });
+});
@@ -0,0 +1,201 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { ShadowSwap } from "../target/types/shadow_swap";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  LAMPORTS_PER_SOL,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createAccount,
+  mintTo,
+  getAccount,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import { Book, pda, airdrop, createMints, createOrderBook, placeOrder } from "./helpers";
+
+/**
+ * Oracle price band tests for submit_match_results
+ *
+ * Uses the mock Pyth SOL/USD account loaded by the local validator from
+ * tests/fixtures/mock_pyth_sol_usd.json (see Anchor.toml): price 150.00 USD
+ * with expo -8, published at unix time 1_700_000_000. Staleness is
+ * controlled per test through `configure_oracle`.
+ */
+describe("ShadowSwap - Oracle Price Bands", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.ShadowSwap as Program<ShadowSwap>;
+
+  const MOCK_ORACLE = new PublicKey("8D4fsMzmfikR9b5pBkiteJ9p4BHhoKzi8VzJAuyXdsa7");
+  const PRICE_EXPO = -6; // execution prices are USDC micro-units per SOL
+  const ORACLE_PRICE = 150_000_000; // 150.00 USDC
+  const NEVER_STALE = new anchor.BN(10 * 365 * 24 * 3600);
+
+  let baseMint: PublicKey;
+  let quoteMint: PublicKey;
+  let book: Book;
+  let callbackAuthPda: PublicKey;
+
+  let buyer: Keypair;
+  let seller: Keypair;
+  let keeper: Keypair;
+  let buyerBaseAccount: PublicKey;
+  let buyerQuoteAccount: PublicKey;
+  let sellerBaseAccount: PublicKey;
+  let sellerQuoteAccount: PublicKey;
+
+  async function configureOracle(maxDeviationBps: number, maxStaleness: anchor.BN) {
+    await program.methods
+      .configureOracle(MOCK_ORACLE, maxDeviationBps, maxStaleness, PRICE_EXPO)
+      .accounts({
+        orderBook: book.orderBook,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+  }
+
+  async function settle(executionPrice: number, oracle: PublicKey | null = MOCK_ORACLE) {
+    const buy = await placeOrder(book, buyer, quoteMint, buyerQuoteAccount, 200_000_000);
+    const sell = await placeOrder(book, seller, baseMint, sellerBaseAccount, LAMPORTS_PER_SOL);
+
+    await program.methods
+      .submitMatchResults({
+        buyerPubkey: buy.order,
+        sellerPubkey: sell.order,
+        matchedAmount: new anchor.BN(LAMPORTS_PER_SOL),
+        executionPrice: new anchor.BN(executionPrice),
+        buyerRemaining: new anchor.BN(0),
+        sellerRemaining: new anchor.BN(0),
+      })
+      .accounts({
+        callbackAuth: callbackAuthPda,
+        orderBook: book.orderBook,
+        buyerOrder: buy.order,
+        sellerOrder: sell.order,
+        buyerEscrow: buy.escrow,
+        sellerEscrow: sell.escrow,
+        keeper: keeper.publicKey,
+        oracle,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        { pubkey: buy.escrowToken, isSigner: false, isWritable: true },
+        { pubkey: sell.escrowToken, isSigner: false, isWritable: true },
+        { pubkey: buyerBaseAccount, isSigner: false, isWritable: true },
+        { pubkey: sellerQuoteAccount, isSigner: false, isWritable: true },
+      ])
+      .signers([keeper])
+      .rpc();
+  }
+
+  before(async () => {
+    buyer = Keypair.generate();
+    seller = Keypair.generate();
+    keeper = Keypair.generate();
+
+    await airdrop(buyer, seller, keeper);
+
+    ({ baseMint, quoteMint } = await createMints(seller));
+
+    buyerBaseAccount = await createAccount(provider.connection, buyer, baseMint, buyer.publicKey);
+    buyerQuoteAccount = await createAccount(provider.connection, buyer, quoteMint, buyer.publicKey);
+    sellerBaseAccount = await createAccount(provider.connection, seller, baseMint, seller.publicKey);
+    sellerQuoteAccount = await createAccount(provider.connection, seller, quoteMint, seller.publicKey);
+
+    await mintTo(provider.connection, seller, quoteMint, buyerQuoteAccount, seller, 10_000 * 10 ** 6);
+    await mintTo(provider.connection, seller, baseMint, sellerBaseAccount, seller, 50 * LAMPORTS_PER_SOL);
+
+    book = await createOrderBook(baseMint, quoteMint);
+
+    callbackAuthPda = pda([
+      Buffer.from("callback_auth"),
+      book.orderBook.toBuffer(),
+      keeper.publicKey.toBuffer(),
+    ]);
+    await program.methods
+      .createCallbackAuth(new anchor.BN(Math.floor(Date.now() / 1000) + 86400))
+      .accounts({
+        orderBook: book.orderBook,
+        callbackAuth: callbackAuthPda,
+        authority: provider.wallet.publicKey,
+        keeper: keeper.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  it("❌ Should fail: Non-authority cannot configure the oracle", async () => {
+    try {
+      await program.methods
+        .configureOracle(MOCK_ORACLE, 100, NEVER_STALE, PRICE_EXPO)
+        .accounts({
+          orderBook: book.orderBook,
+          authority: keeper.publicKey,
+        })
+        .signers([keeper])
+        .rpc();
+      assert.fail("Should have failed - signer is not the book authority");
+    } catch (error) {
+      assert.include(error.toString(), "UnauthorizedCallback");
+    }
+  });
+
+  it("✅ Should succeed: Settlement within the oracle band", async () => {
+    await configureOracle(100, NEVER_STALE);
+
+    const before = await getAccount(provider.connection, sellerQuoteAccount);
+    await settle(ORACLE_PRICE + 500_000); // +33 bps
+
+    const after = await getAccount(provider.connection, sellerQuoteAccount);
+    assert.equal(Number(after.amount - before.amount), ORACLE_PRICE + 500_000);
+  });
+
+  it("❌ Should fail: Execution price outside the oracle band", async () => {
+    await configureOracle(100, NEVER_STALE);
+
+    try {
+      await settle(160_000_000); // +666 bps
+      assert.fail("Should have failed - price deviates too far from the oracle");
+    } catch (error) {
+      assert.include(error.toString(), "PriceOutsideOracleBand");
+    }
+  });
+
+  it("❌ Should fail: Stale oracle price", async () => {
+    await configureOracle(100, new anchor.BN(60));
+
+    try {
+      await settle(ORACLE_PRICE);
+      assert.fail("Should have failed - mock oracle was published long ago");
+    } catch (error) {
+      assert.include(error.toString(), "StaleOraclePrice");
+    }
+  });
+
+  it("❌ Should fail: Oracle account omitted while the book has one", async () => {
+    await configureOracle(100, NEVER_STALE);
+
+    try {
+      await settle(ORACLE_PRICE, null);
+      assert.fail("Should have failed - oracle account is required");
+    } catch (error) {
+      assert.include(error.toString(), "OracleNotConfigured");
+    }
+  });
+
+  it("✅ Should succeed: Any price settles once the oracle is disabled", async () => {
+    await program.methods
+      .configureOracle(PublicKey.default, 0, new anchor.BN(0), PRICE_EXPO)
+      .accounts({
+        orderBook: book.orderBook,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    await settle(160_000_000, null);
+  });
+});
@@ -0,0 +1,149 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { ShadowSwap } from "../target/types/shadow_swap";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  LAMPORTS_PER_SOL,
+} from "@solana/web3.js";
+import {
+  createMint,
+  createAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import {
+  Book,
+  orderShardAddress,
+  airdrop,
+  createMints,
+  createOrderBook,
+  placeOrder,
+} from "./helpers";
+
+/**
+ * Order shard tests
+ *
+ * A book with two shards (shard 0 comes with the book): order PDAs are keyed
+ * by (owner, client order ID) and IDs interleave as `count * 16 + shard`. A
+ * second book's shard can't be used.
+ */
+describe("ShadowSwap - Order Shards", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.ShadowSwap as Program<ShadowSwap>;
+
+  const MAX_ORDER_SHARDS = 16;
+  const ORDER_AMOUNT = 100_000_000;
+
+  let baseMint: PublicKey;
+  let quoteMint: PublicKey;
+  let otherQuoteMint: PublicKey;
+  let book: Book;
+  let otherBook: Book;
+
+  let trader: Keypair;
+  let traderBaseAccount: PublicKey;
+
+  async function addShard(orderBook: PublicKey, shard: number, authority?: Keypair) {
+    await program.methods
+      .addOrderShard()
+      .accounts({
+        orderBook,
+        orderShard: orderShardAddress(orderBook, shard),
+        authority: authority ? authority.publicKey : provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers(authority ? [authority] : [])
+      .rpc();
+  }
+
+  function clientOrderId(tag: number): Buffer {
+    return Buffer.alloc(16, tag);
+  }
+
+  async function placeSell(tag: number, shard: PublicKey): Promise<PublicKey> {
+    const placed = await placeOrder(
+      { orderBook: book.orderBook, orderShard: shard },
+      trader,
+      baseMint,
+      traderBaseAccount,
+      ORDER_AMOUNT,
+      { clientOrderId: clientOrderId(tag) }
+    );
+    return placed.order;
+  }
+
+  before(async () => {
+    trader = Keypair.generate();
+    await airdrop(trader);
+
+    ({ baseMint, quoteMint } = await createMints(trader));
+    otherQuoteMint = await createMint(provider.connection, trader, trader.publicKey, null, 6);
+    traderBaseAccount = await createAccount(provider.connection, trader, baseMint, trader.publicKey);
+    await mintTo(provider.connection, trader, baseMint, traderBaseAccount, trader, LAMPORTS_PER_SOL);
+
+    book = await createOrderBook(baseMint, quoteMint, provider.wallet.publicKey);
+    otherBook = await createOrderBook(baseMint, otherQuoteMint, provider.wallet.publicKey);
+  });
+
+  it("❌ Should fail: Non-authority adds a shard", async () => {
+    try {
+      await addShard(book.orderBook, 1, trader);
+      assert.fail("Should have failed - not the book authority");
+    } catch (error) {
+      assert.include(error.toString(), "UnauthorizedCallback");
+    }
+  });
+
+  it("✅ Should succeed: Book starts with shard 0 and the authority adds another", async () => {
+    const initial = await program.account.orderShard.fetch(orderShardAddress(book.orderBook, 0));
+    assert.equal(initial.shard, 0);
+
+    await addShard(book.orderBook, 1);
+
+    const state = await program.account.orderBook.fetch(book.orderBook);
+    assert.equal(state.orderShards, 2);
+    const shard = await program.account.orderShard.fetch(orderShardAddress(book.orderBook, 1));
+    assert.equal(shard.shard, 1);
+    assert.ok(shard.orderBook.equals(book.orderBook));
+  });
+
+  it("✅ Should succeed: Order IDs interleave across shards", async () => {
+    const first = await placeSell(7, orderShardAddress(book.orderBook, 1));
+    const second = await placeSell(3, orderShardAddress(book.orderBook, 0));
+    const third = await placeSell(42, orderShardAddress(book.orderBook, 1));
+
+    assert.equal((await program.account.encryptedOrder.fetch(first)).orderId.toNumber(), 1);
+    assert.equal((await program.account.encryptedOrder.fetch(second)).orderId.toNumber(), 0);
+    const order = await program.account.encryptedOrder.fetch(third);
+    assert.equal(order.orderId.toNumber(), MAX_ORDER_SHARDS + 1);
+    assert.deepEqual(Buffer.from(order.clientOrderId), clientOrderId(42));
+
+    const shard = await program.account.orderShard.fetch(orderShardAddress(book.orderBook, 1));
+    assert.equal(shard.orderCount.toNumber(), 2);
+  });
+
+  it("❌ Should fail: Retry a submission with the same client order ID", async () => {
+    try {
+      await placeSell(7, orderShardAddress(book.orderBook, 0));
+      assert.fail("Should have failed - duplicate client order ID");
+    } catch (error) {
+      assert.include(error.toString(), "already in use");
+    }
+
+    const shard = await program.account.orderShard.fetch(orderShardAddress(book.orderBook, 0));
+    assert.equal(shard.orderCount.toNumber(), 1, "retry must not take an order ID");
+  });
+
+  it("❌ Should fail: Take an ID from another book's shard", async () => {
+    try {
+      await placeSell(8, orderShardAddress(otherBook.orderBook, 0));
+      assert.fail("Should have failed - shard belongs to another book");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidOrderShard");
+    }
+  });
+});
@@ -0,0 +1,246 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { ShadowSwap } from "../target/types/shadow_swap";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createAccount,
+  mintTo,
+  getAccount,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import { Book, pda, airdrop, createMints, createOrderBook } from "./helpers";
+
+/**
+ * TWAP / iceberg parent order tests
+ *
+ * A parent escrows the full amount and releases ordinary child orders that
+ * each escrow one slice; anyone can crank the release.
+ */
+describe("ShadowSwap - Parent Orders", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.ShadowSwap as Program<ShadowSwap>;
+
+  const TOTAL = 1_000_000_000;
+  const SLICE = 300_000_000;
+
+  let baseMint: PublicKey;
+  let quoteMint: PublicKey;
+  let book: Book;
+
+  let trader: Keypair;
+  let cranker: Keypair;
+  let traderQuoteAccount: PublicKey;
+  let nextNonce = 0;
+
+  interface PlacedParent {
+    parent: PublicKey;
+    parentEscrowToken: PublicKey;
+  }
+
+  async function createParent(
+    sliceJitterBps: number = 0,
+    releaseInterval: number = 0
+  ): Promise<PlacedParent> {
+    const nonce = new anchor.BN(nextNonce++);
+    const parent = pda([
+      Buffer.from("parent_order"),
+      book.orderBook.toBuffer(),
+      trader.publicKey.toBuffer(),
+      nonce.toArrayLike(Buffer, "le", 8),
+    ]);
+    const parentEscrowToken = pda([Buffer.from("parent_escrow_token"), parent.toBuffer()]);
+
+    await program.methods
+      .createParentOrder(nonce, Buffer.alloc(128, 1), Buffer.alloc(32, 2), {
+        totalAmount: new anchor.BN(TOTAL),
+        sliceSize: new anchor.BN(SLICE),
+        sliceJitterBps,
+        releaseInterval: new anchor.BN(releaseInterval),
+      })
+      .accounts({
+        orderBook: book.orderBook,
+        parentOrder: parent,
+        parentEscrowTokenAccount: parentEscrowToken,
+        userTokenAccount: traderQuoteAccount,
+        tokenMint: quoteMint,
+        owner: trader.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([trader])
+      .rpc();
+
+    return { parent, parentEscrowToken };
+  }
+
+  async function releaseChild(placed: PlacedParent): Promise<PublicKey> {
+    const parentOrder = await program.account.parentOrder.fetch(placed.parent);
+    const order = pda([
+      Buffer.from("order"),
+      book.orderBook.toBuffer(),
+      placed.parent.toBuffer(),
+      parentOrder.childCount.toArrayLike(Buffer, "le", 8),
+    ]);
+    const escrowToken = pda([Buffer.from("escrow_token"), order.toBuffer()]);
+
+    await program.methods
+      .releaseChildOrder()
+      .accounts({
+        orderBook: book.orderBook,
+        orderShard: book.orderShard,
+        parentOrder: placed.parent,
+        parentEscrowTokenAccount: placed.parentEscrowToken,
+        order,
+        escrow: pda([Buffer.from("escrow"), order.toBuffer()]),
+        escrowTokenAccount: escrowToken,
+        tokenMint: quoteMint,
+        payer: cranker.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([cranker])
+      .rpc();
+
+    return order;
+  }
+
+  async function escrowedBy(order: PublicKey): Promise<number> {
+    const escrowToken = pda([Buffer.from("escrow_token"), order.toBuffer()]);
+    return Number((await getAccount(provider.connection, escrowToken)).amount);
+  }
+
+  before(async () => {
+    trader = Keypair.generate();
+    cranker = Keypair.generate();
+
+    await airdrop(trader, cranker);
+
+    ({ baseMint, quoteMint } = await createMints(trader));
+    traderQuoteAccount = await createAccount(provider.connection, trader, quoteMint, trader.publicKey);
+    await mintTo(provider.connection, trader, quoteMint, traderQuoteAccount, trader, 10_000 * 10 ** 6);
+
+    book = await createOrderBook(baseMint, quoteMint);
+  });
+
+  it("✅ Should succeed: Crank releases fixed slices until the parent is exhausted", async () => {
+    const placed = await createParent();
+
+    const children: PublicKey[] = [];
+    for (let i = 0; i < 4; i++) {
+      children.push(await releaseChild(placed));
+    }
+
+    const amounts = await Promise.all(children.map(escrowedBy));
+    assert.deepEqual(amounts, [SLICE, SLICE, SLICE, TOTAL - 3 * SLICE]);
+
+    const child = await program.account.encryptedOrder.fetch(children[0]);
+    assert.ok(child.owner.equals(trader.publicKey), "child belongs to the parent's owner");
+    assert.ok(child.parent.equals(placed.parent));
+    assert.equal(child.status, 1);
+    assert.equal(child.encryptedRemaining.length, 0, "children carry no encrypted amount");
+    const last = await program.account.encryptedOrder.fetch(children[3]);
+    assert.deepEqual(last.clientOrderId, [3, ...Array(15).fill(0)], "client order ID is the child index");
+
+    const parent = await program.account.parentOrder.fetch(placed.parent);
+    assert.equal(parent.status, 2, "parent should be completed");
+    assert.equal(parent.childCount.toNumber(), 4);
+    assert.equal(parent.releasedAmount.toNumber(), TOTAL);
+
+    try {
+      await releaseChild(placed);
+      assert.fail("Should have failed - nothing left to release");
+    } catch (error) {
+      assert.include(error.toString(), "ParentOrderNotActive");
+    }
+  });
+
+  it("✅ Should succeed: Jittered slices stay within the configured band", async () => {
+    const placed = await createParent(2_000);
+    const child = await releaseChild(placed);
+
+    const amount = await escrowedBy(child);
+    assert.isAtLeast(amount, SLICE * 0.8);
+    assert.isAtMost(amount, SLICE * 1.2);
+  });
+
+  it("❌ Should fail: Release before the next interval", async () => {
+    const placed = await createParent(0, 3600);
+    await releaseChild(placed);
+
+    try {
+      await releaseChild(placed);
+      assert.fail("Should have failed - next slice is not due");
+    } catch (error) {
+      assert.include(error.toString(), "ReleaseNotDue");
+    }
+  });
+
+  it("✅ Should succeed: Cancelling the parent refunds the unreleased amount", async () => {
+    const placed = await createParent(0, 3600);
+    await releaseChild(placed);
+    const before = await getAccount(provider.connection, traderQuoteAccount);
+
+    await program.methods
+      .cancelParentOrder()
+      .accounts({
+        parentOrder: placed.parent,
+        parentEscrowTokenAccount: placed.parentEscrowToken,
+        userTokenAccount: traderQuoteAccount,
+        owner: trader.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([trader])
+      .rpc();
+
+    const after = await getAccount(provider.connection, traderQuoteAccount);
+    assert.equal(Number(after.amount - before.amount), TOTAL - SLICE);
+
+    const parent = await program.account.parentOrder.fetch(placed.parent);
+    assert.equal(parent.status, 3);
+  });
+
+  it("❌ Should fail: Slice larger than the total", async () => {
+    const nonce = new anchor.BN(nextNonce++);
+    const parent = pda([
+      Buffer.from("parent_order"),
+      book.orderBook.toBuffer(),
+      trader.publicKey.toBuffer(),
+      nonce.toArrayLike(Buffer, "le", 8),
+    ]);
+
+    try {
+      await program.methods
+        .createParentOrder(nonce, Buffer.alloc(128, 1), Buffer.alloc(32, 2), {
+          totalAmount: new anchor.BN(SLICE),
+          sliceSize: new anchor.BN(TOTAL),
+          sliceJitterBps: 0,
+          releaseInterval: new anchor.BN(0),
+        })
+        .accounts({
+          orderBook: book.orderBook,
+          parentOrder: parent,
+          parentEscrowTokenAccount: pda([Buffer.from("parent_escrow_token"), parent.toBuffer()]),
+          userTokenAccount: traderQuoteAccount,
+          tokenMint: quoteMint,
+          owner: trader.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([trader])
+        .rpc();
+      assert.fail("Should have failed - slice exceeds total");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidParentOrder");
+    }
+  });
+});
@@ -0,0 +1,282 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { ShadowSwap } from "../target/types/shadow_swap";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  LAMPORTS_PER_SOL,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createAccount,
+  mintTo,
+  getAccount,
+} from "@solana/spl-token";
+import { createHash, randomBytes } from "crypto";
+import { assert } from "chai";
+import { Book, pda, airdrop, createMints, createOrderBook } from "./helpers";
+
+/**
+ * Request-for-quote tests
+ *
+ * The taker asks two whitelisted makers to quote a 1 SOL buy, depositing
+ * 200 USDC. Both quote 1 SOL (150 and 155 USDC) with a 1.5 SOL deposit; the
+ * taker accepts the cheaper quote.
+ */
+describe("ShadowSwap - RFQ", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.ShadowSwap as Program<ShadowSwap>;
+
+  const BUY = 0;
+  const AMOUNT = 1_000_000_000; // 1 SOL
+  const TAKER_DEPOSIT = 200_000_000; // 200 USDC
+  const MAKER_DEPOSIT = 1_500_000_000; // 1.5 SOL
+
+  let baseMint: PublicKey;
+  let quoteMint: PublicKey;
+  let book: Book;
+  let request: PublicKey;
+
+  let taker: Keypair;
+  let outsider: Keypair;
+  const makers: Keypair[] = [];
+  const accounts = new Map<string, { base: PublicKey; quote: PublicKey }>();
+  const quotes: { price: number; salt: Buffer; quote?: PublicKey }[] = [
+    { price: 150_000_000, salt: randomBytes(32) },
+    { price: 155_000_000, salt: randomBytes(32) },
+  ];
+
+  function rfqMaker(maker: PublicKey): PublicKey {
+    return pda([Buffer.from("rfq_maker"), book.orderBook.toBuffer(), maker.toBuffer()]);
+  }
+
+  function termsCommitment(price: number, amount: number, salt: Buffer): number[] {
+    const terms = Buffer.alloc(16);
+    terms.writeBigUInt64LE(BigInt(price), 0);
+    terms.writeBigUInt64LE(BigInt(amount), 8);
+    return Array.from(createHash("sha256").update(Buffer.concat([terms, salt])).digest());
+  }
+
+  function tokens(owner: Keypair) {
+    return accounts.get(owner.publicKey.toBase58())!;
+  }
+
+  async function balance(account: PublicKey): Promise<number> {
+    return Number((await getAccount(provider.connection, account)).amount);
+  }
+
+  async function requestQuote(makerKeys: PublicKey[], makerAccounts: PublicKey[]) {
+    const orderBook = await program.account.orderBook.fetch(book.orderBook);
+    request = pda([
+      Buffer.from("rfq_request"),
+      book.orderBook.toBuffer(),
+      orderBook.rfqCount.toArrayLike(Buffer, "le", 8),
+    ]);
+
+    await program.methods
+      .requestQuote(
+        BUY,
+        makerKeys,
+        Buffer.alloc(64 * makerKeys.length, 7),
+        new anchor.BN(TAKER_DEPOSIT),
+        new anchor.BN(Math.floor(Date.now() / 1000) + 600)
+      )
+      .accounts({
+        orderBook: book.orderBook,
+        request,
+        escrow: pda([Buffer.from("escrow"), request.toBuffer()]),
+        escrowTokenAccount: pda([Buffer.from("escrow_token"), request.toBuffer()]),
+        userTokenAccount: tokens(taker).quote,
+        tokenMint: quoteMint,
+        taker: taker.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .remainingAccounts(makerAccounts.map((pubkey) => ({ pubkey, isSigner: false, isWritable: false })))
+      .signers([taker])
+      .rpc();
+  }
+
+  async function accept(index: number, price: number) {
+    const quote = quotes[index].quote!;
+    await program.methods
+      .acceptRfqQuote(new anchor.BN(price), new anchor.BN(AMOUNT), Array.from(quotes[index].salt))
+      .accounts({
+        orderBook: book.orderBook,
+        request,
+        quote,
+        requestEscrow: pda([Buffer.from("escrow"), request.toBuffer()]),
+        requestEscrowTokenAccount: pda([Buffer.from("escrow_token"), request.toBuffer()]),
+        quoteEscrow: pda([Buffer.from("escrow"), quote.toBuffer()]),
+        quoteEscrowTokenAccount: pda([Buffer.from("escrow_token"), quote.toBuffer()]),
+        takerBaseAccount: tokens(taker).base,
+        takerQuoteAccount: tokens(taker).quote,
+        makerBaseAccount: tokens(makers[index]).base,
+        makerQuoteAccount: tokens(makers[index]).quote,
+        oracle: null,
+        taker: taker.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([taker])
+      .rpc();
+  }
+
+  before(async () => {
+    taker = Keypair.generate();
+    outsider = Keypair.generate();
+    makers.push(Keypair.generate(), Keypair.generate());
+
+    await airdrop(taker, ...makers);
+
+    ({ baseMint, quoteMint } = await createMints(taker));
+
+    for (const owner of [taker, ...makers]) {
+      accounts.set(owner.publicKey.toBase58(), {
+        base: await createAccount(provider.connection, owner, baseMint, owner.publicKey),
+        quote: await createAccount(provider.connection, owner, quoteMint, owner.publicKey),
+      });
+    }
+    await mintTo(provider.connection, taker, quoteMint, tokens(taker).quote, taker, 1_000_000_000);
+    for (const maker of makers) {
+      await mintTo(provider.connection, taker, baseMint, tokens(maker).base, taker, 2 * LAMPORTS_PER_SOL);
+    }
+
+    book = await createOrderBook(baseMint, quoteMint, provider.wallet.publicKey);
+
+    for (const maker of makers) {
+      await program.methods
+        .addRfqMaker(Array.from(randomBytes(32)))
+        .accounts({
+          orderBook: book.orderBook,
+          rfqMaker: rfqMaker(maker.publicKey),
+          maker: maker.publicKey,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+  });
+
+  it("❌ Should fail: Request sent to a maker that is not whitelisted", async () => {
+    try {
+      await requestQuote([outsider.publicKey], [rfqMaker(makers[0].publicKey)]);
+      assert.fail("Should have failed - maker not whitelisted");
+    } catch (error) {
+      assert.include(error.toString(), "RfqMakerNotWhitelisted");
+    }
+  });
+
+  it("✅ Should succeed: Taker requests quotes from two makers", async () => {
+    await requestQuote(
+      makers.map((m) => m.publicKey),
+      makers.map((m) => rfqMaker(m.publicKey))
+    );
+
+    const rfq = await program.account.rfqRequest.fetch(request);
+    assert.equal(rfq.rfqId.toNumber(), 0);
+    assert.equal(rfq.makers.length, 2);
+    assert.equal(rfq.status, 1);
+    assert.equal(await balance(pda([Buffer.from("escrow_token"), request.toBuffer()])), TAKER_DEPOSIT);
+  });
+
+  it("✅ Should succeed: Both makers quote", async () => {
+    for (const [i, maker] of makers.entries()) {
+      const quote = pda([Buffer.from("rfq_quote"), request.toBuffer(), maker.publicKey.toBuffer()]);
+      await program.methods
+        .submitRfqQuote(
+          Buffer.alloc(96, 9),
+          termsCommitment(quotes[i].price, AMOUNT, quotes[i].salt),
+          new anchor.BN(MAKER_DEPOSIT),
+          new anchor.BN(Math.floor(Date.now() / 1000) + 600)
+        )
+        .accounts({
+          orderBook: book.orderBook,
+          rfqMaker: rfqMaker(maker.publicKey),
+          request,
+          quote,
+          escrow: pda([Buffer.from("escrow"), quote.toBuffer()]),
+          escrowTokenAccount: pda([Buffer.from("escrow_token"), quote.toBuffer()]),
+          userTokenAccount: tokens(maker).base,
+          tokenMint: baseMint,
+          maker: maker.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([maker])
+        .rpc();
+      quotes[i].quote = quote;
+    }
+
+    const rfq = await program.account.rfqRequest.fetch(request);
+    assert.equal(rfq.quoteCount.toNumber(), 2);
+  });
+
+  it("❌ Should fail: Accept with terms that do not match the quote", async () => {
+    try {
+      await accept(0, quotes[0].price - 1);
+      assert.fail("Should have failed - terms do not match commitment");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidRfqQuote");
+    }
+  });
+
+  it("✅ Should succeed: Taker accepts the best quote", async () => {
+    await accept(0, quotes[0].price);
+
+    // 1 SOL at 150 USDC; the rest of both deposits is refunded
+    assert.equal(await balance(tokens(taker).base), AMOUNT);
+    assert.equal(await balance(tokens(taker).quote), 1_000_000_000 - 150_000_000);
+    assert.equal(await balance(tokens(makers[0]).quote), 150_000_000);
+    assert.equal(await balance(tokens(makers[0]).base), 2 * LAMPORTS_PER_SOL - AMOUNT);
+
+    const rfq = await program.account.rfqRequest.fetch(request);
+    assert.equal(rfq.status, 2);
+    assert.ok(rfq.acceptedQuote.equals(quotes[0].quote!));
+  });
+
+  it("❌ Should fail: Cancel a filled request", async () => {
+    try {
+      await program.methods
+        .cancelRfqRequest()
+        .accounts({
+          orderBook: book.orderBook,
+          request,
+          escrow: pda([Buffer.from("escrow"), request.toBuffer()]),
+          escrowTokenAccount: pda([Buffer.from("escrow_token"), request.toBuffer()]),
+          takerTokenAccount: tokens(taker).quote,
+          taker: taker.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([taker])
+        .rpc();
+      assert.fail("Should have failed - request already filled");
+    } catch (error) {
+      assert.include(error.toString(), "RfqNotOpen");
+    }
+  });
+
+  it("✅ Should succeed: Losing maker withdraws its quote", async () => {
+    const quote = quotes[1].quote!;
+    await program.methods
+      .withdrawRfqQuote()
+      .accounts({
+        request,
+        quote,
+        escrow: pda([Buffer.from("escrow"), quote.toBuffer()]),
+        escrowTokenAccount: pda([Buffer.from("escrow_token"), quote.toBuffer()]),
+        makerTokenAccount: tokens(makers[1]).base,
+        maker: makers[1].publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([makers[1]])
+      .rpc();
+
+    assert.equal(await balance(tokens(makers[1]).base), 2 * LAMPORTS_PER_SOL);
+    assert.isNull(await provider.connection.getAccountInfo(quote));
+  });
+});
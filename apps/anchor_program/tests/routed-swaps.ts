@@ -0,0 +1,321 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { ShadowSwap } from "../target/types/shadow_swap";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  createAccount,
+  mintTo,
+  getAccount,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import { Book, pda, orderAddress, newClientOrderId, airdrop, createOrderBook } from "./helpers";
+
+/**
+ * Routed swap tests
+ *
+ * A→C with only A/B and C/B books: the trader sells A for B on the first
+ * book and the B passes through to buy C on the second, in one instruction.
+ */
+describe("ShadowSwap - Routed Swaps", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.ShadowSwap as Program<ShadowSwap>;
+
+  const AMOUNT_IN = 1_000_000_000; // 1 A
+  const FIRST_PRICE = 100_000_000; // 100 B per A
+  const INTERMEDIATE = 100_000_000; // 100 B
+  const SECOND_PRICE = 2_000_000; // 2 B per C
+  const AMOUNT_OUT = 50_000_000_000; // 50 C
+  const MIN_OUT = 40_000_000_000;
+
+  interface RouteBook extends Book {
+    baseMint: PublicKey;
+    quoteMint: PublicKey;
+    callbackAuth: PublicKey;
+  }
+
+  let mintA: PublicKey;
+  let mintB: PublicKey;
+  let mintC: PublicKey;
+  let firstBook: RouteBook;
+  let secondBook: RouteBook;
+
+  let trader: Keypair;
+  let firstMaker: Keypair;
+  let secondMaker: Keypair;
+  let keeper: Keypair;
+
+  let routedOrder: PublicKey;
+  let route: PublicKey;
+  let firstCounterOrder: PublicKey;
+  let secondCounterOrder: PublicKey;
+
+  function vault(book: RouteBook, mint: PublicKey): PublicKey {
+    return pda([Buffer.from("book_vault"), book.orderBook.toBuffer(), mint.toBuffer()]);
+  }
+
+  function balanceOf(book: RouteBook, owner: PublicKey): PublicKey {
+    return pda([Buffer.from("user_balance"), book.orderBook.toBuffer(), owner.toBuffer()]);
+  }
+
+  function escrowOf(order: PublicKey): PublicKey {
+    return pda([Buffer.from("escrow"), order.toBuffer()]);
+  }
+
+  async function createBook(baseMint: PublicKey, quoteMint: PublicKey): Promise<RouteBook> {
+    const { orderBook, orderShard } = await createOrderBook(baseMint, quoteMint);
+    const book = {
+      orderBook,
+      orderShard,
+      baseMint,
+      quoteMint,
+      callbackAuth: pda([Buffer.from("callback_auth"), orderBook.toBuffer(), keeper.publicKey.toBuffer()]),
+    };
+
+    await program.methods
+      .initializeBookVaults()
+      .accounts({
+        orderBook,
+        baseVault: vault(book, baseMint),
+        quoteVault: vault(book, quoteMint),
+        baseMint,
+        quoteMint,
+        authority: provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    await program.methods
+      .createCallbackAuth(new anchor.BN(Math.floor(Date.now() / 1000) + 86400))
+      .accounts({
+        orderBook,
+        callbackAuth: book.callbackAuth,
+        authority: provider.wallet.publicKey,
+        keeper: keeper.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    return book;
+  }
+
+  async function fundBalance(book: RouteBook, owner: Keypair, mint: PublicKey, amount: number) {
+    await program.methods
+      .openUserBalance()
+      .accounts({
+        orderBook: book.orderBook,
+        userBalance: balanceOf(book, owner.publicKey),
+        owner: owner.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+
+    if (amount === 0) {
+      return;
+    }
+
+    const tokenAccount = await createAccount(provider.connection, owner, mint, owner.publicKey);
+    await mintTo(provider.connection, trader, mint, tokenAccount, trader, amount);
+    await program.methods
+      .depositToBalance(new anchor.BN(amount))
+      .accounts({
+        orderBook: book.orderBook,
+        userBalance: balanceOf(book, owner.publicKey),
+        bookVault: vault(book, mint),
+        userTokenAccount: tokenAccount,
+        owner: owner.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([owner])
+      .rpc();
+  }
+
+  async function submitBalanceOrder(
+    book: RouteBook,
+    owner: Keypair,
+    lockedMint: PublicKey,
+    lockedAmount: number
+  ): Promise<PublicKey> {
+    const clientOrderId = newClientOrderId();
+    const order = orderAddress(book.orderBook, owner.publicKey, clientOrderId);
+
+    await program.methods
+      .submitBalanceOrder(Array.from(clientOrderId), Buffer.alloc(128, 1), Buffer.alloc(32, 2), new anchor.BN(lockedAmount), 0)
+      .accounts({
+        orderBook: book.orderBook,
+        orderShard: book.orderShard,
+        userBalance: balanceOf(book, owner.publicKey),
+        bookVault: vault(book, lockedMint),
+        order,
+        escrow: escrowOf(order),
+        owner: owner.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+
+    return order;
+  }
+
+  async function settle(secondMatched: number) {
+    await program.methods
+      .settleRoutedMatch({
+        firstMatchedAmount: new anchor.BN(AMOUNT_IN),
+        firstExecutionPrice: new anchor.BN(FIRST_PRICE),
+        orderRemaining: new anchor.BN(0),
+        firstCounterRemaining: new anchor.BN(0),
+        secondMatchedAmount: new anchor.BN(secondMatched),
+        secondExecutionPrice: new anchor.BN(SECOND_PRICE),
+        secondCounterRemaining: new anchor.BN(AMOUNT_OUT - Math.min(secondMatched, AMOUNT_OUT)),
+      })
+      .accounts({
+        firstCallbackAuth: firstBook.callbackAuth,
+        secondCallbackAuth: secondBook.callbackAuth,
+        firstOrderBook: firstBook.orderBook,
+        secondOrderBook: secondBook.orderBook,
+        route,
+        order: routedOrder,
+        orderEscrow: escrowOf(routedOrder),
+        userFirstBalance: balanceOf(firstBook, trader.publicKey),
+        userSecondBalance: balanceOf(secondBook, trader.publicKey),
+        firstCounterOrder,
+        firstCounterEscrow: escrowOf(firstCounterOrder),
+        firstCounterBalance: balanceOf(firstBook, firstMaker.publicKey),
+        secondCounterOrder,
+        secondCounterEscrow: escrowOf(secondCounterOrder),
+        secondCounterBalance: balanceOf(secondBook, secondMaker.publicKey),
+        firstIntermediateVault: vault(firstBook, mintB),
+        secondIntermediateVault: vault(secondBook, mintB),
+        keeper: keeper.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        firstOracle: null,
+        secondOracle: null,
+      })
+      .signers([keeper])
+      .rpc();
+  }
+
+  before(async () => {
+    trader = Keypair.generate();
+    firstMaker = Keypair.generate();
+    secondMaker = Keypair.generate();
+    keeper = Keypair.generate();
+
+    await airdrop(trader, firstMaker, secondMaker, keeper);
+
+    mintA = await createMint(provider.connection, trader, trader.publicKey, null, 9);
+    mintB = await createMint(provider.connection, trader, trader.publicKey, null, 6);
+    mintC = await createMint(provider.connection, trader, trader.publicKey, null, 9);
+
+    firstBook = await createBook(mintA, mintB);
+    secondBook = await createBook(mintC, mintB);
+
+    await fundBalance(firstBook, trader, mintA, AMOUNT_IN);
+    await fundBalance(secondBook, trader, mintC, 0);
+    await fundBalance(firstBook, firstMaker, mintB, INTERMEDIATE);
+    await fundBalance(secondBook, secondMaker, mintC, AMOUNT_OUT);
+
+    routedOrder = await submitBalanceOrder(firstBook, trader, mintA, AMOUNT_IN);
+    firstCounterOrder = await submitBalanceOrder(firstBook, firstMaker, mintB, INTERMEDIATE);
+    secondCounterOrder = await submitBalanceOrder(secondBook, secondMaker, mintC, AMOUNT_OUT);
+    route = pda([Buffer.from("route"), routedOrder.toBuffer()]);
+  });
+
+  it("❌ Should fail: Route whose second book is the first", async () => {
+    try {
+      await program.methods
+        .createRoute(new anchor.BN(MIN_OUT))
+        .accounts({
+          order: routedOrder,
+          escrow: escrowOf(routedOrder),
+          firstOrderBook: firstBook.orderBook,
+          secondOrderBook: firstBook.orderBook,
+          route,
+          owner: trader.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([trader])
+        .rpc();
+      assert.fail("Should have failed - route needs a second book");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidRoute");
+    }
+  });
+
+  it("✅ Should succeed: Route records the intermediate and output tokens", async () => {
+    await program.methods
+      .createRoute(new anchor.BN(MIN_OUT))
+      .accounts({
+        order: routedOrder,
+        escrow: escrowOf(routedOrder),
+        firstOrderBook: firstBook.orderBook,
+        secondOrderBook: secondBook.orderBook,
+        route,
+        owner: trader.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([trader])
+      .rpc();
+
+    const routeData = await program.account.route.fetch(route);
+    assert.ok(routeData.intermediateMint.equals(mintB));
+    assert.ok(routeData.outputMint.equals(mintC));
+    assert.equal(routeData.amountIn.toNumber(), AMOUNT_IN);
+
+    const order = await program.account.encryptedOrder.fetch(routedOrder);
+    assert.ok(order.route.equals(route));
+  });
+
+  it("❌ Should fail: Output below the route minimum", async () => {
+    try {
+      await settle(30_000_000_000);
+      assert.fail("Should have failed - 30 C is below the 40 C minimum");
+    } catch (error) {
+      assert.include(error.toString(), "SlippageExceeded");
+    }
+  });
+
+  it("❌ Should fail: Second leg spends more than the first leg bought", async () => {
+    try {
+      await settle(60_000_000_000);
+      assert.fail("Should have failed - 60 C costs 120 B");
+    } catch (error) {
+      assert.include(error.toString(), "RouteLegMismatch");
+    }
+  });
+
+  it("✅ Should succeed: Both legs settle and the output lands on the second book", async () => {
+    const secondVaultBefore = await getAccount(provider.connection, vault(secondBook, mintB));
+
+    await settle(AMOUNT_OUT);
+
+    const traderOut = await program.account.userBalance.fetch(balanceOf(secondBook, trader.publicKey));
+    assert.equal(traderOut.baseFree.toString(), AMOUNT_OUT.toString());
+
+    const traderIn = await program.account.userBalance.fetch(balanceOf(firstBook, trader.publicKey));
+    assert.equal(traderIn.baseLocked.toNumber(), 0);
+    assert.equal(traderIn.quoteFree.toNumber(), 0, "all intermediate B passed through");
+
+    const firstMakerBalance = await program.account.userBalance.fetch(balanceOf(firstBook, firstMaker.publicKey));
+    assert.equal(firstMakerBalance.baseFree.toNumber(), AMOUNT_IN);
+
+    const secondMakerBalance = await program.account.userBalance.fetch(balanceOf(secondBook, secondMaker.publicKey));
+    assert.equal(secondMakerBalance.quoteFree.toNumber(), INTERMEDIATE);
+
+    const secondVaultAfter = await getAccount(provider.connection, vault(secondBook, mintB));
+    assert.equal(Number(secondVaultAfter.amount - secondVaultBefore.amount), INTERMEDIATE);
+
+    const order = await program.account.encryptedOrder.fetch(routedOrder);
+    assert.equal(order.status, 3);
+  });
+});
@@ -0,0 +1,286 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { ShadowSwap } from "../target/types/shadow_swap";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  LAMPORTS_PER_SOL,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createAccount,
+  mintTo,
+  getAccount,
+} from "@solana/spl-token";
+import { createHash, randomBytes } from "crypto";
+import { assert } from "chai";
+import { Book, pda, airdrop, createMints, createOrderBook } from "./helpers";
+
+/**
+ * Commit-reveal (sealed) auction tests
+ *
+ * One round with a 1 SOL buy at 160, a 0.6 SOL sell at 140 and a 0.5 SOL sell
+ * that is never revealed. Both revealed prices match 0.6 SOL with the same
+ * imbalance, so the round clears at the lower one (140).
+ */
+describe("ShadowSwap - Sealed Auctions", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.ShadowSwap as Program<ShadowSwap>;
+
+  const COMMIT_DURATION = 6;
+  const REVEAL_DURATION = 6;
+  const PENALTY_BPS = 1000;
+  const BUY = 0;
+  const SELL = 1;
+
+  let baseMint: PublicKey;
+  let quoteMint: PublicKey;
+  let book: Book;
+  let auction: PublicKey;
+  let baseVault: PublicKey;
+  let quoteVault: PublicKey;
+
+  let buyer: Keypair;
+  let seller: Keypair;
+  let quitter: Keypair;
+  let feeCollector: Keypair;
+  const accounts: Record<string, { base: PublicKey; quote: PublicKey }> = {};
+  let feeCollectorBaseAccount: PublicKey;
+
+  const bids: Record<string, { side: number; price: number; amount: number; salt: Buffer; sealedOrder?: PublicKey }> = {
+    buyer: { side: BUY, price: 160_000_000, amount: 1_000_000_000, salt: randomBytes(32) },
+    seller: { side: SELL, price: 140_000_000, amount: 600_000_000, salt: randomBytes(32) },
+    quitter: { side: SELL, price: 100_000_000, amount: 500_000_000, salt: randomBytes(32) },
+  };
+
+  function sleep(ms: number) {
+    return new Promise((resolve) => setTimeout(resolve, ms));
+  }
+
+  function commitment(side: number, price: number, amount: number, salt: Buffer): number[] {
+    const terms = Buffer.alloc(17);
+    terms.writeUInt8(side, 0);
+    terms.writeBigUInt64LE(BigInt(price), 1);
+    terms.writeBigUInt64LE(BigInt(amount), 9);
+    return Array.from(createHash("sha256").update(Buffer.concat([terms, salt])).digest());
+  }
+
+  function deposit(bid: { side: number; price: number; amount: number }): number {
+    return bid.side === BUY ? Math.ceil((bid.amount * bid.price) / LAMPORTS_PER_SOL) : bid.amount;
+  }
+
+  async function commit(name: string, owner: Keypair) {
+    const bid = bids[name];
+    const round = await program.account.sealedAuction.fetch(auction);
+    const sealedOrder = pda([
+      Buffer.from("sealed_order"),
+      auction.toBuffer(),
+      round.commitCount.toArrayLike(Buffer, "le", 8),
+    ]);
+
+    await program.methods
+      .commitSealedOrder(commitment(bid.side, bid.price, bid.amount, bid.salt), new anchor.BN(deposit(bid)))
+      .accounts({
+        auction,
+        sealedOrder,
+        vault: bid.side === BUY ? quoteVault : baseVault,
+        userTokenAccount: bid.side === BUY ? accounts[name].quote : accounts[name].base,
+        owner: owner.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+
+    bid.sealedOrder = sealedOrder;
+  }
+
+  async function reveal(name: string, owner: Keypair, salt: Buffer = bids[name].salt) {
+    const bid = bids[name];
+    await program.methods
+      .revealSealedOrder(bid.side, new anchor.BN(bid.price), new anchor.BN(bid.amount), Array.from(salt))
+      .accounts({
+        orderBook: book.orderBook,
+        auction,
+        sealedOrder: bid.sealedOrder,
+        owner: owner.publicKey,
+      })
+      .signers([owner])
+      .rpc();
+  }
+
+  async function settle(name: string, owner: Keypair, feeCollectorTokenAccount: PublicKey | null = null) {
+    await program.methods
+      .settleSealedOrder()
+      .accounts({
+        orderBook: book.orderBook,
+        auction,
+        sealedOrder: bids[name].sealedOrder,
+        baseVault,
+        quoteVault,
+        ownerBaseAccount: accounts[name].base,
+        ownerQuoteAccount: accounts[name].quote,
+        feeCollectorTokenAccount,
+        owner: owner.publicKey,
+        cranker: provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+  }
+
+  async function balance(account: PublicKey): Promise<number> {
+    return Number((await getAccount(provider.connection, account)).amount);
+  }
+
+  before(async () => {
+    buyer = Keypair.generate();
+    seller = Keypair.generate();
+    quitter = Keypair.generate();
+    feeCollector = Keypair.generate();
+
+    await airdrop(buyer, seller, quitter);
+
+    ({ baseMint, quoteMint } = await createMints(buyer));
+
+    for (const [name, owner] of [["buyer", buyer], ["seller", seller], ["quitter", quitter]] as [string, Keypair][]) {
+      accounts[name] = {
+        base: await createAccount(provider.connection, owner, baseMint, owner.publicKey),
+        quote: await createAccount(provider.connection, owner, quoteMint, owner.publicKey),
+      };
+    }
+    feeCollectorBaseAccount = await createAccount(provider.connection, buyer, baseMint, feeCollector.publicKey);
+
+    await mintTo(provider.connection, buyer, quoteMint, accounts.buyer.quote, buyer, 1_000_000_000);
+    await mintTo(provider.connection, buyer, baseMint, accounts.seller.base, buyer, LAMPORTS_PER_SOL);
+    await mintTo(provider.connection, buyer, baseMint, accounts.quitter.base, buyer, LAMPORTS_PER_SOL);
+
+    book = await createOrderBook(baseMint, quoteMint, feeCollector.publicKey);
+  });
+
+  function openAccounts() {
+    auction = pda([Buffer.from("sealed_auction"), book.orderBook.toBuffer(), Buffer.alloc(8)]);
+    baseVault = pda([Buffer.from("sealed_vault"), auction.toBuffer(), baseMint.toBuffer()]);
+    quoteVault = pda([Buffer.from("sealed_vault"), auction.toBuffer(), quoteMint.toBuffer()]);
+    return {
+      orderBook: book.orderBook,
+      auction,
+      baseVault,
+      quoteVault,
+      baseMint,
+      quoteMint,
+      authority: provider.wallet.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    };
+  }
+
+  const PARAMS = {
+    commitDuration: new anchor.BN(COMMIT_DURATION),
+    revealDuration: new anchor.BN(REVEAL_DURATION),
+    penaltyBps: PENALTY_BPS,
+  };
+
+  it("❌ Should fail: Penalty above 100%", async () => {
+    try {
+      await program.methods
+        .openSealedAuction({ ...PARAMS, penaltyBps: 10_001 })
+        .accounts(openAccounts())
+        .rpc();
+      assert.fail("Should have failed - invalid penalty");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidSealedAuction");
+    }
+  });
+
+  it("✅ Should succeed: Authority opens a round and orders commit", async () => {
+    await program.methods.openSealedAuction(PARAMS).accounts(openAccounts()).rpc();
+
+    await commit("buyer", buyer);
+    await commit("seller", seller);
+    await commit("quitter", quitter);
+
+    const round = await program.account.sealedAuction.fetch(auction);
+    assert.equal(round.round.toNumber(), 0);
+    assert.equal(round.commitCount.toNumber(), 3);
+    assert.equal(round.bids.length, 0);
+    assert.equal(await balance(quoteVault), 160_000_000);
+    assert.equal(await balance(baseVault), 1_100_000_000);
+
+    const orderBook = await program.account.orderBook.fetch(book.orderBook);
+    assert.equal(orderBook.sealedAuctionCount.toNumber(), 1);
+  });
+
+  it("❌ Should fail: Reveal during the commit window", async () => {
+    try {
+      await reveal("buyer", buyer);
+      assert.fail("Should have failed - commit window still open");
+    } catch (error) {
+      assert.include(error.toString(), "SealedAuctionPhase");
+    }
+  });
+
+  it("❌ Should fail: Reveal with the wrong salt", async () => {
+    await sleep(COMMIT_DURATION * 1000);
+
+    try {
+      await reveal("buyer", buyer, randomBytes(32));
+      assert.fail("Should have failed - reveal does not match commitment");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidSealedReveal");
+    }
+  });
+
+  it("✅ Should succeed: Buyer and seller reveal", async () => {
+    await reveal("buyer", buyer);
+    await reveal("seller", seller);
+
+    const round = await program.account.sealedAuction.fetch(auction);
+    assert.equal(round.bids.length, 2);
+    assert.equal(round.bids[0].side, BUY);
+    assert.equal(round.bids[1].price.toNumber(), bids.seller.price);
+  });
+
+  it("✅ Should succeed: Round clears at the lowest best price", async () => {
+    await sleep(REVEAL_DURATION * 1000);
+
+    await program.methods
+      .clearSealedAuction()
+      .accounts({
+        orderBook: book.orderBook,
+        auction,
+        cranker: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const round = await program.account.sealedAuction.fetch(auction);
+    assert.equal(round.status, 2);
+    assert.equal(round.clearingPrice.toNumber(), 140_000_000);
+    assert.equal(round.volume.toNumber(), 600_000_000);
+    assert.equal(round.bids[0].filled.toNumber(), 600_000_000);
+    assert.equal(round.bids[1].filled.toNumber(), 600_000_000);
+  });
+
+  it("✅ Should succeed: Orders are paid out and the unrevealed one penalised", async () => {
+    await settle("buyer", buyer);
+    await settle("seller", seller);
+    await settle("quitter", quitter, feeCollectorBaseAccount);
+
+    // Buyer paid 0.6 SOL * 140 = 84 USDC out of its 160 USDC deposit
+    assert.equal(await balance(accounts.buyer.base), 600_000_000);
+    assert.equal(await balance(accounts.buyer.quote), 1_000_000_000 - 84_000_000);
+    assert.equal(await balance(accounts.seller.base), 400_000_000);
+    assert.equal(await balance(accounts.seller.quote), 84_000_000);
+    assert.equal(await balance(accounts.quitter.base), 950_000_000);
+    assert.equal(await balance(feeCollectorBaseAccount), 50_000_000);
+    assert.equal(await balance(baseVault), 0);
+    assert.equal(await balance(quoteVault), 0);
+
+    const round = await program.account.sealedAuction.fetch(auction);
+    assert.equal(round.settledCount.toNumber(), 3);
+    assert.isNull(await provider.connection.getAccountInfo(bids.buyer.sealedOrder!));
+  });
+});
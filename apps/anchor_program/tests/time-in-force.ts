@@ -0,0 +1,252 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { ShadowSwap } from "../target/types/shadow_swap";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  LAMPORTS_PER_SOL,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createAccount,
+  mintTo,
+  getAccount,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import {
+  Book,
+  PlacedOrder,
+  pda,
+  airdrop,
+  createMints,
+  createOrderBook,
+  placeOrder,
+} from "./helpers";
+
+/**
+ * Time-in-force tests (GTC / IOC / FOK / post-only)
+ *
+ * The keeper reports how much base each order has left after a match; IOC
+ * remainders are cancelled and refunded in the same settlement, FOK orders
+ * must fill completely, and post-only orders must be the maker.
+ */
+describe("ShadowSwap - Time In Force", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.ShadowSwap as Program<ShadowSwap>;
+
+  const GTC = 0;
+  const IOC = 1;
+  const FOK = 2;
+  const POST_ONLY = 3;
+
+  const PRICE = 150_000_000; // 150 USDC per SOL
+  const ONE_SOL = LAMPORTS_PER_SOL;
+
+  let baseMint: PublicKey;
+  let quoteMint: PublicKey;
+  let book: Book;
+  let callbackAuthPda: PublicKey;
+
+  let buyer: Keypair;
+  let seller: Keypair;
+  let keeper: Keypair;
+  let buyerBaseAccount: PublicKey;
+  let buyerQuoteAccount: PublicKey;
+  let sellerBaseAccount: PublicKey;
+  let sellerQuoteAccount: PublicKey;
+
+  async function settle(
+    buy: PlacedOrder,
+    sell: PlacedOrder,
+    matchedAmount: number,
+    buyerRemaining: number,
+    sellerRemaining: number
+  ) {
+    await program.methods
+      .submitMatchResults({
+        buyerPubkey: buy.order,
+        sellerPubkey: sell.order,
+        matchedAmount: new anchor.BN(matchedAmount),
+        executionPrice: new anchor.BN(PRICE),
+        buyerRemaining: new anchor.BN(buyerRemaining),
+        sellerRemaining: new anchor.BN(sellerRemaining),
+      })
+      .accounts({
+        callbackAuth: callbackAuthPda,
+        orderBook: book.orderBook,
+        buyerOrder: buy.order,
+        sellerOrder: sell.order,
+        buyerEscrow: buy.escrow,
+        sellerEscrow: sell.escrow,
+        keeper: keeper.publicKey,
+        oracle: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        { pubkey: buy.escrowToken, isSigner: false, isWritable: true },
+        { pubkey: sell.escrowToken, isSigner: false, isWritable: true },
+        { pubkey: buyerBaseAccount, isSigner: false, isWritable: true },
+        { pubkey: sellerQuoteAccount, isSigner: false, isWritable: true },
+        { pubkey: buyerQuoteAccount, isSigner: false, isWritable: true },
+        { pubkey: sellerBaseAccount, isSigner: false, isWritable: true },
+      ])
+      .signers([keeper])
+      .rpc();
+  }
+
+  before(async () => {
+    buyer = Keypair.generate();
+    seller = Keypair.generate();
+    keeper = Keypair.generate();
+
+    await airdrop(buyer, seller, keeper);
+
+    ({ baseMint, quoteMint } = await createMints(seller));
+
+    buyerBaseAccount = await createAccount(provider.connection, buyer, baseMint, buyer.publicKey);
+    buyerQuoteAccount = await createAccount(provider.connection, buyer, quoteMint, buyer.publicKey);
+    sellerBaseAccount = await createAccount(provider.connection, seller, baseMint, seller.publicKey);
+    sellerQuoteAccount = await createAccount(provider.connection, seller, quoteMint, seller.publicKey);
+
+    await mintTo(provider.connection, seller, quoteMint, buyerQuoteAccount, seller, 10_000 * 10 ** 6);
+    await mintTo(provider.connection, seller, baseMint, sellerBaseAccount, seller, 50 * ONE_SOL);
+
+    book = await createOrderBook(baseMint, quoteMint);
+
+    callbackAuthPda = pda([
+      Buffer.from("callback_auth"),
+      book.orderBook.toBuffer(),
+      keeper.publicKey.toBuffer(),
+    ]);
+    await program.methods
+      .createCallbackAuth(new anchor.BN(Math.floor(Date.now() / 1000) + 86400))
+      .accounts({
+        orderBook: book.orderBook,
+        callbackAuth: callbackAuthPda,
+        authority: provider.wallet.publicKey,
+        keeper: keeper.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  it("❌ Should fail: Unknown time-in-force flag", async () => {
+    try {
+      await placeOrder(book, buyer, quoteMint, buyerQuoteAccount, 200_000_000, { timeInForce: 7 });
+      assert.fail("Should have failed - invalid time-in-force");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidTimeInForce");
+    }
+  });
+
+  it("✅ Should succeed: IOC remainder is cancelled and refunded in the same settlement", async () => {
+    // Buyer wants 2 SOL but only 1 SOL is offered
+    const buy = await placeOrder(book, buyer, quoteMint, buyerQuoteAccount, 300_000_000, { timeInForce: IOC });
+    const sell = await placeOrder(book, seller, baseMint, sellerBaseAccount, ONE_SOL, { timeInForce: GTC });
+
+    const quoteBefore = await getAccount(provider.connection, buyerQuoteAccount);
+    await settle(buy, sell, ONE_SOL, ONE_SOL, 0);
+
+    const buyOrder = await program.account.encryptedOrder.fetch(buy.order);
+    assert.equal(buyOrder.status, 4, "IOC remainder should be cancelled");
+
+    const escrow = await getAccount(provider.connection, buy.escrowToken);
+    assert.equal(Number(escrow.amount), 0);
+
+    const quoteAfter = await getAccount(provider.connection, buyerQuoteAccount);
+    assert.equal(Number(quoteAfter.amount - quoteBefore.amount), 300_000_000 - PRICE);
+  });
+
+  it("❌ Should fail: FOK order is never partially filled", async () => {
+    const buy = await placeOrder(book, buyer, quoteMint, buyerQuoteAccount, 300_000_000, { timeInForce: FOK });
+    const sell = await placeOrder(book, seller, baseMint, sellerBaseAccount, ONE_SOL, { timeInForce: GTC });
+
+    try {
+      await settle(buy, sell, ONE_SOL, ONE_SOL, 0);
+      assert.fail("Should have failed - FOK order would be partially filled");
+    } catch (error) {
+      assert.include(error.toString(), "FillOrKillNotFilled");
+    }
+
+    const buyOrder = await program.account.encryptedOrder.fetch(buy.order);
+    assert.equal(buyOrder.status, 1, "FOK order should be untouched");
+  });
+
+  it("✅ Should succeed: FOK order fills completely and unused escrow is refunded", async () => {
+    const buy = await placeOrder(book, buyer, quoteMint, buyerQuoteAccount, 160_000_000, { timeInForce: FOK });
+    const sell = await placeOrder(book, seller, baseMint, sellerBaseAccount, ONE_SOL, { timeInForce: GTC });
+
+    await settle(buy, sell, ONE_SOL, 0, 0);
+
+    const buyOrder = await program.account.encryptedOrder.fetch(buy.order);
+    assert.equal(buyOrder.status, 3);
+    const escrow = await getAccount(provider.connection, buy.escrowToken);
+    assert.equal(Number(escrow.amount), 0);
+  });
+
+  it("❌ Should fail: Post-only order cannot be the taker", async () => {
+    const sell = await placeOrder(book, seller, baseMint, sellerBaseAccount, ONE_SOL, { timeInForce: GTC });
+    // Ensure the buy order is strictly newer than the sell order
+    await new Promise((resolve) => setTimeout(resolve, 1500));
+    const buy = await placeOrder(book, buyer, quoteMint, buyerQuoteAccount, 200_000_000, { timeInForce: POST_ONLY });
+
+    try {
+      await settle(buy, sell, ONE_SOL, 0, 0);
+      assert.fail("Should have failed - post-only order would take liquidity");
+    } catch (error) {
+      assert.include(error.toString(), "PostOnlyWouldTake");
+    }
+  });
+
+  it("✅ Should succeed: Keeper expires an unmatched IOC order with a refund", async () => {
+    const sell = await placeOrder(book, seller, baseMint, sellerBaseAccount, ONE_SOL, { timeInForce: IOC });
+    const baseBefore = await getAccount(provider.connection, sellerBaseAccount);
+
+    await program.methods
+      .expireImmediateOrder()
+      .accounts({
+        callbackAuth: callbackAuthPda,
+        orderBook: book.orderBook,
+        order: sell.order,
+        escrow: sell.escrow,
+        escrowTokenAccount: sell.escrowToken,
+        ownerTokenAccount: sellerBaseAccount,
+        keeper: keeper.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([keeper])
+      .rpc();
+
+    const order = await program.account.encryptedOrder.fetch(sell.order);
+    assert.equal(order.status, 4);
+    const baseAfter = await getAccount(provider.connection, sellerBaseAccount);
+    assert.equal(Number(baseAfter.amount - baseBefore.amount), ONE_SOL);
+  });
+
+  it("❌ Should fail: GTC orders cannot be expired by the keeper", async () => {
+    const sell = await placeOrder(book, seller, baseMint, sellerBaseAccount, ONE_SOL, { timeInForce: GTC });
+
+    try {
+      await program.methods
+        .expireImmediateOrder()
+        .accounts({
+          callbackAuth: callbackAuthPda,
+          orderBook: book.orderBook,
+          order: sell.order,
+          escrow: sell.escrow,
+          escrowTokenAccount: sell.escrowToken,
+          ownerTokenAccount: sellerBaseAccount,
+          keeper: keeper.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([keeper])
+        .rpc();
+      assert.fail("Should have failed - GTC orders rest on the book");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidTimeInForce");
+    }
+  });
+});
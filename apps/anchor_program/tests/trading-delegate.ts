@@ -0,0 +1,211 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { ShadowSwap } from "../target/types/shadow_swap";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  LAMPORTS_PER_SOL,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createAccount,
+  mintTo,
+  getAccount,
+  approve,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import {
+  Book,
+  PlacedOrder,
+  pda,
+  airdrop,
+  createMints,
+  createOrderBook,
+  placeOrder,
+} from "./helpers";
+
+/**
+ * Session key (trading delegate) tests
+ *
+ * The owner authorizes a session key to place and cancel orders of up to
+ * 1 SOL each and approves the delegate PDA for 2 SOL of its base tokens.
+ */
+describe("ShadowSwap - Trading Delegates", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.ShadowSwap as Program<ShadowSwap>;
+
+  const PLACE = 1;
+  const CANCEL = 2;
+  const MAX_BASE = 1_000_000_000;
+  const ALLOWANCE = 2_000_000_000;
+
+  let baseMint: PublicKey;
+  let quoteMint: PublicKey;
+  let book: Book;
+
+  let owner: Keypair;
+  let sessionKey: Keypair;
+  let placeOnlyKey: Keypair;
+  let ownerBaseAccount: PublicKey;
+  let sessionBaseAccount: PublicKey;
+  let placed: PlacedOrder;
+
+  function tradingDelegate(delegate: PublicKey): PublicKey {
+    return pda([Buffer.from("trading_delegate"), owner.publicKey.toBuffer(), delegate.toBuffer()]);
+  }
+
+  async function authorize(delegate: Keypair, scopes: number) {
+    await program.methods
+      .authorizeTradingDelegate(
+        scopes,
+        new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+        new anchor.BN(MAX_BASE),
+        new anchor.BN(0)
+      )
+      .accounts({
+        tradingDelegate: tradingDelegate(delegate.publicKey),
+        delegate: delegate.publicKey,
+        owner: owner.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+  }
+
+  function placeAsDelegate(delegate: Keypair, amount: number, clientOrderId?: Buffer): Promise<PlacedOrder> {
+    // The order belongs to the owner whichever session key signs
+    return placeOrder(book, owner.publicKey, baseMint, ownerBaseAccount, amount, {
+      delegate: { key: delegate, tradingDelegate: tradingDelegate(delegate.publicKey) },
+      clientOrderId,
+    });
+  }
+
+  async function cancelAsDelegate(delegate: Keypair, refundAccount: PublicKey) {
+    await program.methods
+      .cancelOrder()
+      .accounts({
+        order: placed.order,
+        escrow: placed.escrow,
+        escrowTokenAccount: placed.escrowToken,
+        userTokenAccount: refundAccount,
+        orderBook: book.orderBook,
+        owner: delegate.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tradingDelegate: tradingDelegate(delegate.publicKey),
+      })
+      .signers([delegate])
+      .rpc();
+  }
+
+  before(async () => {
+    owner = Keypair.generate();
+    sessionKey = Keypair.generate();
+    placeOnlyKey = Keypair.generate();
+
+    await airdrop(owner, sessionKey, placeOnlyKey);
+
+    ({ baseMint, quoteMint } = await createMints(owner));
+    ownerBaseAccount = await createAccount(provider.connection, owner, baseMint, owner.publicKey);
+    sessionBaseAccount = await createAccount(provider.connection, sessionKey, baseMint, sessionKey.publicKey);
+    await mintTo(provider.connection, owner, baseMint, ownerBaseAccount, owner, 5 * LAMPORTS_PER_SOL);
+
+    book = await createOrderBook(baseMint, quoteMint, provider.wallet.publicKey);
+  });
+
+  it("❌ Should fail: Authorize an unknown scope", async () => {
+    try {
+      await authorize(sessionKey, 4);
+      assert.fail("Should have failed - unknown scope");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidTradingDelegate");
+    }
+  });
+
+  it("✅ Should succeed: Owner authorizes session keys", async () => {
+    await authorize(sessionKey, PLACE | CANCEL);
+    await authorize(placeOnlyKey, PLACE);
+    await approve(
+      provider.connection,
+      owner,
+      ownerBaseAccount,
+      tradingDelegate(sessionKey.publicKey),
+      owner,
+      ALLOWANCE
+    );
+
+    const delegate = await program.account.tradingDelegate.fetch(tradingDelegate(sessionKey.publicKey));
+    assert.ok(delegate.owner.equals(owner.publicKey));
+    assert.equal(delegate.scopes, PLACE | CANCEL);
+    assert.equal(delegate.maxBasePerOrder.toNumber(), MAX_BASE);
+  });
+
+  it("❌ Should fail: Delegate order above the per-order limit", async () => {
+    try {
+      await placeAsDelegate(sessionKey, MAX_BASE + 1);
+      assert.fail("Should have failed - over the per-order limit");
+    } catch (error) {
+      assert.include(error.toString(), "DelegateLimitExceeded");
+    }
+  });
+
+  it("✅ Should succeed: Session key places an order for the owner", async () => {
+    placed = await placeAsDelegate(sessionKey, 500_000_000);
+
+    const order = await program.account.encryptedOrder.fetch(placed.order);
+    assert.ok(order.owner.equals(owner.publicKey));
+    const escrow = await program.account.escrow.fetch(placed.escrow);
+    assert.ok(escrow.owner.equals(owner.publicKey));
+    assert.equal(Number((await getAccount(provider.connection, placed.escrowToken)).amount), 500_000_000);
+  });
+
+  it("❌ Should fail: Another session key retries the same client order ID", async () => {
+    try {
+      await placeAsDelegate(placeOnlyKey, 500_000_000, placed.clientOrderId);
+      assert.fail("Should have failed - the owner already has this client order ID");
+    } catch (error) {
+      assert.include(error.toString(), "already in use");
+    }
+  });
+
+  it("❌ Should fail: Place-only key cancels", async () => {
+    try {
+      await cancelAsDelegate(placeOnlyKey, ownerBaseAccount);
+      assert.fail("Should have failed - no cancel scope");
+    } catch (error) {
+      assert.include(error.toString(), "UnauthorizedDelegate");
+    }
+  });
+
+  it("❌ Should fail: Delegate cancels into its own account", async () => {
+    try {
+      await cancelAsDelegate(sessionKey, sessionBaseAccount);
+      assert.fail("Should have failed - refund must go to the owner");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidRefundAccount");
+    }
+  });
+
+  it("✅ Should succeed: Session key cancels, refunding the owner", async () => {
+    await cancelAsDelegate(sessionKey, ownerBaseAccount);
+
+    const order = await program.account.encryptedOrder.fetch(placed.order);
+    assert.equal(order.status, 4);
+    assert.equal(Number((await getAccount(provider.connection, ownerBaseAccount)).amount), 5 * LAMPORTS_PER_SOL);
+  });
+
+  it("✅ Should succeed: Owner revokes the session key", async () => {
+    await program.methods
+      .revokeTradingDelegate()
+      .accounts({
+        tradingDelegate: tradingDelegate(sessionKey.publicKey),
+        owner: owner.publicKey,
+      })
+      .signers([owner])
+      .rpc();
+
+    assert.isNull(await provider.connection.getAccountInfo(tradingDelegate(sessionKey.publicKey)));
+  });
+});
@@ -0,0 +1,215 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { ShadowSwap } from "../target/types/shadow_swap";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  createAccount,
+  mintTo,
+  getAccount,
+} from "@solana/spl-token";
+import { assert } from "chai";
+import {
+  Book,
+  pda,
+  orderAddress,
+  newClientOrderId,
+  airdrop,
+  createMints,
+  createOrderBook,
+} from "./helpers";
+
+/**
+ * User balance tests
+ *
+ * Traders deposit once into the book's pooled vaults; balance-backed orders
+ * lock part of the free balance instead of funding their own escrow.
+ */
+describe("ShadowSwap - User Balances", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.ShadowSwap as Program<ShadowSwap>;
+
+  const DEPOSIT = 1_000_000_000;
+  const LOCK = 400_000_000;
+
+  let baseMint: PublicKey;
+  let quoteMint: PublicKey;
+  let book: Book;
+  let baseVault: PublicKey;
+  let quoteVault: PublicKey;
+
+  let trader: Keypair;
+  let traderQuoteAccount: PublicKey;
+  let userBalance: PublicKey;
+
+  async function transfer(kind: "deposit" | "withdraw", amount: number) {
+    const method = kind === "deposit" ? program.methods.depositToBalance : program.methods.withdrawFromBalance;
+    await method(new anchor.BN(amount))
+      .accounts({
+        orderBook: book.orderBook,
+        userBalance,
+        bookVault: quoteVault,
+        userTokenAccount: traderQuoteAccount,
+        owner: trader.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([trader])
+      .rpc();
+  }
+
+  async function submitBalanceOrder(lockedAmount: number): Promise<PublicKey> {
+    const clientOrderId = newClientOrderId();
+    const order = orderAddress(book.orderBook, trader.publicKey, clientOrderId);
+
+    await program.methods
+      .submitBalanceOrder(Array.from(clientOrderId), Buffer.alloc(128, 1), Buffer.alloc(32, 2), new anchor.BN(lockedAmount), 0)
+      .accounts({
+        orderBook: book.orderBook,
+        orderShard: book.orderShard,
+        userBalance,
+        bookVault: quoteVault,
+        order,
+        escrow: pda([Buffer.from("escrow"), order.toBuffer()]),
+        owner: trader.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([trader])
+      .rpc();
+
+    return order;
+  }
+
+  before(async () => {
+    trader = Keypair.generate();
+    await airdrop(trader);
+
+    ({ baseMint, quoteMint } = await createMints(trader));
+    traderQuoteAccount = await createAccount(provider.connection, trader, quoteMint, trader.publicKey);
+    await mintTo(provider.connection, trader, quoteMint, traderQuoteAccount, trader, 10_000 * 10 ** 6);
+
+    book = await createOrderBook(baseMint, quoteMint);
+
+    baseVault = pda([Buffer.from("book_vault"), book.orderBook.toBuffer(), baseMint.toBuffer()]);
+    quoteVault = pda([Buffer.from("book_vault"), book.orderBook.toBuffer(), quoteMint.toBuffer()]);
+    await program.methods
+      .initializeBookVaults()
+      .accounts({
+        orderBook: book.orderBook,
+        baseVault,
+        quoteVault,
+        baseMint,
+        quoteMint,
+        authority: provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    userBalance = pda([Buffer.from("user_balance"), book.orderBook.toBuffer(), trader.publicKey.toBuffer()]);
+    await program.methods
+      .openUserBalance()
+      .accounts({
+        orderBook: book.orderBook,
+        userBalance,
+        owner: trader.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([trader])
+      .rpc();
+  });
+
+  it("✅ Should succeed: Deposit credits the free balance and the book vault", async () => {
+    await transfer("deposit", DEPOSIT);
+
+    const balance = await program.account.userBalance.fetch(userBalance);
+    assert.equal(balance.quoteFree.toNumber(), DEPOSIT);
+    assert.equal(balance.quoteLocked.toNumber(), 0);
+
+    const vault = await getAccount(provider.connection, quoteVault);
+    assert.equal(Number(vault.amount), DEPOSIT);
+  });
+
+  it("❌ Should fail: Withdraw more than the free balance", async () => {
+    try {
+      await transfer("withdraw", DEPOSIT + 1);
+      assert.fail("Should have failed - not enough free balance");
+    } catch (error) {
+      assert.include(error.toString(), "InsufficientFreeBalance");
+    }
+  });
+
+  it("✅ Should succeed: Balance order locks funds without moving tokens", async () => {
+    const order = await submitBalanceOrder(LOCK);
+
+    const balance = await program.account.userBalance.fetch(userBalance);
+    assert.equal(balance.quoteFree.toNumber(), DEPOSIT - LOCK);
+    assert.equal(balance.quoteLocked.toNumber(), LOCK);
+
+    const placed = await program.account.encryptedOrder.fetch(order);
+    assert.ok(placed.userBalance.equals(userBalance));
+
+    const vault = await getAccount(provider.connection, quoteVault);
+    assert.equal(Number(vault.amount), DEPOSIT, "tokens stay in the book vault");
+
+    try {
+      await transfer("withdraw", DEPOSIT - LOCK + 1);
+      assert.fail("Should have failed - locked funds cannot be withdrawn");
+    } catch (error) {
+      assert.include(error.toString(), "InsufficientFreeBalance");
+    }
+  });
+
+  it("❌ Should fail: Escrow cancel on a balance-backed order", async () => {
+    const order = await submitBalanceOrder(LOCK);
+
+    try {
+      await program.methods
+        .cancelOrder()
+        .accounts({
+          order,
+          escrow: pda([Buffer.from("escrow"), order.toBuffer()]),
+          escrowTokenAccount: quoteVault,
+          userTokenAccount: traderQuoteAccount,
+          orderBook: book.orderBook,
+          owner: trader.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([trader])
+        .rpc();
+      assert.fail("Should have failed - order is balance-backed");
+    } catch (error) {
+      assert.include(error.toString(), "BalanceBackedOrder");
+    }
+  });
+
+  it("✅ Should succeed: Cancelling a balance order returns its lock", async () => {
+    const order = await submitBalanceOrder(LOCK);
+    const before = await program.account.userBalance.fetch(userBalance);
+
+    await program.methods
+      .cancelBalanceOrder()
+      .accounts({
+        order,
+        escrow: pda([Buffer.from("escrow"), order.toBuffer()]),
+        userBalance,
+        orderBook: book.orderBook,
+        owner: trader.publicKey,
+      })
+      .signers([trader])
+      .rpc();
+
+    const after = await program.account.userBalance.fetch(userBalance);
+    assert.equal(after.quoteFree.toNumber() - before.quoteFree.toNumber(), LOCK);
+    assert.equal(before.quoteLocked.toNumber() - after.quoteLocked.toNumber(), LOCK);
+
+    const cancelled = await program.account.encryptedOrder.fetch(order);
+    assert.equal(cancelled.status, 4);
+  });
+});
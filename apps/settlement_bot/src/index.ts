@@ -27,6 +27,12 @@ import {
   PlainOrder,
   MatchedPair,
   OrderStatus,
+  ParentOrderStatus,
+  SealedAuctionStatus,
+  TimeInForce,
+  isImmediate,
+  isBalanceBacked,
+  isRouted,
 } from './types';
 import { ArciumClient, MockArciumClient } from './arcium-client';
 import { SanctumClient, MockSanctumClient, DirectRPCClient } from './sanctum-client';
@@ -144,6 +150,16 @@ class ShadowSwapKeeper {
     console.log(`\n⏱️  [${new Date().toISOString()}] Starting matching cycle #${++this.matchCount}...`);
 
     try {
+      // A decommissioning book is only wound down, never matched
+      if (await this.windDownIfDecommissioning()) {
+        return;
+      }
+
+      // Step 0: Release due child orders of TWAP / iceberg parents and
+      // clear / pay out commit-reveal rounds whose reveal window is over
+      await this.releaseChildOrders();
+      await this.crankSealedAuctions();
+
       // Step 1: Fetch active encrypted orders
       const encryptedOrders = await this.fetchActiveOrders();
       
@@ -165,6 +181,8 @@ class ShadowSwapKeeper {
       
       if (matches.length === 0) {
         console.log('   💤 No matches found');
+        await this.backstopUnmatchedOrders(plainOrders, []);
+        await this.expireImmediateOrders(plainOrders);
         return;
       }
 
@@ -184,6 +202,12 @@ class ShadowSwapKeeper {
       // Step 5: Submit matches for settlement
       await this.submitMatches(prioritizedMatches);
 
+      // Step 6: Old GTC orders nobody matched can fill against the LP vault
+      await this.backstopUnmatchedOrders(plainOrders, prioritizedMatches);
+
+      // Step 7: IOC/FOK orders that did not fill this round are cancelled
+      await this.expireImmediateOrders(plainOrders);
+
       const duration = Date.now() - cycleStart;
       console.log(`\n✅ Matching cycle completed in ${duration}ms`);
       console.log('━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━');
@@ -212,7 +236,8 @@ class ShadowSwapKeeper {
         },
       ]);
 
-      // Filter for active orders
+      // Filter for active orders. Routed orders settle against a second
+      // book via settle_routed_match, which this single-book keeper skips
       const activeOrders = accounts
         .map((acc: any) => ({
           publicKey: acc.publicKey,
@@ -220,8 +245,9 @@ class ShadowSwapKeeper {
         }))
         .filter(
           (order: any) =>
-            order.account.status === OrderStatus.ACTIVE ||
-            order.account.status === OrderStatus.PARTIAL
+            (order.account.status === OrderStatus.ACTIVE ||
+              order.account.status === OrderStatus.PARTIAL) &&
+            !isRouted(order.account)
         );
 
       console.log(`   ✅ Found ${activeOrders.length} active orders`);
@@ -304,7 +330,20 @@ class ShadowSwapKeeper {
             createdAt: encryptedOrders[i].createdAt.toNumber(),
             orderId: encryptedOrders[i].orderId.toNumber(),
             status: encryptedOrders[i].status,
+            timeInForce: encryptedOrders[i].timeInForce ?? TimeInForce.GTC,
+            balanceBacked: isBalanceBacked(encryptedOrders[i]),
           };
+
+          // Children of a parent order share its payload, and balance-backed
+          // orders keep their original payload across partial fills; only
+          // what is still escrowed or locked can be filled
+          const parent: PublicKey | undefined = encryptedOrders[i].parent;
+          if ((parent && !parent.equals(PublicKey.default)) || plainOrder.balanceBacked) {
+            plainOrder.remainingAmount = await this.capToEscrowSlice(plainOrder);
+            if (plainOrder.remainingAmount <= 0n) {
+              continue;
+            }
+          }
           
           console.log(`   🔍 Order #${i}: side=${plainOrder.side}, price=${plainOrder.price.toString()}, amount=${plainOrder.amount.toString()}`);
           plainOrders.push(plainOrder);
@@ -394,6 +433,434 @@ class ShadowSwapKeeper {
     }
   }
 
+  /**
+   * Base amount an order can fill given its escrowed slice or balance lock
+   */
+  private async capToEscrowSlice(order: PlainOrder): Promise<bigint> {
+    const escrowData = await (this.program.account as any).escrow.fetch(order.escrow);
+    let escrowed: bigint;
+    if (order.balanceBacked) {
+      escrowed = BigInt(escrowData.lockedAmount.toString());
+    } else {
+      const balance = await this.connection.getTokenAccountBalance(escrowData.tokenAccount);
+      escrowed = BigInt(balance.value.amount);
+    }
+
+    // Sell children escrow base; buy children escrow quote at their limit price
+    const sliceBase = order.side === 1 || order.side === 'sell'
+      ? escrowed
+      : order.price > 0n ? (escrowed * BASE_DECIMALS) / order.price : 0n;
+    return order.remainingAmount < sliceBase ? order.remainingAmount : sliceBase;
+  }
+
+  /**
+   * Order ID shard an owner's orders take IDs from
+   *
+   * Mirrors the SDK's `order_shard_for`: owners are spread over the book's
+   * shards by the first byte of their key.
+   */
+  private async orderShardFor(owner: PublicKey): Promise<PublicKey> {
+    const orderBookData = await (this.program.account as any).orderBook.fetch(this.orderBook);
+    const shard = owner.toBuffer()[0] % Math.max(orderBookData.orderShards, 1);
+    const [orderShard] = PublicKey.findProgramAddressSync(
+      [Buffer.from('order_shard'), this.orderBook.toBuffer(), Buffer.from([shard])],
+      this.program.programId
+    );
+    return orderShard;
+  }
+
+  /**
+   * Crank every active parent order of this book whose next release is due
+   */
+  private async releaseChildOrders(): Promise<void> {
+    let parents: any[];
+    try {
+      // Struct layout: discriminator (8) + owner (32) + order_book (32) + ...
+      parents = await (this.program.account as any).parentOrder.all([
+        { memcmp: { offset: 8 + 32, bytes: this.orderBook.toBase58() } },
+      ]);
+    } catch (error) {
+      this.logError('Error fetching parent orders', error);
+      return;
+    }
+
+    const now = Math.floor(Date.now() / 1000);
+    const due = parents.filter(
+      (p: any) => p.account.status === ParentOrderStatus.ACTIVE && p.account.nextReleaseAt.toNumber() <= now
+    );
+
+    for (const parent of due) {
+      try {
+        // Children are seeded by their parent and index within it
+        const [order] = PublicKey.findProgramAddressSync(
+          [
+            Buffer.from('order'),
+            this.orderBook.toBuffer(),
+            parent.publicKey.toBuffer(),
+            parent.account.childCount.toArrayLike(Buffer, 'le', 8),
+          ],
+          this.program.programId
+        );
+        const [escrow] = PublicKey.findProgramAddressSync(
+          [Buffer.from('escrow'), order.toBuffer()],
+          this.program.programId
+        );
+        const [escrowToken] = PublicKey.findProgramAddressSync(
+          [Buffer.from('escrow_token'), order.toBuffer()],
+          this.program.programId
+        );
+
+        const ix = await this.program.methods
+          .releaseChildOrder()
+          .accounts({
+            orderBook: this.orderBook,
+            orderShard: await this.orderShardFor(parent.account.owner),
+            parentOrder: parent.publicKey,
+            parentEscrowTokenAccount: parent.account.escrowTokenAccount,
+            order,
+            escrow,
+            escrowTokenAccount: escrowToken,
+            tokenMint: parent.account.tokenMint,
+            payer: this.keeper.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          })
+          .instruction();
+
+        const tx = new Transaction().add(ix);
+        tx.recentBlockhash = (await this.connection.getLatestBlockhash()).blockhash;
+        tx.feePayer = this.keeper.publicKey;
+        tx.sign(this.keeper);
+
+        const res = await this.sanctumClient.submitTransaction(tx, this.config.maxRetries);
+        if (res.signature) {
+          console.log(`   🧩 Released child order from parent ${parent.publicKey.toBase58()} (tx ${res.signature})`);
+        } else {
+          console.log(`   ❌ Failed to release child order :: ${res.error || 'Unknown submission error'}`);
+        }
+      } catch (error) {
+        this.logError(`Error releasing child of parent ${parent.publicKey.toBase58()}`, error);
+      }
+    }
+  }
+
+  /**
+   * Clear commit-reveal rounds of this book whose reveal window is over and
+   * pay out every sealed order of cleared rounds
+   */
+  private async crankSealedAuctions(): Promise<void> {
+    let auctions: any[];
+    try {
+      // Struct layout: discriminator (8) + order_book (32) + ...
+      auctions = await (this.program.account as any).sealedAuction.all([
+        { memcmp: { offset: 8, bytes: this.orderBook.toBase58() } },
+      ]);
+    } catch (error) {
+      this.logError('Error fetching sealed auctions', error);
+      return;
+    }
+
+    const now = Math.floor(Date.now() / 1000);
+    for (const auction of auctions) {
+      const data = auction.account;
+      const pendingPayouts = data.settledCount.lt(data.commitCount);
+      const due = data.status === SealedAuctionStatus.OPEN && data.revealEnd.toNumber() <= now;
+      if (!due && !(data.status === SealedAuctionStatus.CLEARED && pendingPayouts)) {
+        continue;
+      }
+
+      try {
+        if (due) {
+          const ix = await this.program.methods
+            .clearSealedAuction()
+            .accounts({
+              orderBook: this.orderBook,
+              auction: auction.publicKey,
+              cranker: this.keeper.publicKey,
+            })
+            .instruction();
+          if (!(await this.submitCrank(ix, `clear sealed auction ${data.round}`))) {
+            continue;
+          }
+        }
+
+        const orderBookData = await (this.program.account as any).orderBook.fetch(this.orderBook);
+        // Struct layout: discriminator (8) + auction (32) + ...
+        const sealedOrders = await (this.program.account as any).sealedOrder.all([
+          { memcmp: { offset: 8, bytes: auction.publicKey.toBase58() } },
+        ]);
+        for (const sealedOrder of sealedOrders) {
+          const owner: PublicKey = sealedOrder.account.owner;
+          const ix = await this.program.methods
+            .settleSealedOrder()
+            .accounts({
+              orderBook: this.orderBook,
+              auction: auction.publicKey,
+              sealedOrder: sealedOrder.publicKey,
+              baseVault: data.baseVault,
+              quoteVault: data.quoteVault,
+              ownerBaseAccount: this.getAssociatedTokenAccount(owner, orderBookData.baseMint),
+              ownerQuoteAccount: this.getAssociatedTokenAccount(owner, orderBookData.quoteMint),
+              feeCollectorTokenAccount: sealedOrder.account.revealed
+                ? null
+                : this.getAssociatedTokenAccount(orderBookData.feeCollector, sealedOrder.account.depositMint),
+              owner,
+              cranker: this.keeper.publicKey,
+              tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .instruction();
+          await this.submitCrank(ix, `settle sealed order ${sealedOrder.account.index}`);
+        }
+      } catch (error) {
+        this.logError(`Error cranking sealed auction ${auction.publicKey.toBase58()}`, error);
+      }
+    }
+  }
+
+  /**
+   * Refund and close every order of a decommissioning book
+   *
+   * Returns false when the book is live. Orders go to `wind_down_orders` in
+   * batches of four groups of `[order, escrow, escrow token, refund, owner]`.
+   */
+  private async windDownIfDecommissioning(): Promise<boolean> {
+    const orderBookData = await (this.program.account as any).orderBook.fetch(this.orderBook);
+    if (orderBookData.decommissionedAt.isZero()) {
+      return false;
+    }
+
+    let orders: any[];
+    try {
+      // Struct layout: discriminator (8) + owner (32) + order_book (32) + ...
+      orders = await (this.program.account as any).encryptedOrder.all([
+        { memcmp: { offset: 8 + 32, bytes: this.orderBook.toBase58() } },
+      ]);
+    } catch (error) {
+      this.logError('Error fetching orders to wind down', error);
+      return true;
+    }
+    orders = orders.filter((order: any) => order.account.status !== OrderStatus.MATCHED_PENDING);
+    console.log(`   🏚️  Book is decommissioning (${orders.length} orders left to wind down)`);
+
+    const BATCH_SIZE = 4;
+    for (let i = 0; i < orders.length; i += BATCH_SIZE) {
+      const batch = orders.slice(i, i + BATCH_SIZE);
+      try {
+        const remainingAccounts = [];
+        for (const order of batch) {
+          const escrowData = await (this.program.account as any).escrow.fetch(order.account.escrow);
+          const owner: PublicKey = order.account.owner;
+          const refund = isBalanceBacked(order.account)
+            ? order.account.userBalance
+            : this.getAssociatedTokenAccount(owner, escrowData.tokenMint);
+          remainingAccounts.push(
+            { pubkey: order.publicKey, isSigner: false, isWritable: true },
+            { pubkey: order.account.escrow, isSigner: false, isWritable: true },
+            { pubkey: escrowData.tokenAccount, isSigner: false, isWritable: true },
+            { pubkey: refund, isSigner: false, isWritable: true },
+            { pubkey: owner, isSigner: false, isWritable: true }
+          );
+        }
+
+        const ix = await this.program.methods
+          .windDownOrders()
+          .accounts({
+            orderBook: this.orderBook,
+            cranker: this.keeper.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .remainingAccounts(remainingAccounts)
+          .instruction();
+        await this.submitCrank(ix, `wind down ${batch.length} orders`);
+      } catch (error) {
+        this.logError('Error winding down orders', error);
+      }
+    }
+    return true;
+  }
+
+  /**
+   * Sign and submit a single permissionless crank instruction
+   */
+  private async submitCrank(ix: anchor.web3.TransactionInstruction, label: string): Promise<boolean> {
+    const tx = new Transaction().add(ix);
+    tx.recentBlockhash = (await this.connection.getLatestBlockhash()).blockhash;
+    tx.feePayer = this.keeper.publicKey;
+    tx.sign(this.keeper);
+
+    const res = await this.sanctumClient.submitTransaction(tx, this.config.maxRetries);
+    if (res.signature) {
+      console.log(`   🔏 ${label} (tx ${res.signature})`);
+      return true;
+    }
+    console.log(`   ❌ Failed to ${label} :: ${res.error || 'Unknown submission error'}`);
+    return false;
+  }
+
+  /**
+   * Cancel and refund IOC/FOK orders still active after a matching round
+   */
+  private async expireImmediateOrders(orders: PlainOrder[]): Promise<void> {
+    const immediate = orders.filter((order) => isImmediate(order.timeInForce));
+    if (immediate.length === 0) {
+      return;
+    }
+
+    for (const order of immediate) {
+      try {
+        const onChain = await (this.program.account as any).encryptedOrder.fetch(order.publicKey);
+        if (onChain.status !== OrderStatus.ACTIVE) {
+          continue;
+        }
+
+        const escrowData = await (this.program.account as any).escrow.fetch(order.escrow);
+        const ix = await this.program.methods
+          .expireImmediateOrder()
+          .accounts({
+            callbackAuth: this.callbackAuth,
+            orderBook: this.orderBook,
+            order: order.publicKey,
+            escrow: order.escrow,
+            escrowTokenAccount: escrowData.tokenAccount,
+            ownerTokenAccount: this.getAssociatedTokenAccount(order.owner, escrowData.tokenMint),
+            keeper: this.keeper.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .instruction();
+
+        const tx = new Transaction().add(ix);
+        tx.recentBlockhash = (await this.connection.getLatestBlockhash()).blockhash;
+        tx.feePayer = this.keeper.publicKey;
+        tx.sign(this.keeper);
+
+        const res = await this.sanctumClient.submitTransaction(tx, this.config.maxRetries);
+        if (res.signature) {
+          console.log(`   ⌛ Expired unfilled IOC/FOK order ${order.orderId} (tx ${res.signature})`);
+        } else {
+          console.log(`   ❌ Failed to expire order ${order.orderId} :: ${res.error || 'Unknown submission error'}`);
+        }
+      } catch (error) {
+        this.logError(`Error expiring order ${order.orderId}`, error);
+      }
+    }
+  }
+
+  /**
+   * Fill resting orders nobody matched against the book's LP vault
+   *
+   * Only escrow-funded GTC orders older than `backstopAfterSeconds` whose
+   * limit allows the vault's oracle +/- spread price are sent; the program
+   * enforces the vault's inventory cap.
+   */
+  private async backstopUnmatchedOrders(orders: PlainOrder[], matches: MatchedPair[]): Promise<void> {
+    if (!this.config.backstopAfterSeconds) {
+      return;
+    }
+
+    const [lpVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from('lp_vault'), this.orderBook.toBuffer()],
+      this.program.programId
+    );
+    let vaultData: any;
+    try {
+      vaultData = await (this.program.account as any).lpVault.fetch(lpVault);
+    } catch {
+      return; // No LP vault on this book
+    }
+
+    const matched = new Set(
+      matches.flatMap((m) => [m.buyOrder.publicKey.toBase58(), m.sellOrder.publicKey.toBase58()])
+    );
+    const now = Math.floor(Date.now() / 1000);
+    const candidates = orders.filter(
+      (order) =>
+        !matched.has(order.publicKey.toBase58()) &&
+        !order.balanceBacked &&
+        order.timeInForce === TimeInForce.GTC &&
+        now - order.createdAt >= this.config.backstopAfterSeconds
+    );
+    if (candidates.length === 0) {
+      return;
+    }
+
+    const orderBookData = await (this.program.account as any).orderBook.fetch(this.orderBook);
+    const referencePrice = await this.readOraclePrice(orderBookData.oracle, orderBookData.oraclePriceExpo);
+    if (referencePrice === null) {
+      return;
+    }
+    const spread = (referencePrice * BigInt(vaultData.params.spreadBps)) / 10_000n;
+
+    for (const order of candidates) {
+      const isBuy = order.side === 0 || order.side === 'buy';
+      const vaultPrice = isBuy ? referencePrice + spread : referencePrice - spread;
+      if (isBuy ? vaultPrice > order.price : vaultPrice < order.price) {
+        continue;
+      }
+
+      try {
+        const escrowData = await (this.program.account as any).escrow.fetch(order.escrow);
+        const proceedsMint: PublicKey = isBuy ? orderBookData.baseMint : orderBookData.quoteMint;
+        const ix = await this.program.methods
+          .backstopFill(new BN(order.remainingAmount.toString()), new BN(0))
+          .accounts({
+            callbackAuth: this.callbackAuth,
+            orderBook: this.orderBook,
+            lpVault,
+            lpBaseVault: vaultData.baseVault,
+            lpQuoteVault: vaultData.quoteVault,
+            order: order.publicKey,
+            escrow: order.escrow,
+            escrowTokenAccount: escrowData.tokenAccount,
+            ownerTokenAccount: this.getAssociatedTokenAccount(order.owner, proceedsMint),
+            ownerRefundAccount: this.getAssociatedTokenAccount(order.owner, escrowData.tokenMint),
+            feeCollectorTokenAccount: this.getAssociatedTokenAccount(orderBookData.feeCollector, proceedsMint),
+            oracle: orderBookData.oracle,
+            keeper: this.keeper.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .instruction();
+
+        const tx = new Transaction().add(ix);
+        tx.recentBlockhash = (await this.connection.getLatestBlockhash()).blockhash;
+        tx.feePayer = this.keeper.publicKey;
+        tx.sign(this.keeper);
+
+        const res = await this.sanctumClient.submitTransaction(tx, this.config.maxRetries);
+        if (res.signature) {
+          console.log(`   🛟 Backstopped order ${order.orderId} at ${vaultPrice} (tx ${res.signature})`);
+        } else {
+          console.log(`   ❌ Failed to backstop order ${order.orderId} :: ${res.error || 'Unknown submission error'}`);
+        }
+      } catch (error) {
+        this.logError(`Error backstopping order ${order.orderId}`, error);
+      }
+    }
+  }
+
+  /**
+   * Pyth v2 aggregate price rescaled to `10^targetExpo` units
+   */
+  private async readOraclePrice(oracle: PublicKey, targetExpo: number): Promise<bigint | null> {
+    if (oracle.equals(PublicKey.default)) {
+      return null;
+    }
+
+    const info = await this.connection.getAccountInfo(oracle);
+    if (!info || info.data.length < 240) {
+      return null;
+    }
+
+    const expo = info.data.readInt32LE(20);
+    const price = info.data.readBigInt64LE(208);
+    if (price <= 0n) {
+      return null;
+    }
+
+    const shift = expo - targetExpo;
+    return shift >= 0 ? price * 10n ** BigInt(shift) : price / 10n ** BigInt(-shift);
+  }
+
   private async ordersAreActive(match: MatchedPair): Promise<boolean> {
     try {
       const buyerOrder = await (this.program.account as any).encryptedOrder.fetch(match.buyOrder.publicKey);
@@ -441,6 +908,14 @@ class ShadowSwapKeeper {
     const matchedAmountBn = new BN(matchAmountBigInt.toString());
     const executionPriceBn = new BN(executionPriceBigInt.toString());
 
+    // Base amount each order has left after this match (drives IOC/FOK handling)
+    const remainingAfter = (order: PlainOrder) =>
+      new BN((order.remainingAmount > matchAmountBigInt ? order.remainingAmount - matchAmountBigInt : 0n).toString());
+
+    if (match.buyOrder.balanceBacked || match.sellOrder.balanceBacked) {
+      return this.buildBalanceSettlementTransaction(match, matchedAmountBn, executionPriceBn, remainingAfter);
+    }
+
     // Get escrow accounts - they contain the token account addresses
     const buyerEscrowData = await (this.program.account as any).escrow.fetch(match.buyOrder.escrow);
     const sellerEscrowData = await (this.program.account as any).escrow.fetch(match.sellOrder.escrow);
@@ -520,6 +995,8 @@ class ShadowSwapKeeper {
         sellerPubkey: match.sellOrder.publicKey,
         matchedAmount: matchedAmountBn,
         executionPrice: executionPriceBn,
+        buyerRemaining: remainingAfter(match.buyOrder),
+        sellerRemaining: remainingAfter(match.sellOrder),
       })
       .accounts({
         callbackAuth: this.callbackAuth,
@@ -529,6 +1006,8 @@ class ShadowSwapKeeper {
         buyerEscrow: match.buyOrder.escrow,
         sellerEscrow: match.sellOrder.escrow,
         keeper: this.keeper.publicKey,
+        // Settlement is checked against the book's oracle band when one is configured
+        oracle: orderBookData.oracle.equals(PublicKey.default) ? null : orderBookData.oracle,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .remainingAccounts([
@@ -537,6 +1016,21 @@ class ShadowSwapKeeper {
         { pubkey: sellerEscrowTokenAccount, isSigner: false, isWritable: true },
         { pubkey: buyerTokenAccount, isSigner: false, isWritable: true },
         { pubkey: sellerTokenAccount, isSigner: false, isWritable: true },
+        // Refund accounts for IOC/FOK remainders: buyer_refund (quote), seller_refund (base)
+        ...(isImmediate(match.buyOrder.timeInForce) || isImmediate(match.sellOrder.timeInForce)
+          ? [
+              {
+                pubkey: this.getAssociatedTokenAccount(match.buyOrder.owner, orderBookData.quoteMint),
+                isSigner: false,
+                isWritable: true,
+              },
+              {
+                pubkey: this.getAssociatedTokenAccount(match.sellOrder.owner, orderBookData.baseMint),
+                isSigner: false,
+                isWritable: true,
+              },
+            ]
+          : []),
       ])
       .instruction();
 
@@ -551,6 +1045,57 @@ class ShadowSwapKeeper {
     return tx;
   }
 
+  /**
+   * Build a settle_balance_match transaction for two balance-backed orders
+   *
+   * No token accounts are involved; the program moves amounts between the
+   * two orders' user balances.
+   */
+  private async buildBalanceSettlementTransaction(
+    match: MatchedPair,
+    matchedAmountBn: BN,
+    executionPriceBn: BN,
+    remainingAfter: (order: PlainOrder) => BN
+  ): Promise<Transaction> {
+    if (!match.buyOrder.balanceBacked || !match.sellOrder.balanceBacked) {
+      throw new Error('Cannot settle a balance-backed order against an escrow-funded order');
+    }
+
+    const buyerOrderData = await (this.program.account as any).encryptedOrder.fetch(match.buyOrder.publicKey);
+    const sellerOrderData = await (this.program.account as any).encryptedOrder.fetch(match.sellOrder.publicKey);
+    const orderBookData = await (this.program.account as any).orderBook.fetch(this.orderBook);
+
+    const ix = await this.program.methods
+      .settleBalanceMatch({
+        buyerPubkey: match.buyOrder.publicKey,
+        sellerPubkey: match.sellOrder.publicKey,
+        matchedAmount: matchedAmountBn,
+        executionPrice: executionPriceBn,
+        buyerRemaining: remainingAfter(match.buyOrder),
+        sellerRemaining: remainingAfter(match.sellOrder),
+      })
+      .accounts({
+        callbackAuth: this.callbackAuth,
+        orderBook: this.orderBook,
+        buyerOrder: match.buyOrder.publicKey,
+        sellerOrder: match.sellOrder.publicKey,
+        buyerEscrow: match.buyOrder.escrow,
+        sellerEscrow: match.sellOrder.escrow,
+        buyerBalance: buyerOrderData.userBalance,
+        sellerBalance: sellerOrderData.userBalance,
+        keeper: this.keeper.publicKey,
+        oracle: orderBookData.oracle.equals(PublicKey.default) ? null : orderBookData.oracle,
+      })
+      .instruction();
+
+    const tx = new Transaction().add(ix);
+    tx.recentBlockhash = (await this.connection.getLatestBlockhash()).blockhash;
+    tx.feePayer = this.keeper.publicKey;
+    tx.sign(this.keeper);
+
+    return tx;
+  }
+
   /**
    * Verify keeper is authorized
    */
@@ -878,6 +1423,7 @@ async function main() {
     matchInterval: parseInt(process.env.MATCH_INTERVAL || '10000'),
     maxRetries: parseInt(process.env.MAX_RETRIES || '3'),
     retryDelayMs: parseInt(process.env.RETRY_DELAY_MS || '1000'),
+    backstopAfterSeconds: parseInt(process.env.BACKSTOP_AFTER_SECONDS || '0'),
     logLevel: (process.env.LOG_LEVEL as any) || 'info',
   };
 
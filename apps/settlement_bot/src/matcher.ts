@@ -4,7 +4,7 @@
  * Implements price-time priority matching algorithm for ShadowSwap
  */
 
-import { PlainOrder, MatchedPair } from './types';
+import { PlainOrder, MatchedPair, TimeInForce } from './types';
 
 /**
  * Match orders using price-time priority algorithm
@@ -15,12 +15,22 @@ import { PlainOrder, MatchedPair } from './types';
  * 3. Sort sells: ascending price, ascending timestamp
  * 4. Match: buyPrice >= sellPrice
  * 5. Execution price: maker order price (time priority)
- * 6. Handle partial fills
+ * 6. Handle partial fills (FOK orders are skipped unless filled completely)
  * 
  * @param orders - Array of decrypted plain orders
  * @returns Array of matched pairs ready for settlement
  */
 export function matchOrders(orders: PlainOrder[]): MatchedPair[] {
+  // Balance-backed and escrow-funded orders settle through different
+  // instructions, so each group is matched on its own
+  const balanceBacked = orders.filter(o => o.balanceBacked);
+  if (balanceBacked.length > 0 && balanceBacked.length < orders.length) {
+    return [
+      ...matchOrders(orders.filter(o => !o.balanceBacked)),
+      ...matchOrders(balanceBacked),
+    ];
+  }
+
   console.log(`\n📊 Matching ${orders.length} orders...`);
 
   // Separate buy and sell orders
@@ -84,6 +94,16 @@ export function matchOrders(orders: PlainOrder[]): MatchedPair[] {
       continue;
     }
 
+    // Fill-or-kill orders must fill completely against a single counterparty
+    if (buyOrder.timeInForce === TimeInForce.FOK && matchedAmount < buyOrder.remainingAmount) {
+      buyIdx++;
+      continue;
+    }
+    if (sellOrder.timeInForce === TimeInForce.FOK && matchedAmount < sellOrder.remainingAmount) {
+      sellIdx++;
+      continue;
+    }
+
     // Determine execution price (time priority: maker's price)
     // The order that was placed first (maker) gets their price
     const executionPrice = buyOrder.createdAt < sellOrder.createdAt
@@ -18,6 +18,10 @@ export interface EncryptedOrder {
   createdAt: BN;
   updatedAt: BN;
   orderId: BN;
+  timeInForce: number;
+  parent: PublicKey;
+  userBalance: PublicKey;
+  route: PublicKey;
   bump: number;
 }
 
@@ -36,6 +40,8 @@ export interface PlainOrder {
   createdAt: number;
   orderId: number;
   status: number;
+  timeInForce: number;
+  balanceBacked: boolean;  // Funded from a UserBalance; settles via settle_balance_match
 }
 
 /**
@@ -46,6 +52,8 @@ export interface MatchResultInput {
   sellerPubkey: PublicKey;
   matchedAmount: BN;
   executionPrice: BN;
+  buyerRemaining: BN;
+  sellerRemaining: BN;
 }
 
 /**
@@ -69,6 +77,54 @@ export enum OrderStatus {
   MATCHED_PENDING = 5,
 }
 
+/**
+ * Parent (TWAP / iceberg) order status constants
+ */
+export enum ParentOrderStatus {
+  ACTIVE = 1,
+  COMPLETED = 2,
+  CANCELLED = 3,
+}
+
+/**
+ * Commit-reveal (sealed) auction round status constants
+ */
+export enum SealedAuctionStatus {
+  OPEN = 1,
+  CLEARED = 2,
+}
+
+/**
+ * Time-in-force constants (public on EncryptedOrder)
+ */
+export enum TimeInForce {
+  GTC = 0,
+  IOC = 1,
+  FOK = 2,
+  POST_ONLY = 3,
+}
+
+/**
+ * Whether the order is closed out after its first matching round
+ */
+export function isImmediate(timeInForce: number): boolean {
+  return timeInForce === TimeInForce.IOC || timeInForce === TimeInForce.FOK;
+}
+
+/**
+ * Whether the order locks funds in a UserBalance instead of its own escrow
+ */
+export function isBalanceBacked(order: EncryptedOrder): boolean {
+  return !!order.userBalance && !order.userBalance.equals(PublicKey.default);
+}
+
+/**
+ * Whether the order's proceeds are routed through a second order book
+ */
+export function isRouted(order: EncryptedOrder): boolean {
+  return !!order.route && !order.route.equals(PublicKey.default);
+}
+
 /**
  * Bot configuration
  */
@@ -99,6 +155,7 @@ export interface KeeperConfig {
   matchInterval: number; // milliseconds
   maxRetries: number;
   retryDelayMs: number;
+  backstopAfterSeconds: number; // 0 = never fill against the LP vault
   
   // Logging
   logLevel: 'debug' | 'info' | 'warn' | 'error';
@@ -16,6 +16,25 @@ export enum OrderStatus {
   Cancelled = 4,
 }
 
+/**
+ * Time-in-force flag (public on EncryptedOrder)
+ */
+export enum TimeInForce {
+  GoodTillCancelled = 0,
+  ImmediateOrCancel = 1,
+  FillOrKill = 2,
+  PostOnly = 3,
+}
+
+/**
+ * Parent (TWAP / iceberg) order status
+ */
+export enum ParentOrderStatus {
+  Active = 1,
+  Completed = 2,
+  Cancelled = 3,
+}
+
 /**
  * Order side (buy/sell)
  */
@@ -47,6 +66,37 @@ export interface EncryptedOrderData {
   createdAt: number;
   updatedAt: number;
   orderId: bigint;
+  /** Client order ID the order PDA is seeded with (child orders: index in the parent, LE) */
+  clientOrderId: Uint8Array;
+  timeInForce: TimeInForce;
+  /** Parent order that released this order (PublicKey.default if standalone) */
+  parent: PublicKey;
+  /** User balance funding this order (PublicKey.default if escrow-funded) */
+  userBalance: PublicKey;
+  route: PublicKey;
+  bump: number;
+}
+
+/**
+ * TWAP / iceberg parent order data
+ */
+export interface ParentOrderData {
+  owner: PublicKey;
+  orderBook: PublicKey;
+  tokenMint: PublicKey;
+  escrowTokenAccount: PublicKey;
+  cipherPayload: Uint8Array;
+  encryptedAmount: Uint8Array;
+  totalAmount: bigint;
+  releasedAmount: bigint;
+  sliceSize: bigint;
+  sliceJitterBps: number;
+  releaseInterval: number;
+  nextReleaseAt: number;
+  childCount: bigint;
+  status: ParentOrderStatus;
+  parentNonce: bigint;
+  createdAt: number;
   bump: number;
 }
 
@@ -57,8 +107,10 @@ export interface OrderBookData {
   authority: PublicKey;
   baseMint: PublicKey;
   quoteMint: PublicKey;
-  orderCount: bigint;
-  activeOrders: bigint;
+  /** Number of order ID shards */
+  orderShards: number;
+  /** Orders closed so far; active = shards' orderCount total - closedOrders */
+  closedOrders: bigint;
   encryptedVolumeBase: Uint8Array;
   encryptedVolumeQuote: Uint8Array;
   createdAt: number;
@@ -67,6 +119,19 @@ export interface OrderBookData {
   feeCollector: PublicKey;
   minBaseOrderSize: bigint;
   isActive: boolean;
+  oracle: PublicKey;
+  oracleMaxDeviationBps: number;
+  oracleMaxStaleness: number;
+  oraclePriceExpo: number;
+  fallbackAmm: PublicKey;
+  sealedAuctionCount: bigint;
+  rfqCount: bigint;
+  /** Sealed auction rounds not yet fully settled */
+  liveSealedAuctions: bigint;
+  /** RFQs neither filled nor cancelled */
+  openRfqs: bigint;
+  /** When decommissioning started (0 = live book) */
+  decommissionedAt: number;
   bump: number;
 }
 
@@ -81,10 +146,257 @@ export interface EscrowData {
   tokenMint: PublicKey;
   encryptedAmount: Uint8Array;
   encryptedRemaining: Uint8Array;
+  /** Amount locked in the owner's user balance (balance-backed orders only) */
+  lockedAmount: bigint;
+  createdAt: number;
+  bump: number;
+}
+
+/**
+ * Per-user trading balance on an order book
+ */
+export interface UserBalanceData {
+  owner: PublicKey;
+  orderBook: PublicKey;
+  baseFree: bigint;
+  baseLocked: bigint;
+  quoteFree: bigint;
+  quoteLocked: bigint;
   createdAt: number;
   bump: number;
 }
 
+/**
+ * Two-leg route attached to a balance-backed order
+ */
+export interface RouteData {
+  owner: PublicKey;
+  order: PublicKey;
+  firstOrderBook: PublicKey;
+  secondOrderBook: PublicKey;
+  intermediateMint: PublicKey;
+  outputMint: PublicKey;
+  amountIn: bigint;
+  minAmountOut: bigint;
+  createdAt: number;
+  bump: number;
+}
+
+/**
+ * AMM fallback settings of an escrow-funded order
+ */
+export interface FallbackConfigData {
+  order: PublicKey;
+  owner: PublicKey;
+  deadline: number;
+  /** sha256(limitPrice as u64 LE || salt) */
+  limitCommitment: Uint8Array;
+  createdAt: number;
+  bump: number;
+}
+
+/**
+ * LP vault parameters
+ */
+export interface LpVaultParams {
+  /** Distance from the oracle price the vault trades at */
+  spreadBps: number;
+  /** Maximum share of vault value either side may reach (5000-10000) */
+  maxInventoryBps: number;
+  /** Share of the book fee on backstop fills kept by the vault */
+  lpFeeShareBps: number;
+  /** Seconds a queued withdrawal waits before processing */
+  withdrawalDelay: number;
+}
+
+/**
+ * LP backstop vault data
+ */
+export interface LpVaultData {
+  orderBook: PublicKey;
+  shareMint: PublicKey;
+  baseVault: PublicKey;
+  quoteVault: PublicKey;
+  /** Holds shares of queued withdrawals until they are burned */
+  pendingShares: PublicKey;
+  params: LpVaultParams;
+  withdrawalCount: bigint;
+  nextWithdrawal: bigint;
+  createdAt: number;
+  bump: number;
+}
+
+/**
+ * Queued LP withdrawal data
+ */
+export interface LpWithdrawalRequestData {
+  lpVault: PublicKey;
+  owner: PublicKey;
+  index: bigint;
+  shares: bigint;
+  baseDestination: PublicKey;
+  quoteDestination: PublicKey;
+  requestedAt: number;
+  bump: number;
+}
+
+/**
+ * Commit-reveal auction round parameters
+ */
+export interface SealedAuctionParams {
+  /** Seconds orders can be committed after the round opens */
+  commitDuration: number;
+  /** Seconds orders can be revealed after the commit window */
+  revealDuration: number;
+  /** Share of an unrevealed order's deposit forfeited */
+  penaltyBps: number;
+}
+
+/**
+ * A revealed sealed order
+ */
+export interface SealedBid {
+  /** 0 = buy, 1 = sell */
+  side: number;
+  price: bigint;
+  amount: bigint;
+  /** Base amount filled at the clearing price */
+  filled: bigint;
+}
+
+/**
+ * Commit-reveal auction round data
+ */
+export interface SealedAuctionData {
+  orderBook: PublicKey;
+  round: bigint;
+  baseVault: PublicKey;
+  quoteVault: PublicKey;
+  commitEnd: number;
+  revealEnd: number;
+  penaltyBps: number;
+  /** 1 = open, 2 = cleared */
+  status: number;
+  commitCount: bigint;
+  settledCount: bigint;
+  bids: SealedBid[];
+  clearingPrice: bigint;
+  volume: bigint;
+  createdAt: number;
+  bump: number;
+}
+
+/**
+ * Committed sealed order data
+ */
+export interface SealedOrderData {
+  auction: PublicKey;
+  owner: PublicKey;
+  index: bigint;
+  /** sha256(side as u8 || price as u64 LE || amount as u64 LE || salt) */
+  commitment: Uint8Array;
+  depositMint: PublicKey;
+  deposit: bigint;
+  revealed: boolean;
+  bidIndex: number;
+  committedAt: number;
+  bump: number;
+}
+
+/**
+ * Whitelisted RFQ maker data
+ */
+export interface RfqMakerData {
+  orderBook: PublicKey;
+  maker: PublicKey;
+  /** x25519 key request sizes are encrypted to */
+  encryptionKey: Uint8Array;
+  addedAt: number;
+  bump: number;
+}
+
+/**
+ * RFQ request status constants
+ */
+export enum RfqStatus {
+  OPEN = 1,
+  FILLED = 2,
+  CANCELLED = 3,
+}
+
+/**
+ * RFQ request data
+ */
+export interface RfqRequestData {
+  orderBook: PublicKey;
+  taker: PublicKey;
+  rfqId: bigint;
+  /** Taker side: 0 = buy, 1 = sell */
+  side: number;
+  makers: PublicKey[];
+  /** One ciphertext of the size per maker, in `makers` order */
+  encryptedSize: Uint8Array;
+  escrow: PublicKey;
+  expiresAt: number;
+  status: RfqStatus;
+  quoteCount: bigint;
+  acceptedQuote: PublicKey;
+  createdAt: number;
+  bump: number;
+}
+
+/**
+ * RFQ quote data
+ */
+export interface RfqQuoteData {
+  request: PublicKey;
+  maker: PublicKey;
+  /** Price, size and salt encrypted to the taker */
+  encryptedQuote: Uint8Array;
+  /** sha256(price as u64 LE || amount as u64 LE || salt) */
+  termsCommitment: Uint8Array;
+  escrow: PublicKey;
+  expiresAt: number;
+  createdAt: number;
+  bump: number;
+}
+
+/**
+ * Trading delegate scope bits (there is no withdraw scope)
+ */
+export enum DelegateScope {
+  PLACE = 1,
+  CANCEL = 2,
+}
+
+/**
+ * Session key (trading delegate) data
+ */
+export interface TradingDelegateData {
+  owner: PublicKey;
+  delegate: PublicKey;
+  /** Mask of DelegateScope bits */
+  scopes: number;
+  expiresAt: number;
+  /** Maximum base escrow per placed order (0 = none) */
+  maxBasePerOrder: bigint;
+  /** Maximum quote escrow per placed order (0 = none) */
+  maxQuotePerOrder: bigint;
+  createdAt: number;
+  bump: number;
+}
+
+/**
+ * Order ID shard data
+ */
+export interface OrderShardData {
+  orderBook: PublicKey;
+  shard: number;
+  /** Orders placed through this shard */
+  orderCount: bigint;
+  bump: number;
+}
+
 /**
  * Callback auth data
  */